@@ -0,0 +1,49 @@
+// Benchmark for the parallel collection phase (--jobs) over 500 live processes
+//
+// Starts 500 sleeping children and runs the per-process reads memon does for
+// matched trees through collect::parallel_map at 1, 2, 4 and 8 jobs: command
+// lines for -v, smaps_rollup for --mem-details and the full smaps walk of
+// --dedupe-shared. Linux only; elsewhere the reads return nothing.
+
+use std::process::{Child, Command, Stdio};
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use memon::collect;
+use memon::platform;
+
+const PROCESSES: usize = 500;
+const JOBS: [usize; 4] = [1, 2, 4, 8];
+
+fn sleepers() -> Vec<Child> {
+    (0..PROCESSES)
+        .map(|_| Command::new("sleep").arg("600").stdin(Stdio::null()).spawn().expect("cannot start sleep"))
+        .collect()
+}
+
+fn bench_collect(c: &mut Criterion, pids: &[u32]) {
+    let mut group = c.benchmark_group(format!("collect {} processes", pids.len()));
+    for jobs in JOBS {
+        group.bench_with_input(BenchmarkId::new("cmdline", jobs), &jobs, |b, &jobs| {
+            b.iter(|| collect::parallel_map(black_box(pids), jobs, |pid| (platform::read_comm(pid), platform::read_cmdline(pid))))
+        });
+        group.bench_with_input(BenchmarkId::new("smaps_rollup", jobs), &jobs, |b, &jobs| {
+            b.iter(|| collect::parallel_map(black_box(pids), jobs, platform::read_mem_details))
+        });
+        group.bench_with_input(BenchmarkId::new("smaps", jobs), &jobs, |b, &jobs| {
+            b.iter(|| collect::parallel_map(black_box(pids), jobs, platform::read_sharing))
+        });
+    }
+    group.finish();
+}
+
+fn main() {
+    let mut children = sleepers();
+    let pids: Vec<u32> = children.iter().map(Child::id).collect();
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_collect(&mut criterion, &pids);
+    criterion.final_summary();
+    for child in &mut children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,108 @@
+// Benchmark for per-tree statistics on a synthetic 10k-node process tree
+//
+// Compares the single-pass TreeStats::collect against the previous approach of
+// separate recursive passes for RSS values, total, count and the top-3 ranks.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use memon::process::ProcessInfo;
+use memon::stats::TreeStats;
+use std::collections::HashMap;
+
+const NODES: u32 = 10_000;
+const FAN_OUT: u32 = 8;
+
+// Build a balanced tree of NODES processes rooted at PID 1
+fn synthetic_tree() -> HashMap<u32, ProcessInfo> {
+    let mut processes = HashMap::new();
+    for pid in 1..=NODES {
+        let parent_pid = if pid == 1 { None } else { Some((pid - 2) / FAN_OUT + 1) };
+        // Spread RSS values so there are plenty of distinct sizes
+        let rss = ((pid as u64 * 7919) % 4096) * 1024 * 1024;
+        let name = format!("worker-process-with-a-long-name-{}", pid);
+        processes.insert(pid, ProcessInfo::new(pid, name, rss, parent_pid));
+    }
+    for pid in 2..=NODES {
+        let parent_pid = processes[&pid].parent_pid.unwrap();
+        processes.get_mut(&parent_pid).unwrap().add_child(pid);
+    }
+    processes
+}
+
+// The multi-pass computation that TreeStats replaced
+mod legacy {
+    use super::*;
+    
+    fn collect_all_rss(processes: &HashMap<u32, ProcessInfo>, root: &ProcessInfo) -> Vec<u64> {
+        let mut rss_values = vec![root.rss];
+        for child_pid in &root.children {
+            if let Some(child) = processes.get(child_pid) {
+                rss_values.extend(collect_all_rss(processes, child));
+            }
+        }
+        rss_values
+    }
+    
+    fn total(processes: &HashMap<u32, ProcessInfo>, root: &ProcessInfo) -> u64 {
+        let mut total_rss = root.rss;
+        for child_pid in &root.children {
+            if let Some(child) = processes.get(child_pid) {
+                total_rss += total(processes, child);
+            }
+        }
+        total_rss
+    }
+    
+    fn count(processes: &HashMap<u32, ProcessInfo>, root: &ProcessInfo) -> usize {
+        let mut count_value = 1;
+        for child_pid in &root.children {
+            if let Some(child) = processes.get(child_pid) {
+                count_value += count(processes, child);
+            }
+        }
+        count_value
+    }
+    
+    fn collect_all(processes: &HashMap<u32, ProcessInfo>, root: &ProcessInfo, out: &mut Vec<ProcessInfo>) {
+        out.push(root.clone());
+        for child_pid in &root.children {
+            if let Some(child) = processes.get(child_pid) {
+                collect_all(processes, child, out);
+            }
+        }
+    }
+    
+    fn top_three(rss_values: &[u64]) -> (u64, u64, u64) {
+        let max = *rss_values.iter().max().unwrap_or(&0);
+        let second = *rss_values.iter().filter(|&&rss| rss != max).max().unwrap_or(&0);
+        let third = *rss_values.iter().filter(|&&rss| rss != max && rss != second).max().unwrap_or(&0);
+        (max, second, third)
+    }
+    
+    pub fn analyze(processes: &HashMap<u32, ProcessInfo>, root_pid: u32) -> (usize, u64, (u64, u64, u64)) {
+        let root = processes[&root_pid].clone();
+        let highlight = top_three(&collect_all_rss(processes, &root));
+        let total_rss = total(processes, &root);
+        let mut all = Vec::new();
+        collect_all(processes, &root, &mut all);
+        let process_count = count(processes, &root);
+        let summary = top_three(&collect_all_rss(processes, &root));
+        assert_eq!(highlight, summary);
+        (process_count, total_rss, summary)
+    }
+}
+
+fn bench_tree_stats(c: &mut Criterion) {
+    let processes = synthetic_tree();
+    
+    c.bench_function("legacy multi-pass 10k", |b| {
+        b.iter(|| legacy::analyze(black_box(&processes), 1))
+    });
+    
+    c.bench_function("TreeStats::collect 10k", |b| {
+        let mut processes = processes.clone();
+        b.iter(|| TreeStats::collect(black_box(&mut processes), 1))
+    });
+}
+
+criterion_group!(benches, bench_tree_stats);
+criterion_main!(benches);
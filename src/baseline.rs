@@ -0,0 +1,152 @@
+// Baseline comparison for CI memory regression checks (--baseline / --tolerance)
+
+use crate::snapshot::Snapshot;
+use crate::units::{format_memory, parse_size};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Allowed growth over the baseline before a row counts as a regression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    Percent(f64),
+    Bytes(u64),
+}
+
+impl Tolerance {
+    // "10%" or an absolute size such as "+200M" / "200MB"
+    pub fn parse(text: &str) -> Result<Tolerance, String> {
+        let trimmed = text.trim();
+        if let Some(percent) = trimmed.strip_suffix('%') {
+            let value: f64 = percent.trim().parse().map_err(|_| format!("invalid tolerance '{}'", text))?;
+            if value < 0.0 {
+                return Err(format!("invalid tolerance '{}': must not be negative", text));
+            }
+            return Ok(Tolerance::Percent(value));
+        }
+        parse_size(trimmed.strip_prefix('+').unwrap_or(trimmed)).map(Tolerance::Bytes)
+    }
+    
+    pub fn allows(&self, baseline: u64, current: u64) -> bool {
+        let growth = current.saturating_sub(baseline);
+        match *self {
+            Tolerance::Percent(percent) => growth as f64 <= baseline as f64 * percent / 100.0,
+            Tolerance::Bytes(bytes) => growth <= bytes,
+        }
+    }
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tolerance::Percent(percent) => write!(f, "{}%", percent),
+            Tolerance::Bytes(bytes) => write!(f, "+{}", format_memory(*bytes)),
+        }
+    }
+}
+
+// One compared quantity: the overall total or one process-name group
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub label: String,
+    pub baseline: u64,
+    pub current: u64,
+    pub regressed: bool,
+}
+
+#[derive(Debug)]
+pub struct BaselineError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot read baseline {}: {}", self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for BaselineError {}
+
+pub fn load(path: &Path) -> Result<Snapshot, BaselineError> {
+    let text = fs::read_to_string(path).map_err(|err| BaselineError { path: path.to_path_buf(), message: err.to_string() })?;
+    serde_json::from_str(&text).map_err(|err| BaselineError { path: path.to_path_buf(), message: err.to_string() })
+}
+
+// Write the current snapshot as the new baseline (--save)
+pub fn save(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    fs::write(path, crate::render::render_json(snapshot))
+}
+
+// Total memory of every process, grouped by process name
+fn name_totals(snapshot: &Snapshot) -> BTreeMap<String, u64> {
+    let mut totals = BTreeMap::new();
+    for (node, _) in snapshot.trees.iter().flat_map(|tree| tree.iter_preorder()) {
+        *totals.entry(node.name.clone()).or_insert(0) += node.rss_bytes;
+    }
+    totals
+}
+
+// Compare the tree total, and with `by_name` every process-name group, against the baseline
+pub fn compare(baseline: &Snapshot, current: &Snapshot, tolerance: Tolerance, by_name: bool) -> Vec<DiffRow> {
+    let total = |snapshot: &Snapshot| snapshot.trees.iter().map(|tree| tree.total_bytes).sum::<u64>();
+    let mut rows = vec![row("total".to_string(), total(baseline), total(current), tolerance)];
+    
+    if by_name {
+        let mut groups: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+        for (name, bytes) in name_totals(baseline) {
+            groups.entry(name).or_default().0 = bytes;
+        }
+        for (name, bytes) in name_totals(current) {
+            groups.entry(name).or_default().1 = bytes;
+        }
+        rows.extend(groups.into_iter().map(|(name, (before, after))| row(name, before, after, tolerance)));
+    }
+    rows
+}
+
+fn row(label: String, baseline: u64, current: u64, tolerance: Tolerance) -> DiffRow {
+    DiffRow { label, baseline, current, regressed: !tolerance.allows(baseline, current) }
+}
+
+// Human-readable diff table with aligned columns
+pub fn render_table(rows: &[DiffRow], tolerance: Tolerance) -> String {
+    let cells: Vec<[String; 4]> = rows
+        .iter()
+        .map(|row| [row.label.clone(), format_memory(row.baseline), format_memory(row.current), change(row.baseline, row.current)])
+        .collect();
+    let header = ["Group", "Baseline", "Current", "Change"];
+    let mut widths = header.map(str::len);
+    for cell in &cells {
+        for (width, text) in widths.iter_mut().zip(cell) {
+            *width = (*width).max(text.len());
+        }
+    }
+    
+    let mut out = String::new();
+    let _ = writeln!(out, "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}", header[0], header[1], header[2], header[3],
+                     w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    for (cell, row) in cells.iter().zip(rows) {
+        let _ = writeln!(out, "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}{}", cell[0], cell[1], cell[2], cell[3],
+                         if row.regressed { "  REGRESSION" } else { "" },
+                         w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    }
+    let regressions = rows.iter().filter(|row| row.regressed).count();
+    if regressions == 0 {
+        let _ = writeln!(out, "Within tolerance ({})", tolerance);
+    } else {
+        let _ = writeln!(out, "{} of {} rows grew more than the tolerance ({})", regressions, rows.len(), tolerance);
+    }
+    out
+}
+
+// "+200.0MB (+16.7%)", "-1.0GB (-50.0%)" or "new"
+fn change(baseline: u64, current: u64) -> String {
+    let (sign, delta) = if current >= baseline { ('+', current - baseline) } else { ('-', baseline - current) };
+    if baseline == 0 {
+        return if current == 0 { "0B".to_string() } else { "new".to_string() };
+    }
+    format!("{}{} ({}{:.1}%)", sign, format_memory(delta), sign, delta as f64 * 100.0 / baseline as f64)
+}
@@ -0,0 +1,66 @@
+// Memory budget gauge (--budget / --enforce-budget)
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::units::parse_size;
+
+// Usage above this share of the budget is shown as a warning
+pub const WARNING_PERCENT: f64 = 80.0;
+
+// Combined memory of the matched trees against the configured budget
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Budget {
+    pub budget_bytes: u64,
+    pub used_bytes: u64,
+    pub percent: f64,
+}
+
+// How close usage is to the budget, for coloring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLevel {
+    Normal,
+    // Above WARNING_PERCENT
+    Warning,
+    // Above 100%
+    Over,
+}
+
+impl Budget {
+    pub fn new(budget_bytes: u64, used_bytes: u64) -> Self {
+        Budget { budget_bytes, used_bytes, percent: share(used_bytes, budget_bytes) }
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.used_bytes > self.budget_bytes
+    }
+
+    pub fn level(&self) -> BudgetLevel {
+        level(self.percent)
+    }
+}
+
+// Percentage of the budget taken by `used`
+pub fn share(used: u64, budget: u64) -> f64 {
+    used as f64 / budget.max(1) as f64 * 100.0
+}
+
+// --budget value: a size as accepted by --fail-if-total, but never zero
+pub fn parse_budget(text: &str) -> Result<u64, String> {
+    match parse_size(text)? {
+        0 => Err("budget must be larger than zero".to_string()),
+        bytes => Ok(bytes),
+    }
+}
+
+pub fn level(percent: f64) -> BudgetLevel {
+    if percent > 100.0 {
+        BudgetLevel::Over
+    } else if percent > WARNING_PERCENT {
+        BudgetLevel::Warning
+    } else {
+        BudgetLevel::Normal
+    }
+}
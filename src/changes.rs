@@ -0,0 +1,111 @@
+// Process changes between two watch samples (--changes-only)
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::snapshot::Snapshot;
+use crate::units::SizeFormat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Started,
+    Exited,
+    // RSS moved by more than the --change-threshold
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub pid: u32,
+    pub name: String,
+    // RSS in the newer sample; for an exited process, its last known RSS
+    pub rss_bytes: u64,
+    // Growth since the older sample: the whole RSS for a started process,
+    // minus it for an exited one
+    pub delta_bytes: i64,
+    // With --stability: when the process's memory last moved, in seconds
+    // since the epoch; None for an exited process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_change_epoch: Option<i64>,
+}
+
+// One NDJSON line per change
+#[derive(Serialize)]
+struct ChangeEvent<'a> {
+    timestamp: i64,
+    #[serde(flatten)]
+    change: &'a Change,
+}
+
+// Every process of every tree, keyed by PID
+fn processes(snapshot: &Snapshot) -> BTreeMap<u32, (&str, u64, Option<i64>)> {
+    snapshot.trees
+        .iter()
+        .flat_map(|tree| tree.iter_preorder())
+        .map(|(node, _)| (node.pid, (node.name.as_str(), node.rss_bytes, node.stability.map(|stability| stability.last_change_epoch))))
+        .collect()
+}
+
+// Changes from `previous` to `current` in PID order. A PID that now belongs
+// to a process with another name was reused, so it counts as exited and
+// started rather than changed.
+pub fn diff(previous: &Snapshot, current: &Snapshot, threshold: u64) -> Vec<Change> {
+    let before = processes(previous);
+    let after = processes(current);
+    let mut changes = Vec::new();
+    let change = |kind, pid: u32, name: &str, rss_bytes, delta_bytes, last_change_epoch| Change { kind, pid, name: name.to_string(), rss_bytes, delta_bytes, last_change_epoch };
+
+    let mut pids: Vec<u32> = before.keys().chain(after.keys()).copied().collect();
+    pids.sort_unstable();
+    pids.dedup();
+    for pid in pids {
+        match (before.get(&pid), after.get(&pid)) {
+            (Some(&(old_name, old_rss, _)), Some(&(name, rss, last_change))) if old_name == name => {
+                if old_rss.abs_diff(rss) > threshold {
+                    changes.push(change(ChangeKind::Changed, pid, name, rss, rss as i64 - old_rss as i64, last_change));
+                }
+            }
+            (old, new) => {
+                if let Some(&(name, rss, _)) = old {
+                    changes.push(change(ChangeKind::Exited, pid, name, rss, -(rss as i64), None));
+                }
+                if let Some(&(name, rss, last_change)) = new {
+                    changes.push(change(ChangeKind::Started, pid, name, rss, rss as i64, last_change));
+                }
+            }
+        }
+    }
+    changes
+}
+
+// Text block for one sample: a timestamp header, then one line per change
+pub fn render_text(timestamp: &str, changes: &[Change], sizes: &SizeFormat) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "[{}] {} changes", timestamp, sizes.count(changes.len() as u64));
+    for change in changes {
+        let _ = match change.kind {
+            ChangeKind::Started => writeln!(out, "+ {} {} started at {}", change.pid, change.name, sizes.memory(change.rss_bytes)),
+            ChangeKind::Exited => writeln!(out, "- {} {} exited, was {}", change.pid, change.name, sizes.memory(change.rss_bytes)),
+            ChangeKind::Changed => {
+                let sign = if change.delta_bytes < 0 { '-' } else { '+' };
+                writeln!(out, "~ {} {} {} ({}{})", change.pid, change.name, sizes.memory(change.rss_bytes),
+                         sign, sizes.memory(change.delta_bytes.unsigned_abs()))
+            }
+        };
+    }
+    out
+}
+
+// NDJSON for one sample: one object per change, nothing when nothing changed
+pub fn render_ndjson(timestamp: i64, changes: &[Change]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        out.push_str(&serde_json::to_string(&ChangeEvent { timestamp, change }).expect("change serializes to JSON"));
+        out.push('\n');
+    }
+    out
+}
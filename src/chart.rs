@@ -0,0 +1,98 @@
+// ASCII charts of tree totals: the watch-mode chart and `memon report`
+
+use std::collections::VecDeque;
+
+use crate::units::format_memory;
+
+// Chart rows in watch mode unless --chart-height says otherwise
+pub const DEFAULT_HEIGHT: usize = 6;
+
+// Samples kept for the watch chart, one column each
+pub const WATCH_SAMPLES: usize = 60;
+
+// The most recent tree totals, oldest first
+#[derive(Debug, Clone)]
+pub struct TotalHistory {
+    capacity: usize,
+    totals: VecDeque<u64>,
+}
+
+impl TotalHistory {
+    pub fn new(capacity: usize) -> Self {
+        TotalHistory { capacity, totals: VecDeque::with_capacity(capacity) }
+    }
+
+    // Add a sample, dropping the oldest once the history is full
+    pub fn push(&mut self, total: u64) {
+        if self.totals.len() == self.capacity {
+            self.totals.pop_front();
+        }
+        self.totals.push_back(total);
+    }
+
+    pub fn totals(&self) -> Vec<u64> {
+        self.totals.iter().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.totals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.totals.is_empty()
+    }
+}
+
+// Bar chart of `values` in `height` rows, scaled between their minimum and
+// maximum, which label the top and bottom rows. With more values than `width`
+// columns each column shows the largest value of its bucket.
+pub fn render(values: &[u64], height: usize, width: usize) -> String {
+    let columns = values.len().min(width);
+    if columns == 0 || height == 0 {
+        return String::new();
+    }
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let buckets: Vec<u64> = (0..columns)
+        .map(|column| {
+            let start = column * values.len() / columns;
+            let end = ((column + 1) * values.len() / columns).max(start + 1);
+            values[start..end].iter().copied().max().unwrap_or(0)
+        })
+        .collect();
+    // Bar height 1..=height, so the smallest value still shows
+    let heights: Vec<usize> = buckets
+        .iter()
+        .map(|&value| match max - min {
+            0 => height,
+            range => 1 + ((value - min) as f64 / range as f64 * (height - 1) as f64).round() as usize,
+        })
+        .collect();
+
+    let (top_label, bottom_label) = (format_memory(max), format_memory(min));
+    let label_width = top_label.len().max(bottom_label.len());
+    let mut out = String::new();
+    for row in 0..height {
+        let label = if row == 0 {
+            top_label.as_str()
+        } else if row == height - 1 {
+            bottom_label.as_str()
+        } else {
+            ""
+        };
+        let level = height - row;
+        let bars: String = heights.iter().map(|&bar| if bar >= level { '#' } else { ' ' }).collect();
+        out.push_str(&format!("  {:>width$} |{}\n", label, bars.trim_end(), width = label_width));
+    }
+    out.push_str(&format!("  {:>width$} +{}\n", "", "-".repeat(columns), width = label_width));
+    out
+}
+
+// --chart-height value: at least two rows, so min and max get their own
+pub fn parse_height(text: &str) -> Result<usize, String> {
+    let height: usize = text.trim().parse().map_err(|_| format!("invalid chart height '{}'", text))?;
+    if height < 2 {
+        return Err("chart height must be at least 2".to_string());
+    }
+    Ok(height)
+}
@@ -0,0 +1,63 @@
+// Health-probe mode (--check): one summary line and a stable exit status
+
+use crate::snapshot::Snapshot;
+use crate::threshold::{Breach, Exceeded, Thresholds};
+use crate::units::format_memory;
+
+// Exit statuses; scripts and probes rely on these, so they must not change
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_NO_MATCH: i32 = 1;
+pub const EXIT_EXCEEDED: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckOutcome {
+    Healthy,
+    NoMatch,
+    Exceeded(Breach),
+    // Usage and budget in bytes, with --enforce-budget
+    OverBudget(u64, u64),
+}
+
+impl CheckOutcome {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CheckOutcome::Healthy => EXIT_OK,
+            CheckOutcome::NoMatch => EXIT_NO_MATCH,
+            CheckOutcome::Exceeded(_) | CheckOutcome::OverBudget(..) => EXIT_EXCEEDED,
+        }
+    }
+}
+
+// Judge a snapshot: no tree is a missing process, then the --fail-if-*
+// limits, then the budget when it is enforced
+pub fn evaluate(snapshot: &Snapshot, thresholds: &Thresholds, enforce_budget: bool) -> CheckOutcome {
+    if snapshot.trees.is_empty() {
+        return CheckOutcome::NoMatch;
+    }
+    if let Some(breach) = thresholds.check(snapshot) {
+        return CheckOutcome::Exceeded(breach);
+    }
+    match snapshot.budget {
+        Some(budget) if enforce_budget && budget.exceeded() => CheckOutcome::OverBudget(budget.used_bytes, budget.budget_bytes),
+        _ => CheckOutcome::Healthy,
+    }
+}
+
+// The single line --check prints, e.g. "OK nginx: 5 procs in 1 trees, 42.0MB total"
+pub fn summary(snapshot: &Snapshot, outcome: &CheckOutcome) -> String {
+    let pattern = &snapshot.pattern;
+    let total: u64 = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+    match outcome {
+        CheckOutcome::Healthy => format!("OK {}: {} procs in {} trees, {} total",
+                                         pattern, snapshot.matched, snapshot.trees.len(), format_memory(total)),
+        CheckOutcome::NoMatch => format!("NO MATCH {}: no matching process", pattern),
+        CheckOutcome::Exceeded(breach) => match breach.exceeded {
+            Exceeded::Total(limit) => format!("EXCEEDED {}: {} total is over the {} limit",
+                                              pattern, format_memory(breach.total), format_memory(limit)),
+            Exceeded::Process(limit) => format!("EXCEEDED {}: {} ({}) uses {}, over the {} per-process limit",
+                                                pattern, breach.name, breach.pid, format_memory(breach.rss), format_memory(limit)),
+        },
+        CheckOutcome::OverBudget(used, budget) => format!("EXCEEDED {}: {} is over the {} budget",
+                                                          pattern, format_memory(*used), format_memory(*budget)),
+    }
+}
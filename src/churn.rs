@@ -0,0 +1,118 @@
+// Restarts of supervised processes across watch samples (--churn)
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::process::ProcessInfo;
+use crate::snapshot::{ProcessNode, ProcessTree};
+
+// (pid, start_time): one process, as opposed to a reused PID
+pub type Identity = (u32, u64);
+
+// Separates the names of a slot path, e.g. "gunicorn > gunicorn"
+const PATH_SEPARATOR: &str = " > ";
+
+// One role in the tree: the processes with a name under the same parent slot
+#[derive(Debug, Clone, Default)]
+struct Slot {
+    identities: HashSet<Identity>,
+    // Most processes seen in the slot at once, e.g. the size of a worker pool
+    max_alive: usize,
+}
+
+impl Slot {
+    // Identities beyond what the slot ever held at once came in as replacements
+    fn restarts(&self) -> usize {
+        self.identities.len().saturating_sub(self.max_alive)
+    }
+}
+
+// Distinct identities per slot over a watch session. A slot is the path of
+// names from the tree root, so a worker restarted by its supervisor lands in
+// the same slot under a new PID, and so do the workers of a restarted
+// supervisor. A pool that shrinks and grows again counts its new members as
+// restarts too.
+#[derive(Debug, Clone, Default)]
+pub struct ChurnTracker {
+    slots: BTreeMap<String, Slot>,
+}
+
+impl ChurnTracker {
+    // Record the processes of one sample, each with the slot it occupies
+    pub fn observe<I: IntoIterator<Item = (String, Identity)>>(&mut self, sample: I) {
+        let mut alive: HashMap<String, usize> = HashMap::new();
+        for (path, identity) in sample {
+            self.slots.entry(path.clone()).or_default().identities.insert(identity);
+            *alive.entry(path).or_default() += 1;
+        }
+        for (path, count) in alive {
+            if let Some(slot) = self.slots.get_mut(&path) {
+                slot.max_alive = slot.max_alive.max(count);
+            }
+        }
+    }
+
+    // Observe the processes of these trees, and with `annotate` set the
+    // restarts of each node's slot (--churn). The synthetic --merge-roots root
+    // is not a process, but its name still leads the paths below it.
+    pub fn apply(&mut self, trees: &mut [ProcessTree], processes: &HashMap<u32, ProcessInfo>, annotate: bool) {
+        let mut sample = Vec::new();
+        for tree in trees.iter() {
+            let mut stack = vec![(&tree.root, String::new())];
+            while let Some((node, parent)) = stack.pop() {
+                let path = slot_path(&parent, &node.name);
+                if !node.is_virtual {
+                    let start_time = processes.get(&node.pid).map_or(0, |proc_info| proc_info.start_time);
+                    sample.push((path.clone(), (node.pid, start_time)));
+                }
+                stack.extend(node.children.iter().map(|child| (child, path.clone())));
+            }
+        }
+        self.observe(sample);
+        if annotate {
+            for tree in trees {
+                self.annotate(&mut tree.root, "");
+            }
+        }
+    }
+
+    fn annotate(&self, node: &mut ProcessNode, parent: &str) {
+        let path = slot_path(parent, &node.name);
+        if !node.is_virtual {
+            node.restarts = Some(self.restarts(&path));
+        }
+        for child in &mut node.children {
+            self.annotate(child, &path);
+        }
+    }
+
+    // Restarts seen in one slot so far
+    pub fn restarts(&self, path: &str) -> usize {
+        self.slots.get(path).map_or(0, Slot::restarts)
+    }
+
+    // Restarts over all slots
+    pub fn total(&self) -> usize {
+        self.slots.values().map(Slot::restarts).sum()
+    }
+
+    // Slots that saw restarts, most first, then by path
+    pub fn busiest(&self) -> Vec<(&str, usize)> {
+        let mut busiest: Vec<(&str, usize)> = self.slots
+            .iter()
+            .map(|(path, slot)| (path.as_str(), slot.restarts()))
+            .filter(|&(_, restarts)| restarts > 0)
+            .collect();
+        busiest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        busiest
+    }
+}
+
+// Path of a process named `name` whose parent has the slot path `parent`;
+// empty for a tree root's parent
+pub fn slot_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}{}{}", parent, PATH_SEPARATOR, name)
+    }
+}
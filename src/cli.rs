@@ -0,0 +1,676 @@
+// Command line interface definition, shared by the binary and the tests
+
+use crate::baseline::Tolerance;
+use crate::budget;
+use crate::chart;
+use crate::exec_column::{self, ExecColumn};
+use crate::format::OutputFormat;
+use crate::nice::{self, NiceFilter};
+use crate::paths;
+use crate::record;
+use crate::roots::RootPolicy;
+use crate::since_last;
+use crate::smooth;
+use crate::sort::SortKey;
+use crate::top;
+use crate::units;
+use clap::{ArgGroup, Parser, Subcommand};
+use std::path::PathBuf;
+
+// Values accepted by --color
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+// Values accepted by --marker: how the top three memory ranks are marked
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Marker {
+    // 🥇 🥈 🥉 at the end of the line
+    #[default]
+    Emoji,
+    // #1 #2 #3 after the memory value
+    Rank,
+    // * + . in front of the memory value
+    Ascii,
+}
+
+// Values accepted by --tree-style
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TreeStyle {
+    // ├─ and └─ connectors
+    #[default]
+    Unicode,
+    // |- and `- connectors
+    Ascii,
+    // Plain spaces followed by the depth
+    Indent,
+}
+
+// Values accepted by --group-by
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    // Kubernetes pod, from --k8s
+    #[cfg_attr(not(feature = "k8s"), value(skip))]
+    Pod,
+    // Executable path, so same-named binaries from different places stay apart
+    Exe,
+    // Process group, such as a shell pipeline or a background job
+    Pgid,
+}
+
+impl GroupBy {
+    // Header of the group table
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Pod => "pod",
+            GroupBy::Exe => "exe",
+            GroupBy::Pgid => "pgid",
+        }
+    }
+}
+
+// Subcommands
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print the recorded tree-total time series for a process name
+    #[cfg(feature = "history")]
+    History {
+        /// Process name the runs were recorded for
+        #[clap(name = "PROCESS_NAME")]
+        process_name: String,
+        
+        /// SQLite history database written with --db
+        #[clap(long, value_name = "FILE", value_parser = paths::parse_path)]
+        db: PathBuf,
+    },
+    
+    /// Compare the process trees of two process names side by side
+    Compare {
+        /// First process name
+        #[clap(name = "NAME_A")]
+        name_a: String,
+        
+        /// Second process name
+        #[clap(name = "NAME_B")]
+        name_b: String,
+        
+        /// Print both snapshots and the computed diff as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// Break down the memory of one process by mapping (Linux)
+    Maps {
+        /// Process ID to inspect
+        #[clap(name = "PID")]
+        pid: u32,
+        
+        /// Number of mappings to show
+        #[clap(long, value_name = "N", default_value_t = 20)]
+        top: usize,
+        
+        /// Hide mappings with less RSS than SIZE
+        #[clap(long, value_name = "SIZE", value_parser = units::parse_size)]
+        min: Option<u64>,
+        
+        /// Print the breakdown as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// List the threads of one process with their CPU time and stack size (Linux)
+    Threads {
+        /// Process ID to inspect
+        #[clap(name = "PID")]
+        pid: u32,
+        
+        /// Number of threads to show, most CPU time first
+        #[clap(long, value_name = "N", default_value_t = 20)]
+        top: usize,
+        
+        /// Print the listing as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// Start a command and watch its process tree until it exits
+    Run {
+        /// Seconds between samples
+        #[clap(long, value_name = "SECS", default_value_t = 5)]
+        interval: u64,
+        
+        /// Command to start, with its arguments (put it after --)
+        #[clap(name = "COMMAND", required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    
+    /// Show the process tree of the terminal session memon runs in
+    Shell {
+        /// Print the snapshot as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// Rank the applications of the whole system by the memory of their process trees
+    Top {
+        /// Number of applications to show
+        #[clap(long, value_name = "N", default_value_t = top::DEFAULT_LIMIT)]
+        limit: usize,
+        
+        /// Print the ranking as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    
+    /// Summarize a --record file: totals, process peaks and a growth chart
+    #[cfg(feature = "record")]
+    Report {
+        /// Recording written with --record
+        #[clap(name = "FILE", value_parser = paths::parse_path)]
+        file: PathBuf,
+    },
+}
+
+// Command line arguments
+#[derive(Parser, Debug)]
+#[clap(
+    name = "memon",
+    version = "0.1.0",
+    author = "Your Name <you@example.com>",
+    about = "Analyzes memory usage of a process and its children",
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true,
+    group(ArgGroup::new("threshold").multiple(true)),
+    after_help = "Exit status:\n  \
+        0  Success; with --check, a match within all limits\n  \
+        1  No matching process, or an error\n  \
+        2  A --fail-if-*, --enforce-budget or --baseline limit was exceeded"
+)]
+pub struct Args {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    
+    /// Name of the process to analyze; optional with --under, --port or --show-config, and with flags that print and exit
+    #[clap(name = "PROCESS_NAME", required_unless_present_any = ["under", "port", "show_config"])]
+    #[cfg_attr(feature = "schema", clap(required_unless_present_any = ["schema"]))]
+    pub process_name: Option<String>,
+    
+    /// Verbose output: debug diagnostics on stderr (same as MEMON_LOG=debug)
+    #[clap(long)]
+    pub verbose: bool,
+    
+    /// At exit, print one key=value summary line to stderr (matched, trees, total_bytes, max_pid, duration_ms, exit)
+    #[clap(long)]
+    pub status_line: bool,
+    
+    /// At exit, print where memon spent its time to stderr: wall time per phase and the number of processes scanned, matched and in trees; with --output also written as FILE.profile.json
+    #[clap(long)]
+    pub self_profile: bool,
+    
+    /// Config file to read instead of ~/.config/memon/config.toml
+    #[clap(long, value_name = "FILE", value_parser = paths::parse_path)]
+    pub config: Option<PathBuf>,
+    
+    /// Print the options a run for PATTERN would use, merged from the config file's [pattern."..."] sections and the command line, and where each came from
+    #[clap(long, value_name = "PATTERN")]
+    pub show_config: Option<String>,
+    
+    /// Print the JSON Schema of the --json snapshot format and exit
+    #[cfg_attr(feature = "schema", clap(long))]
+    #[cfg_attr(not(feature = "schema"), clap(skip))]
+    pub schema: bool,
+    
+    /// Display process startup arguments
+    #[clap(short = 'v', long = "show-args")]
+    pub show_args: bool,
+    
+    /// Show "[redacted]" instead of the command line of processes whose name or command line contains PATTERN (repeatable)
+    #[clap(long, value_name = "PATTERN")]
+    pub redact_args: Vec<String>,
+    
+    /// Replace the hostname and user names with stable hashes in the run metadata, command lines and executable paths
+    #[clap(long)]
+    pub anonymize: bool,
+    
+    /// Never cut long process names; lines run past the name column instead
+    #[clap(long)]
+    pub full_names: bool,
+    
+    /// Mark --show-args output with a green dot before each PID and a magnifier before the arguments
+    #[clap(long)]
+    pub decorations: bool,
+    
+    /// Disable colored output
+    #[clap(long)]
+    pub no_color: bool,
+    
+    /// When to use colored output
+    #[clap(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorWhen,
+    
+    /// Write the output to a file instead of stdout
+    #[clap(long, value_name = "PATH", value_parser = paths::parse_path)]
+    pub output: Option<PathBuf>,
+    
+    /// Create missing parent directories of the --output file
+    #[clap(long, requires = "output")]
+    pub mkdir: bool,
+    
+    /// Append to the --output file instead of rewriting it
+    #[clap(long, requires = "output")]
+    pub append: bool,
+    
+    /// Page text output on a terminal even when it fits on the screen ($MEMON_PAGER, then $PAGER, else "less -RFX")
+    #[clap(long, overrides_with = "no_pager", conflicts_with_all = ["watch", "json"])]
+    pub pager: bool,
+    
+    /// Never page the output; by default text taller than the terminal is paged
+    #[clap(long, overrides_with = "pager")]
+    pub no_pager: bool,
+    
+    /// Output format (defaults to the --output extension, then text)
+    #[clap(long, value_enum, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+    
+    /// Shorthand for --format json
+    #[clap(long, conflicts_with = "format")]
+    pub json: bool,
+    
+    /// Print only KEY=value lines for `eval` in a POSIX shell: MEMON_TOTAL_BYTES, MEMON_PROC_COUNT, MEMON_MAX_PID, MEMON_MAX_BYTES, and MEMON_TREE_<N>_* per tree when there are several
+    #[clap(long, conflicts_with_all = ["format", "json", "overview", "watch", "check"])]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    #[cfg_attr(feature = "record", clap(conflicts_with = "record"))]
+    pub shell_vars: bool,
+    
+    /// Fail instead of warning when --format conflicts with the --output extension
+    #[clap(long)]
+    pub strict_format: bool,
+    
+    /// Record every run (or watch iteration) into this SQLite history database
+    #[cfg_attr(feature = "history", clap(long, value_name = "FILE", value_parser = paths::parse_path))]
+    #[cfg_attr(not(feature = "history"), clap(skip))]
+    pub db: Option<PathBuf>,
+    
+    /// Exit with status 2 when all matched trees together use more than SIZE (e.g. 2G, 512MB)
+    #[clap(long, value_name = "SIZE", value_parser = units::parse_size, group = "threshold")]
+    pub fail_if_total: Option<u64>,
+    
+    /// Exit with status 2 when any single process uses more than SIZE (e.g. 2G, 512MB)
+    #[clap(long, value_name = "SIZE", value_parser = units::parse_size, group = "threshold")]
+    pub fail_if_process: Option<u64>,
+    
+    /// Memory budget for the matched trees, shown as a gauge in the summary (e.g. 6G)
+    #[clap(long, value_name = "SIZE", value_parser = budget::parse_budget)]
+    pub budget: Option<u64>,
+    
+    /// Exit with status 2 when the matched trees use more than the --budget
+    #[clap(long, requires = "budget")]
+    pub enforce_budget: bool,
+    
+    /// Run this shell command when a --fail-if-* threshold is crossed
+    #[clap(long, value_name = "CMD", requires = "threshold")]
+    pub on_threshold: Option<String>,
+    
+    /// POST the JSON snapshot to this URL when a --fail-if-* threshold is crossed
+    #[cfg_attr(feature = "notify", clap(long, value_name = "URL", requires = "threshold"))]
+    #[cfg_attr(not(feature = "notify"), clap(skip))]
+    pub webhook: Option<String>,
+    
+    /// Compare the tree total against a snapshot saved with --json and fail on growth
+    #[clap(long, value_name = "FILE", value_parser = paths::parse_path, conflicts_with = "watch")]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    pub baseline: Option<PathBuf>,
+    
+    /// Allowed growth over the baseline: a percentage (10%) or an absolute size (+200M)
+    #[clap(long, value_name = "TOLERANCE", default_value = "10%", value_parser = Tolerance::parse, requires = "baseline")]
+    pub tolerance: Tolerance,
+    
+    /// Also compare the totals of each process name against the baseline
+    #[clap(long, requires = "baseline")]
+    pub by_name: bool,
+    
+    /// Refresh the baseline file with the current snapshot when the check passes
+    #[clap(long, requires = "baseline")]
+    pub save: bool,
+    
+    /// Also write the trees as folded stacks for inferno-flamegraph or flamegraph.pl; rewritten every sample in watch mode
+    #[clap(long, value_name = "FILE", value_parser = paths::parse_path)]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    #[cfg_attr(feature = "record", clap(conflicts_with = "record"))]
+    pub folded: Option<PathBuf>,
+    
+    /// Also write the trees as an SVG treemap, areas following memory; rewritten every sample in watch mode
+    #[clap(long, value_name = "FILE", value_parser = paths::parse_path)]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    #[cfg_attr(feature = "record", clap(conflicts_with = "record"))]
+    pub svg: Option<PathBuf>,
+    
+    /// Serve live JSON snapshots over HTTP on ADDR (GET /snapshot, /healthz, /stream)
+    #[cfg_attr(feature = "http", clap(long, value_name = "ADDR", conflicts_with = "watch"))]
+    #[cfg_attr(not(feature = "http"), clap(skip))]
+    pub http: Option<String>,
+    
+    /// Seconds between server-sent events on GET /stream
+    #[cfg_attr(feature = "http", clap(long, value_name = "SECS", default_value_t = 5, requires = "http"))]
+    #[cfg_attr(not(feature = "http"), clap(skip = 5u64))]
+    pub stream_interval: u64,
+    
+    /// Show a desktop notification when a --fail-if-* threshold is crossed
+    #[cfg_attr(feature = "notify", clap(long, requires = "threshold"))]
+    #[cfg_attr(not(feature = "notify"), clap(skip))]
+    pub notify: bool,
+    
+    /// Minimum seconds between two threshold alerts
+    #[clap(long, value_name = "SECS", default_value_t = 300)]
+    pub hook_cooldown: u64,
+    
+    /// Health probe: print one summary line and exit 0 (healthy), 1 (no match) or 2 (a limit exceeded)
+    #[clap(long, conflicts_with = "watch")]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    pub check: bool,
+    
+    /// Exit 1 with a list of what failed when any data could not be collected (unreadable memory, command lines, cgroups, processes that exited mid-scan) instead of reporting what was read
+    #[clap(long, conflicts_with = "watch")]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    #[cfg_attr(feature = "record", clap(conflicts_with = "record"))]
+    pub strict: bool,
+    
+    /// Watch mode - continuously update every N seconds
+    #[clap(short, long)]
+    pub watch: Option<u64>,
+    
+    /// In watch mode, also sample the trees every MS milliseconds between renders and report the peak total and the short-lived processes each render missed
+    #[clap(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(10..), requires = "watch")]
+    pub fine: Option<u64>,
+    
+    /// Smooth RSS readings across watch samples with this weight for the newest reading (0 < ALPHA <= 1)
+    #[clap(long, value_name = "ALPHA", value_parser = smooth::parse_alpha)]
+    pub smooth: Option<f64>,
+    
+    /// In watch mode, print the full tree once, then only started, exited and changed processes
+    #[clap(long, requires = "watch")]
+    pub changes_only: bool,
+    
+    /// Smallest RSS change reported by --changes-only, e.g. 1M or 512K
+    #[clap(long, value_name = "SIZE", value_parser = units::parse_size, default_value = "1M")]
+    pub change_threshold: u64,
+    
+    /// Rows of the tree-total chart above the watch-mode report
+    #[clap(long, value_name = "N", value_parser = chart::parse_height, default_value_t = chart::DEFAULT_HEIGHT, requires = "watch")]
+    pub chart_height: usize,
+    
+    /// Leave out the tree-total chart in watch mode
+    #[clap(long, requires = "watch")]
+    pub no_chart: bool,
+    
+    /// In watch mode, stop showing workers that were re-parented out of a matched tree when their parent died
+    #[clap(long, requires = "watch")]
+    pub no_follow_orphans: bool,
+    
+    /// In watch mode, stay on the roots of the first sample and show them exiting, instead of following a root restarted under a new PID
+    #[clap(long, requires = "watch")]
+    pub no_reroot: bool,
+    
+    /// Record snapshots for this long (e.g. 90s, 10m, 1h) into the --out file, at the --watch interval or every 5s
+    #[cfg_attr(feature = "record", clap(long, value_name = "DURATION", value_parser = record::parse_duration, requires = "out", conflicts_with_all = ["check", "changes_only"]))]
+    #[cfg_attr(all(feature = "record", feature = "http"), clap(conflicts_with = "http"))]
+    #[cfg_attr(not(feature = "record"), clap(skip))]
+    pub record: Option<u64>,
+    
+    /// Recording file written by --record (zstd-compressed NDJSON)
+    #[cfg_attr(feature = "record", clap(long, value_name = "FILE", value_parser = paths::parse_path, requires = "record"))]
+    #[cfg_attr(not(feature = "record"), clap(skip))]
+    pub out: Option<PathBuf>,
+    
+    /// How to mark the three largest processes: emoji, rank (#1..#3) or ascii (* + .)
+    #[clap(long, value_enum, value_name = "STYLE", default_value_t = Marker::Emoji)]
+    pub marker: Marker,
+    
+    /// Tree drawing: unicode (├─ └─), ascii (|- `-) or indent (spaces and depth numbers)
+    #[clap(long, value_enum, value_name = "STYLE", default_value_t = TreeStyle::Unicode)]
+    pub tree_style: TreeStyle,
+    
+    /// Width of each tree level in columns
+    #[clap(long, value_name = "N", default_value_t = 2)]
+    pub indent: usize,
+    
+    /// Size unit: auto (MB/GB), b (exact bytes) or pages (exact pages of the system page size)
+    #[clap(long, value_enum, value_name = "UNIT", default_value_t = units::Unit::Auto)]
+    pub unit: units::Unit,
+    
+    /// Do not group digits of exact byte and process counts (48211234816 instead of 48,211,234,816)
+    #[clap(long)]
+    pub no_group: bool,
+    
+    /// Make PIDs clickable links in terminals that support OSC 8 hyperlinks
+    #[clap(long)]
+    pub hyperlinks: bool,
+    
+    /// Show direct children and total descendants for each process
+    #[clap(long)]
+    pub counts: bool,
+    
+    /// Show each process's share of the tree total
+    #[clap(long)]
+    pub percent: bool,
+    
+    /// Show each process subtree's share of its parent's subtree
+    #[clap(long)]
+    pub percent_of_parent: bool,
+    
+    /// Show locked (mlock) and dirty/clean memory for each process (Linux)
+    #[clap(long)]
+    pub mem_details: bool,
+    
+    /// Show transparent and explicit huge pages for each process (Linux)
+    #[clap(long)]
+    pub hugepages: bool,
+    
+    /// Show each process's address-space rlimit and how much of it is used (Linux)
+    #[clap(long)]
+    pub limits: bool,
+    
+    /// Show the TCP ports each process listens on (Linux)
+    #[clap(long)]
+    pub ports: bool,
+    
+    /// Show the heap size set with -Xmx next to each JVM (reads command lines)
+    #[clap(long)]
+    pub jvm: bool,
+    
+    /// Like --jvm, plus current heap usage from `jcmd <pid> GC.heap_info` where jcmd is available
+    #[clap(long)]
+    pub jvm_probe: bool,
+    
+    /// Annotate well-known processes with what they are, e.g. "Web Content (Firefox tab renderer)"; extend or override in the config file
+    #[clap(long)]
+    pub labels: bool,
+    
+    /// Show GPU memory for each process (NVIDIA)
+    #[cfg_attr(feature = "nvml", clap(long))]
+    #[cfg_attr(not(feature = "nvml"), clap(skip))]
+    pub gpu: bool,
+    
+    /// Also leave out the processes memon descends from, such as the shell it was started from
+    #[clap(long, conflicts_with = "include_self")]
+    pub exclude_self_tree: bool,
+    
+    /// Match memon's own process too; it is left out by default
+    #[clap(long)]
+    pub include_self: bool,
+    
+    /// Show the highest RSS of each process and tree: the kernel's lifetime peak on Linux, else the highest seen while watching
+    #[clap(long)]
+    pub peak: bool,
+    
+    /// In watch mode, show how long each process's memory has been stable, or how recently it changed
+    #[clap(long, requires = "watch")]
+    pub stability: bool,
+    
+    /// In watch mode, show how often each process's role was taken over by a new PID, e.g. a worker its supervisor restarted
+    #[clap(long, requires = "watch")]
+    pub churn: bool,
+    
+    /// In watch mode, show the lowest, average and highest RSS of each process so far next to the live value, from the second sample on
+    #[clap(long, requires = "watch")]
+    pub history_cols: bool,
+    
+    /// Show the nice value of each process (Linux)
+    #[clap(long)]
+    pub nice: bool,
+    
+    /// Show the process group ID of each process, shared by the members of a shell pipeline or job (Linux, macOS)
+    #[clap(long)]
+    pub pgid: bool,
+    
+    /// Mark processes whose effective user differs from the tree root's, setuid ones and those holding capabilities without being root, and list them under each tree (Linux)
+    #[clap(long)]
+    pub privs: bool,
+    
+    /// Extra column NAME filled with the first line CMD prints, run through the shell once per process with MEMON_PID set (2s timeout; "-" when it fails)
+    #[clap(long, value_name = "NAME=CMD", value_parser = exec_column::parse_exec_column)]
+    pub exec_column: Option<ExecColumn>,
+    
+    /// Show how much each process and tree grew since the last run with the same pattern on this host, which is cached automatically
+    #[clap(long, conflicts_with = "from_file")]
+    pub since_last: bool,
+    
+    /// Ignore a cached --since-last run older than this (90, 30s, 10m, 1h)
+    #[clap(long, value_name = "DURATION", default_value = since_last::DEFAULT_TTL, value_parser = record::parse_duration, requires = "since_last")]
+    pub since_last_ttl: u64,
+    
+    /// Clear the cached --since-last run of the pattern and exit
+    #[clap(long, conflicts_with = "since_last")]
+    pub forget: bool,
+    
+    /// Let --exec-column run for more than 200 processes
+    #[clap(long, requires = "exec_column")]
+    pub force: bool,
+    
+    /// Only show processes whose nice value passes OP N, e.g. '<0' for boosted ones, with the ancestors that connect them (Linux)
+    #[clap(long, value_name = "FILTER", value_parser = nice::parse_filter, allow_hyphen_values = true)]
+    pub only_nice: Option<NiceFilter>,
+    
+    /// Show the Kubernetes namespace/pod/container of containerized processes (Linux; names need the kubelet read-only API)
+    #[cfg_attr(feature = "k8s", clap(long))]
+    #[cfg_attr(not(feature = "k8s"), clap(skip))]
+    pub k8s: bool,
+    
+    /// Show the controlling terminal of each process (Linux)
+    #[clap(long)]
+    pub show_tty: bool,
+    
+    /// Only show trees in the same session as memon, e.g. those started from this terminal
+    #[clap(long, conflicts_with = "from_file")]
+    pub current_session_only: bool,
+    
+    /// Keep a match under a matched process of another user and session in that process's tree, instead of giving it a tree of its own
+    #[clap(long)]
+    pub cross_user_trees: bool,
+    
+    /// After each tree, show process count and memory per depth
+    #[clap(long)]
+    pub by_depth: bool,
+    
+    /// Head each tree with the root's own memory against its descendants', e.g. "nginx (pid 811): 8.0MB self + 740.0MB descendants = 748.0MB"
+    #[clap(long)]
+    pub breakdown: bool,
+    
+    /// Also total each tree with shared memory (e.g. shm segments) counted once instead of in every process's RSS (Linux)
+    #[clap(long)]
+    pub dedupe_shared: bool,
+    
+    /// After the last tree, show process count and memory per group over all trees
+    #[clap(long, value_enum, value_name = "KEY")]
+    #[cfg_attr(feature = "k8s", clap(requires_if("pod", "k8s")))]
+    pub group_by: Option<GroupBy>,
+    
+    /// Show sibling subtrees with the same shape (process names at each depth) once, with the group's min/median/max memory
+    #[clap(long)]
+    pub dedupe_subtrees: bool,
+    
+    /// Only match processes below this PID (and the PID itself); without a PROCESS_NAME, show its whole tree
+    #[clap(long, value_name = "PID")]
+    pub under: Option<u32>,
+    
+    /// Only match processes listening on this TCP port; repeat for several ports, any of which may match (Linux)
+    #[clap(long, value_name = "PORT", conflicts_with_all = ["from_file", "ssh"])]
+    pub port: Vec<u16>,
+    
+    /// Also total only the processes whose own name matched, next to the whole-tree total
+    #[clap(long)]
+    pub matched_only_totals: bool,
+    
+    /// Also show each tree root's parent with all of its children, the other children collapsed to their totals
+    #[clap(long)]
+    pub siblings: bool,
+    
+    /// Give the top-3 highlights only to processes whose own name matched
+    #[clap(long)]
+    pub highlight_matched: bool,
+    
+    /// Which matches become tree roots: strict (a match whose parent is not matched), matched-top (the highest matched ancestor, across unmatched processes in between) or all-matched (every match, without nesting)
+    #[clap(long, value_enum, value_name = "POLICY", default_value_t = RootPolicy::Strict)]
+    pub roots: RootPolicy,
+    
+    /// Order of trees and children: pid, or delta for the largest growth since the previous sample first (watch mode only)
+    #[clap(long, value_enum, value_name = "KEY", default_value_t = SortKey::Pid)]
+    pub sort: SortKey,
+    
+    /// Put every matched tree under one synthetic "<pattern> (merged)" root, for a single tree and summary
+    #[clap(long)]
+    pub merge_roots: bool,
+    
+    /// Print only the Nth tree (1-based, in root PID order)
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["tree_root", "max_trees", "first"])]
+    pub tree: Option<u64>,
+    
+    /// Print only the tree whose root process has this PID
+    #[clap(long, value_name = "PID", conflicts_with_all = ["max_trees", "first"])]
+    pub tree_root: Option<u32>,
+    
+    /// Print only the N largest trees by total memory
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_trees: Option<u64>,
+    
+    /// Print at most N children under each process, the largest by RSS, and one line summing up the rest; 0 prints them all
+    #[clap(long, value_name = "N", default_value_t = 200)]
+    pub max_children: usize,
+    
+    /// Apply --max-children to JSON output too, with the left-out children summed up in `more_children`
+    #[clap(long)]
+    pub max_children_json: bool,
+    
+    /// Print one line per tree (root name and PID, process count, total, largest descendant), largest total first, and nothing else
+    #[clap(long, conflicts_with_all = ["tree", "tree_root", "watch", "check"])]
+    #[cfg_attr(feature = "http", clap(conflicts_with = "http"))]
+    #[cfg_attr(feature = "record", clap(conflicts_with = "record"))]
+    pub overview: bool,
+    
+    /// Print only the largest tree (same as --max-trees 1)
+    #[clap(long, conflicts_with = "max_trees")]
+    pub first: bool,
+    
+    /// Hide subtrees contributing less than this percentage of the tree total
+    #[clap(long, value_name = "PERCENT", value_parser = units::parse_percent)]
+    pub min_percent: Option<f64>,
+    
+    /// Read the process table from FILE (PID PPID RSS NAME per line) instead of scanning the system
+    #[clap(long, value_name = "FILE", value_parser = paths::parse_path)]
+    pub from_file: Option<PathBuf>,
+    
+    /// Read the process table of a remote Linux host over ssh (e.g. user@host), using your ssh config and agent
+    #[clap(long, value_name = "DESTINATION", conflicts_with_all = ["from_file", "current_session_only"])]
+    pub ssh: Option<String>,
+    
+    /// Number of threads for per-process reads (defaults to available CPUs, max 8)
+    #[clap(long, value_name = "N")]
+    pub jobs: Option<usize>,
+    
+    /// Abort process collection after N seconds, showing partial results where possible
+    #[clap(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+}
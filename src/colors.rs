@@ -0,0 +1,89 @@
+// ANSI color codes for cross-platform colored output
+
+// Reset
+pub const RESET: &str = "\x1b[0m";
+
+// Foreground colors
+pub const CYAN: &str = "\x1b[36m";
+pub const AMBER: &str = "\x1b[33m";
+pub const RED: &str = "\x1b[31m";
+
+// Styles
+pub const DIM: &str = "\x1b[2m";
+
+// Background colors - light gray background
+pub const BG_LIGHT_GRAY: &str = "\x1b[47m";  // Light gray background
+
+// Foreground colors - dark gray for contrast
+pub const DARK_GRAY: &str = "\x1b[30m";  // Dark gray foreground
+
+// Styles - removed bold for cleaner output
+// pub const BOLD: &str = "\1b[1m"; // Removed
+
+// Color requests from the environment, read once at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColorEnv {
+    // CLICOLOR_FORCE or FORCE_COLOR asks for color even without a terminal
+    pub force: bool,
+    // NO_COLOR, CLICOLOR=0 or FORCE_COLOR=0 asks for none
+    pub disable: bool,
+}
+
+impl ColorEnv {
+    pub fn from_env() -> Self {
+        ColorEnv::from_lookup(|name| std::env::var(name).ok())
+    }
+    
+    // `lookup` returns the value of an environment variable, if set
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let clicolor_force = lookup("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+        let force_color = lookup("FORCE_COLOR");
+        // FORCE_COLOR=0 and FORCE_COLOR=false turn color off, as in the npm ecosystem
+        let force_color_off = force_color.as_deref().is_some_and(|value| value == "0" || value == "false");
+        let force_color_on = force_color.is_some() && !force_color_off;
+        // no-color.org: set and not empty
+        let no_color = lookup("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let clicolor_off = lookup("CLICOLOR").as_deref() == Some("0");
+        ColorEnv {
+            force: clicolor_force || force_color_on,
+            disable: no_color || clicolor_off || force_color_off,
+        }
+    }
+}
+
+// Whether to color the output. Precedence, highest first: the --color flag
+// (`flag`, None for auto), CLICOLOR_FORCE/FORCE_COLOR, NO_COLOR/CLICOLOR=0,
+// then whether the output is a terminal.
+pub fn should_use_colors(flag: Option<bool>, env: ColorEnv, is_terminal: bool) -> bool {
+    if let Some(enabled) = flag {
+        return enabled;
+    }
+    if env.force {
+        return true;
+    }
+    if env.disable {
+        return false;
+    }
+    is_terminal
+}
+
+// Whether the terminal is known to render OSC 8 hyperlinks, from $TERM and
+// $TERM_PROGRAM. Terminals that do not understand them print the escape
+// sequence as garbage, so only an explicit denylist is kept out.
+pub fn hyperlinks_supported(term: Option<&str>, term_program: Option<&str>) -> bool {
+    let term = term.unwrap_or("");
+    if term.is_empty() || term == "dumb" || term == "linux" || term.starts_with("screen") {
+        return false;
+    }
+    !matches!(term_program, Some("Apple_Terminal"))
+}
+
+// Wrap `text` in an OSC 8 hyperlink to `uri`; the sequence itself takes no columns
+pub fn hyperlink(uri: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", uri, text)
+}
+
+// Functions to combine colors - removed as no longer used
+// pub fn combine_colors(color1: &str, color2: &str) -> String {
+//     format!("{}{}", color1, color2)
+// }
@@ -0,0 +1,159 @@
+// Side-by-side comparison of two process forests (`memon compare`)
+
+pub use crate::snapshot::DepthTotal;
+use crate::snapshot::Snapshot;
+use crate::units::format_memory;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+// Figures compared between the two forests
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForestSummary {
+    pub processes: usize,
+    pub total_bytes: u64,
+    pub average_bytes: u64,
+    pub max_process: Option<MaxProcess>,
+    // Process count and memory at each depth below the roots
+    pub by_depth: Vec<DepthTotal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaxProcess {
+    pub pid: u32,
+    pub name: String,
+    pub rss_bytes: u64,
+}
+
+// b minus a for every compared figure
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diff {
+    pub processes: i64,
+    pub total_bytes: i64,
+    pub average_bytes: i64,
+    pub max_process_bytes: i64,
+    pub by_depth: Vec<DepthDiff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DepthDiff {
+    pub depth: usize,
+    pub processes: i64,
+    pub bytes: i64,
+}
+
+// --json document: both snapshots plus the computed diff
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub a: Snapshot,
+    pub b: Snapshot,
+    pub diff: Diff,
+}
+
+impl ForestSummary {
+    pub fn of(snapshot: &Snapshot) -> Self {
+        let mut processes = 0;
+        let mut total_bytes = 0;
+        let mut max_process: Option<MaxProcess> = None;
+        for (node, _) in snapshot.trees.iter().flat_map(|tree| tree.iter_preorder()) {
+            processes += 1;
+            total_bytes += node.rss_bytes;
+            if max_process.as_ref().is_none_or(|max| node.rss_bytes > max.rss_bytes) {
+                max_process = Some(MaxProcess { pid: node.pid, name: node.name.clone(), rss_bytes: node.rss_bytes });
+            }
+        }
+        
+        // Depth totals of every tree, added up level by level
+        let mut by_depth: Vec<DepthTotal> = Vec::new();
+        for total in snapshot.trees.iter().flat_map(|tree| tree.depth_totals()) {
+            match by_depth.get_mut(total.depth) {
+                Some(sum) => {
+                    sum.processes += total.processes;
+                    sum.bytes += total.bytes;
+                }
+                None => by_depth.push(total),
+            }
+        }
+        let average_bytes = if processes > 0 { total_bytes / processes as u64 } else { 0 };
+        ForestSummary { processes, total_bytes, average_bytes, max_process, by_depth }
+    }
+}
+
+pub fn diff(a: &ForestSummary, b: &ForestSummary) -> Diff {
+    let delta = |a: u64, b: u64| b as i64 - a as i64;
+    let max_bytes = |summary: &ForestSummary| summary.max_process.as_ref().map_or(0, |max| max.rss_bytes);
+    let depth_at = |summary: &ForestSummary, depth: usize| summary.by_depth.get(depth).map_or((0, 0), |total| (total.processes, total.bytes));
+    let by_depth = (0..a.by_depth.len().max(b.by_depth.len()))
+        .map(|depth| {
+            let (a_procs, a_bytes) = depth_at(a, depth);
+            let (b_procs, b_bytes) = depth_at(b, depth);
+            DepthDiff { depth, processes: b_procs as i64 - a_procs as i64, bytes: delta(a_bytes, b_bytes) }
+        })
+        .collect();
+    Diff {
+        processes: b.processes as i64 - a.processes as i64,
+        total_bytes: delta(a.total_bytes, b.total_bytes),
+        average_bytes: delta(a.average_bytes, b.average_bytes),
+        max_process_bytes: delta(max_bytes(a), max_bytes(b)),
+        by_depth,
+    }
+}
+
+// Two-column table: one row per figure, one column per forest, then b - a
+pub fn render_text(a: &Snapshot, b: &Snapshot) -> String {
+    let (sa, sb) = (ForestSummary::of(a), ForestSummary::of(b));
+    let d = diff(&sa, &sb);
+    let max_cell = |summary: &ForestSummary| {
+        summary.max_process.as_ref().map_or("-".to_string(), |max| format!("{} ({}) {}", max.name, max.pid, format_memory(max.rss_bytes)))
+    };
+    let depth_cell = |summary: &ForestSummary, depth: usize| {
+        summary.by_depth.get(depth).map_or("-".to_string(), |total| format!("{} procs {}", total.processes, format_memory(total.bytes)))
+    };
+    
+    let mut rows = vec![
+        [String::new(), a.pattern.clone(), b.pattern.clone(), "diff".to_string()],
+        ["processes".to_string(), sa.processes.to_string(), sb.processes.to_string(), signed_count(d.processes)],
+        ["total".to_string(), format_memory(sa.total_bytes), format_memory(sb.total_bytes), signed_bytes(d.total_bytes)],
+        ["average".to_string(), format_memory(sa.average_bytes), format_memory(sb.average_bytes), signed_bytes(d.average_bytes)],
+        ["max process".to_string(), max_cell(&sa), max_cell(&sb), signed_bytes(d.max_process_bytes)],
+    ];
+    for depth_diff in &d.by_depth {
+        rows.push([
+            format!("depth {}", depth_diff.depth),
+            depth_cell(&sa, depth_diff.depth),
+            depth_cell(&sb, depth_diff.depth),
+            signed_bytes(depth_diff.bytes),
+        ]);
+    }
+    
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in &rows {
+        let _ = writeln!(out, "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}", row[0], row[1], row[2], row[3],
+                         w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    }
+    out
+}
+
+pub fn render_json(a: Snapshot, b: Snapshot) -> String {
+    let diff = diff(&ForestSummary::of(&a), &ForestSummary::of(&b));
+    let mut json = serde_json::to_string_pretty(&Comparison { a, b, diff }).expect("comparison serializes to JSON");
+    json.push('\n');
+    json
+}
+
+fn signed_count(value: i64) -> String {
+    if value > 0 { format!("+{}", value) } else { value.to_string() }
+}
+
+fn signed_bytes(value: i64) -> String {
+    match value {
+        0 => "0B".to_string(),
+        v if v > 0 => format!("+{}", format_memory(v as u64)),
+        v => format!("-{}", format_memory(v.unsigned_abs())),
+    }
+}
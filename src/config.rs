@@ -0,0 +1,151 @@
+// The memon config file: TOML at $XDG_CONFIG_HOME/memon/config.toml, or
+// ~/.config/memon/config.toml, unless --config names another file
+//
+//     [labels]
+//     "Web Content" = "Firefox tab renderer"
+//     "my-worker*" = "Queue worker"
+//
+//     [pattern."postgres"]
+//     peak = true
+//     group-by = "exe"
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::labels;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Parse(path, message) => write!(f, "{}: invalid config: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// The value of one option in a [pattern."..."] section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    // `true` passes a flag such as --peak; `false` leaves it out
+    Switch(bool),
+    // One value, or an array for an option that can be repeated
+    Values(Vec<String>),
+}
+
+// A [pattern."<pattern>"] section: options, by long name, for the runs whose
+// PROCESS_NAME matches the pattern, where `*` matches any run of characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternSection {
+    pub pattern: String,
+    pub options: Vec<(String, OptionValue)>,
+}
+
+impl PatternSection {
+    // `[pattern."postgres"]`, for messages
+    pub fn header(&self) -> String {
+        format!("[pattern.\"{}\"]", self.pattern)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    // (name pattern, label) pairs from [labels], in file order
+    pub labels: Vec<(String, String)>,
+    // [pattern."..."] sections, in file order
+    pub patterns: Vec<PatternSection>,
+}
+
+impl Config {
+    // Parse the text of a config file; unknown tables are ignored so older
+    // versions of memon can read newer files
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let document: DocumentMut = text.parse().map_err(|err: toml_edit::TomlError| err.message().to_string())?;
+        let mut config = Config::default();
+        if let Some(labels) = document.get("labels") {
+            let table = labels.as_table_like().ok_or("labels must be a table")?;
+            for (pattern, label) in table.iter() {
+                let label = label.as_str().ok_or_else(|| format!("label for \"{}\" must be a string", pattern))?;
+                config.labels.push((pattern.to_string(), label.to_string()));
+            }
+        }
+        if let Some(patterns) = document.get("pattern") {
+            let table = patterns.as_table_like().ok_or("pattern must be a table")?;
+            for (pattern, options) in table.iter() {
+                let options = options.as_table_like().ok_or_else(|| format!("pattern \"{}\" must be a table", pattern))?;
+                let options = options
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = option_value(value).ok_or_else(|| format!("option \"{}\" of pattern \"{}\" must be a boolean, number, string or array", key, pattern))?;
+                        Ok((key.replace('_', "-"), value))
+                    })
+                    .collect::<Result<_, String>>()?;
+                config.patterns.push(PatternSection { pattern: pattern.to_string(), options });
+            }
+        }
+        Ok(config)
+    }
+
+    // The sections whose pattern matches `process_name`, in file order
+    pub fn sections_for(&self, process_name: &str) -> Vec<&PatternSection> {
+        self.patterns.iter().filter(|section| labels::matches(&section.pattern, process_name)).collect()
+    }
+
+    // Read `path`, or the default location when None. A missing default file
+    // is an empty config; a missing explicit one is an error.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !explicit => return Ok(Config::default()),
+            Err(err) => return Err(ConfigError::Io(path, err)),
+        };
+        log::debug!("config: {}", path.display());
+        Config::parse(&text).map_err(|message| ConfigError::Parse(path, message))
+    }
+}
+
+fn option_value(item: &Item) -> Option<OptionValue> {
+    if let Some(switch) = item.as_bool() {
+        return Some(OptionValue::Switch(switch));
+    }
+    let scalar = |value: &Value| match value {
+        Value::String(text) => Some(text.value().clone()),
+        Value::Integer(number) => Some(number.value().to_string()),
+        Value::Float(number) => Some(number.value().to_string()),
+        _ => None,
+    };
+    match item.as_value()? {
+        Value::Array(array) => array.iter().map(scalar).collect::<Option<_>>().map(OptionValue::Values),
+        value => scalar(value).map(|value| OptionValue::Values(vec![value])),
+    }
+}
+
+// Where the config file lives when --config is not given
+pub fn default_path() -> Option<PathBuf> {
+    config_path(|name| std::env::var_os(name).map(PathBuf::from))
+}
+
+// `lookup` returns the value of an environment variable, if set
+pub fn config_path(lookup: impl Fn(&str) -> Option<PathBuf>) -> Option<PathBuf> {
+    let base = lookup("XDG_CONFIG_HOME")
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| lookup("HOME").map(|home| home.join(".config")))?;
+    Some(base.join("memon").join("config.toml"))
+}
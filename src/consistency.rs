@@ -0,0 +1,82 @@
+// Checks of the process table across refreshes, for PID reuse and
+// re-parenting that would otherwise draw impossible trees
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::churn::Identity;
+use crate::process::ProcessInfo;
+
+// Something that changed about a PID since the previous refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableEvent {
+    // The PID now belongs to another process
+    Reused { pid: u32, old_start: u64, new_start: u64 },
+    // The same process under another parent, e.g. adopted after its parent died
+    Reparented { pid: u32, old_parent: Option<u32>, new_parent: Option<u32> },
+}
+
+impl TableEvent {
+    // The process that is gone for a reused PID
+    pub fn stale_identity(&self) -> Option<Identity> {
+        match *self {
+            TableEvent::Reused { pid, old_start, .. } => Some((pid, old_start)),
+            TableEvent::Reparented { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for TableEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parent = |parent: Option<u32>| parent.map_or("none".to_string(), |pid| pid.to_string());
+        match *self {
+            TableEvent::Reused { pid, old_start, new_start } => {
+                write!(f, "PID {} was reused: started at {}, before at {}", pid, new_start, old_start)
+            }
+            TableEvent::Reparented { pid, old_parent, new_parent } => {
+                write!(f, "PID {} changed parent from {} to {}", pid, parent(old_parent), parent(new_parent))
+            }
+        }
+    }
+}
+
+// Start time and parent of every PID of the previous refresh
+#[derive(Debug, Clone, Default)]
+pub struct TableCheck {
+    previous: HashMap<u32, (u64, Option<u32>)>,
+}
+
+impl TableCheck {
+    // Compare a fresh table with the previous one, in PID order, and keep it
+    // for the next refresh
+    pub fn check(&mut self, processes: &HashMap<u32, ProcessInfo>) -> Vec<TableEvent> {
+        let mut events: Vec<TableEvent> = processes
+            .values()
+            .filter_map(|proc_info| {
+                let &(old_start, old_parent) = self.previous.get(&proc_info.pid)?;
+                if old_start != proc_info.start_time {
+                    Some(TableEvent::Reused { pid: proc_info.pid, old_start, new_start: proc_info.start_time })
+                } else if old_parent != proc_info.parent_pid {
+                    Some(TableEvent::Reparented { pid: proc_info.pid, old_parent, new_parent: proc_info.parent_pid })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        events.sort_unstable_by_key(|event| match *event {
+            TableEvent::Reused { pid, .. } | TableEvent::Reparented { pid, .. } => pid,
+        });
+        self.previous = processes
+            .values()
+            .map(|proc_info| (proc_info.pid, (proc_info.start_time, proc_info.parent_pid)))
+            .collect();
+        events
+    }
+}
+
+// Whether `child` can be a child of `parent`: no process starts before its
+// parent, so a parent PID held by a younger process was reused after the
+// real parent exited. Unknown start times (0, as in table files) pass.
+pub fn plausible_parent(parent: &ProcessInfo, child: &ProcessInfo) -> bool {
+    parent.start_time == 0 || child.start_time == 0 || parent.start_time <= child.start_time
+}
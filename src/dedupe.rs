@@ -0,0 +1,80 @@
+// Folding structurally identical sibling subtrees into one (--dedupe-subtrees)
+
+use std::collections::HashMap;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::ProcessNode;
+
+// The siblings a representative subtree stands for, itself included. Memory
+// values are subtree totals (cumulative bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SimilarGroup {
+    pub count: usize,
+    pub total_bytes: u64,
+    pub min_bytes: u64,
+    pub median_bytes: u64,
+    pub max_bytes: u64,
+}
+
+impl SimilarGroup {
+    fn new(totals: &mut [u64]) -> Self {
+        totals.sort_unstable();
+        let count = totals.len();
+        let median_bytes = match count {
+            0 => 0,
+            _ if count % 2 == 1 => totals[count / 2],
+            _ => (totals[count / 2 - 1] + totals[count / 2]) / 2,
+        };
+        SimilarGroup {
+            count,
+            total_bytes: totals.iter().sum(),
+            min_bytes: totals.first().copied().unwrap_or(0),
+            median_bytes,
+            max_bytes: totals.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+// Structure of a subtree: the sorted multiset of (depth, name) of its
+// processes, the root at depth 0. PIDs and memory do not take part.
+pub fn fingerprint(node: &ProcessNode) -> Vec<(usize, String)> {
+    let mut entries = Vec::new();
+    let mut stack = vec![(node, 0)];
+    while let Some((current, depth)) = stack.pop() {
+        entries.push((depth, current.name.clone()));
+        stack.extend(current.children.iter().map(|child| (child, depth + 1)));
+    }
+    entries.sort_unstable();
+    entries
+}
+
+// Replace every group of two or more siblings with the same fingerprint by
+// its first member, annotated with the group. Siblings are compared before
+// their own children are folded, so subtrees that differ anywhere stay apart.
+pub fn dedupe(node: &mut ProcessNode) {
+    let children = std::mem::take(&mut node.children);
+    let mut groups: Vec<Vec<ProcessNode>> = Vec::new();
+    let mut index: HashMap<Vec<(usize, String)>, usize> = HashMap::new();
+    for child in children {
+        let position = *index.entry(fingerprint(&child)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[position].push(child);
+    }
+    for group in groups {
+        let mut totals: Vec<u64> = group.iter().map(|member| member.cumulative_bytes).collect();
+        let Some(mut representative) = group.into_iter().next() else {
+            continue;
+        };
+        if totals.len() > 1 {
+            representative.similar = Some(SimilarGroup::new(&mut totals));
+        }
+        dedupe(&mut representative);
+        node.children.push(representative);
+    }
+}
@@ -0,0 +1,73 @@
+// Data a run could not collect, where memon fell back to showing less. The
+// monitor keeps them per sample for --strict and the --verbose summary.
+
+use std::fmt::{self, Write as _};
+
+// PIDs listed in a message before the rest are only counted
+const LISTED_PIDS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Degradation {
+    // Reads that failed for live processes, such as other users' command lines
+    Unreadable { what: &'static str, pids: Vec<u32> },
+    // Processes that exited between the scan and reading their details
+    Exited { what: &'static str, pids: Vec<u32> },
+    // Reads still pending when --timeout passed
+    TimedOut { what: &'static str, count: usize },
+    // A whole source failed, such as NVML or the kubelet
+    Unavailable { what: &'static str, reason: String },
+}
+
+impl Degradation {
+    // Failed reads of `what` for `pids`, split into processes that exited and
+    // ones that are still there; `alive` returns None where it cannot tell,
+    // which counts as unreadable
+    pub fn failed_reads(what: &'static str, pids: Vec<u32>, alive: impl Fn(u32) -> Option<bool>) -> Vec<Degradation> {
+        let (exited, unreadable): (Vec<u32>, Vec<u32>) = pids.into_iter().partition(|&pid| alive(pid) == Some(false));
+        let mut degradations = Vec::new();
+        if !unreadable.is_empty() {
+            degradations.push(Degradation::Unreadable { what, pids: unreadable });
+        }
+        if !exited.is_empty() {
+            degradations.push(Degradation::Exited { what, pids: exited });
+        }
+        degradations
+    }
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Degradation::Unreadable { what, pids } => write!(f, "{}: unreadable for {} ({})", what, processes(pids.len()), pid_list(pids)),
+            Degradation::Exited { what, pids } => write!(f, "{}: {} exited mid-scan ({})", what, processes(pids.len()), pid_list(pids)),
+            Degradation::TimedOut { what, count } => write!(f, "{}: {} still pending at the timeout", what, processes(*count)),
+            Degradation::Unavailable { what, reason } => write!(f, "{}: unavailable: {}", what, reason),
+        }
+    }
+}
+
+// One indented line per degradation, for stderr
+pub fn summary(degradations: &[Degradation]) -> String {
+    let mut out = String::new();
+    for degradation in degradations {
+        let _ = writeln!(out, "  {}", degradation);
+    }
+    out
+}
+
+fn processes(count: usize) -> String {
+    match count {
+        1 => "1 process".to_string(),
+        count => format!("{} processes", count),
+    }
+}
+
+// "PID 12" or "PIDs 12, 14, … and 3 more"
+fn pid_list(pids: &[u32]) -> String {
+    let listed: Vec<String> = pids.iter().take(LISTED_PIDS).map(u32::to_string).collect();
+    let label = if pids.len() == 1 { "PID" } else { "PIDs" };
+    match pids.len().saturating_sub(LISTED_PIDS) {
+        0 => format!("{} {}", label, listed.join(", ")),
+        more => format!("{} {}, \u{2026} and {} more", label, listed.join(", "), more),
+    }
+}
@@ -0,0 +1,158 @@
+// --exec-column: a site-specific column filled by running a command once per
+// process, e.g. an app-level metric or a ticket tag
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::collect;
+
+// How long one run may take before it is killed and shows "-"
+pub const EXEC_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Processes the command runs for unless --force is given
+pub const MAX_PROCESSES: usize = 200;
+
+// Runs in flight at once, whatever --jobs says
+pub const MAX_CONCURRENT: usize = 4;
+
+// `name=CMD` from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecColumn {
+    pub name: String,
+    pub command: String,
+}
+
+// --exec-column value: a column name without spaces, `=`, and the command
+pub fn parse_exec_column(text: &str) -> Result<ExecColumn, String> {
+    let (name, command) = text.split_once('=').ok_or_else(|| format!("invalid exec column '{}': expected NAME=CMD", text))?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("invalid exec column name '{}': expected a word such as 'ticket'", name));
+    }
+    if command.trim().is_empty() {
+        return Err(format!("exec column '{}' has no command", name));
+    }
+    Ok(ExecColumn { name: name.to_string(), command: command.to_string() })
+}
+
+// What one run of the command gave
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecOutcome {
+    // Trimmed stdout of a successful run, first line only
+    Output(String),
+    Failed,
+    TimedOut,
+}
+
+// Run `command` through the shell with MEMON_PID set, killing it after `timeout`
+pub fn run(command: &str, pid: u32, timeout: Duration) -> ExecOutcome {
+    let mut shell = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let mut child = match shell
+        .arg(command)
+        .env("MEMON_PID", pid.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            log::debug!("cannot run exec column command for pid {}: {}", pid, err);
+            return ExecOutcome::Failed;
+        }
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(status)) => {
+                log::trace!("exec column command for pid {} failed: {}", pid, status);
+                return ExecOutcome::Failed;
+            }
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return ExecOutcome::TimedOut;
+            }
+        }
+    }
+    let mut output = String::new();
+    if child.stdout.take().is_none_or(|mut stdout| stdout.read_to_string(&mut output).is_err()) {
+        return ExecOutcome::Failed;
+    }
+    ExecOutcome::Output(output.trim().lines().next().unwrap_or_default().trim().to_string())
+}
+
+// Tally of the last refresh, for --verbose
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecStats {
+    pub ran: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+}
+
+// Column values keyed by (pid, start_time), refreshed at most once per
+// `min_interval` however often the trees are analyzed
+#[derive(Debug)]
+pub struct ExecRunner {
+    pub column: ExecColumn,
+    min_interval: Duration,
+    timeout: Duration,
+    last_run: Option<Instant>,
+    values: HashMap<(u32, u64), String>,
+}
+
+impl ExecRunner {
+    pub fn new(column: ExecColumn, min_interval: Duration) -> Self {
+        ExecRunner { column, min_interval, timeout: EXEC_TIMEOUT, last_run: None, values: HashMap::new() }
+    }
+    
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    // Whether the interval since the last refresh is over
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_run.is_none_or(|last_run| now.duration_since(last_run) >= self.min_interval)
+    }
+    
+    // Run the command for every process, `jobs` at a time up to
+    // MAX_CONCURRENT, replacing the previous values. Failed and timed-out
+    // runs keep no value.
+    pub fn refresh(&mut self, processes: &[(u32, u64)], jobs: usize) -> ExecStats {
+        self.last_run = Some(Instant::now());
+        let pids: Vec<u32> = processes.iter().map(|&(pid, _)| pid).collect();
+        let start_times: HashMap<u32, u64> = processes.iter().copied().collect();
+        let (command, timeout) = (&self.column.command, self.timeout);
+        let outcomes = collect::parallel_map(&pids, jobs.min(MAX_CONCURRENT), |pid| run(command, pid, timeout));
+        let mut stats = ExecStats { ran: outcomes.len(), ..ExecStats::default() };
+        self.values.clear();
+        for (pid, outcome) in outcomes {
+            match outcome {
+                ExecOutcome::Output(value) => {
+                    self.values.insert((pid, start_times[&pid]), value);
+                }
+                ExecOutcome::Failed => stats.failed += 1,
+                ExecOutcome::TimedOut => stats.timed_out += 1,
+            }
+        }
+        stats
+    }
+    
+    // Value of a process from the last refresh
+    pub fn value(&self, pid: u32, start_time: u64) -> Option<&str> {
+        self.values.get(&(pid, start_time)).map(String::as_str)
+    }
+}
@@ -0,0 +1,49 @@
+// Optional subsystems behind cargo features and the flags and subcommands each
+// one adds. A build without a feature has none of them, not even in --help;
+// tests/features.rs checks this for whichever combination it is built with.
+
+// A cargo feature of memon
+pub struct Feature {
+    pub name: &'static str,
+    pub enabled: bool,
+    // Flags that only exist with the feature
+    pub flags: &'static [&'static str],
+    // Subcommands that only exist with the feature
+    pub subcommands: &'static [&'static str],
+}
+
+pub const FEATURES: &[Feature] = &[
+    Feature { name: "cli", enabled: cfg!(feature = "cli"), flags: &[], subcommands: &[] },
+    Feature { name: "http", enabled: cfg!(feature = "http"), flags: &["--http", "--stream-interval"], subcommands: &[] },
+    Feature { name: "notify", enabled: cfg!(feature = "notify"), flags: &["--notify", "--webhook"], subcommands: &[] },
+    Feature { name: "parallel-scan", enabled: cfg!(feature = "parallel-scan"), flags: &[], subcommands: &[] },
+    Feature { name: "history", enabled: cfg!(feature = "history"), flags: &["--db"], subcommands: &["history"] },
+    Feature { name: "record", enabled: cfg!(feature = "record"), flags: &["--record"], subcommands: &["report"] },
+    Feature { name: "schema", enabled: cfg!(feature = "schema"), flags: &["--schema"], subcommands: &[] },
+    Feature { name: "nvml", enabled: cfg!(feature = "nvml"), flags: &["--gpu"], subcommands: &[] },
+    Feature { name: "k8s", enabled: cfg!(feature = "k8s"), flags: &["--k8s"], subcommands: &[] },
+];
+
+// The combinations CI builds and tests with --no-default-features, from the
+// core binary for servers up to everything
+pub const MATRIX: &[&[&str]] = &[
+    &["cli"],
+    &["cli", "http"],
+    &["cli", "notify"],
+    &["cli", "parallel-scan"],
+    &["cli", "history"],
+    &["cli", "record"],
+    &["cli", "schema"],
+    &["default"],
+    &["full"],
+];
+
+// Names of the features this build has
+pub fn enabled() -> Vec<&'static str> {
+    FEATURES.iter().filter(|feature| feature.enabled).map(|feature| feature.name).collect()
+}
+
+// Error text for a subsystem left out of this build
+pub fn missing(name: &str) -> String {
+    format!("memon was built without the `{}` feature", name)
+}
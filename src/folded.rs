@@ -0,0 +1,51 @@
+// Folded-stack export of the process forest for flamegraphs (--folded)
+//
+// One line per distinct ancestry path, "root;parent;...;name <rss_kb>", the
+// input format of inferno-flamegraph and flamegraph.pl. A line's value is the
+// RSS of the processes at that path alone; the flamegraph adds up the
+// children to size each frame.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::snapshot::Snapshot;
+
+// Folded stacks have no escape syntax: ';' separates frames and the last
+// space the value, so both are replaced in names
+pub fn frame(name: &str) -> String {
+    name.replace(';', ":").replace(char::is_whitespace, "_")
+}
+
+// Folded lines for every tree of the snapshot. Processes sharing a path, such
+// as sibling workers with the same name, are summed into one line; lines keep
+// the order in which their path first appears.
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut order: Vec<String> = Vec::new();
+    let mut values: HashMap<String, u64> = HashMap::new();
+    for tree in &snapshot.trees {
+        let mut path: Vec<String> = Vec::new();
+        for (node, depth) in tree.iter_preorder() {
+            path.truncate(depth);
+            path.push(frame(&node.name));
+            let stack = path.join(";");
+            match values.get_mut(&stack) {
+                Some(value) => *value += node.rss_bytes,
+                None => {
+                    values.insert(stack.clone(), node.rss_bytes);
+                    order.push(stack);
+                }
+            }
+        }
+    }
+    let mut out = String::new();
+    for stack in order {
+        out.push_str(&format!("{} {}\n", stack, values[&stack] / 1024));
+    }
+    out
+}
+
+pub fn write(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    fs::write(path, render(snapshot))
+}
@@ -0,0 +1,96 @@
+// Output format selection
+//
+// Precedence: an explicit --format flag, then the --output file extension,
+// then plain text. Generic extensions (.txt, .log, unknown ones) never
+// conflict with an explicit flag.
+
+use std::fmt;
+use std::path::Path;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Html,
+    #[value(name = "md")]
+    Markdown,
+    Dot,
+}
+
+impl OutputFormat {
+    // Format implied by a file extension, if any
+    pub fn from_extension(path: &Path) -> Option<OutputFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(OutputFormat::Json),
+            "csv" => Some(OutputFormat::Csv),
+            "html" | "htm" => Some(OutputFormat::Html),
+            "md" | "markdown" => Some(OutputFormat::Markdown),
+            "dot" | "gv" => Some(OutputFormat::Dot),
+            "txt" | "text" | "log" => Some(OutputFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Html => "html",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Dot => "dot",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Result of format resolution; warning is set when an explicit flag
+// overrode a conflicting file extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedFormat {
+    pub format: OutputFormat,
+    pub warning: Option<String>,
+}
+
+// An explicit format flag disagreed with the --output extension under --strict-format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatConflict {
+    pub explicit: OutputFormat,
+    pub implied: OutputFormat,
+    pub path: String,
+}
+
+impl fmt::Display for FormatConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "--format {} conflicts with the {} extension of {}", self.explicit, self.implied, self.path)
+    }
+}
+
+impl std::error::Error for FormatConflict {}
+
+pub fn resolve_format(explicit: Option<OutputFormat>, output: Option<&Path>, strict: bool) -> Result<ResolvedFormat, FormatConflict> {
+    // Only specific extensions carry an opinion; .txt and unknown ones defer to the flag
+    let implied = output
+        .and_then(OutputFormat::from_extension)
+        .filter(|format| *format != OutputFormat::Text);
+    
+    match (explicit, implied) {
+        (Some(explicit), Some(implied)) if explicit != implied => {
+            let path = output.map(|path| path.display().to_string()).unwrap_or_default();
+            if strict {
+                return Err(FormatConflict { explicit, implied, path });
+            }
+            Ok(ResolvedFormat {
+                format: explicit,
+                warning: Some(format!("--format {} overrides the {} extension of {}", explicit, implied, path)),
+            })
+        }
+        (Some(explicit), _) => Ok(ResolvedFormat { format: explicit, warning: None }),
+        (None, Some(implied)) => Ok(ResolvedFormat { format: implied, warning: None }),
+        (None, None) => Ok(ResolvedFormat { format: OutputFormat::Text, warning: None }),
+    }
+}
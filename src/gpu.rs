@@ -0,0 +1,75 @@
+// Per-process GPU memory from NVML (--gpu), available with the `nvml` feature
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct GpuError(String);
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+// An initialised NVML library, kept for the whole run so watch mode does not
+// load it again on every refresh
+#[cfg(feature = "nvml")]
+pub struct GpuReader {
+    nvml: nvml_wrapper::Nvml,
+}
+
+#[cfg(feature = "nvml")]
+impl GpuReader {
+    // Fails when the NVIDIA driver library is missing or there is no GPU
+    pub fn new() -> Result<GpuReader, GpuError> {
+        let nvml = nvml_wrapper::Nvml::init().map_err(|err| GpuError(format!("cannot load NVML: {}", err)))?;
+        let devices = nvml.device_count().map_err(|err| GpuError(format!("cannot list GPUs: {}", err)))?;
+        if devices == 0 {
+            return Err(GpuError("no NVIDIA GPU found".to_string()));
+        }
+        Ok(GpuReader { nvml })
+    }
+
+    // GPU memory in bytes per PID, summed over all devices. A process listed
+    // as both a compute and a graphics user of a device is counted once.
+    pub fn read(&self) -> Result<HashMap<u32, u64>, GpuError> {
+        use nvml_wrapper::enums::device::UsedGpuMemory;
+
+        let error = |err: nvml_wrapper::error::NvmlError| GpuError(err.to_string());
+        let mut usage = HashMap::new();
+        for index in 0..self.nvml.device_count().map_err(error)? {
+            let device = self.nvml.device_by_index(index).map_err(error)?;
+            let mut on_device: HashMap<u32, u64> = HashMap::new();
+            let processes = device.running_compute_processes().map_err(error)?
+                .into_iter()
+                .chain(device.running_graphics_processes().map_err(error)?);
+            for process in processes {
+                if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory {
+                    let entry = on_device.entry(process.pid).or_default();
+                    *entry = (*entry).max(bytes);
+                }
+            }
+            for (pid, bytes) in on_device {
+                *usage.entry(pid).or_default() += bytes;
+            }
+        }
+        Ok(usage)
+    }
+}
+
+#[cfg(not(feature = "nvml"))]
+pub struct GpuReader;
+
+#[cfg(not(feature = "nvml"))]
+impl GpuReader {
+    pub fn new() -> Result<GpuReader, GpuError> {
+        Err(GpuError(crate::features::missing("nvml")))
+    }
+
+    pub fn read(&self) -> Result<HashMap<u32, u64>, GpuError> {
+        Ok(HashMap::new())
+    }
+}
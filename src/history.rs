@@ -0,0 +1,127 @@
+// SQLite history backend for long-term tracking (--db and `memon history`),
+// built with the `history` feature. format_timestamp is used by every build.
+
+#[cfg(feature = "history")]
+use rusqlite::{params, Connection};
+#[cfg(feature = "history")]
+use std::path::Path;
+
+#[cfg(feature = "history")]
+use crate::snapshot::Snapshot;
+
+#[cfg(feature = "history")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    pattern TEXT NOT NULL,
+    matched INTEGER NOT NULL,
+    tree_count INTEGER NOT NULL,
+    total_bytes INTEGER NOT NULL,
+    metadata TEXT
+);
+CREATE TABLE IF NOT EXISTS processes (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    timestamp INTEGER NOT NULL,
+    pid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    rss INTEGER NOT NULL,
+    tree_root INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_pattern_time ON runs(pattern, timestamp);
+";
+
+// One recorded run as read back for `memon history`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunTotal {
+    pub run_id: i64,
+    pub timestamp: i64,
+    pub tree_count: u64,
+    pub total_bytes: u64,
+}
+
+#[cfg(feature = "history")]
+pub struct HistoryDb {
+    conn: Connection,
+}
+
+#[cfg(feature = "history")]
+impl HistoryDb {
+    // Open (or create) the database file, creating the schema on first use
+    pub fn open(path: &Path) -> rusqlite::Result<HistoryDb> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        // Databases from before the metadata column get it added
+        let has_metadata = conn.prepare("SELECT 1 FROM pragma_table_info('runs') WHERE name = 'metadata'")?.exists([])?;
+        if !has_metadata {
+            conn.execute_batch("ALTER TABLE runs ADD COLUMN metadata TEXT")?;
+        }
+        Ok(HistoryDb { conn })
+    }
+    
+    // Insert one run and all of its processes in a single transaction
+    pub fn record(&mut self, snapshot: &Snapshot, timestamp: i64) -> rusqlite::Result<i64> {
+        let total_bytes: u64 = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+        // The JSON of the snapshot's metadata block, NULL without one
+        let metadata = snapshot.metadata.as_ref().map(|metadata| serde_json::to_string(metadata).expect("metadata serializes to JSON"));
+        let transaction = self.conn.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (timestamp, pattern, matched, tree_count, total_bytes, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![timestamp, snapshot.pattern, snapshot.matched as i64, snapshot.trees.len() as i64, total_bytes as i64, metadata],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO processes (run_id, timestamp, pid, name, rss, tree_root) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for tree in &snapshot.trees {
+                for (node, _) in tree.iter_preorder() {
+                    insert.execute(params![run_id, timestamp, node.pid, node.name, node.rss_bytes as i64, tree.root_pid])?;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(run_id)
+    }
+    
+    // The metadata JSON recorded with a run, None for runs without it
+    pub fn run_metadata(&self, run_id: i64) -> rusqlite::Result<Option<String>> {
+        self.conn.query_row("SELECT metadata FROM runs WHERE id = ?1", params![run_id], |row| row.get(0))
+    }
+    
+    // Tree-total time series for a pattern, oldest first
+    pub fn tree_totals(&self, pattern: &str) -> rusqlite::Result<Vec<RunTotal>> {
+        let mut query = self.conn.prepare(
+            "SELECT id, timestamp, tree_count, total_bytes FROM runs WHERE pattern = ?1 ORDER BY timestamp, id",
+        )?;
+        let rows = query.query_map(params![pattern], |row| {
+            Ok(RunTotal {
+                run_id: row.get(0)?,
+                timestamp: row.get(1)?,
+                tree_count: row.get::<_, i64>(2)? as u64,
+                total_bytes: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+// Format seconds since the epoch as "YYYY-MM-DD HH:MM:SS UTC"
+pub fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            year, month, day, seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
@@ -0,0 +1,146 @@
+// Lowest, average and highest RSS of each process over a watch session (--history-cols)
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::churn::Identity;
+use crate::process::ProcessInfo;
+
+// Fewer samples than this make no range worth showing
+pub const MIN_SAMPLES: usize = 2;
+
+// Processes listed in the watch recap
+pub const RECAP_PROCESSES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RssHistory {
+    pub samples: usize,
+    pub min_bytes: u64,
+    pub avg_bytes: u64,
+    pub max_bytes: u64,
+}
+
+// One process as the recap names it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessHistory {
+    pub pid: u32,
+    pub name: String,
+    pub history: RssHistory,
+}
+
+// Running aggregates of one process
+#[derive(Debug, Clone)]
+struct Record {
+    name: String,
+    samples: usize,
+    min: u64,
+    max: u64,
+    sum: u128,
+}
+
+impl Record {
+    fn history(&self) -> Option<RssHistory> {
+        (self.samples >= MIN_SAMPLES).then(|| RssHistory {
+            samples: self.samples,
+            min_bytes: self.min,
+            avg_bytes: (self.sum / self.samples as u128) as u64,
+            max_bytes: self.max,
+        })
+    }
+}
+
+// RSS seen per (pid, start_time), so a reused PID starts afresh. Exited
+// processes are kept for the recap at the end of the session.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryTracker {
+    records: HashMap<Identity, Record>,
+}
+
+impl HistoryTracker {
+    pub fn new() -> Self {
+        HistoryTracker::default()
+    }
+
+    // Record the current RSS of the processes in `pids` and set their history
+    // once they have been seen at least MIN_SAMPLES times
+    pub fn observe(&mut self, processes: &mut HashMap<u32, ProcessInfo>, pids: &[u32]) {
+        for pid in pids {
+            let Some(proc_info) = processes.get_mut(pid) else {
+                continue;
+            };
+            let rss = proc_info.rss;
+            let record = self.records
+                .entry((proc_info.pid, proc_info.start_time))
+                .or_insert_with(|| Record { name: proc_info.name.clone(), samples: 0, min: rss, max: rss, sum: 0 });
+            record.samples += 1;
+            record.min = record.min.min(rss);
+            record.max = record.max.max(rss);
+            record.sum += rss as u128;
+            proc_info.history = record.history();
+        }
+    }
+
+    // The processes with the highest maximum, including exited ones, from
+    // the same aggregates as the columns
+    pub fn largest(&self, count: usize) -> Vec<ProcessHistory> {
+        let mut largest: Vec<ProcessHistory> = self.records
+            .iter()
+            .filter_map(|(&(pid, _), record)| Some(ProcessHistory { pid, name: record.name.clone(), history: record.history()? }))
+            .collect();
+        largest.sort_by(|a, b| b.history.max_bytes.cmp(&a.history.max_bytes).then(a.pid.cmp(&b.pid)));
+        largest.truncate(count);
+        largest
+    }
+
+    // Continue the record of a restarted root under its new identity, before
+    // the new process is observed
+    pub fn carry_over(&mut self, from: Identity, to: Identity) {
+        if let Some(record) = self.records.remove(&from) {
+            self.records.insert(to, record);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+// Watch recap lines for `largest`, each memory column right-aligned to its
+// widest value; empty when no process was seen MIN_SAMPLES times
+pub fn render_recap(largest: &[ProcessHistory], memory: impl Fn(u64) -> String) -> String {
+    let mut out = String::new();
+    if largest.is_empty() {
+        return out;
+    }
+    let rows: Vec<[String; 4]> = largest
+        .iter()
+        .map(|process| [
+            process.pid.to_string(),
+            memory(process.history.min_bytes),
+            memory(process.history.avg_bytes),
+            memory(process.history.max_bytes),
+        ])
+        .collect();
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let name_width = largest.iter().map(|process| process.name.chars().count()).max().unwrap_or(0);
+    let _ = writeln!(out, "RSS min/avg/max:");
+    for (row, process) in rows.iter().zip(largest) {
+        let _ = writeln!(out, "  {:>w0$} {:<name_width$}  {:>w1$}  {:>w2$}  {:>w3$}", row[0], process.name, row[1], row[2], row[3],
+                         w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    }
+    out
+}
@@ -0,0 +1,81 @@
+// Alert hooks run when a threshold is crossed: a shell command, and with the
+// `notify` feature a webhook or a desktop notification
+
+use crate::threshold::{Breach, Exceeded};
+use crate::units::format_memory;
+use std::fmt;
+use std::process::Command;
+#[cfg(feature = "notify")]
+use std::time::Duration;
+
+#[cfg(feature = "notify")]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct HookError(String);
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for HookError {}
+
+// Run `command` through the shell with MEMON_PID, MEMON_NAME, MEMON_RSS and
+// MEMON_TOTAL describing the breach
+pub fn run_command(command: &str, breach: &Breach) -> Result<(), HookError> {
+    let mut shell = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let status = shell
+        .arg(command)
+        .env("MEMON_PID", breach.pid.to_string())
+        .env("MEMON_NAME", &breach.name)
+        .env("MEMON_RSS", breach.rss.to_string())
+        .env("MEMON_TOTAL", breach.total.to_string())
+        .status()
+        .map_err(|err| HookError(format!("cannot run threshold command '{}': {}", command, err)))?;
+    if !status.success() {
+        return Err(HookError(format!("threshold command '{}' failed: {}", command, status)));
+    }
+    Ok(())
+}
+
+// POST the JSON snapshot to `url`
+#[cfg(feature = "notify")]
+pub fn post_webhook(url: &str, body: &str) -> Result<(), HookError> {
+    ureq::post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+        .map_err(|err| HookError(format!("webhook {} failed: {}", url, err)))?;
+    Ok(())
+}
+
+// One-line summary used for desktop notifications and the stderr fallback,
+// e.g. "myapp tree exceeded 2.0GB: pid 1234 at 1.6GB"
+pub fn notification_text(pattern: &str, breach: &Breach) -> String {
+    let (what, limit) = match breach.exceeded {
+        Exceeded::Total(limit) => ("tree", limit),
+        Exceeded::Process(limit) => ("process", limit),
+    };
+    format!("{} {} exceeded {}: pid {} at {}", pattern, what, format_memory(limit), breach.pid, format_memory(breach.rss))
+}
+
+// Show a desktop notification
+#[cfg(feature = "notify")]
+pub fn notify(text: &str) -> Result<(), HookError> {
+    notify_rust::Notification::new()
+        .summary("memon")
+        .body(text)
+        .show()
+        .map_err(|err| HookError(format!("desktop notification failed: {}", err)))?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// Java heap settings from JVM command lines, and current usage from jcmd (--jvm, --jvm-probe)
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// How long one jcmd call may take before it is killed
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Heap of one JVM. The flags come from the command line; used and committed
+// only from a successful --jvm-probe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct JvmHeap {
+    // -Xmx or -XX:MaxHeapSize; None means the JVM picks its default
+    pub max_bytes: Option<u64>,
+    // -Xms or -XX:InitialHeapSize
+    pub initial_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committed_bytes: Option<u64>,
+}
+
+// Whether a process looks like a JVM: named java, or started through a java binary
+pub fn is_jvm(name: &str, args: &str) -> bool {
+    let program = args.split_whitespace().next().unwrap_or("");
+    let program = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    ["java", "javaw", "java.exe", "javaw.exe"].iter().any(|java| name == *java || program == *java)
+}
+
+// Launcher options whose value is the next argument
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "-cp", "-classpath", "--class-path", "-p", "--module-path", "--upgrade-module-path", "--add-modules",
+    "--add-opens", "--add-exports", "--add-reads", "--patch-module", "--limit-modules",
+];
+
+// Heap flags from a JVM command line. Like the JVM, the last occurrence of a
+// flag wins; arguments after the main class, -jar or -m belong to the application.
+pub fn parse_heap_flags(args: &str) -> JvmHeap {
+    let mut heap = JvmHeap::default();
+    let mut words = args.split_whitespace().skip(1);
+    while let Some(arg) = words.next() {
+        if matches!(arg, "-jar" | "-m" | "--module") || !arg.starts_with('-') {
+            break;
+        }
+        if OPTIONS_WITH_VALUE.contains(&arg) {
+            words.next();
+        } else if let Some(size) = arg.strip_prefix("-Xmx").or_else(|| arg.strip_prefix("-XX:MaxHeapSize=")) {
+            heap.max_bytes = parse_jvm_size(size).or(heap.max_bytes);
+        } else if let Some(size) = arg.strip_prefix("-Xms").or_else(|| arg.strip_prefix("-XX:InitialHeapSize=")) {
+            heap.initial_bytes = parse_jvm_size(size).or(heap.initial_bytes);
+        }
+    }
+    heap
+}
+
+// JVM size syntax: bytes, or a number with a k, m, g or t suffix in either case
+pub fn parse_jvm_size(text: &str) -> Option<u64> {
+    let (number, shift) = match text.chars().last()?.to_ascii_lowercase() {
+        'k' => (&text[..text.len() - 1], 10),
+        'm' => (&text[..text.len() - 1], 20),
+        'g' => (&text[..text.len() - 1], 30),
+        't' => (&text[..text.len() - 1], 40),
+        _ => (text, 0),
+    };
+    number.parse::<u64>().ok()?.checked_mul(1 << shift)
+}
+
+// Heap used and committed (capacity) in bytes from `jcmd <pid> GC.heap_info`.
+// Generational collectors print one "total NK, used NK" line per generation,
+// G1 a single heap line and ZGC "used NM, capacity NM"; Metaspace and class
+// space lines have no total and are left out.
+pub fn parse_heap_info(output: &str) -> Option<(u64, u64)> {
+    let mut found = false;
+    let (mut used, mut committed) = (0, 0);
+    for line in output.lines() {
+        let field = |key: &str| {
+            let rest = &line[line.find(key)? + key.len()..];
+            let value = rest.trim_start().split(|c: char| c == ',' || c.is_whitespace()).next()?;
+            parse_jvm_size(value.strip_suffix('B').unwrap_or(value))
+        };
+        let capacity = field(" total ").or_else(|| field(" capacity "));
+        if let (Some(capacity), Some(line_used)) = (capacity, field(" used ")) {
+            found = true;
+            used += line_used;
+            committed += capacity;
+        }
+    }
+    found.then_some((used, committed))
+}
+
+// Run `jcmd <pid> GC.heap_info`, killing it after PROBE_TIMEOUT. None when
+// jcmd is missing, fails, times out or prints nothing recognisable.
+pub fn probe(pid: u32) -> Option<(u64, u64)> {
+    let mut child = Command::new("jcmd")
+        .args([pid.to_string().as_str(), "GC.heap_info"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| log::debug!("cannot run jcmd for pid {}: {}", pid, err))
+        .ok()?;
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(status)) => {
+                log::debug!("jcmd for pid {} failed: {}", pid, status);
+                return None;
+            }
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                log::debug!("jcmd for pid {} did not finish within {:?}", pid, PROBE_TIMEOUT);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    parse_heap_info(&output)
+}
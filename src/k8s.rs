@@ -0,0 +1,170 @@
+// Kubernetes pods of containerized processes (--k8s). The pod UID and
+// container ID come from the cgroup path; namespace, pod and container names
+// from the kubelet's read-only API, fetched with the `k8s` feature.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::snapshot::{self, GroupTotal, ProcessTree};
+
+// The kubelet's read-only port; MEMON_KUBELET_URL points elsewhere
+pub const KUBELET_PODS_URL: &str = "http://127.0.0.1:10255/pods";
+
+// Row of --group-by pod for processes outside any pod
+pub const NO_POD: &str = "(no pod)";
+
+#[derive(Debug)]
+pub struct K8sError(String);
+
+impl fmt::Display for K8sError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for K8sError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PodRef {
+    pub uid: String,
+    // 64 hex digits as the runtime names it; absent for the pod-level cgroup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    // The rest only when the kubelet knew the pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+}
+
+impl PodRef {
+    // `namespace/pod/container`, or the UID when the pod was not resolved
+    pub fn label(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{}/{}", self.group_key(), container),
+            None => self.group_key(),
+        }
+    }
+
+    // `namespace/pod`, or the UID when the pod was not resolved
+    pub fn group_key(&self) -> String {
+        match (&self.namespace, &self.pod) {
+            (Some(namespace), Some(pod)) => format!("{}/{}", namespace, pod),
+            _ => self.uid.clone(),
+        }
+    }
+}
+
+// The pod of a process from its /proc/<pid>/cgroup, for both cgroup drivers:
+//   systemd:  .../kubepods-burstable-pod<uid with _>.slice/cri-containerd-<id>.scope
+//   cgroupfs: /kubepods/burstable/pod<uid>/<id>
+pub fn parse_cgroup(cgroup: &str) -> Option<PodRef> {
+    cgroup.lines().find_map(|line| {
+        // hierarchy-ID:controllers:path
+        let path = line.splitn(3, ':').nth(2)?;
+        if !path.contains("kubepods") {
+            return None;
+        }
+        let segments: Vec<&str> = path.split('/').collect();
+        let (index, uid) = segments.iter().enumerate().rev().find_map(|(index, segment)| pod_uid(segment).map(|uid| (index, uid)))?;
+        let container_id = segments.get(index + 1).and_then(|segment| container_id(segment));
+        Some(PodRef { uid, container_id, namespace: None, pod: None, container: None })
+    })
+}
+
+fn pod_uid(segment: &str) -> Option<String> {
+    let uid = match segment.strip_suffix(".slice") {
+        // The systemd driver escapes the dashes of the UID as underscores
+        Some(slice) => slice.rsplit_once("-pod")?.1.replace('_', "-"),
+        None => segment.strip_prefix("pod")?.to_string(),
+    };
+    (!uid.is_empty() && uid.chars().all(|c| c.is_ascii_hexdigit() || c == '-')).then_some(uid)
+}
+
+fn container_id(segment: &str) -> Option<String> {
+    let name = segment.strip_suffix(".scope").unwrap_or(segment);
+    // cri-containerd-<id>, crio-<id>, docker-<id>, or the bare ID
+    let id = name.rsplit('-').next()?;
+    (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+}
+
+// Pods on this node keyed by UID, with container names keyed by container ID
+#[derive(Debug, Default)]
+pub struct PodDirectory {
+    pods: HashMap<String, PodEntry>,
+}
+
+#[derive(Debug)]
+struct PodEntry {
+    namespace: String,
+    name: String,
+    containers: HashMap<String, String>,
+}
+
+impl PodDirectory {
+    // Parse the PodList the kubelet serves at /pods
+    pub fn from_json(json: &str) -> Result<PodDirectory, K8sError> {
+        let list: Value = serde_json::from_str(json).map_err(|err| K8sError(format!("invalid pod list: {}", err)))?;
+        let items = list["items"].as_array().ok_or_else(|| K8sError("pod list without items".to_string()))?;
+        let mut pods = HashMap::new();
+        for item in items {
+            let metadata = &item["metadata"];
+            let (Some(uid), Some(namespace), Some(name)) = (metadata["uid"].as_str(), metadata["namespace"].as_str(), metadata["name"].as_str()) else {
+                continue;
+            };
+            let statuses = ["containerStatuses", "initContainerStatuses", "ephemeralContainerStatuses"];
+            let containers = statuses
+                .iter()
+                .filter_map(|key| item["status"][key].as_array())
+                .flatten()
+                .filter_map(|status| {
+                    // containerID is `<runtime>://<id>`
+                    let id = status["containerID"].as_str()?.rsplit("://").next()?;
+                    Some((id.to_string(), status["name"].as_str()?.to_string()))
+                })
+                .collect();
+            pods.insert(uid.to_string(), PodEntry { namespace: namespace.to_string(), name: name.to_string(), containers });
+        }
+        Ok(PodDirectory { pods })
+    }
+
+    // Fill in the names the kubelet knows; an unknown pod keeps only its UID
+    pub fn resolve(&self, pod: &mut PodRef) {
+        let Some(entry) = self.pods.get(&pod.uid) else {
+            return;
+        };
+        pod.namespace = Some(entry.namespace.clone());
+        pod.pod = Some(entry.name.clone());
+        pod.container = pod.container_id.as_ref().and_then(|id| entry.containers.get(id)).cloned();
+    }
+}
+
+// Fetch the pods of this node from the kubelet
+#[cfg(feature = "k8s")]
+pub fn fetch_pods(url: &str) -> Result<PodDirectory, K8sError> {
+    let response = ureq::get(url)
+        .timeout(std::time::Duration::from_secs(2))
+        .call()
+        .map_err(|err| K8sError(format!("cannot reach the kubelet at {}: {}", url, err)))?;
+    let body = response.into_string().map_err(|err| K8sError(format!("cannot read the pod list: {}", err)))?;
+    PodDirectory::from_json(&body)
+}
+
+#[cfg(not(feature = "k8s"))]
+pub fn fetch_pods(_url: &str) -> Result<PodDirectory, K8sError> {
+    Err(K8sError(crate::features::missing("k8s")))
+}
+
+// --group-by pod: processes and memory per pod over every tree, largest
+// first, with the processes outside any pod in one NO_POD row
+pub fn pod_totals(trees: &[ProcessTree]) -> Vec<GroupTotal> {
+    snapshot::group_totals(trees, NO_POD, |node| node.pod.as_ref().map(PodRef::group_key))
+}
@@ -0,0 +1,109 @@
+// Friendly labels for well-known process names (--labels)
+
+use crate::snapshot::ProcessNode;
+
+// Built-in (pattern, label) pairs. Names are matched as the kernel reports
+// them, which Linux cuts to 15 characters; `*` matches any run of characters.
+const BUILTIN: &[(&str, &str)] = &[
+    // Firefox
+    ("Web Content", "Firefox tab renderer"),
+    ("Isolated Web Co", "Firefox site-isolated tab"),
+    ("WebExtensions", "Firefox extensions"),
+    ("Socket Process", "Firefox network sandbox"),
+    ("RDD Process", "Firefox media decoder"),
+    ("Privileged Cont", "Firefox privileged pages"),
+    ("Utility Process", "Firefox utility sandbox"),
+    ("Isolated Servic", "Firefox service worker"),
+    // Chromium and Electron apps keep their binary name; the role is in the arguments
+    ("chrome_crashpad", "Chrome crash reporter"),
+    ("crashpad_handle*", "crash reporter"),
+    // macOS
+    ("mdworker_shared", "Spotlight indexer"),
+    ("mdworker", "Spotlight indexer"),
+    ("mds_stores", "Spotlight index store"),
+    ("WindowServer", "macOS display server"),
+    ("kernel_task", "macOS kernel"),
+    // Linux desktops and services
+    ("Xwayland", "X11 compatibility server"),
+    ("gnome-shell", "GNOME desktop shell"),
+    ("plasmashell", "KDE desktop shell"),
+    ("kwin_wayland", "KDE compositor"),
+    ("pipewire", "audio and video server"),
+    ("wireplumber", "PipeWire session manager"),
+    ("pulseaudio", "audio server"),
+    ("dbus-daemon", "message bus"),
+    ("tracker-miner-f*", "GNOME file indexer"),
+    ("baloo_file", "KDE file indexer"),
+    ("kworker/*", "kernel worker thread"),
+    ("ksoftirqd/*", "kernel interrupt thread"),
+    // Language servers and editor helpers
+    ("rust-analyzer", "Rust language server"),
+    ("tsserver", "TypeScript language server"),
+    ("gopls", "Go language server"),
+    ("clangd", "C/C++ language server"),
+];
+
+// Pattern to label mapping: config entries first, then the built-ins
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels {
+    entries: Vec<(String, String)>,
+}
+
+impl Labels {
+    pub fn builtin() -> Labels {
+        Labels::with_overrides(&[])
+    }
+
+    // The built-ins extended by `overrides`. An override wins over a
+    // built-in with the same pattern and is tried before all built-ins.
+    pub fn with_overrides(overrides: &[(String, String)]) -> Labels {
+        let mut entries = overrides.to_vec();
+        for (pattern, label) in BUILTIN {
+            if !overrides.iter().any(|(overridden, _)| overridden == pattern) {
+                entries.push((pattern.to_string(), label.to_string()));
+            }
+        }
+        Labels { entries }
+    }
+
+    // Label of the first entry whose pattern matches `name`; an empty label
+    // hides a built-in
+    pub fn lookup(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(pattern, _)| matches(pattern, name))
+            .map(|(_, label)| label.as_str())
+            .filter(|label| !label.is_empty())
+    }
+
+    // Set the label of every process in the subtree
+    pub fn apply(&self, node: &mut ProcessNode) {
+        node.label = self.lookup(&node.name)
+            .or_else(|| node.raw_name.as_deref().and_then(|raw_name| self.lookup(raw_name)))
+            .map(str::to_string);
+        for child in &mut node.children {
+            self.apply(child);
+        }
+    }
+}
+
+// Whole-name match where `*` stands for any run of characters, none included
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
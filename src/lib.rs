@@ -0,0 +1,71 @@
+// Memon library - process model and tree statistics shared by the CLI and benchmarks
+
+pub mod baseline;
+pub mod budget;
+pub mod changes;
+pub mod churn;
+pub mod chart;
+pub mod check;
+pub mod cli;
+pub mod collect;
+pub mod colors;
+pub mod compare;
+pub mod config;
+pub mod consistency;
+pub mod dedupe;
+pub mod degradation;
+pub mod exec_column;
+pub mod features;
+pub mod fine;
+pub mod folded;
+pub mod format;
+pub mod gpu;
+pub mod history;
+pub mod history_cols;
+pub mod hook;
+pub mod jvm;
+pub mod k8s;
+pub mod labels;
+pub mod maps;
+pub mod match_stats;
+pub mod metadata;
+pub mod names;
+pub mod nice;
+pub mod options;
+pub mod orphans;
+pub mod peak;
+pub mod output;
+pub mod overview;
+pub mod pager;
+pub mod paths;
+pub mod pattern_config;
+pub mod platform;
+pub mod privs;
+pub mod process;
+pub mod progress;
+pub mod record;
+pub mod redact;
+pub mod remote;
+pub mod reroot;
+pub mod roots;
+pub mod render;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod self_profile;
+#[cfg(feature = "http")]
+pub mod server;
+pub mod shell;
+pub mod shell_vars;
+pub mod since_last;
+pub mod smooth;
+pub mod snapshot;
+pub mod sort;
+pub mod source;
+pub mod stability;
+pub mod stats;
+pub mod status_line;
+pub mod threads;
+pub mod threshold;
+pub mod top;
+pub mod treemap;
+pub mod units;
@@ -2,136 +2,231 @@
 // Analyzes memory usage of a process and its children, displaying as a tree structure
 
 use clap::Parser;
-use std::collections::HashMap;
-use sysinfo::System;
-
-// ANSI color codes for cross-platform colored output
-mod colors {
-    // Reset
-    pub const RESET: &str = "\x1b[0m";
-    
-    // Foreground colors
-    pub const CYAN: &str = "\x1b[36m";
-    
-    // Background colors - light gray background
-    pub const BG_LIGHT_GRAY: &str = "\x1b[47m";  // Light gray background
-    
-    // Foreground colors - dark gray for contrast
-    pub const DARK_GRAY: &str = "\x1b[30m";  // Dark gray foreground
-    
-    // Styles - removed bold for cleaner output
-    // pub const BOLD: &str = "\1b[1m"; // Removed
-    
-    // Check if colors should be used
-    pub fn should_use_colors(no_color_flag: bool) -> bool {
-        // Check if NO_COLOR environment variable is set
-        if no_color_flag || std::env::var("NO_COLOR").is_ok() {
-            return false;
-        }
-        
-        // Check if output is a terminal
-        // This is a simplified check. In a real application, you might want to use a crate like `atty`.
-        true
-    }
-    
-    // Functions to combine colors - removed as no longer used
-    // pub fn combine_colors(color1: &str, color2: &str) -> String {
-    //     format!("{}{}", color1, color2)
-    // }
-}
-
-// Command line arguments
-#[derive(Parser, Debug)]
-#[clap(
-    name = "memon",
-    version = "0.1.0",
-    author = "Your Name <you@example.com>",
-    about = "Analyzes memory usage of a process and its children"
-)]
-struct Args {
-    /// Name of the process to analyze
-    #[clap(name = "PROCESS_NAME")]
-    process_name: String,
-    
-    /// Verbose output
-    #[clap(long)]
-    verbose: bool,
-    
-    /// Display process startup arguments
-    #[clap(short = 'v', long = "show-args")]
-    show_args: bool,
-    
-    /// Disable colored output
-    #[clap(long)]
-    no_color: bool,
-    
-    /// Watch mode - continuously update every N seconds
-    #[clap(short, long)]
-    watch: Option<u64>,
-}
-
-// Process information structure
-#[derive(Debug, Clone)]
-struct ProcessInfo {
-    pid: u32,
-    name: String,
-    rss: u64, // Resident Set Size in bytes
-    parent_pid: Option<u32>,
-    children: Vec<u32>,
-    is_max_memory: bool,
-    is_second_max_memory: bool,
-    is_third_max_memory: bool,
-    args: Option<String>, // Command line arguments
-}
-
-impl ProcessInfo {
-    fn new(pid: u32, name: String, rss: u64, parent_pid: Option<u32>) -> Self {
-        ProcessInfo {
-            pid,
-            name,
-            rss,
-            parent_pid,
-            children: Vec::new(),
-            is_max_memory: false,
-            is_second_max_memory: false,
-            is_third_max_memory: false,
-            args: None,
-        }
-    }
-    
-    fn add_child(&mut self, child_pid: u32) {
-        self.children.push(child_pid);
-    }
-}
+use log::{debug, log_enabled, trace, warn, Level};
+use memon::baseline;
+use memon::budget::Budget;
+use memon::changes;
+use memon::chart::{self, TotalHistory};
+use memon::churn::ChurnTracker;
+use memon::check;
+use memon::cli::{Args, ColorWhen, Command, GroupBy};
+use memon::collect;
+use memon::colors::{self, ColorEnv};
+use memon::compare;
+use memon::config::{self, Config};
+use memon::consistency::TableCheck;
+use memon::dedupe;
+use memon::degradation::{self, Degradation};
+use memon::exec_column::{self, ExecRunner};
+use memon::features;
+use memon::fine::{self, FineWindow};
+use memon::folded;
+use memon::format::{self, OutputFormat};
+use memon::gpu::GpuReader;
+use memon::history;
+#[cfg(feature = "history")]
+use memon::history::HistoryDb;
+use memon::history_cols::{self, HistoryTracker};
+use memon::hook;
+use memon::jvm;
+use memon::k8s::{self, PodRef};
+use memon::labels::Labels;
+use memon::maps::{self, Sharing};
+use memon::names;
+use memon::nice;
+use memon::orphans::OrphanTracker;
+use memon::match_stats::{self, MatchRule, MatchStats};
+use memon::metadata::{self, Anonymizer, RunMetadata};
+use memon::options::{ColorChoice, Columns, MonitorOptions, TreeSelection};
+use memon::output::OutputTarget;
+use memon::overview::{self, Overview};
+use memon::pager;
+use memon::pattern_config;
+use memon::peak::PeakTracker;
+use memon::platform;
+use memon::privs;
+use memon::process::{ProcessInfo, Session};
+use memon::progress::Spinner;
+use memon::record::HostInfo;
+#[cfg(feature = "record")]
+use memon::record::{self, RecordHeader, RecordSample, RecordWriter};
+use memon::remote::SshHost;
+use memon::reroot::RootTracker;
+use memon::roots::{self, RootPolicy};
+use memon::render::{self, Renderer, StickyWidths, TextRenderer, Theme};
+#[cfg(feature = "schema")]
+use memon::schema;
+use memon::self_profile::{self, SelfProfile};
+#[cfg(feature = "http")]
+use memon::server;
+use memon::shell;
+use memon::shell_vars;
+use memon::since_last::{self, RunCache, SavedRun};
+use memon::smooth::Smoother;
+use memon::stability::StabilityTracker;
+use memon::status_line::StatusLine;
+use memon::snapshot::{self, ProcessTree, SiblingContext, Snapshot};
+use memon::sort::{self, DeltaSort, SortKey};
+use memon::source::{self, ProcessSource, SelfExclusion, TableFile};
+use memon::threads;
+use memon::threshold::{Breach, Thresholds, Trigger};
+use memon::top::{self, TopReport};
+use memon::treemap;
+use memon::units;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessRefreshKind, Signal, System, UpdateKind};
 
 // Memory Monitor
 struct MemoryMonitor {
     processes: HashMap<u32, ProcessInfo>,
-    no_color: bool,
-    show_args: bool,
+    options: MonitorOptions,
+    // Replaces the live scan when set (--from-file, --ssh)
+    source: Option<Box<dyn ProcessSource + Send>>,
+    // Snapshot of the most recent analysis, for history and other consumers
+    last_snapshot: Option<Snapshot>,
+    // Joined command lines keyed by (pid, start_time), reused across watch iterations
+    args_cache: HashMap<(u32, u64), String>,
+    // Start times and parents of the previous refresh, to spot reused PIDs
+    table_check: TableCheck,
+    // Moving averages for --smooth, also keyed by (pid, start_time)
+    smoother: Option<Smoother>,
+    // Highest RSS per process and tree for --peak
+    peaks: Option<PeakTracker>,
+    // Last memory change per process for --stability
+    stability: Option<StabilityTracker>,
+    // Lowest, average and highest RSS per process for --history-cols
+    history: Option<HistoryTracker>,
+    // How the matcher behaved in the current analysis
+    match_stats: Option<MatchStats>,
+    // PIDs seen per tree role in watch mode, for restart counts
+    churn: Option<ChurnTracker>,
+    // Every process seen in a matched tree in watch mode, to follow re-parented workers
+    orphans: Option<OrphanTracker>,
+    // Roots of the previous watch sample, to follow a restarted root
+    roots: Option<RootTracker>,
+    // Samples between watch renders for --fine
+    fine: Option<FineWindow>,
+    // Subtree totals of the previous watch sample for --sort delta
+    deltas: Option<DeltaSort>,
+    // Deadline for the current collection, derived from timeout
+    deadline: Option<Instant>,
+    // What the current sample could not collect, for --strict and --verbose
+    degradations: Vec<Degradation>,
+    // NVML handle for --gpu: None until first used, Some(None) if it failed to load
+    gpu: Option<Option<GpuReader>>,
+    // Set once the trees-span-several-sessions warning was shown
+    warned_sessions: bool,
+    // Set once the matches-split-across-logins warning was shown
+    warned_logins: bool,
+    // Print progress banners on stderr; off where no report is shown
+    banners: bool,
+    // Text column widths kept across watch samples, and whether they are
+    // clamped to the terminal
+    widths: Option<(StickyWidths, bool)>,
+    // Set once the kubelet could not be reached for --k8s; pods keep their UIDs from then on
+    kubelet_unreachable: bool,
+    // User names by UID for --privs, read once
+    user_names: Option<HashMap<u32, String>>,
+    // Runs of --exec-column, and whether --force lifts the process limit
+    exec: Option<(ExecRunner, bool)>,
+    // The cached run of the pattern for --since-last
+    since_last: Option<RunCache>,
+    // Run metadata for every snapshot, its timestamp set per sample
+    metadata: Option<RunMetadata>,
+    // --anonymize for the command lines of the snapshots
+    anonymizer: Anonymizer,
+    // Phase times and work counts for --self-profile, shared with main() so
+    // they are reported on every way out of the run
+    profile: Option<Arc<Mutex<SelfProfile>>>,
     system: System,
 }
 
 impl MemoryMonitor {
-    fn new(no_color: bool, show_args: bool) -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
+    fn new(options: MonitorOptions) -> Self {
         MemoryMonitor {
             processes: HashMap::new(),
-            no_color,
-            show_args,
-            system,
+            smoother: options.smooth.map(Smoother::new),
+            peaks: options.columns.peak.then(PeakTracker::new),
+            stability: options.columns.stability.then(StabilityTracker::default),
+            history: options.columns.history.then(HistoryTracker::new),
+            banners: options.format == OutputFormat::Text,
+            churn: None,
+            orphans: None,
+            roots: None,
+            fine: None,
+            deltas: None,
+            match_stats: None,
+            options,
+            source: None,
+            last_snapshot: None,
+            args_cache: HashMap::new(),
+            table_check: TableCheck::default(),
+            deadline: None,
+            degradations: Vec::new(),
+            gpu: None,
+            warned_sessions: false,
+            warned_logins: false,
+            kubelet_unreachable: false,
+            user_names: None,
+            exec: None,
+            since_last: None,
+            metadata: None,
+            anonymizer: Anonymizer::new(None, false),
+            profile: None,
+            widths: None,
+            system: System::new(),
         }
     }
     
-    // Get all processes using sysinfo crate
-    fn get_all_processes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Refresh system information
-        self.system.refresh_processes();
+    // Get all processes using sysinfo crate; returns false if the scan hit the timeout
+    fn get_all_processes(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        // A saved process table replaces the live scan
+        if let Some(source) = self.source.as_mut() {
+            self.processes = source.load()?;
+            self.check_table();
+            return Ok(true);
+        }
+        
+        // Refresh system information; command lines are fetched later, only for matched trees.
+        // The refresh runs on a worker thread so a --timeout can abandon it.
+        let refresh_started = Instant::now();
+        let mut system = std::mem::take(&mut self.system);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            system.refresh_processes_specifics(ProcessRefreshKind::new().with_memory().with_user(UpdateKind::OnlyIfNotSet));
+            let _ = sender.send(system);
+        });
+        let refreshed = match self.deadline {
+            Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())).ok(),
+            None => receiver.recv().ok(),
+        };
+        match refreshed {
+            Some(system) => self.system = system,
+            None => {
+                let timeout = self.options.timeout.map_or(0, |timeout| timeout.as_secs());
+                warn!("process scan did not finish within {}s; no results collected", timeout);
+                self.degradations.push(Degradation::Unavailable { what: "process table", reason: format!("scan did not finish within {}s", timeout) });
+                return Ok(false);
+            }
+        }
         
         // Clear existing processes to avoid duplicates
         self.processes.clear();
         
+        // An RSS of 0 may only mean the memory was hidden; where the platform
+        // cannot tell, another user's process is taken to be hidden
+        let own_uid = sysinfo::get_current_pid().ok()
+            .and_then(|pid| self.system.process(pid))
+            .and_then(|process| process.user_id())
+            .cloned();
+        
         // Iterate through all processes
         for (pid, process) in self.system.processes() {
             let pid_value = pid.as_u32();
@@ -139,543 +234,2262 @@ impl MemoryMonitor {
             let rss = process.memory(); // Already in bytes
             let ppid = process.parent().map(|p| p.as_u32());
             
-            // Get command line arguments if show_args is enabled
-            let args = if self.show_args {
-                process.cmd().join(" ")
-            } else {
-                String::new()
-            };
-            
             let mut proc_info = ProcessInfo::new(pid_value, name, rss, ppid);
-            if self.show_args && !args.is_empty() {
-                proc_info.args = Some(args);
-            }
+            proc_info.start_time = process.start_time();
+            proc_info.uid = process.user_id().and_then(numeric_uid);
+            proc_info.readable = rss > 0 || platform::memory_readable(pid_value)
+                .unwrap_or_else(|| own_uid.is_none() || process.user_id() == own_uid.as_ref());
             
             self.processes.insert(pid_value, proc_info);
         }
+        self.resolve_names();
+        debug!("process refresh took {:?} for {} processes", refresh_started.elapsed(), self.processes.len());
+        self.check_table();
         
-        Ok(())
+        Ok(true)
     }
     
-    // Build process tree starting from root PID
-    fn build_process_tree(&mut self, root_pid: u32) -> Option<ProcessInfo> {
-        if !self.processes.contains_key(&root_pid) {
-            return None;
+    // Replace names that may be empty or cut short with a more informative one
+    // from the comm file and the command line, keeping the reported name as
+    // raw_name. Linux only, where both are read from /proc.
+    fn resolve_names(&mut self) {
+        if !cfg!(target_os = "linux") {
+            return;
         }
-        
-        // Clear existing children relationships to avoid duplicates
-        for (_, proc_info) in self.processes.iter_mut() {
-            proc_info.children.clear();
+        let pids: Vec<u32> = self.processes
+            .values()
+            .filter(|proc_info| names::needs_resolution(&proc_info.name))
+            .map(|proc_info| proc_info.pid)
+            .collect();
+        let read = collect::parallel_map(&pids, self.options.jobs, |pid| (platform::read_comm(pid), platform::read_cmdline(pid)));
+        let mut renamed = 0;
+        for (pid, (comm, cmdline)) in read {
+            let Some(proc_info) = self.processes.get_mut(&pid) else {
+                continue;
+            };
+            let resolved = names::resolve(&proc_info.name, comm.as_deref(), cmdline.as_deref());
+            if resolved.raw.is_some() {
+                trace!("PID {} reported as '{}', shown as '{}'", pid, proc_info.name, resolved.display);
+                proc_info.name = resolved.display;
+                proc_info.raw_name = resolved.raw;
+                renamed += 1;
+            }
         }
-        
-        // Get all processes and their children
-        let pids: Vec<u32> = self.processes.keys().cloned().collect();
-        for pid in pids {
-            if let Some(parent_pid) = self.processes[&pid].parent_pid {
-                if self.processes.contains_key(&parent_pid) {
-                    self.processes.get_mut(&parent_pid).unwrap().add_child(pid);
-                }
+        debug!("{} of {} short process names resolved from the command line", renamed, pids.len());
+    }
+    
+    // Log the PIDs that were reused or changed parent since the last refresh,
+    // and drop the command line cached for a process whose PID was reused.
+    // The identity-keyed trackers forget such a process on their own.
+    fn check_table(&mut self) {
+        for event in self.table_check.check(&self.processes) {
+            debug!("{}", event);
+            if let Some(stale) = event.stale_identity() {
+                self.args_cache.remove(&stale);
             }
         }
-        
-        self.processes.get(&root_pid).cloned()
     }
     
-    // Find root processes (processes whose parent is not in the matching list)
-    fn find_root_processes(&self, matching_pids: &[u32]) -> Vec<u32> {
-        let mut root_pids = Vec::new();
+    // Link every process to its children once per refresh
+    fn link_children(&mut self) {
+        source::link_children(&mut self.processes);
+    }
+    
+    // --fine: one sample of the trees of the last render, reading only memory
+    // and parents into a table of its own
+    fn fine_sample(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(window), Some(snapshot)) = (self.fine.as_mut(), &self.last_snapshot) else {
+            return Ok(());
+        };
+        let mut processes = match self.source.as_mut() {
+            Some(source) => source.load()?,
+            None => {
+                self.system.refresh_processes_specifics(ProcessRefreshKind::new().with_memory());
+                self.system.processes().iter().map(|(pid, process)| {
+                    let mut proc_info = ProcessInfo::new(pid.as_u32(), process.name().to_string(), process.memory(), process.parent().map(|parent| parent.as_u32()));
+                    proc_info.start_time = process.start_time();
+                    (pid.as_u32(), proc_info)
+                }).collect()
+            }
+        };
+        source::link_children(&mut processes);
+        window.observe(&processes, &fine::root_pids(snapshot));
+        Ok(())
+    }
+    
+    // --fine: close the sampling window at the render just done, as a line
+    // for the report; empty before the first fine sample
+    fn fine_report(&mut self, interval_ms: u64) -> String {
+        let (Some(window), Some(snapshot)) = (self.fine.as_mut(), &self.last_snapshot) else {
+            return String::new();
+        };
+        let total = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+        window.finish(fine::identities(snapshot, &self.processes), total)
+            .map_or(String::new(), |report| fine::line(&report, interval_ms, &self.options.sizes))
+    }
+    
+    // Attach command line arguments to every process in the given trees, fetching
+    // only for processes not already cached from a previous refresh
+    fn collect_args(&mut self, root_pids: &[u32]) {
+        // A saved process table has no live processes to read from
+        if self.source.is_some() {
+            return;
+        }
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .collect();
         
-        for &pid in matching_pids {
-            if let Some(proc_info) = self.processes.get(&pid) {
-                // If parent is not in matching list or parent is 1 (launchd), consider it a root
-                if let Some(parent_pid) = proc_info.parent_pid {
-                    if !matching_pids.contains(&parent_pid) || parent_pid == 1 || !self.processes.contains_key(&parent_pid) {
-                        root_pids.push(pid);
+        // Drop cached entries for processes that have exited or whose PID was reused
+        let live: HashSet<(u32, u64)> = self.processes
+            .values()
+            .map(|proc_info| (proc_info.pid, proc_info.start_time))
+            .collect();
+        self.args_cache.retain(|key, _| live.contains(key));
+        
+        let missing: Vec<u32> = tree_pids
+            .iter()
+            .filter(|pid| !self.args_cache.contains_key(&(**pid, self.processes[pid].start_time)))
+            .cloned()
+            .collect();
+        
+        // Read command lines in parallel where the platform allows direct reads,
+        // falling back to sysinfo for anything the fast path could not read.
+        // Reads still pending when the deadline passes are skipped (outer None).
+        let deadline = self.deadline;
+        let mut fallback = Vec::new();
+        let mut skipped = 0;
+        let results = collect::parallel_map(&missing, self.options.jobs, |pid| {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+            Some(platform::read_cmdline(pid))
+        });
+        for (pid, cmdline) in results {
+            match cmdline {
+                Some(Some(cmdline)) => {
+                    let key = (pid, self.processes[&pid].start_time);
+                    self.args_cache.insert(key, cmdline);
+                }
+                Some(None) => fallback.push(Pid::from_u32(pid)),
+                None => skipped += 1,
+            }
+        }
+        if skipped > 0 {
+            warn!("collection timed out; command lines missing for {} processes (partial results)", skipped);
+            self.degradations.push(Degradation::TimedOut { what: "command lines", count: skipped });
+        }
+        if !fallback.is_empty() {
+            debug!("{} command lines not readable directly, falling back to sysinfo", fallback.len());
+        }
+        let mut unread = Vec::new();
+        if !fallback.is_empty() && skipped == 0 {
+            self.system.refresh_pids_specifics(&fallback, ProcessRefreshKind::new().with_cmd(UpdateKind::Always));
+            for pid in fallback {
+                match self.system.process(pid) {
+                    Some(process) => {
+                        let key = (pid.as_u32(), process.start_time());
+                        self.args_cache.insert(key, process.cmd().join(" "));
                     }
-                } else {
-                    root_pids.push(pid);
+                    None => unread.push(pid.as_u32()),
                 }
             }
+        } else {
+            unread.extend(fallback.iter().map(|pid| pid.as_u32()));
         }
+        self.failed_reads("command lines", unread);
         
-        root_pids
+        for pid in tree_pids {
+            if let Some(proc_info) = self.processes.get_mut(&pid)
+                && let Some(args) = self.args_cache.get(&(pid, proc_info.start_time))
+                && !args.is_empty() {
+                proc_info.args = Some(args.clone());
+                self.options.redact.apply(proc_info);
+            }
+        }
     }
     
-    // Convert bytes to human readable format (MB/GB)
-    fn format_memory(&self, bytes_value: u64) -> String {
-        if bytes_value == 0 {
-            return "0B".to_string();
-        }
-        
-        let mb = bytes_value as f64 / (1024.0 * 1024.0);
-        let gb = mb / 1024.0;
-        
-        if gb >= 1.0 {
-            format!("{:.1}GB", gb)
-        } else {
-            format!("{:.1}MB", mb)
+    // Add a run of `phase` to the --self-profile report
+    fn record_phase(&self, phase: &str, elapsed: Duration) {
+        if let Some(profile) = &self.profile && let Ok(mut profile) = profile.lock() {
+            profile.record(phase, elapsed);
         }
     }
     
-    // Get color based on memory usage level
-    fn get_memory_color(&self, _bytes_value: u64, is_max_memory: bool, is_second_max_memory: bool, is_third_max_memory: bool) -> String {
-        if self.no_color {
-            return String::new();
+    // Run one phase of the analysis, timed for --self-profile
+    fn phase<R>(&mut self, phase: &str, run: impl FnOnce(&mut Self) -> R) -> R {
+        let started = Instant::now();
+        let result = run(self);
+        self.record_phase(phase, started.elapsed());
+        result
+    }
+    
+    // Read per-process values that change between refreshes for every process
+    // in the given trees, so unlike command lines nothing is cached. Processes
+    // that could not be read, or were still pending at the deadline, are left
+    // out and recorded as degradations
+    fn read_tree_details<T: Send>(&mut self, root_pids: &[u32], what: &'static str, read: fn(u32) -> Option<T>) -> Vec<(u32, T)> {
+        if self.source.is_some() {
+            return Vec::new();
         }
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .collect();
         
-        // Use dark gray text with light gray background for top 1-3 memory processes
-        if is_max_memory || is_second_max_memory || is_third_max_memory {
-            // Dark gray text on light gray background
-            return format!("{}{}", colors::DARK_GRAY, colors::BG_LIGHT_GRAY);
+        // Reads still pending at the deadline are skipped (outer None)
+        let deadline = self.deadline;
+        let mut results = Vec::new();
+        let mut failed = Vec::new();
+        let mut pending = 0;
+        for (pid, value) in collect::parallel_map(&tree_pids, self.options.jobs, |pid| {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+            Some(read(pid))
+        }) {
+            match value {
+                Some(Some(value)) => results.push((pid, value)),
+                Some(None) => failed.push(pid),
+                None => pending += 1,
+            }
         }
-        
-        // No special color for non-trophy processes
-        String::new()
+        self.failed_reads(what, failed);
+        if pending > 0 {
+            self.degradations.push(Degradation::TimedOut { what, count: pending });
+        }
+        results
     }
     
-    // Get memory string with color coding
-    fn get_colored_memory_str(&self, bytes_value: u64, is_max_memory: bool, is_second_max_memory: bool, is_third_max_memory: bool) -> String {
-        let color = self.get_memory_color(bytes_value, is_max_memory, is_second_max_memory, is_third_max_memory);
-        let memory_str = self.format_memory(bytes_value);
-        if self.no_color {
-            memory_str
-        } else {
-            format!("{}{}{}", color, memory_str, colors::RESET)
+    // Record reads of `what` that failed, telling exited processes apart
+    // where the table is this host's live one
+    fn failed_reads(&mut self, what: &'static str, mut pids: Vec<u32>) {
+        if pids.is_empty() {
+            return;
         }
+        pids.sort_unstable();
+        let live = self.source.is_none();
+        self.degradations.extend(Degradation::failed_reads(what, pids, |pid| if live { platform::process_exists(pid) } else { None }));
     }
     
-    // Calculate column widths for proper alignment
-    fn calculate_column_widths(&self, root: &ProcessInfo) -> (usize, usize) {
-        let mut max_pid_width = 0;
-        let mut max_name_width = 40; // Default minimum width
-        
-        // Collect all processes in the tree
-        let mut all_processes = Vec::new();
-        self.collect_all_processes_in_tree(root, &mut all_processes);
-        
-        // Find maximum PID width and process name width
-        for proc_info in &all_processes {
-            let pid_str = proc_info.pid.to_string();
-            max_pid_width = max_pid_width.max(pid_str.len());
-            
-            // Calculate actual display name width
-            let display_name = if proc_info.name.len() > 40 {
-                format!("{}...", &proc_info.name[..37])
-            } else {
-                proc_info.name.clone()
-            };
-            max_name_width = max_name_width.max(display_name.len());
+    // Locked and dirty memory for --mem-details
+    fn collect_mem_details(&mut self, root_pids: &[u32]) {
+        for (pid, details) in self.read_tree_details(root_pids, "memory details", platform::read_mem_details) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.mem_details = Some(details);
+            }
         }
-        
-        (max_pid_width, max_name_width)
     }
     
-    // Collect all processes in the tree for width calculation
-    fn collect_all_processes_in_tree(&self, root: &ProcessInfo, processes: &mut Vec<ProcessInfo>) {
-        processes.push(root.clone());
-        for &child_pid in &root.children {
-            if let Some(child) = self.processes.get(&child_pid) {
-                self.collect_all_processes_in_tree(child, processes);
+    // Transparent and explicit huge pages for --hugepages
+    fn collect_huge_pages(&mut self, root_pids: &[u32]) {
+        for (pid, pages) in self.read_tree_details(root_pids, "huge pages", platform::read_huge_pages) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.huge_pages = Some(pages);
             }
         }
     }
     
-    // Print process tree with memory information
-    fn print_tree(&self, root: &ProcessInfo, level: usize, is_last: bool, total_memory: u64, pid_width: usize, name_width: usize) {
-        // Format the current node with colors
-        let memory_str = self.get_colored_memory_str(root.rss, root.is_max_memory, root.is_second_max_memory, root.is_third_max_memory);
-        
-        // Calculate and format overall percentage if total_memory is provided
-        let _percentage_str = if total_memory > 0 {
-            let percentage = (root.rss as f64 / total_memory as f64) * 100.0;
-            format!(" ({:.1}%)", percentage)
-        } else {
-            String::new()
-        };
-        
-        // Create compact tree structure
-        let tree_prefix = if level > 0 {
-            let mut prefix = String::new();
-            for _ in 0..(level - 1) {
-                prefix.push_str("  ");
-            }
-            prefix.push_str(if is_last { "└─ " } else { "├─ " });
-            prefix
-        } else {
-            String::new()
-        };
-        
-        // No PID coloring for cleaner output
-        let _pid_color = "";
-        
-        // Add emoji for memory ranking
-        let rank_emoji = if root.is_max_memory {
-            "🥇"
-        } else if root.is_second_max_memory {
-            "🥈"
-        } else if root.is_third_max_memory {
-            "🥉"
-        } else {
-            ""
-        };
-        
-        // Truncate or pad process name to dynamic width
-        let display_name = if root.name.len() > name_width {
-            if name_width > 3 {
-                format!("{}...", &root.name[..name_width-3])
-            } else {
-                "...".to_string()
+    // Memory rlimits and virtual size for --limits
+    fn collect_limits(&mut self, root_pids: &[u32]) {
+        for (pid, limits) in self.read_tree_details(root_pids, "limits", platform::read_limits) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.limits = Some(limits);
             }
-        } else {
-            format!("{:width$}", root.name, width = name_width)
-        };
-
-        // Print process info with dynamic column widths
-        print!("{}", tree_prefix);
-        
-        // Display green dot emoji before PID if show_args is enabled
-        if self.show_args {
-            print!("🟢");
         }
-        
-        print!("{:width$} {} {}", root.pid, display_name, memory_str, width = pid_width);
-        
-        // Display arguments if available
-        if let Some(ref args) = root.args {
-            print!(" 🔍{}", args);
+    }
+    
+    // Listening TCP ports for --ports: the system's listening sockets matched
+    // against the fds of each process. Processes whose fds cannot be read
+    // show no ports.
+    fn collect_ports(&mut self, root_pids: &[u32]) {
+        let listening = platform::read_listening_ports();
+        if listening.is_empty() {
+            return;
         }
-        
-        // Display the rank emoji
-        print!("{}", rank_emoji);
-        
-        // Print new line
-        println!();
-        
-        // Print children
-        let child_count = root.children.len();
-        for (i, child_pid) in root.children.iter().enumerate() {
-            if let Some(child) = self.processes.get(child_pid) {
-                self.print_tree(child, level + 1, i == child_count - 1, total_memory, pid_width, name_width);
+        for (pid, inodes) in self.read_tree_details(root_pids, "open sockets", platform::read_socket_inodes) {
+            // Threads share the fd table of their process
+            if self.is_thread(pid) {
+                continue;
+            }
+            let mut ports: Vec<u16> = inodes.iter().filter_map(|inode| listening.get(inode).copied()).collect();
+            ports.sort_unstable();
+            ports.dedup();
+            if let Some(proc_info) = self.processes.get_mut(&pid) && !ports.is_empty() {
+                proc_info.ports = Some(ports);
             }
         }
     }
     
-    // Main analysis function
-    fn analyze_process_tree(&mut self, process_name: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        let search_msg = if self.no_color {
-            format!("Searching: {}", process_name)
-        } else {
-            format!("Searching:{} {}{}", 
-                    colors::CYAN, process_name, colors::RESET)
-        };
-        println!("{}", search_msg);
-        
-        // Get all processes
-        self.get_all_processes()?;
-        
-        // Find matching processes with improved matching logic
-        let matching_pids: Vec<u32> = self.processes
+    // Socket inodes listening on one of the --port ports
+    fn listening_inodes(&self) -> HashSet<u64> {
+        platform::read_listening_ports()
+            .into_iter()
+            .filter(|(_, port)| self.options.listen_ports.contains(port))
+            .map(|(inode, _)| inode)
+            .collect()
+    }
+    
+    // Whether the process holds one of `inodes`; threads share the fd table
+    // of their process and never count
+    fn listens_on(&self, pid: u32, inodes: &HashSet<u64>) -> bool {
+        !inodes.is_empty() && !self.is_thread(pid)
+            && platform::read_socket_inodes(pid).is_some_and(|sockets| sockets.iter().any(|inode| inodes.contains(inode)))
+    }
+    
+    // Whether `pid` is a thread that sysinfo lists as a process
+    fn is_thread(&self, pid: u32) -> bool {
+        self.system.process(Pid::from_u32(pid)).is_some_and(|process| process.thread_kind().is_some())
+    }
+    
+    // Heap flags of the JVMs for --jvm, from their command lines, and with
+    // --jvm-probe their heap usage from jcmd. Command lines read only for
+    // this are not shown unless -v asked for them.
+    fn collect_jvm(&mut self, root_pids: &[u32]) {
+        if !self.options.columns.args {
+            self.collect_args(root_pids);
+        }
+        let tree_pids: Vec<u32> = root_pids
             .iter()
-            .filter(|(_, proc_info)| {
-                self.is_process_matching(&proc_info.name, process_name)
-            })
-            .map(|(&pid, _)| pid)
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
             .collect();
-        
-        if matching_pids.is_empty() {
-            let not_found_msg = if self.no_color {
-                format!("No processes found matching '{}'", process_name)
-            } else {
-                format!("No processes found matching '{}'{}", 
-                        process_name, colors::RESET)
+        let mut jvms = Vec::new();
+        for pid in tree_pids {
+            // Threads of a JVM share its command line; only the process is one
+            let thread = self.is_thread(pid);
+            let Some(proc_info) = self.processes.get_mut(&pid) else {
+                continue;
             };
-            println!("{}", not_found_msg);
-            return Ok(false);
+            if !thread && let Some(args) = &proc_info.args && jvm::is_jvm(&proc_info.name, args) {
+                proc_info.jvm = Some(jvm::parse_heap_flags(args));
+                jvms.push(pid);
+            }
+            if !self.options.columns.args {
+                proc_info.args = None;
+            }
         }
-        
-        let found_msg = if self.no_color {
-            format!("Found {} procs", matching_pids.len())
-        } else {
-            format!("Found {} procs{}", 
-                    matching_pids.len(), colors::RESET)
-        };
-        println!("{}", found_msg);
-        
-        // Find root processes
-        let root_pids = self.find_root_processes(&matching_pids);
-        
-        if root_pids.is_empty() {
-            let no_root_msg = if self.no_color {
-                "No root processes found".to_string()
-            } else {
-                "No root processes found".to_string()
-            };
-            println!("{}", no_root_msg);
-            return Ok(false);
+        if !self.options.jvm_probe || jvms.is_empty() {
+            return;
         }
-        
-        let root_msg = if self.no_color {
-            format!("Found {} trees", root_pids.len())
-        } else {
-            format!("Found {} trees{}", 
-                    root_pids.len(), colors::RESET)
-        };
-        println!("{}", root_msg);
-        
-        // Analyze each process tree
-        for (i, &root_pid) in root_pids.iter().enumerate() {
-            if i > 0 {
-                if self.no_color {
-                    println!("\n{}", "=".repeat(60));
-                } else {
-                    println!();
-                }
-            }
-            
-            // Build and print tree
-            if let Some(root_process) = self.build_process_tree(root_pid) {
-                // Collect all RSS values in this tree and find max, second max, and third max
-                let all_rss_in_tree = self.collect_all_rss_in_tree(&root_process);
-                
-                // Calculate total memory for this tree
-                let total_memory = self.calculate_total_memory(&root_process);
-                
-                // Mark processes with max, second max, and third max memory
-                if !all_rss_in_tree.is_empty() {
-                    let tree_max_rss = *all_rss_in_tree.iter().max().unwrap();
-                    let filtered_rss: Vec<u64> = all_rss_in_tree.iter().filter(|&&rss| rss != tree_max_rss).cloned().collect();
-                    let tree_second_max_rss = if !filtered_rss.is_empty() {
-                        *filtered_rss.iter().max().unwrap()
-                    } else {
-                        0
-                    };
-                    
-                    // Find third max
-                    let third_filtered_rss: Vec<u64> = filtered_rss.iter().filter(|&&rss| rss != tree_second_max_rss).cloned().collect();
-                    let tree_third_max_rss = if !third_filtered_rss.is_empty() {
-                        *third_filtered_rss.iter().max().unwrap()
-                    } else {
-                        0
-                    };
-                    
-                    // Mark processes with max, second max, and third max memory
-                    self.mark_memory_highlights_in_tree(root_pid, tree_max_rss, tree_second_max_rss, tree_third_max_rss);
-                }
-                
-                // Get the updated root process after marking highlights
-                if let Some(updated_root_process) = self.processes.get(&root_pid).cloned() {
-                    // Calculate column widths for proper alignment
-                    let (pid_width, name_width) = self.calculate_column_widths(&updated_root_process);
-                    self.print_tree(&updated_root_process, 0, false, total_memory, pid_width, name_width);
-                }
-                
-                // Print summary
-                let process_count = self.count_processes(&root_process);
-                
-                // Calculate and print average memory
-                let average_memory = if process_count > 0 {
-                    total_memory / process_count as u64
-                } else {
-                    0
-                };
-                
-                // For summary, we need to check if this tree contains top 3 memory processes
-                let all_rss_in_tree = self.collect_all_rss_in_tree(&root_process);
-                let tree_max_rss = *all_rss_in_tree.iter().max().unwrap_or(&0);
-                let tree_second_max_rss = if all_rss_in_tree.len() > 1 {
-                    *all_rss_in_tree.iter().filter(|&&rss| rss != tree_max_rss).max().unwrap_or(&0)
-                } else { 0 };
-                let tree_third_max_rss = if all_rss_in_tree.len() > 2 {
-                    *all_rss_in_tree.iter().filter(|&&rss| rss != tree_max_rss && rss != tree_second_max_rss).max().unwrap_or(&0)
-                } else { 0 };
-                
-                let has_top_memory = tree_max_rss > 0 || tree_second_max_rss > 0 || tree_third_max_rss > 0;
-                
-                let avg_memory_str = if has_top_memory {
-                    self.get_colored_memory_str(average_memory, true, true, true)
-                } else {
-                    self.format_memory(average_memory)
-                };
-                let total_memory_str = if has_top_memory {
-                    self.get_colored_memory_str(total_memory, true, true, true)
-                } else {
-                    self.format_memory(total_memory)
-                };
-                
-                let summary = if self.no_color {
-                    format!("{} procs | {} avg | {} total", 
-                            process_count, 
-                            self.format_memory(average_memory), 
-                            self.format_memory(total_memory))
-                } else {
-                    format!("{} procs | {} avg | {} total", 
-                            process_count,
-                            avg_memory_str, total_memory_str)
-                };
-                println!("{}", summary);
+        let spinner = Spinner::start("Probing JVM heaps");
+        let usage = collect::parallel_map(&jvms, self.options.jobs, jvm::probe);
+        spinner.stop();
+        let mut unprobed = Vec::new();
+        for (pid, usage) in usage {
+            if let Some((used, committed)) = usage
+                && let Some(heap) = self.processes.get_mut(&pid).and_then(|proc_info| proc_info.jvm.as_mut()) {
+                heap.used_bytes = Some(used);
+                heap.committed_bytes = Some(committed);
             } else {
-                let error_msg = if self.no_color {
-                    format!("Could not build process tree for PID {}", root_pid)
-                } else {
-                    format!("Could not build process tree for PID {}", root_pid)
-                };
-                println!("{}", error_msg);
+                unprobed.push(pid);
             }
         }
-        
-        Ok(true)
+        if !unprobed.is_empty() {
+            warn!("jcmd could not report the heap of {} of {} JVMs; showing their -Xmx only", unprobed.len(), jvms.len());
+            self.failed_reads("JVM heaps", unprobed);
+        }
     }
     
-    // Improved process name matching logic
-    fn is_process_matching(&self, proc_name: &str, target_name: &str) -> bool {
-        let proc_name_lower = proc_name.to_lowercase();
-        let target_name_lower = target_name.to_lowercase();
-        
-        // Handle truncated process names (common on macOS with ps -c)
-        // If target name is being searched and process name might be truncated
-        if proc_name_lower.len() >= 15 && target_name_lower.starts_with(&proc_name_lower) {
-            return true;
-        }
-        
-        // Handle case where target name is long and might be truncated
-        if target_name_lower.len() > 15 && proc_name_lower.starts_with(&target_name_lower[..15]) {
-            return true;
-        }
-        
-        // Exact match
-        if proc_name_lower == target_name_lower {
-            return true;
-        }
-        
-        // Check if target name starts with process name (for truncated names)
-        if target_name_lower.starts_with(&proc_name_lower) {
-            return true;
-        }
-        
-        // Check if process name starts with target name (for partial matching)
-        if proc_name_lower.starts_with(&target_name_lower) {
-            return true;
+    // GPU memory for --gpu. NVML is loaded on first use; when that fails the
+    // column stays empty for the rest of the run after a single warning
+    fn collect_gpu(&mut self, root_pids: &[u32]) {
+        if self.source.is_some() {
+            return;
         }
-        
-        // Extract basename from process name if it contains a path
-        let proc_basename = if proc_name_lower.contains('/') {
-            proc_name_lower.split('/').last().unwrap_or(&proc_name_lower).to_string()
-        } else {
-            proc_name_lower.clone()
-        };
-        
-        // Extract basename from target name if it contains a path
-        let target_basename = if target_name_lower.contains('/') {
-            target_name_lower.split('/').last().unwrap_or(&target_name_lower).to_string()
-        } else {
-            target_name_lower.clone()
-        };
-        
-        // Handle common executable extensions
-        let base_proc = if proc_basename.ends_with(".exe") || proc_basename.ends_with(".app") || 
-                          proc_basename.ends_with(".bin") || proc_basename.ends_with(".run") {
-            proc_basename[..proc_basename.len()-4].to_string()
-        } else {
-            proc_basename
+        let degradations = &mut self.degradations;
+        let reader = self.gpu.get_or_insert_with(|| {
+            GpuReader::new().map_err(|err| {
+                warn!("GPU memory unavailable: {}", err);
+                degradations.push(Degradation::Unavailable { what: "GPU memory", reason: err.to_string() });
+            }).ok()
+        });
+        let Some(reader) = reader else {
+            return;
         };
-        
-        let base_target = if target_basename.ends_with(".exe") || target_basename.ends_with(".app") || 
-                            target_basename.ends_with(".bin") || target_basename.ends_with(".run") {
-            target_basename[..target_basename.len()-4].to_string()
-        } else {
-            target_basename
+        let usage = match reader.read() {
+            Ok(usage) => usage,
+            Err(err) => {
+                debug!("reading GPU processes failed: {}", err);
+                self.degradations.push(Degradation::Unavailable { what: "GPU memory", reason: err.to_string() });
+                return;
+            }
         };
-        
-        if base_proc == base_target {
-            return true;
-        }
-        
-        // Check for common macOS app naming patterns
-        // Some apps have process names like "App Name" when app is "AppName"
-        if target_name.contains(' ') {
-            let compact_name = target_name.replace(' ', "").to_lowercase();
-            if proc_name_lower == compact_name {
-                return true;
+        // Processes NVML does not list hold no GPU memory
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .collect();
+        for pid in tree_pids {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.gpu_bytes = Some(usage.get(&pid).copied().unwrap_or(0));
             }
         }
-        
-        false
     }
     
-    // Count total number of processes in tree
-    fn count_processes(&self, root: &ProcessInfo) -> usize {
-        let mut count = 1; // Root itself
-        for child_pid in &root.children {
-            if let Some(child) = self.processes.get(child_pid) {
-                count += self.count_processes(child);
+    // Nice values for --nice and --only-nice
+    fn collect_nice(&mut self, root_pids: &[u32]) {
+        for (pid, nice) in self.read_tree_details(root_pids, "nice values", platform::read_nice) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.nice = Some(nice);
             }
         }
-        count
     }
     
-    // Calculate total RSS memory for a process tree
-    fn calculate_total_memory(&self, root: &ProcessInfo) -> u64 {
-        let mut total_memory = root.rss; // Root's memory
-        for child_pid in &root.children {
-            if let Some(child) = self.processes.get(child_pid) {
-                total_memory += self.calculate_total_memory(child);
+    // Users and capabilities for --privs, with the effective user's name
+    fn collect_privs(&mut self, root_pids: &[u32]) {
+        let privs = self.read_tree_details(root_pids, "privileges", platform::read_privileges);
+        if privs.is_empty() {
+            return;
+        }
+        let users = self.user_names.get_or_insert_with(|| {
+            fs::read_to_string("/etc/passwd").map_or_else(|_| HashMap::new(), |passwd| privs::parse_passwd(&passwd))
+        });
+        for (pid, mut privileges) in privs {
+            privileges.user = users.get(&privileges.euid).cloned();
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.privs = Some(privileges);
             }
         }
-        total_memory
     }
     
-    // Collect all RSS values from processes in the tree
-    fn collect_all_rss_in_tree(&self, root: &ProcessInfo) -> Vec<u64> {
-        let mut rss_values = vec![root.rss]; // Root's RSS
-        for child_pid in &root.children {
-            if let Some(child) = self.processes.get(child_pid) {
-                rss_values.extend(self.collect_all_rss_in_tree(child));
+    // Process groups for --pgid and --group-by pgid
+    fn collect_pgids(&mut self, root_pids: &[u32]) {
+        for (pid, pgid) in self.read_tree_details(root_pids, "process groups", platform::read_pgid) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.pgid = Some(pgid);
             }
         }
-        rss_values
     }
     
-    // Mark processes with max, second max, and third max memory in the tree
-    fn mark_memory_highlights_in_tree(&mut self, root_pid: u32, max_rss: u64, second_max_rss: u64, third_max_rss: u64) {
-        // Create a list of all process IDs in the tree to avoid borrowing issues
-        let mut process_ids = Vec::new();
-        self.collect_process_ids_in_tree(root_pid, &mut process_ids);
-        
-        // Mark processes with max, second max, and third max memory
-        for pid in process_ids {
+    // Executable paths for --group-by exe
+    fn collect_exes(&mut self, root_pids: &[u32]) {
+        for (pid, exe) in self.read_tree_details(root_pids, "executable paths", platform::read_exe) {
             if let Some(proc_info) = self.processes.get_mut(&pid) {
-                if proc_info.rss == max_rss {
-                    proc_info.is_max_memory = true;
-                } else if proc_info.rss == second_max_rss && second_max_rss > 0 {
-                    proc_info.is_second_max_memory = true;
-                } else if proc_info.rss == third_max_rss && third_max_rss > 0 {
-                    proc_info.is_third_max_memory = true;
-                }
+                proc_info.exe = Some(exe);
             }
         }
     }
     
-    // Collect all process IDs in the tree
-    fn collect_process_ids_in_tree(&self, root_pid: u32, process_ids: &mut Vec<u32>) {
-        process_ids.push(root_pid);
-        if let Some(proc_info) = self.processes.get(&root_pid) {
-            for child_pid in &proc_info.children {
-                self.collect_process_ids_in_tree(*child_pid, process_ids);
+    // Private and shared memory from smaps for --dedupe-shared
+    fn collect_sharing(&mut self, root_pids: &[u32]) {
+        for (pid, sharing) in self.read_tree_details(root_pids, "smaps", platform::read_sharing) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.sharing = Some(sharing);
             }
         }
     }
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
     
-    // Create memory monitor and analyze
-    let mut monitor = MemoryMonitor::new(!colors::should_use_colors(args.no_color), args.show_args);
-    let success = monitor.analyze_process_tree(&args.process_name)?;
+    // Tree total for --dedupe-shared; processes whose smaps could not be
+    // read count their whole RSS
+    fn shared_deduped_total(&self, tree: &ProcessTree) -> u64 {
+        let sharings: Vec<Sharing> = tree.iter_preorder()
+            .filter(|(node, _)| !node.is_virtual)
+            .map(|(node, _)| {
+                self.processes.get(&node.pid)
+                    .and_then(|proc_info| proc_info.sharing.clone())
+                    .unwrap_or_else(|| Sharing::unsplit(node.rss_bytes))
+            })
+            .collect();
+        maps::deduped_total(&sharings)
+    }
     
-    if !success {
-        std::process::exit(1);
+    // Pods for --k8s from the cgroup paths, named by the kubelet. When it
+    // cannot be reached the pods show their UIDs for the rest of the run,
+    // after a single warning
+    fn collect_pods(&mut self, root_pids: &[u32]) {
+        let mut pods: Vec<(u32, PodRef)> = self.read_tree_details(root_pids, "cgroups", platform::read_cgroup)
+            .into_iter()
+            .filter_map(|(pid, cgroup)| k8s::parse_cgroup(&cgroup).map(|pod| (pid, pod)))
+            .collect();
+        if pods.is_empty() {
+            return;
+        }
+        if !self.kubelet_unreachable {
+            let url = std::env::var("MEMON_KUBELET_URL").unwrap_or_else(|_| k8s::KUBELET_PODS_URL.to_string());
+            match k8s::fetch_pods(&url) {
+                Ok(directory) => pods.iter_mut().for_each(|(_, pod)| directory.resolve(pod)),
+                Err(err) => {
+                    warn!("pod names unavailable, showing pod UIDs: {}", err);
+                    self.degradations.push(Degradation::Unavailable { what: "pod names", reason: err.to_string() });
+                    self.kubelet_unreachable = true;
+                }
+            }
+        }
+        for (pid, pod) in pods {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.pod = Some(pod);
+            }
+        }
     }
     
-    Ok(())
-}
\ No newline at end of file
+    // Output of the --exec-column command for every process of the trees.
+    // Threads are skipped, and a live scan is needed since the command runs
+    // on this host. Refreshed at most once per watch or stream interval;
+    // in between, the values of the last refresh are shown.
+    fn collect_exec(&mut self, root_pids: &[u32]) -> Result<(), String> {
+        if self.source.is_some() {
+            return Ok(());
+        }
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .filter(|&pid| !self.is_thread(pid))
+            .collect();
+        let processes: Vec<(u32, u64)> = tree_pids
+            .iter()
+            .filter_map(|pid| self.processes.get(pid).map(|proc_info| (proc_info.pid, proc_info.start_time)))
+            .collect();
+        let jobs = self.options.jobs;
+        let Some((runner, force)) = self.exec.as_mut() else {
+            return Ok(());
+        };
+        if processes.len() > exec_column::MAX_PROCESSES && !*force {
+            return Err(format!("--exec-column would run '{}' for {} processes, more than {}; pass --force to allow it",
+                               runner.column.command, processes.len(), exec_column::MAX_PROCESSES));
+        }
+        if runner.due(Instant::now()) {
+            let spinner = Spinner::start("Running exec column");
+            let stats = runner.refresh(&processes, jobs);
+            spinner.stop();
+            if stats.failed + stats.timed_out > 0 {
+                debug!("--exec-column '{}': {} of {} runs failed, {} of them timed out",
+                       runner.column.name, stats.failed + stats.timed_out, stats.ran, stats.timed_out);
+            }
+        }
+        for (pid, start_time) in processes {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.exec = runner.value(pid, start_time).map(str::to_string);
+            }
+        }
+        Ok(())
+    }
+    
+    // Peak RSS for --peak: VmHWM where the kernel provides it, plus the
+    // highest value seen across samples
+    fn collect_peaks(&mut self, root_pids: &[u32]) {
+        let kernel: HashMap<u32, u64> = self.read_tree_details(root_pids, "peak RSS", platform::read_peak_rss).into_iter().collect();
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .collect();
+        if let Some(peaks) = self.peaks.as_mut() {
+            peaks.observe(&mut self.processes, &tree_pids, &kernel);
+        }
+    }
+    
+    // RSS range over the watch session for --history-cols
+    fn collect_history(&mut self, root_pids: &[u32]) {
+        let tree_pids: Vec<u32> = root_pids
+            .iter()
+            .flat_map(|&root_pid| self.tree_pids(root_pid))
+            .collect();
+        if let Some(history) = self.history.as_mut() {
+            history.observe(&mut self.processes, &tree_pids);
+            trace!("tracking the RSS history of {} processes", history.len());
+        }
+    }
+    
+    // Controlling terminals for --show-tty
+    fn collect_sessions(&mut self, root_pids: &[u32]) {
+        for (pid, session) in self.read_tree_details(root_pids, "sessions", platform::read_session) {
+            if let Some(proc_info) = self.processes.get_mut(&pid) {
+                proc_info.session = Some(session);
+            }
+        }
+    }
+    
+    // Session IDs of every process started under another user than its
+    // parent, and of that parent, for roots::other_login; None when no user
+    // changes, as in a loaded table, which has no users
+    fn login_sessions(&self) -> Option<HashMap<u32, u32>> {
+        let sessions: HashMap<u32, u32> = self.processes
+            .values()
+            .filter(|proc_info| {
+                let parent_uid = proc_info.parent_pid.and_then(|parent_pid| self.processes.get(&parent_pid)).and_then(|parent| parent.uid);
+                matches!((proc_info.uid, parent_uid), (Some(uid), Some(parent_uid)) if uid != parent_uid)
+            })
+            .flat_map(|proc_info| [proc_info.pid].into_iter().chain(proc_info.parent_pid))
+            .filter_map(|pid| Some((pid, self.session_of(pid)?.id)))
+            .collect();
+        (!sessions.is_empty()).then_some(sessions)
+    }
+    
+    // Session of a live process: /proc on Linux, the session ID from sysinfo elsewhere
+    fn session_of(&self, pid: u32) -> Option<Session> {
+        platform::read_session(pid).or_else(|| {
+            let id = self.system.process(Pid::from_u32(pid))?.session_id()?;
+            Some(Session { id: id.as_u32(), tty: None })
+        })
+    }
+    
+    // Keep only the roots in memon's own session with --current-session-only;
+    // otherwise warn once when the roots belong to several sessions
+    fn filter_sessions(&mut self, root_pids: Vec<u32>) -> Result<Vec<u32>, String> {
+        if self.source.is_some() {
+            return Ok(root_pids);
+        }
+        let sessions: Vec<Option<Session>> = root_pids.iter().map(|&pid| self.session_of(pid)).collect();
+        if self.options.current_session_only {
+            let own = self.session_of(std::process::id())
+                .ok_or("cannot determine the session memon runs in")?;
+            return Ok(root_pids
+                .into_iter()
+                .zip(&sessions)
+                .filter(|(_, session)| session.as_ref().is_some_and(|session| session.id == own.id))
+                .map(|(pid, _)| pid)
+                .collect());
+        }
+        
+        let ids: HashSet<u32> = sessions.iter().flatten().map(|session| session.id).collect();
+        let ttys: HashSet<&str> = sessions.iter().flatten().filter_map(|session| session.tty.as_deref()).collect();
+        if ids.len() > 1 && !self.warned_sessions {
+            self.warned_sessions = true;
+            let terminals = if ttys.len() > 1 { format!(" on {} terminals", ttys.len()) } else { String::new() };
+            warn!("the {} matched trees span {} sessions{}; use --current-session-only to keep the trees of this session",
+                  root_pids.len(), ids.len(), terminals);
+        }
+        Ok(root_pids)
+    }
+    
+    // memon and the processes it descends from, such as its shell; empty for a
+    // table loaded with --from-file or --ssh
+    fn own_ancestors(&self) -> HashSet<u32> {
+        if self.source.is_some() {
+            return HashSet::new();
+        }
+        source::ancestor_chain(&self.processes, std::process::id()).into_iter().collect()
+    }
+    
+    // The tree rooted at `root_pid` from a fresh scan, for `memon run`; None
+    // once the process is gone or when the scan timed out
+    fn sample_tree(&mut self, root_pid: u32) -> Result<Option<ProcessTree>, Box<dyn std::error::Error>> {
+        if !self.get_all_processes()? {
+            return Ok(None);
+        }
+        self.link_children();
+        if self.peaks.is_some() {
+            self.collect_peaks(&[root_pid]);
+        }
+        let Some(mut tree) = ProcessTree::from_processes(&mut self.processes, root_pid) else {
+            return Ok(None);
+        };
+        if let Some(peaks) = self.peaks.as_mut() {
+            let start_time = self.processes.get(&root_pid).map_or(0, |proc_info| proc_info.start_time);
+            tree.peak_bytes = Some(peaks.observe_tree((root_pid, start_time), tree.total_bytes));
+            peaks.observe_total(tree.total_bytes);
+        }
+        Ok(Some(tree))
+    }
+    
+    // Collect all process IDs in the tree rooted at root_pid
+    fn tree_pids(&self, root_pid: u32) -> Vec<u32> {
+        let mut pids = Vec::new();
+        let mut stack = vec![root_pid];
+        while let Some(pid) = stack.pop() {
+            if let Some(proc_info) = self.processes.get(&pid) {
+                pids.push(pid);
+                stack.extend(&proc_info.children);
+            }
+        }
+        pids
+    }
+    
+    // Find root processes under the --roots policy; with all-matched each
+    // match is also cut off its parent, so no tree holds another
+    fn find_root_processes(&mut self, matching_pids: &[u32]) -> Vec<u32> {
+        let logins = if self.options.cross_user_trees { None } else { self.login_sessions() };
+        let root_pids = roots::find_roots(&self.processes, matching_pids, self.options.roots, logins.as_ref());
+        if logins.is_some() && !self.warned_logins {
+            let joined = roots::find_roots(&self.processes, matching_pids, self.options.roots, None);
+            let split: Vec<u32> = root_pids.iter().copied().filter(|pid| !joined.contains(pid)).collect();
+            if !split.is_empty() {
+                self.warned_logins = true;
+                warn!("{} matched processes run in another user's login than the matched process above them and get trees of their own (PIDs {:?}); use --cross-user-trees to keep them in the tree above",
+                      split.len(), split);
+            }
+        }
+        if self.options.roots == RootPolicy::AllMatched {
+            roots::detach_matched(&mut self.processes, matching_pids);
+        }
+        root_pids
+    }
+    
+    // What is searched for: "nginx", "PID 42", "nginx under PID 42", with
+    // --port followed by "listening on port 8080 or 8443"
+    fn target(&self, process_name: &str) -> String {
+        let mut target = match self.options.under {
+            Some(under) if process_name.is_empty() => format!("PID {}", under),
+            Some(under) => format!("{} under PID {}", process_name, under),
+            None => process_name.to_string(),
+        };
+        if !self.options.listen_ports.is_empty() {
+            let ports: Vec<String> = self.options.listen_ports.iter().map(u16::to_string).collect();
+            let listening = format!("listening on port {}", ports.join(" or "));
+            target = if target.is_empty() { listening } else { format!("{} {}", target, listening) };
+        }
+        target
+    }
+    
+    // Main analysis function; the rendered report is appended to out
+    fn analyze_process_tree(&mut self, process_name: &str, out: &mut String) -> Result<bool, Box<dyn std::error::Error>> {
+        let target = self.target(process_name);
+        self.banner(&format!("Searching: {}", target));
+        
+        // Get all processes, showing a spinner on stderr if this takes a while
+        self.deadline = self.options.timeout.map(|timeout| Instant::now() + timeout);
+        self.degradations.clear();
+        let spinner = Spinner::start("Scanning processes");
+        self.match_stats = None;
+        let scan_started = Instant::now();
+        if !self.get_all_processes()? {
+            return Ok(false);
+        }
+        if let Some(smoother) = self.smoother.as_mut() {
+            smoother.apply(&mut self.processes);
+            trace!("smoothing {} processes", smoother.len());
+        }
+        if let Some(stability) = self.stability.as_mut() {
+            stability.apply(&mut self.processes, unix_now());
+            trace!("tracking stability of {} processes", stability.len());
+        }
+        self.link_children();
+        let scan_elapsed = scan_started.elapsed();
+        self.record_phase("scan", scan_elapsed);
+        
+        let match_started = Instant::now();
+        let (matching_pids, mut match_stats) = self.matching_pids(process_name);
+        self.record_phase("match", match_started.elapsed());
+        match_stats.timings.scan_ms = match_stats::millis(scan_elapsed);
+        match_stats.timings.match_ms = match_stats::millis(match_started.elapsed());
+        self.match_stats = Some(match_stats);
+        spinner.stop();
+        
+        // Watch mode: workers that left the matched trees when their parent died
+        let orphan_roots = self.follow_orphans(&matching_pids);
+        
+        if matching_pids.is_empty() && orphan_roots.is_empty() {
+            let not_found = match self.options.under {
+                _ if !self.options.listen_ports.is_empty() => format!("No processes found: {}", target),
+                Some(under) if process_name.is_empty() => format!("No process with PID {}", under),
+                Some(under) => format!("No processes found matching '{}' under PID {}", process_name, under),
+                None => format!("No processes found matching '{}'", process_name),
+            };
+            self.finish_snapshot(out, process_name, 0, Vec::new());
+            self.banner(&not_found);
+            return Ok(false);
+        }
+        
+        self.banner(&format!("Found {} procs", self.options.sizes.count(matching_pids.len() as u64)));
+        
+        // Find root processes
+        let roots_started = Instant::now();
+        let root_pids = self.find_root_processes(&matching_pids);
+        self.record_phase("roots", roots_started.elapsed());
+        if let Some(match_stats) = self.match_stats.as_mut() {
+            match_stats.set_roots(root_pids.len());
+            match_stats.timings.roots_ms = match_stats::millis(roots_started.elapsed());
+        }
+        debug!("{} matched processes form {} trees: roots {:?}", matching_pids.len(), root_pids.len(), root_pids);
+        
+        if root_pids.is_empty() && orphan_roots.is_empty() {
+            self.finish_snapshot(out, process_name, matching_pids.len(), Vec::new());
+            self.banner("No root processes found");
+            return Ok(false);
+        }
+        
+        let root_pids = self.filter_sessions(root_pids)?;
+        if root_pids.is_empty() && orphan_roots.is_empty() {
+            self.finish_snapshot(out, process_name, matching_pids.len(), Vec::new());
+            self.banner("No matched trees in the current session");
+            return Ok(false);
+        }
+        
+        // Watch mode: a root restarted under a new PID, or with --no-reroot
+        // only the roots of the first sample
+        let root_pids = self.follow_roots(root_pids);
+        if root_pids.is_empty() && orphan_roots.is_empty() {
+            let exited = self.roots.as_ref().map_or(Vec::new(), |roots| roots.exited(&self.processes));
+            let exited: Vec<String> = exited.iter().map(u32::to_string).collect();
+            self.finish_snapshot(out, process_name, matching_pids.len(), Vec::new());
+            self.banner(&format!("Watched roots exited: {}", exited.join(", ")));
+            return Ok(false);
+        }
+        
+        self.banner(&format!("Found {} trees", self.options.sizes.count(root_pids.len() as u64)));
+        
+        // Fetch command lines only for processes that will be displayed
+        let detail_pids: Vec<u32> = root_pids.iter().chain(&orphan_roots).copied().collect();
+        if self.options.columns.args {
+            let spinner = Spinner::start("Reading command lines");
+            self.phase("collect args", |monitor| monitor.collect_args(&detail_pids));
+            spinner.stop();
+        }
+        if self.options.columns.mem_details {
+            self.phase("collect mem-details", |monitor| monitor.collect_mem_details(&detail_pids));
+        }
+        if self.options.columns.hugepages {
+            self.phase("collect hugepages", |monitor| monitor.collect_huge_pages(&detail_pids));
+        }
+        if self.options.columns.limits {
+            self.phase("collect limits", |monitor| monitor.collect_limits(&detail_pids));
+        }
+        if self.options.columns.ports {
+            self.phase("collect ports", |monitor| monitor.collect_ports(&detail_pids));
+        }
+        if self.options.columns.jvm {
+            self.phase("collect jvm", |monitor| monitor.collect_jvm(&detail_pids));
+        }
+        if self.options.columns.gpu {
+            self.phase("collect gpu", |monitor| monitor.collect_gpu(&detail_pids));
+        }
+        if self.options.columns.tty {
+            self.phase("collect tty", |monitor| monitor.collect_sessions(&detail_pids));
+        }
+        if self.options.columns.k8s {
+            self.phase("collect k8s", |monitor| monitor.collect_pods(&detail_pids));
+        }
+        if self.options.dedupe_shared {
+            self.phase("collect dedupe-shared", |monitor| monitor.collect_sharing(&detail_pids));
+        }
+        if self.options.group_by == Some(GroupBy::Exe) {
+            self.phase("collect exe", |monitor| monitor.collect_exes(&detail_pids));
+        }
+        if self.options.columns.pgid || self.options.group_by == Some(GroupBy::Pgid) {
+            self.phase("collect pgid", |monitor| monitor.collect_pgids(&detail_pids));
+        }
+        if self.options.columns.privs {
+            self.phase("collect privs", |monitor| monitor.collect_privs(&detail_pids));
+        }
+        if self.options.columns.nice || self.options.only_nice.is_some() {
+            self.phase("collect nice", |monitor| monitor.collect_nice(&detail_pids));
+        }
+        if self.options.columns.peak {
+            self.phase("collect peak", |monitor| monitor.collect_peaks(&detail_pids));
+        }
+        if self.history.is_some() {
+            self.phase("collect history-cols", |monitor| monitor.collect_history(&detail_pids));
+        }
+        
+        // --tree / --tree-root: keep the one selected root, or list the choices
+        let root_pids = match self.options.tree {
+            Some(selection) => match selection.select(&root_pids) {
+                Some(root_pid) => vec![root_pid],
+                None => return Err(self.unknown_tree_error(selection, &root_pids).into()),
+            },
+            None => root_pids,
+        };
+        
+        // --only-nice: cut each tree down to the passing processes and their ancestors
+        let root_pids = match self.options.only_nice {
+            Some(filter) => {
+                let kept: Vec<u32> = root_pids.into_iter().filter(|&root_pid| nice::prune(&mut self.processes, root_pid, filter)).collect();
+                if kept.is_empty() {
+                    self.finish_snapshot(out, process_name, matching_pids.len(), Vec::new());
+                    self.banner("No processes pass --only-nice");
+                    return Ok(false);
+                }
+                kept
+            }
+            None => root_pids,
+        };
+        let orphan_roots: Vec<u32> = match self.options.only_nice {
+            Some(filter) => orphan_roots.into_iter().filter(|&orphan_pid| nice::prune(&mut self.processes, orphan_pid, filter)).collect(),
+            None => orphan_roots,
+        };
+        
+        // After --tree and --only-nice, so the command only runs for processes that are shown
+        if self.exec.is_some() {
+            let shown: Vec<u32> = root_pids.iter().chain(&orphan_roots).copied().collect();
+            self.phase("collect exec-column", |monitor| monitor.collect_exec(&shown))?;
+        }
+        
+        // Analyze each process tree
+        let ancestors = self.own_ancestors();
+        let mut trees = Vec::new();
+        // --merge-roots: one tree with every root under a synthetic node
+        let build_started = Instant::now();
+        let mut built: Vec<ProcessTree> = if self.options.merge_roots {
+            ProcessTree::merged(&mut self.processes, process_name, &root_pids).into_iter().collect()
+        } else {
+            root_pids.iter().filter_map(|&root_pid| ProcessTree::from_processes(&mut self.processes, root_pid)).collect()
+        };
+        // Re-parented workers follow as trees of their own, never merged
+        for &orphan_pid in &orphan_roots {
+            if let Some(mut tree) = ProcessTree::from_processes(&mut self.processes, orphan_pid) {
+                tree.reparented = true;
+                built.push(tree);
+            }
+        }
+        self.record_phase("build", build_started.elapsed());
+        if let Some(match_stats) = self.match_stats.as_mut() {
+            match_stats.timings.build_ms = match_stats::millis(build_started.elapsed());
+        }
+        // Memory hidden from this user, or of processes gone since the scan;
+        // the totals are then a lower bound
+        let unreadable: Vec<u32> = built.iter()
+            .flat_map(|tree| tree.iter_preorder())
+            .filter(|(node, _)| !node.readable && !node.is_virtual)
+            .map(|(node, _)| node.pid)
+            .collect();
+        self.failed_reads("memory", unreadable);
+        let matched: HashSet<u32> = matching_pids.iter().copied().collect();
+        for mut tree in built {
+            // Every tree goes into the snapshot, which is rendered once all
+            // trees are collected
+            let root_pid = tree.root_pid;
+            if self.options.matched_only_totals {
+                tree.matched_total = Some(tree.matched_total(&matched));
+            }
+            if self.options.siblings {
+                tree.siblings = SiblingContext::collect(&self.processes, root_pid);
+            }
+            if self.options.highlight_matched {
+                tree.rank_matched(&matched);
+            }
+            if self.options.dedupe_shared {
+                tree.shared_deduped_bytes = Some(self.shared_deduped_total(&tree));
+            }
+            tree.current_session = tree.iter_preorder().any(|(node, _)| ancestors.contains(&node.pid));
+            if let Some(peaks) = self.peaks.as_mut() {
+                let start_time = self.processes.get(&root_pid).map_or(0, |proc_info| proc_info.start_time);
+                tree.peak_bytes = Some(peaks.observe_tree((root_pid, start_time), tree.total_bytes));
+            }
+            debug!("tree {}: {} procs, {} bytes total", root_pid, tree.process_count, tree.total_bytes);
+            trees.push(tree);
+        }
+        if let Some(peaks) = self.peaks.as_mut() {
+            peaks.observe_total(trees.iter().map(|tree| tree.total_bytes).sum());
+        }
+        if let Some(deltas) = self.deltas.as_mut() {
+            deltas.sort(&mut trees, &self.processes);
+        }
+        
+        self.finish_snapshot(out, process_name, matching_pids.len(), trees);
+        
+        Ok(true)
+    }
+    
+    // Physical memory of this host for the summary highlight; unknown for a
+    // table loaded with --from-file or read over --ssh
+    fn system_memory(&mut self) -> Option<u64> {
+        if self.source.is_some() {
+            return None;
+        }
+        if self.system.total_memory() == 0 {
+            self.system.refresh_memory();
+        }
+        Some(self.system.total_memory()).filter(|&memory| memory > 0)
+    }
+    
+    // Progress banner on stderr, so stdout carries only the report
+    fn banner(&self, message: &str) {
+        if self.banners {
+            eprintln!("{}", render::banner(&Theme::new(self.options.banner_color.enabled()), message));
+        }
+    }
+    
+    // Roots of the processes that were part of a matched tree earlier in the
+    // watch session and are still alive outside the trees of `matching_pids`
+    fn follow_orphans(&mut self, matching_pids: &[u32]) -> Vec<u32> {
+        let Some(orphans) = self.orphans.as_mut() else {
+            return Vec::new();
+        };
+        let mut reachable = HashSet::new();
+        for &pid in matching_pids {
+            if !reachable.contains(&pid) {
+                reachable.extend(source::descendant_set(&self.processes, pid));
+            }
+        }
+        let orphan_roots = orphans.update(&self.processes, &reachable);
+        if !orphan_roots.is_empty() {
+            debug!("following {} re-parented processes: roots {:?}", orphans.len(), orphan_roots);
+        }
+        orphan_roots
+    }
+    
+    // The roots to show this sample; the history of a restarted root moves
+    // over to the first process that replaced it
+    fn follow_roots(&mut self, root_pids: Vec<u32>) -> Vec<u32> {
+        let Some(roots) = self.roots.as_mut() else {
+            return root_pids;
+        };
+        let root_pids = roots.update(&self.processes, root_pids);
+        for change in roots.pending() {
+            debug!("{}", change.label());
+            let Some(&to) = change.to.first() else {
+                continue;
+            };
+            if let Some(peaks) = self.peaks.as_mut() {
+                peaks.carry_over(change.from, to);
+            }
+            if let Some(history) = self.history.as_mut() {
+                history.carry_over(change.from, to);
+            }
+        }
+        root_pids
+    }
+    
+    // Error for a --tree / --tree-root that matches none of the found roots
+    fn unknown_tree_error(&self, selection: TreeSelection, root_pids: &[u32]) -> String {
+        let mut message = match selection {
+            TreeSelection::Index(index) => format!("there is no tree {}; {} trees were found:", index, root_pids.len()),
+            TreeSelection::RootPid(pid) => format!("PID {} is not the root of a matched tree; the roots are:", pid),
+        };
+        for (i, pid) in root_pids.iter().enumerate() {
+            let name = self.processes.get(pid).map_or("?", |proc_info| proc_info.name.as_str());
+            let _ = write!(message, "\n  --tree {}  --tree-root {}  {}", i + 1, pid, name);
+        }
+        message
+    }
+    
+    // Find matching processes with improved matching logic
+    // and a tally of how the matcher decided
+    fn matching_pids(&self, process_name: &str) -> (Vec<u32>, MatchStats) {
+        let mut stats = MatchStats::default();
+        // memon itself is never part of a table loaded with --from-file or read over --ssh
+        let excluded = match self.source {
+            Some(_) => HashSet::new(),
+            None => source::self_pids(&self.processes, std::process::id(), self.options.exclude_self),
+        };
+        // --under: candidates come from one subtree; with no name, its root is the match
+        let by_port = !self.options.listen_ports.is_empty();
+        if let Some(under) = self.options.under && process_name.is_empty() && !by_port {
+            let pids = if self.processes.contains_key(&under) { vec![under] } else { Vec::new() };
+            stats.examined = pids.len();
+            stats.matched = pids.len();
+            return (pids, stats);
+        }
+        let scope = self.options.under.map(|under| source::descendant_set(&self.processes, under));
+        let port_inodes = if by_port { self.listening_inodes() } else { HashSet::new() };
+        let mut pids = Vec::new();
+        for (&pid, proc_info) in &self.processes {
+            if excluded.contains(&pid) || scope.as_ref().is_some_and(|scope| !scope.contains(&pid)) {
+                stats.excluded += 1;
+                continue;
+            }
+            // --port: without a name the port alone selects, with one both must hold
+            let rule = if by_port && process_name.is_empty() {
+                Some(MatchRule::Port)
+            } else {
+                self.is_process_matching(&proc_info.name, process_name)
+                    .or_else(|| proc_info.raw_name.as_deref().and_then(|raw_name| self.is_process_matching(raw_name, process_name)))
+            };
+            let rule = rule.filter(|_| !by_port || self.listens_on(pid, &port_inodes));
+            stats.record(rule);
+            if rule.is_some() {
+                pids.push(pid);
+            }
+        }
+        // PID order keeps the trees in a stable order between runs
+        pids.sort_unstable();
+        (pids, stats)
+    }
+    
+    // Snapshot of every tree matching `process_name` in the already collected
+    // process table, so several forests can come from one refresh
+    fn forest_snapshot(&mut self, process_name: &str) -> Snapshot {
+        let (matching_pids, _) = self.matching_pids(process_name);
+        let root_pids = self.find_root_processes(&matching_pids);
+        let mut trees = Vec::new();
+        for root_pid in root_pids {
+            trees.extend(ProcessTree::from_processes(&mut self.processes, root_pid));
+        }
+        Snapshot {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            pattern: process_name.to_string(),
+            matched: matching_pids.len(),
+            trees,
+            budget: None,
+            match_stats: None,
+            groups: None,
+            since_last_age_secs: None,
+            metadata: None,
+        }
+    }
+    
+    // Copy the figures of the latest snapshot for --status-line
+    fn observe_status(&self, status: &mut StatusLine) {
+        if let Some(snapshot) = &self.last_snapshot {
+            status.observe(snapshot);
+        }
+    }
+    
+    // --since-last: growth of every process and tree since the cached run,
+    // then this run replaces it. Runs without trees are not cached, so the
+    // next run still compares with the last one that found the process.
+    // Returns the age of the cached run.
+    fn compare_since_last(&mut self, trees: &mut [ProcessTree]) -> Option<u64> {
+        let cache = self.since_last.as_mut()?;
+        let now = unix_now();
+        let processes = &self.processes;
+        let start_time = |pid| processes.get(&pid).map_or(0, |proc_info: &ProcessInfo| proc_info.start_time);
+        let age = match cache.previous(now) {
+            Some(previous) => {
+                since_last::apply(trees, previous, start_time);
+                Some(now.saturating_sub(previous.timestamp).max(0) as u64)
+            }
+            None => {
+                debug!("no earlier run cached for --since-last");
+                None
+            }
+        };
+        if !trees.is_empty() && let Err(err) = cache.save(&SavedRun::from_trees(trees, now, start_time)) {
+            warn!("cannot cache this run for --since-last: {}", err);
+        }
+        age
+    }
+    
+    // Keep the snapshot of this analysis and render its trees, counted and
+    // timed for --self-profile
+    fn finish_snapshot(&mut self, out: &mut String, process_name: &str, matched: usize, trees: Vec<ProcessTree>) {
+        let started = Instant::now();
+        self.render_snapshot(out, process_name, matched, trees);
+        if let Some(profile) = &self.profile && let Ok(mut profile) = profile.lock() {
+            profile.record("render", started.elapsed());
+            if let Some(snapshot) = &self.last_snapshot {
+                profile.count_sample(self.processes.len(), snapshot);
+            }
+        }
+    }
+    
+    // The work of finish_snapshot. The kept snapshot always holds every tree;
+    // --max-trees only limits what is rendered.
+    fn render_snapshot(&mut self, out: &mut String, process_name: &str, matched: usize, mut trees: Vec<ProcessTree>) {
+        if self.options.by_depth {
+            for tree in &mut trees {
+                tree.by_depth = Some(tree.depth_totals());
+            }
+        }
+        if self.options.breakdown {
+            for tree in &mut trees {
+                tree.set_breakdown();
+            }
+        }
+        if let Some(churn) = self.churn.as_mut() {
+            churn.apply(&mut trees, &self.processes, self.options.columns.churn);
+        }
+        let groups = self.options.group_by.map(|group_by| match group_by {
+            GroupBy::Pod => k8s::pod_totals(&trees),
+            GroupBy::Exe => snapshot::exe_totals(&trees),
+            GroupBy::Pgid => snapshot::pgid_totals(&trees),
+        });
+        let since_last_age_secs = self.compare_since_last(&mut trees);
+        // Folded after the depth and group totals, which count every process
+        if self.options.dedupe_subtrees {
+            for tree in &mut trees {
+                dedupe::dedupe(&mut tree.root);
+            }
+        }
+        if let Some(labels) = &self.options.labels {
+            for tree in &mut trees {
+                labels.apply(&mut tree.root);
+            }
+        }
+        let used: u64 = trees.iter().map(|tree| tree.total_bytes).sum();
+        // --verbose: the matcher's tally is kept for the JSON and the table after the report
+        let match_stats = self.match_stats.take().filter(|_| log_enabled!(Level::Debug));
+        let mut snapshot = Snapshot {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            pattern: process_name.to_string(),
+            matched,
+            trees,
+            budget: self.options.budget.map(|budget| Budget::new(budget, used)),
+            match_stats,
+            groups,
+            since_last_age_secs,
+            metadata: self.metadata.clone().map(|metadata| RunMetadata { timestamp: unix_now(), ..metadata }),
+        };
+        self.anonymizer.apply(&mut snapshot);
+        if self.options.overview {
+            let overview = Overview::new(&snapshot, self.options.max_trees);
+            out.push_str(&match self.options.format {
+                OutputFormat::Json => overview::render_json(&overview),
+                _ => overview::render_text(&overview, &self.options.sizes),
+            });
+            self.last_snapshot = Some(snapshot);
+            return;
+        }
+        if self.options.shell_vars {
+            out.push_str(&shell_vars::render(&snapshot));
+            self.last_snapshot = Some(snapshot);
+            return;
+        }
+        let renderer: Box<dyn Renderer> = match self.options.format {
+            OutputFormat::Text => {
+                let mut renderer = TextRenderer::new(
+                    Theme::new(self.options.color.enabled()),
+                    self.options.columns,
+                    self.options.min_percent,
+                ).with_hyperlinks(self.options.hyperlinks).with_decorations(self.options.decorations)
+                    .with_full_names(self.options.full_names)
+                    .with_tree_style(self.options.tree_style, self.options.indent)
+                    .with_sizes(self.options.sizes)
+                    .with_exec_column(self.exec.as_ref().map(|(runner, _)| runner.column.name.clone()))
+                    .with_group_by(self.options.group_by)
+                    .with_max_children(self.options.max_children)
+                    .with_system_memory(self.system_memory());
+                // Watch mode: one layout for every tree, widened as needed
+                if let Some((widths, fit_terminal)) = self.widths.as_mut() && !snapshot.trees.is_empty() {
+                    let needed = snapshot.trees.iter()
+                        .map(TextRenderer::column_widths)
+                        .fold((0, 0), |(pid, name), (tree_pid, tree_name)| (pid.max(tree_pid), name.max(tree_name)));
+                    let terminal_columns = fit_terminal.then(terminal_size::terminal_size).flatten()
+                        .map(|(terminal_size::Width(columns), _)| usize::from(columns));
+                    let (pid_width, name_width) = widths.observe(needed, terminal_columns);
+                    renderer = renderer.with_widths(pid_width, name_width);
+                }
+                Box::new(renderer)
+            }
+            format => render::renderer_for(format),
+        };
+        // --max-children-json prunes a copy; the kept snapshot has every process
+        let capped = match self.options.max_children {
+            Some(max_children) if self.options.max_children_json && self.options.format == OutputFormat::Json => Some(snapshot.cap_children(max_children)),
+            _ => None,
+        };
+        let shown = capped.as_ref().unwrap_or(&snapshot);
+        match self.options.max_trees {
+            Some(max_trees) if shown.trees.len() > max_trees => {
+                let (shown, omitted) = shown.split_largest(max_trees);
+                out.push_str(&render::render_to_string(&*renderer, &shown));
+                if self.options.format == OutputFormat::Text {
+                    let omitted_bytes: u64 = omitted.iter().map(|tree| tree.total_bytes).sum();
+                    let _ = writeln!(out, "\u{2026} {} more trees, {} total", self.options.sizes.count(omitted.len() as u64), self.options.sizes.memory(omitted_bytes));
+                }
+            }
+            _ => out.push_str(&render::render_to_string(&*renderer, shown)),
+        }
+        self.last_snapshot = Some(snapshot);
+    }
+    
+    // Improved process name matching logic
+    fn is_process_matching(&self, proc_name: &str, target_name: &str) -> Option<MatchRule> {
+        let rule = self.match_rule(proc_name, target_name);
+        match rule {
+            Some(rule) => debug!("'{}' matches '{}' by {} rule", proc_name, target_name, rule),
+            None => trace!("'{}' does not match '{}'", proc_name, target_name),
+        }
+        rule
+    }
+    
+    // Return the first matching rule that accepts proc_name for target_name
+    fn match_rule(&self, proc_name: &str, target_name: &str) -> Option<MatchRule> {
+        let proc_name_lower = proc_name.to_lowercase();
+        let target_name_lower = target_name.to_lowercase();
+        
+        // Handle truncated process names (common on macOS with ps -c)
+        // If target name is being searched and process name might be truncated
+        if proc_name_lower.len() >= 15 && target_name_lower.starts_with(&proc_name_lower) {
+            return Some(MatchRule::TruncatedName);
+        }
+        
+        // Handle case where target name is long and might be truncated
+        if target_name_lower.len() > 15 && proc_name_lower.starts_with(&target_name_lower[..15]) {
+            return Some(MatchRule::TruncatedName);
+        }
+        
+        // Exact match
+        if proc_name_lower == target_name_lower {
+            return Some(MatchRule::Exact);
+        }
+        
+        // Check if target name starts with process name (for truncated names)
+        if target_name_lower.starts_with(&proc_name_lower) {
+            return Some(MatchRule::Prefix);
+        }
+        
+        // Check if process name starts with target name (for partial matching)
+        if proc_name_lower.starts_with(&target_name_lower) {
+            return Some(MatchRule::Prefix);
+        }
+        
+        // Extract basename from process name if it contains a path
+        let proc_basename = if proc_name_lower.contains('/') {
+            proc_name_lower.split('/').next_back().unwrap_or(&proc_name_lower).to_string()
+        } else {
+            proc_name_lower.clone()
+        };
+        
+        // Extract basename from target name if it contains a path
+        let target_basename = if target_name_lower.contains('/') {
+            target_name_lower.split('/').next_back().unwrap_or(&target_name_lower).to_string()
+        } else {
+            target_name_lower.clone()
+        };
+        
+        // Handle common executable extensions
+        let base_proc = if proc_basename.ends_with(".exe") || proc_basename.ends_with(".app") || 
+                          proc_basename.ends_with(".bin") || proc_basename.ends_with(".run") {
+            proc_basename[..proc_basename.len()-4].to_string()
+        } else {
+            proc_basename
+        };
+        
+        let base_target = if target_basename.ends_with(".exe") || target_basename.ends_with(".app") || 
+                            target_basename.ends_with(".bin") || target_basename.ends_with(".run") {
+            target_basename[..target_basename.len()-4].to_string()
+        } else {
+            target_basename
+        };
+        
+        if base_proc == base_target {
+            return Some(MatchRule::Basename);
+        }
+        
+        // Check for common macOS app naming patterns
+        // Some apps have process names like "App Name" when app is "AppName"
+        if target_name.contains(' ') {
+            let compact_name = target_name.replace(' ', "").to_lowercase();
+            if proc_name_lower == compact_name {
+                return Some(MatchRule::CompactName);
+            }
+        }
+        
+        // A word of a setproctitle title, "walwriter" for "postgres: walwriter"
+        if names::title_matches(proc_name, target_name) {
+            return Some(MatchRule::Title);
+        }
+        
+        None
+    }
+}
+
+// Route diagnostics through the log facade to stderr. MEMON_LOG takes an
+// env_logger filter (e.g. "debug" or "memon=trace"); --verbose forces debug.
+fn init_logging(verbose: bool) {
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::new().filter_or("MEMON_LOG", "warn"));
+    if verbose {
+        builder.parse_filters("debug");
+    }
+    builder
+        .format(|buf, record| match record.level() {
+            log::Level::Error => writeln!(buf, "Error: {}", record.args()),
+            log::Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            level => writeln!(buf, "[{} {}] {}", level, record.target(), record.args()),
+        })
+        .init();
+}
+
+// Install SIGINT/SIGTERM handlers that ask a long-running loop to stop after
+// the current sample instead of dying mid-print
+fn install_stop_handler() -> Result<mpsc::Receiver<()>, ctrlc::Error> {
+    let (sender, receiver) = mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = sender.send(());
+    })?;
+    Ok(receiver)
+}
+
+fn main() {
+    let started = Instant::now();
+    // Usage errors exit 1 like any other error; 2 only means a limit was exceeded
+    let args = Args::try_parse().unwrap_or_else(|err| {
+        let _ = err.print();
+        let code = if err.use_stderr() { 1 } else { 0 };
+        // The arguments did not parse, so look for the flag directly
+        if std::env::args().any(|arg| arg == "--status-line") {
+            print_status_line(StatusLine::default(), started, code);
+        }
+        std::process::exit(code);
+    });
+    // Sections of the config file for the pattern fill in options not given
+    let (args, effective_options) = with_pattern_config(args).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    });
+    init_logging(args.verbose);
+    debug!("built with features: {}", features::enabled().join(", "));
+    
+    let mut status = StatusLine::default();
+    let profile = args.self_profile.then(|| {
+        Arc::new(Mutex::new(SelfProfile::new(&effective_options, args.jobs.unwrap_or_else(collect::default_jobs))))
+    });
+    let code = match run(&args, &effective_options, &mut status, profile.as_ref()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            1
+        }
+    };
+    if let Some(profile) = profile && let Ok(mut profile) = profile.lock() {
+        profile.wall_ms = match_stats::millis(started.elapsed());
+        report_self_profile(&args, &profile);
+    }
+    if args.status_line {
+        print_status_line(status, started, code);
+    }
+    std::process::exit(code);
+}
+
+// The command line with the options of the config file's sections for its
+// pattern filled in, and the options then in effect for the run metadata.
+// --show-config prints the merged options and exits.
+fn with_pattern_config(args: Args) -> Result<(Args, Vec<String>), Box<dyn std::error::Error>> {
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    let Some(pattern) = args.show_config.clone().or_else(|| args.process_name.clone()) else {
+        let options = pattern_config::resolve(&argv, &[]).map_or(Vec::new(), |resolved| pattern_config::effective_options(&resolved));
+        return Ok((args, options));
+    };
+    let config = Config::load(args.config.as_deref())?;
+    let resolved = pattern_config::resolve(&argv, &config.sections_for(&pattern))?;
+    if args.show_config.is_some() {
+        let path = args.config.clone().or_else(config::default_path);
+        println!("Config file: {}", path.map_or("none".to_string(), |path| path.display().to_string()));
+        print!("{}", pattern_config::render(&pattern, &resolved));
+        std::process::exit(0);
+    }
+    let options = pattern_config::effective_options(&resolved);
+    Ok((resolved.args, options))
+}
+
+// Cache file of --since-last and --forget for the pattern, on the --ssh host
+// or this one
+fn since_last_path(args: &Args, process_name: &str) -> Result<PathBuf, String> {
+    let dir = since_last::cache_dir(|name| std::env::var_os(name).map(PathBuf::from))
+        .ok_or("--since-last needs $XDG_CACHE_HOME or $HOME to find its cache")?;
+    let host = args.ssh.clone().or_else(System::host_name).unwrap_or_default();
+    Ok(since_last::entry_path(&dir, &host, process_name))
+}
+
+// --self-profile: the phase table on stderr, and the JSON report next to
+// the --output file
+fn report_self_profile(args: &Args, profile: &SelfProfile) {
+    let _ = std::io::stdout().flush();
+    eprint!("{}", profile.table());
+    if let Some(output) = &args.output {
+        let path = self_profile::json_path(output);
+        if let Err(err) = fs::write(&path, profile.to_json()) {
+            warn!("cannot write the self-profile to {}: {}", path.display(), err);
+        }
+    }
+}
+
+// --status-line: the last line on stderr, after any error message
+fn print_status_line(mut status: StatusLine, started: Instant, code: i32) {
+    status.duration_ms = started.elapsed().as_millis();
+    status.exit = code;
+    let _ = std::io::stdout().flush();
+    eprintln!("{}", status);
+}
+
+// Run memon with parsed arguments, returning the process exit code;
+// `effective_options` go into the run metadata, `status` follows the
+// latest snapshot for --status-line and `profile` collects --self-profile
+fn run(args: &Args, effective_options: &[String], status: &mut StatusLine, profile: Option<&Arc<Mutex<SelfProfile>>>) -> Result<i32, Box<dyn std::error::Error>> {
+    match &args.command {
+        #[cfg(feature = "history")]
+        Some(Command::History { process_name, db }) => return print_history(process_name, db),
+        Some(Command::Compare { name_a, name_b, json }) => return run_compare(name_a, name_b, *json),
+        Some(Command::Maps { pid, top, min, json }) => return run_maps(*pid, *top, min.unwrap_or(0), *json),
+        Some(Command::Threads { pid, top, json }) => return run_threads(*pid, *top, *json),
+        Some(Command::Run { interval, command }) => return run_command(command, *interval),
+        #[cfg(feature = "record")]
+        Some(Command::Report { file }) => return print_report(file),
+        Some(Command::Shell { json }) => return run_shell(*json),
+        Some(Command::Top { limit, json }) => return run_top(*limit, *json),
+        None => {}
+    }
+    #[cfg(feature = "schema")]
+    if args.schema {
+        print!("{}", schema::render());
+        return Ok(0);
+    }
+    let process_name = args.process_name.clone().unwrap_or_default();
+    // Rather than matching nothing where the sockets cannot be read
+    if !args.port.is_empty() && !cfg!(target_os = "linux") {
+        return Err("--port is only supported on Linux, where listening sockets are read from /proc".into());
+    }
+    if args.forget {
+        let path = since_last_path(args, &process_name)?;
+        if since_last::forget(&path)? {
+            println!("Forgot the last run of '{}'", process_name);
+        } else {
+            println!("No run of '{}' was cached", process_name);
+        }
+        return Ok(0);
+    }
+    
+    // Explicit format flag > --output extension > text
+    let explicit_format = if args.json { Some(OutputFormat::Json) } else { args.format };
+    let resolved = format::resolve_format(explicit_format, args.output.as_deref(), args.strict_format)?;
+    if let Some(warning) = &resolved.warning {
+        warn!("{}", warning);
+    }
+    
+    // Deltas need a previous sample
+    if args.sort == SortKey::Delta && args.watch.is_none() {
+        return Err("--sort delta needs --watch".into());
+    }
+    
+    // Files and pipes get color only when --color=always or the environment forces it
+    let mut options = MonitorOptions::from_args(args, resolved.format, ColorEnv::from_env(), std::io::stdout().is_terminal(), std::io::stderr().is_terminal());
+    debug!("color: {:?}, banners: {:?}", options.color, options.banner_color);
+    if args.labels {
+        let config = Config::load(args.config.as_deref())?;
+        options.labels = Some(Labels::with_overrides(&config.labels));
+    }
+    
+    // Hyperlinks only make sense on a terminal that renders them
+    if options.hyperlinks {
+        let term = std::env::var("TERM").ok();
+        let term_program = std::env::var("TERM_PROGRAM").ok();
+        options.hyperlinks = args.output.is_none()
+            && std::io::stdout().is_terminal()
+            && colors::hyperlinks_supported(term.as_deref(), term_program.as_deref());
+        debug!("hyperlinks: {}", options.hyperlinks);
+    }
+    let thresholds = options.thresholds;
+    let output = OutputTarget::new(args.output.clone(), args.append, args.mkdir);
+    
+    // Create memory monitor and analyze
+    let anonymize = options.anonymize;
+    let mut monitor = MemoryMonitor::new(options);
+    let host = host_info();
+    let argv: Vec<String> = std::env::args_os().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let user = metadata::current_user(|name| std::env::var(name).ok());
+    monitor.metadata = Some(RunMetadata::new(&host, user.as_deref(), unix_now(), &argv, effective_options, anonymize));
+    monitor.anonymizer = Anonymizer::new(host.hostname.as_deref(), anonymize);
+    monitor.profile = profile.cloned();
+    if let Some(path) = &args.from_file {
+        monitor.source = Some(Box::new(TableFile::new(path)));
+    }
+    if let Some(destination) = &args.ssh {
+        monitor.source = Some(Box::new(SshHost::new(destination)));
+    }
+    if args.since_last {
+        monitor.since_last = Some(RunCache::new(since_last_path(args, &process_name)?, args.since_last_ttl));
+    }
+    // --exec-column runs at most once per watch or stream interval
+    if let Some(column) = &args.exec_column {
+        let interval = match (args.watch, &args.http) {
+            (Some(watch), _) => watch.max(1),
+            (None, Some(_)) => args.stream_interval.max(1),
+            (None, None) => 0,
+        };
+        monitor.exec = Some((ExecRunner::new(column.clone(), Duration::from_secs(interval)), args.force));
+    }
+    // Watch sessions keep the PIDs of each role for the restart counts, and
+    // the column widths so the layout does not jump between samples
+    if args.watch.is_some() {
+        monitor.churn = Some(ChurnTracker::default());
+        if !args.no_follow_orphans {
+            monitor.orphans = Some(OrphanTracker::default());
+        }
+        monitor.roots = Some(RootTracker::new(args.no_reroot));
+        monitor.fine = args.fine.map(|_| FineWindow::default());
+        monitor.deltas = (args.sort == SortKey::Delta).then(DeltaSort::default);
+        monitor.widths = Some((StickyWidths::default(), args.output.is_none()));
+    }
+    
+    // Overview: the tree lines alone, so no progress banners either
+    if args.overview {
+        if !matches!(resolved.format, OutputFormat::Text | OutputFormat::Json) {
+            return Err(format!("--overview works with text and json output, not {}", resolved.format).into());
+        }
+        monitor.banners = false;
+    }
+    
+    // Shell variables: stdout is for eval, so nothing else goes there
+    if args.shell_vars {
+        monitor.banners = false;
+    }
+    
+    // HTTP mode: every request collects a fresh snapshot
+    #[cfg(feature = "http")]
+    if let Some(addr) = &args.http {
+        let listener = server::bind(addr)?;
+        // Every request would repeat them
+        monitor.banners = false;
+        println!("Serving '{}' on http://{} (GET /snapshot, /healthz, /stream)", process_name, listener.local_addr()?);
+        let monitor = Mutex::new(monitor);
+        let collect = move || {
+            let mut monitor = monitor.lock().map_err(|_| "collector panicked".to_string())?;
+            let mut out = String::new();
+            monitor.analyze_process_tree(&process_name, &mut out).map_err(|err| err.to_string())?;
+            monitor.last_snapshot.as_ref().map(render::render_json).ok_or_else(|| "no snapshot collected".to_string())
+        };
+        server::serve(listener, Arc::new(collect), Duration::from_secs(args.stream_interval.max(1)));
+        return Ok(0);
+    }
+    
+    let mut trigger = Trigger::new(Duration::from_secs(args.hook_cooldown));
+    
+    // Health probe: the report is replaced by one summary line
+    if args.check {
+        monitor.banners = false;
+        let mut report = String::new();
+        monitor.analyze_process_tree(&process_name, &mut report)?;
+        monitor.observe_status(status);
+        check_thresholds(args, &thresholds, &mut trigger, &monitor);
+        let snapshot = monitor.last_snapshot.as_ref().ok_or("process scan did not finish")?;
+        let outcome = check::evaluate(snapshot, &thresholds, args.enforce_budget);
+        println!("{}", check::summary(snapshot, &outcome));
+        report_degradations(args, &monitor)?;
+        return Ok(outcome.exit_code());
+    }
+    
+    // Record mode: snapshots go to the --out file instead of the terminal
+    #[cfg(feature = "record")]
+    if let (Some(duration), Some(path)) = (args.record, &args.out) {
+        monitor.banners = false;
+        let code = record_snapshots(&mut monitor, &process_name, Duration::from_secs(duration), args.watch.unwrap_or(5).max(1), path)?;
+        monitor.observe_status(status);
+        return Ok(code);
+    }
+    
+    #[cfg(feature = "history")]
+    let mut history_db = match &args.db {
+        Some(path) => Some(HistoryDb::open(path).map_err(|err| format!("cannot open history database {}: {}", path.display(), err))?),
+        None => None,
+    };
+    
+    // Watch mode: clear the screen and re-analyze every N seconds until interrupted
+    if let Some(interval) = args.watch {
+        if args.changes_only && !matches!(resolved.format, OutputFormat::Text | OutputFormat::Json) {
+            return Err(format!("--changes-only works with text and json output, not {}", resolved.format).into());
+        }
+        let stop = install_stop_handler()?;
+        let started = Instant::now();
+        let mut samples = 0;
+        // --changes-only: the previous sample, once the full tree was printed
+        let mut previous: Option<Snapshot> = None;
+        // Tree totals for the chart above the report
+        let mut chart_history = (!args.no_chart).then(|| TotalHistory::new(chart::WATCH_SAMPLES));
+        // A redrawn screen would wipe the banners right away
+        let redraw = !args.changes_only && output.is_stdout() && resolved.format == OutputFormat::Text;
+        if redraw {
+            monitor.banners = false;
+        }
+        loop {
+            let mut out = String::new();
+            if args.changes_only {
+                monitor.analyze_process_tree(&process_name, &mut out)?;
+                out = changes_output(&monitor, previous.as_ref(), resolved.format, args.change_threshold, out);
+                previous = monitor.last_snapshot.clone();
+            } else if redraw {
+                out.push_str("\x1b[2J\x1b[H");
+                let mut report = String::new();
+                monitor.analyze_process_tree(&process_name, &mut report)?;
+                out.push_str(&root_changes(&mut monitor));
+                out.push_str(&monitor.fine_report(args.fine.unwrap_or_default()));
+                if let Some(history) = &mut chart_history {
+                    out.push_str(&total_chart(history, &monitor, args.chart_height));
+                }
+                out.push_str(&report);
+            } else {
+                monitor.analyze_process_tree(&process_name, &mut out)?;
+                if resolved.format == OutputFormat::Json && output.is_stdout() {
+                    out = ndjson_line(&out);
+                }
+            }
+            // Restarted roots and the --fine window lead the text report; they would break JSON
+            let changes = root_changes(&mut monitor) + &monitor.fine_report(args.fine.unwrap_or_default());
+            if resolved.format == OutputFormat::Text {
+                out.insert_str(0, &changes);
+            } else {
+                eprint!("{}", changes);
+            }
+            monitor.observe_status(status);
+            output.write(&out)?;
+            print_match_stats(&monitor);
+            report_degradations(args, &monitor)?;
+            write_exports(args, &monitor)?;
+            #[cfg(feature = "history")]
+            record_history(&mut history_db, &monitor)?;
+            check_thresholds(args, &thresholds, &mut trigger, &monitor);
+            samples += 1;
+            
+            if !wait_for_next_sample(&mut monitor, &stop, Duration::from_secs(interval.max(1)), args.fine.map(Duration::from_millis))? {
+                break;
+            }
+        }
+        
+        let recap = watch_recap(&monitor, &process_name, started.elapsed(), samples);
+        // Machine-readable samples on stdout must not get a text tail
+        if resolved.format == OutputFormat::Text {
+            print!("\n{}", recap);
+            std::io::stdout().flush()?;
+        } else {
+            eprint!("{}", recap);
+        }
+        return Ok(0);
+    }
+    
+    let mut out = String::new();
+    let success = monitor.analyze_process_tree(&process_name, &mut out)?;
+    monitor.observe_status(status);
+    write_paged(args, &output, resolved.format, &out)?;
+    print_match_stats(&monitor);
+    write_exports(args, &monitor)?;
+    #[cfg(feature = "history")]
+    record_history(&mut history_db, &monitor)?;
+    report_degradations(args, &monitor)?;
+    if check_thresholds(args, &thresholds, &mut trigger, &monitor).is_some() {
+        return Ok(2);
+    }
+    if args.enforce_budget
+        && let Some(budget) = monitor.last_snapshot.as_ref().and_then(|snapshot| snapshot.budget)
+        && budget.exceeded() {
+        warn!("memory budget exceeded: {} used of {}", units::format_memory(budget.used_bytes), units::format_memory(budget.budget_bytes));
+        return Ok(2);
+    }
+    if let (Some(path), Some(snapshot)) = (&args.baseline, &monitor.last_snapshot) {
+        return check_baseline(args, path, snapshot, resolved.format == OutputFormat::Text && output.is_stdout());
+    }
+    
+    Ok(if success { 0 } else { 1 })
+}
+
+// Write a one-shot report, through the pager when it is text going to a
+// terminal and taller than it, or with --pager; a pager that cannot be run
+// falls back to writing directly
+fn write_paged(args: &Args, output: &OutputTarget, format: OutputFormat, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let to_terminal = output.is_stdout() && std::io::stdout().is_terminal();
+    let height = terminal_size::terminal_size().map(|(_, terminal_size::Height(rows))| usize::from(rows));
+    let command = pager::command(|name| std::env::var(name).ok());
+    if let Some(command) = command
+        && to_terminal
+        && format == OutputFormat::Text
+        && !args.no_pager
+        && (args.pager || pager::needs_paging(text, height)) {
+        debug!("paging through '{}'", command);
+        match pager::page(&command, text) {
+            Ok(()) => return Ok(()),
+            Err(err) => warn!("{}; writing the output directly", err),
+        }
+    }
+    Ok(output.write(text)?)
+}
+
+// Sleep until the next watch sample, waking early on SIGINT/SIGTERM, and with
+// --fine sample the trees every `fine` in the meantime. False once stopped.
+fn wait_for_next_sample(monitor: &mut MemoryMonitor, stop: &mpsc::Receiver<()>, interval: Duration, fine: Option<Duration>) -> Result<bool, Box<dyn std::error::Error>> {
+    let next_sample = Instant::now() + interval;
+    loop {
+        let remaining = next_sample.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(true);
+        }
+        match stop.recv_timeout(fine.map_or(remaining, |fine| fine.min(remaining))) {
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            _ => return Ok(false),
+        }
+        if fine.is_some() && Instant::now() < next_sample {
+            monitor.fine_sample()?;
+        }
+    }
+}
+
+// --verbose: how the matcher behaved, on stderr after the report
+fn print_match_stats(monitor: &MemoryMonitor) {
+    if let Some(snapshot) = &monitor.last_snapshot && let Some(match_stats) = &snapshot.match_stats {
+        eprint!("{}", match_stats.table(&snapshot.pattern));
+    }
+}
+
+// What the latest sample could not collect: with --strict an error listing
+// it, otherwise shown on stderr with --verbose
+fn report_degradations(args: &Args, monitor: &MemoryMonitor) -> Result<(), String> {
+    if monitor.degradations.is_empty() {
+        return Ok(());
+    }
+    let summary = degradation::summary(&monitor.degradations);
+    if args.strict {
+        return Err(format!("--strict: data collection was degraded:\n{}", summary.trim_end()));
+    }
+    if log_enabled!(Level::Debug) {
+        eprint!("Degraded data collection:\n{}", summary);
+    }
+    Ok(())
+}
+
+// Write the latest snapshot to the --folded and --svg files asked for
+fn write_exports(args: &Args, monitor: &MemoryMonitor) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(snapshot) = &monitor.last_snapshot else {
+        return Ok(());
+    };
+    if let Some(path) = &args.folded {
+        folded::write(path, snapshot).map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+    }
+    if let Some(path) = &args.svg {
+        treemap::write(path, snapshot).map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
+
+// One warning-colored line per root restarted since the last sample, such as
+// "root changed: 1234 → 9876 (restart detected)"
+fn root_changes(monitor: &mut MemoryMonitor) -> String {
+    let Some(roots) = monitor.roots.as_mut() else {
+        return String::new();
+    };
+    let theme = Theme::new(monitor.options.color.enabled());
+    roots.take_changes()
+        .iter()
+        .map(|change| format!("{}{}{}\n", theme.warning, change.label(), theme.reset))
+        .collect()
+}
+
+// Chart of the combined tree total over the recent watch samples, with the
+// current sample added to `history`
+fn total_chart(history: &mut TotalHistory, monitor: &MemoryMonitor, height: usize) -> String {
+    let total = monitor.last_snapshot.as_ref().map_or(0, |snapshot| snapshot.trees.iter().map(|tree| tree.total_bytes).sum());
+    history.push(total);
+    format!("Tree total, last {} samples\n{}\n", history.len(), chart::render(&history.totals(), height, chart::WATCH_SAMPLES))
+}
+
+// What --changes-only prints for one sample. The first sample is the full
+// tree (`full` as rendered, or one compact JSON line), later ones only the
+// changes since `previous`.
+fn changes_output(monitor: &MemoryMonitor, previous: Option<&Snapshot>, format: OutputFormat, threshold: u64, full: String) -> String {
+    let Some(current) = &monitor.last_snapshot else {
+        return full;
+    };
+    let json = format == OutputFormat::Json;
+    let Some(previous) = previous else {
+        if json {
+            return serde_json::to_string(current).expect("snapshot serializes to JSON") + "\n";
+        }
+        return full;
+    };
+    let mut changes = changes::diff(previous, current, threshold);
+    if monitor.options.sort == SortKey::Delta {
+        sort::sort_changes(&mut changes);
+    }
+    let timestamp = unix_now();
+    if json {
+        changes::render_ndjson(timestamp, &changes)
+    } else {
+        changes::render_text(&history::format_timestamp(timestamp), &changes, &monitor.options.sizes)
+    }
+}
+
+// A rendered JSON document as one NDJSON line, so the --watch --json samples
+// on stdout can be read line by line
+fn ndjson_line(json: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(value) => value.to_string() + "\n",
+        Err(_) => json.to_string(),
+    }
+}
+
+// Session summary printed when watch mode stops
+fn watch_recap(monitor: &MemoryMonitor, process_name: &str, elapsed: Duration, samples: usize) -> String {
+    let mut recap = format!("Watched '{}' for {}s: {} samples\n", process_name, elapsed.as_secs(), samples);
+    if let Some(peaks) = &monitor.peaks {
+        let _ = write!(recap, "Peak: {} total", monitor.options.sizes.memory(peaks.max_total));
+        if let Some(max) = &peaks.max_process {
+            let _ = write!(recap, ", largest process {} ({}) at {}", max.name, max.pid, monitor.options.sizes.memory(max.bytes));
+        }
+        recap.push('\n');
+    }
+    if let Some(history) = &monitor.history {
+        recap.push_str(&history_cols::render_recap(&history.largest(history_cols::RECAP_PROCESSES), |bytes| monitor.options.sizes.memory(bytes)));
+    }
+    if let Some(roots) = &monitor.roots && roots.total() > 0 {
+        let _ = writeln!(recap, "Root changes: {}", roots.total());
+    }
+    if let Some(churn) = &monitor.churn {
+        let _ = write!(recap, "Restarts: {}", churn.total());
+        let busiest: Vec<String> = churn.busiest().iter().map(|(path, restarts)| format!("{} {}", path, restarts)).collect();
+        if !busiest.is_empty() {
+            let _ = write!(recap, " ({})", busiest.join(", "));
+        }
+        recap.push('\n');
+    }
+    recap
+}
+
+// --record: write a snapshot every `interval` seconds to `path` until
+// `duration` has passed or memon is interrupted
+#[cfg(feature = "record")]
+fn record_snapshots(monitor: &mut MemoryMonitor, process_name: &str, duration: Duration, interval: u64, path: &Path) -> Result<i32, Box<dyn std::error::Error>> {
+    let stop = install_stop_handler()?;
+    let header = RecordHeader::new(process_name, interval, unix_now(), host_info());
+    let mut writer = RecordWriter::create(path, &header)?;
+    eprintln!("Recording '{}' every {}s for {}s to {}", process_name, interval, duration.as_secs(), path.display());
+    
+    let started = Instant::now();
+    let mut samples = 0;
+    loop {
+        // The rendered report is not needed, only the snapshot behind it
+        let mut out = String::new();
+        monitor.analyze_process_tree(process_name, &mut out)?;
+        if let Some(snapshot) = monitor.last_snapshot.clone() {
+            writer.append(&RecordSample { timestamp: unix_now(), snapshot })?;
+            samples += 1;
+        }
+        
+        let remaining = duration.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            break;
+        }
+        match stop.recv_timeout(remaining.min(Duration::from_secs(interval))) {
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            _ => break,
+        }
+    }
+    eprintln!("Recorded {} samples to {}", samples, path.display());
+    Ok(0)
+}
+
+// Host metadata embedded in recordings
+fn host_info() -> HostInfo {
+    let mut system = System::new();
+    system.refresh_memory();
+    HostInfo {
+        hostname: System::host_name(),
+        os: System::long_os_version(),
+        kernel: System::kernel_version(),
+        cpus: std::thread::available_parallelism().map_or(1, |count| count.get()),
+        total_memory: system.total_memory(),
+    }
+}
+
+// Numeric UID of a sysinfo user; Windows SIDs have none
+#[cfg(unix)]
+fn numeric_uid(uid: &sysinfo::Uid) -> Option<u32> {
+    Some(**uid)
+}
+
+#[cfg(not(unix))]
+fn numeric_uid(_uid: &sysinfo::Uid) -> Option<u32> {
+    None
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+// `memon report FILE`: render a recording offline
+#[cfg(feature = "record")]
+fn print_report(path: &Path) -> Result<i32, Box<dyn std::error::Error>> {
+    let recording = record::read_recording(path)?;
+    print!("{}", record::render_report(&recording));
+    Ok(0)
+}
+
+// `memon run -- COMMAND`: start the command, print a line per sample of its
+// tree until it exits, then a recap. Returns the command's exit status.
+fn run_command(command: &[String], interval: u64) -> Result<i32, Box<dyn std::error::Error>> {
+    let stop = install_stop_handler()?;
+    let mut process = std::process::Command::new(&command[0]);
+    process.args(&command[1..]);
+    // In its own process group Ctrl-C reaches only memon, which forwards it once
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut process, 0);
+    let mut child = process.spawn().map_err(|err| format!("cannot start '{}': {}", command[0], err))?;
+    let pid = child.id();
+    
+    let options = MonitorOptions {
+        columns: Columns { peak: true, ..Default::default() },
+        ..MonitorOptions::default()
+    };
+    let mut monitor = MemoryMonitor::new(options);
+    let name = Path::new(&command[0]).file_name().map_or(command[0].clone(), |name| name.to_string_lossy().into_owned());
+    eprintln!("Started {} (PID {})", name, pid);
+    
+    let started = Instant::now();
+    let sample_every = Duration::from_secs(interval.max(1));
+    let mut next_sample = started;
+    let mut totals: Vec<u64> = Vec::new();
+    let mut forwarded = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= next_sample {
+            if let Some(tree) = monitor.sample_tree(pid)? {
+                println!("[{:>5}s] {} procs | {} total | {} peak", started.elapsed().as_secs(), tree.process_count,
+                         units::format_memory(tree.total_bytes), units::format_memory(tree.peak_bytes.unwrap_or(0)));
+                totals.push(tree.total_bytes);
+            }
+            next_sample += sample_every;
+        }
+        // Poll the child often so its exit is noticed quickly; a stop signal
+        // is passed on as SIGINT, and a second one kills the command
+        match stop.recv_timeout(Duration::from_millis(100)) {
+            Ok(()) if !forwarded => {
+                forwarded = true;
+                let interrupted = monitor.system.process(Pid::from_u32(pid)).and_then(|process| process.kill_with(Signal::Interrupt));
+                if interrupted != Some(true) {
+                    let _ = child.kill();
+                }
+            }
+            Ok(()) => {
+                let _ = child.kill();
+            }
+            Err(_) => {}
+        }
+    };
+    
+    let code = exit_code(status);
+    let average = if totals.is_empty() { 0 } else { totals.iter().sum::<u64>() / totals.len() as u64 };
+    println!();
+    println!("{} exited with status {} after {}s", name, code, started.elapsed().as_secs());
+    print!("{} samples | {} avg | {} peak total", totals.len(), units::format_memory(average),
+           units::format_memory(monitor.peaks.as_ref().map_or(0, |peaks| peaks.max_total)));
+    if let Some(max) = monitor.peaks.as_ref().and_then(|peaks| peaks.max_process.as_ref()) {
+        print!(" | largest process {} ({}) at {}", max.name, max.pid, units::format_memory(max.bytes));
+    }
+    println!();
+    Ok(code)
+}
+
+// Exit status to pass on for a finished command; killed by a signal maps to
+// 128 + signal like a shell does
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(1)
+}
+
+// Append the latest snapshot to the --db history, if one is open
+#[cfg(feature = "history")]
+fn record_history(history_db: &mut Option<HistoryDb>, monitor: &MemoryMonitor) -> Result<(), Box<dyn std::error::Error>> {
+    if let (Some(db), Some(snapshot)) = (history_db.as_mut(), &monitor.last_snapshot) {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        db.record(snapshot, timestamp)?;
+    }
+    Ok(())
+}
+
+// Check the latest snapshot against the --fail-if-* limits and fire the
+// alert hooks on the rising edge. Hooks run on their own threads; a failing
+// hook is logged and never stops the caller.
+fn check_thresholds(args: &Args, thresholds: &Thresholds, trigger: &mut Trigger, monitor: &MemoryMonitor) -> Option<Breach> {
+    let snapshot = monitor.last_snapshot.as_ref()?;
+    if thresholds.is_empty() {
+        return None;
+    }
+    let breach = thresholds.check(snapshot);
+    if !trigger.update(breach.is_some(), Instant::now()) {
+        return breach;
+    }
+    
+    let breach = breach?;
+    warn!("threshold crossed: {} (PID {}) uses {}, total {}",
+          breach.name, breach.pid, units::format_memory(breach.rss), units::format_memory(breach.total));
+    let mut hooks = Vec::new();
+    if let Some(command) = args.on_threshold.clone() {
+        let breach = breach.clone();
+        hooks.push(std::thread::spawn(move || {
+            if let Err(err) = hook::run_command(&command, &breach) {
+                warn!("{}", err);
+            }
+        }));
+    }
+    #[cfg(feature = "notify")]
+    if let Some(url) = args.webhook.clone() {
+        let body = render::render_json(snapshot);
+        hooks.push(std::thread::spawn(move || {
+            if let Err(err) = hook::post_webhook(&url, &body) {
+                warn!("{}", err);
+            }
+        }));
+    }
+    #[cfg(feature = "notify")]
+    if args.notify {
+        let text = hook::notification_text(&snapshot.pattern, &breach);
+        hooks.push(std::thread::spawn(move || {
+            // Without a notification daemon, ring the terminal bell instead
+            if let Err(err) = hook::notify(&text) {
+                debug!("{}", err);
+                eprint!("\x07");
+                warn!("{}", text);
+            }
+        }));
+    }
+    // One-shot runs exit right after this, so wait for the hooks to finish
+    if args.watch.is_none() {
+        for handle in hooks {
+            let _ = handle.join();
+        }
+    }
+    Some(breach)
+}
+
+// Compare against --baseline, print the diff table and refresh the baseline
+// with --save when the check passes. A missing baseline is created with --save.
+fn check_baseline(args: &Args, path: &Path, snapshot: &Snapshot, table_on_stdout: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    if args.save && !path.exists() {
+        baseline::save(path, snapshot)?;
+        eprintln!("Saved new baseline to {}", path.display());
+        return Ok(0);
+    }
+    
+    let saved = baseline::load(path)?;
+    let rows = baseline::compare(&saved, snapshot, args.tolerance, args.by_name);
+    let table = baseline::render_table(&rows, args.tolerance);
+    // Keep machine-readable stdout parseable
+    if table_on_stdout {
+        print!("\n{}", table);
+    } else {
+        eprint!("{}", table);
+    }
+    
+    if rows.iter().any(|row| row.regressed) {
+        return Ok(2);
+    }
+    if args.save {
+        baseline::save(path, snapshot)?;
+        eprintln!("Baseline {} refreshed", path.display());
+    }
+    Ok(0)
+}
+
+// `memon compare`: both forests come from a single process scan
+fn run_compare(name_a: &str, name_b: &str, json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut monitor = MemoryMonitor::new(MonitorOptions::default());
+    let spinner = Spinner::start("Scanning processes");
+    if !monitor.get_all_processes()? {
+        return Ok(1);
+    }
+    monitor.link_children();
+    let a = monitor.forest_snapshot(name_a);
+    let b = monitor.forest_snapshot(name_b);
+    spinner.stop();
+    
+    let mut all_found = true;
+    for snapshot in [&a, &b] {
+        if snapshot.trees.is_empty() {
+            warn!("no processes found matching '{}'", snapshot.pattern);
+            all_found = false;
+        }
+    }
+    
+    if json {
+        print!("{}", compare::render_json(a, b));
+    } else {
+        print!("{}", compare::render_text(&a, &b));
+    }
+    Ok(if all_found { 0 } else { 1 })
+}
+
+// `memon shell`: the tree of the terminal session memon runs in, without
+// memon itself
+fn run_shell(json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let options = MonitorOptions {
+        color: ColorChoice::resolve(ColorWhen::Auto, false, std::io::stdout().is_terminal(), ColorEnv::from_env()),
+        columns: Columns { tty: true, ..Default::default() },
+        format: if json { OutputFormat::Json } else { OutputFormat::Text },
+        ..MonitorOptions::default()
+    };
+    let mut monitor = MemoryMonitor::new(options);
+    if !monitor.get_all_processes()? {
+        return Ok(1);
+    }
+    monitor.link_children();
+    let root = shell::find_root(&monitor.processes, std::process::id()).ok_or("cannot find the session memon runs in")?;
+    
+    let excluded = source::self_pids(&monitor.processes, std::process::id(), SelfExclusion::Process);
+    monitor.processes.retain(|pid, _| !excluded.contains(pid));
+    monitor.link_children();
+    monitor.collect_sessions(&[root.pid]);
+    let name = monitor.processes.get(&root.pid).map_or(String::new(), |proc_info| proc_info.name.clone());
+    let tree = ProcessTree::from_processes(&mut monitor.processes, root.pid).ok_or("the session root exited")?;
+    let matched = tree.process_count;
+    
+    let mut out = String::new();
+    if !json {
+        writeln!(out, "Session root: {} ({}), {}", name, root.pid, root.kind.label())?;
+    }
+    monitor.finish_snapshot(&mut out, &name, matched, vec![tree]);
+    if !json {
+        // The clients sit in the trees of the terminals they are attached from
+        for pid in &root.tmux_clients {
+            let tty = monitor.session_of(*pid).and_then(|session| session.tty);
+            let rss = monitor.processes.get(pid).map_or(0, |proc_info| proc_info.rss);
+            writeln!(out, "tmux client: {} on {}, {}", pid, tty.as_deref().unwrap_or("?"), units::format_memory(rss))?;
+        }
+    }
+    print!("{}", out);
+    Ok(0)
+}
+
+// `memon maps`: the largest mappings of one process, grouped by backing
+fn run_maps(pid: u32, top: usize, min: u64, json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let smaps = platform::read_smaps(pid).map_err(|err| format!("cannot read memory maps of pid {}: {}", pid, err))?;
+    let mut system = System::new();
+    system.refresh_process(Pid::from_u32(pid));
+    let name = system.process(Pid::from_u32(pid)).map(|process| process.name().to_string());
+    
+    let report = maps::MapsReport::new(pid, name, maps::parse_smaps(&smaps), top, min);
+    if json {
+        print!("{}", maps::render_json(&report));
+    } else {
+        let color = ColorChoice::resolve(ColorWhen::Auto, false, std::io::stdout().is_terminal(), ColorEnv::from_env());
+        print!("{}", maps::render_text(&report, Theme::new(color.enabled())));
+    }
+    Ok(0)
+}
+
+// `memon top`: every application of the system ranked by tree memory
+fn run_top(limit: usize, json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut monitor = MemoryMonitor::new(MonitorOptions::default());
+    let spinner = Spinner::start("Scanning processes");
+    if !monitor.get_all_processes()? {
+        return Ok(1);
+    }
+    monitor.link_children();
+    let excluded = source::self_pids(&monitor.processes, std::process::id(), SelfExclusion::Process);
+    monitor.processes.retain(|pid, _| !excluded.contains(pid));
+    monitor.link_children();
+    let report = TopReport::new(&monitor.processes, limit);
+    spinner.stop();
+    
+    if json {
+        print!("{}", top::render_json(&report));
+    } else {
+        print!("{}", top::render_text(&report));
+    }
+    Ok(0)
+}
+
+// `memon threads`: thread pools and the busiest threads of one process
+fn run_threads(pid: u32, top: usize, json: bool) -> Result<i32, Box<dyn std::error::Error>> {
+    let threads = platform::read_threads(pid).map_err(|err| format!("cannot read threads of pid {}: {}", pid, err))?;
+    // Without the maps the stacks are unknown, which is no reason to fail
+    let maps = platform::read_maps(pid).unwrap_or_default();
+    let mut system = System::new();
+    system.refresh_process(Pid::from_u32(pid));
+    let process = system.process(Pid::from_u32(pid));
+    let name = process.map(|process| process.name().to_string());
+    let rss = process.map_or(0, |process| process.memory());
+    
+    let report = threads::ThreadsReport::new(pid, name, rss, threads, &maps, top);
+    if json {
+        print!("{}", threads::render_json(&report));
+    } else {
+        let color = ColorChoice::resolve(ColorWhen::Auto, false, std::io::stdout().is_terminal(), ColorEnv::from_env());
+        print!("{}", threads::render_text(&report, Theme::new(color.enabled())));
+    }
+    Ok(0)
+}
+
+// `memon history`: tree-total time series with min/max/latest
+#[cfg(feature = "history")]
+fn print_history(process_name: &str, db_path: &Path) -> Result<i32, Box<dyn std::error::Error>> {
+    let db = HistoryDb::open(db_path).map_err(|err| format!("cannot open history database {}: {}", db_path.display(), err))?;
+    let runs = db.tree_totals(process_name)?;
+    if runs.is_empty() {
+        println!("No runs recorded for '{}' in {}", process_name, db_path.display());
+        return Ok(1);
+    }
+    
+    println!("History for '{}' ({} runs)", process_name, runs.len());
+    for run in &runs {
+        println!("{}  {:>3} trees  {}", history::format_timestamp(run.timestamp), run.tree_count, memon::units::format_memory(run.total_bytes));
+    }
+    
+    let min = runs.iter().map(|run| run.total_bytes).min().unwrap_or(0);
+    let max = runs.iter().map(|run| run.total_bytes).max().unwrap_or(0);
+    let latest = runs.last().map_or(0, |run| run.total_bytes);
+    println!("min {} | max {} | latest {}",
+             memon::units::format_memory(min),
+             memon::units::format_memory(max),
+             memon::units::format_memory(latest));
+    Ok(0)
+}
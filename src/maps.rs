@@ -0,0 +1,249 @@
+// Per-process memory map breakdown (`memon maps <pid>`), grouped by backing
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::render::Theme;
+use crate::units::format_memory;
+
+// What backs a group of mappings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapKind {
+    Anonymous,
+    Heap,
+    Stack,
+    // Kernel-provided regions such as [vdso] and [vvar]
+    Special,
+    // Shared libraries (.so files)
+    Library,
+    // Any other mapped file
+    File,
+}
+
+// All mappings with the same backing, summed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MapGroup {
+    pub name: String,
+    pub kind: MapKind,
+    pub regions: usize,
+    pub size_bytes: u64,
+    pub rss_bytes: u64,
+}
+
+// --json document for `memon maps`
+#[derive(Debug, Clone, Serialize)]
+pub struct MapsReport {
+    pub pid: u32,
+    pub name: Option<String>,
+    pub rss_bytes: u64,
+    // The shown groups, largest RSS first
+    pub groups: Vec<MapGroup>,
+    // Groups left out by --top or --min, and their combined RSS
+    pub hidden_groups: usize,
+    pub hidden_rss_bytes: u64,
+}
+
+impl MapsReport {
+    // Keep the `top` largest groups at or above `min` bytes of RSS
+    pub fn new(pid: u32, name: Option<String>, mut groups: Vec<MapGroup>, top: usize, min: u64) -> Self {
+        let rss_bytes = groups.iter().map(|group| group.rss_bytes).sum();
+        let shown = groups.iter().take(top).take_while(|group| group.rss_bytes >= min).count();
+        let hidden: Vec<MapGroup> = groups.split_off(shown);
+        MapsReport {
+            pid,
+            name,
+            rss_bytes,
+            groups,
+            hidden_groups: hidden.len(),
+            hidden_rss_bytes: hidden.iter().map(|group| group.rss_bytes).sum(),
+        }
+    }
+}
+
+// Parse the contents of /proc/<pid>/smaps into groups sorted by RSS
+// descending, then by name
+pub fn parse_smaps(text: &str) -> Vec<MapGroup> {
+    let mut groups: HashMap<String, MapGroup> = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        if let Some((key, value)) = field(line) {
+            let Some(group) = current.as_ref().and_then(|name| groups.get_mut(name)) else {
+                continue;
+            };
+            match key {
+                "Size" => group.size_bytes += value,
+                "Rss" => group.rss_bytes += value,
+                _ => {}
+            }
+        } else if let Some(path) = region_path(line) {
+            let (name, kind) = classify(path);
+            groups.entry(name.clone())
+                .or_insert_with(|| MapGroup { name: name.clone(), kind, regions: 0, size_bytes: 0, rss_bytes: 0 })
+                .regions += 1;
+            current = Some(name);
+        }
+    }
+
+    let mut groups: Vec<MapGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.rss_bytes.cmp(&a.rss_bytes).then_with(|| a.name.cmp(&b.name)));
+    groups
+}
+
+// A mapping as every process sees it: device, inode and offset into the file
+pub type MappingKey = (String, u64, u64);
+
+// RSS of one process split for --dedupe-shared
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sharing {
+    // Private pages, plus the shared pages of anonymous mappings, which
+    // cannot be told apart from another process's
+    pub own_bytes: u64,
+    // Shared pages per mapping of a file, shm segment or /dev/zero
+    pub shared: HashMap<MappingKey, u64>,
+}
+
+impl Sharing {
+    // A process without smaps data counts all of its RSS as its own
+    pub fn unsplit(rss: u64) -> Self {
+        Sharing { own_bytes: rss, shared: HashMap::new() }
+    }
+}
+
+// Split the contents of /proc/<pid>/smaps into private and shared memory
+pub fn parse_sharing(text: &str) -> Sharing {
+    let mut sharing = Sharing::default();
+    let mut current: Option<MappingKey> = None;
+    for line in text.lines() {
+        if let Some((key, value)) = field(line) {
+            match (key, &current) {
+                ("Private_Clean" | "Private_Dirty", _) | ("Shared_Clean" | "Shared_Dirty", None) => sharing.own_bytes += value,
+                ("Shared_Clean" | "Shared_Dirty", Some(mapping)) => *sharing.shared.entry(mapping.clone()).or_default() += value,
+                _ => {}
+            }
+        } else if let Some(mapping) = region_key(line) {
+            // Inode 0 is anonymous memory
+            current = (mapping.1 != 0).then_some(mapping);
+        }
+    }
+    sharing
+}
+
+// Total of a set of processes with each shared mapping counted once, at the
+// most any of them has resident
+pub fn deduped_total<'a>(sharings: impl IntoIterator<Item = &'a Sharing>) -> u64 {
+    let mut own = 0;
+    let mut shared: HashMap<&MappingKey, u64> = HashMap::new();
+    for sharing in sharings {
+        own += sharing.own_bytes;
+        for (mapping, &bytes) in &sharing.shared {
+            let entry = shared.entry(mapping).or_default();
+            *entry = (*entry).max(bytes);
+        }
+    }
+    own + shared.values().sum::<u64>()
+}
+
+// Device, inode and offset of a region header such as
+// "7f1c2a000000-7f1c2a021000 r-xp 00021000 08:01 1234   /usr/lib/libc.so.6"
+fn region_key(line: &str) -> Option<MappingKey> {
+    let mut columns = line.split_whitespace();
+    let range = columns.next()?;
+    if !range.contains('-') {
+        return None;
+    }
+    let _perms = columns.next()?;
+    let offset = u64::from_str_radix(columns.next()?, 16).ok()?;
+    let device = columns.next()?;
+    let inode = columns.next()?.parse().ok()?;
+    Some((device.to_string(), inode, offset))
+}
+
+// "Rss:   1408 kB" -> ("Rss", bytes); region header lines are not fields
+fn field(line: &str) -> Option<(&str, u64)> {
+    let (key, rest) = line.split_once(':')?;
+    if key.contains(char::is_whitespace) {
+        return None;
+    }
+    let kb: u64 = rest.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some((key, kb * 1024))
+}
+
+// Path column of a region header such as
+// "7f1c2a000000-7f1c2a021000 r-xp 00000000 08:01 1234   /usr/lib/libc.so.6".
+// The path may contain spaces, so it is whatever follows the fifth column.
+fn region_path(line: &str) -> Option<&str> {
+    let mut rest = line;
+    for index in 0..5 {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        let column = &trimmed[..end];
+        if column.is_empty() || (index == 0 && !column.contains('-')) {
+            return None;
+        }
+        rest = &trimmed[end..];
+    }
+    Some(rest.trim())
+}
+
+fn classify(path: &str) -> (String, MapKind) {
+    match path {
+        "" => ("[anon]".to_string(), MapKind::Anonymous),
+        "[heap]" => (path.to_string(), MapKind::Heap),
+        _ if path.starts_with("[stack") => ("[stack]".to_string(), MapKind::Stack),
+        _ if path.starts_with("[anon") => ("[anon]".to_string(), MapKind::Anonymous),
+        _ if path.starts_with('[') => (path.to_string(), MapKind::Special),
+        _ if is_library(path) => (path.to_string(), MapKind::Library),
+        _ => (path.to_string(), MapKind::File),
+    }
+}
+
+// libfoo.so, libfoo.so.6, libfoo.so.6.0.1
+fn is_library(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.ends_with(".so") || file.contains(".so.")
+}
+
+// Aligned table, largest group first; the largest group's RSS is highlighted
+pub fn render_text(report: &MapsReport, theme: Theme) -> String {
+    let mut out = String::new();
+    let title = match &report.name {
+        Some(name) => format!("{} ({})", report.pid, name),
+        None => report.pid.to_string(),
+    };
+    let _ = writeln!(out, "Memory maps of {}: {} RSS", title, format_memory(report.rss_bytes));
+
+    let rows: Vec<[String; 4]> = report.groups
+        .iter()
+        .map(|group| [format_memory(group.rss_bytes), format_memory(group.size_bytes), group.regions.to_string(), group.name.clone()])
+        .collect();
+    let header = ["RSS", "SIZE", "REGIONS", "MAPPING"].map(str::to_string);
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let _ = writeln!(out, "{:>w0$}  {:>w1$}  {:>w2$}  {}", header[0], header[1], header[2], header[3],
+                     w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+    for (index, row) in rows.iter().enumerate() {
+        // Pad before coloring so escape sequences do not count towards the width
+        let mut rss = format!("{:>width$}", row[0], width = widths[0]);
+        if index == 0 && !theme.is_plain() {
+            rss = format!("{}{}{}", theme.highlight, rss, theme.reset);
+        }
+        let _ = writeln!(out, "{}  {:>w1$}  {:>w2$}  {}", rss, row[1], row[2], row[3], w1 = widths[1], w2 = widths[2]);
+    }
+    if report.hidden_groups > 0 {
+        let _ = writeln!(out, "({} more mappings, {})", report.hidden_groups, format_memory(report.hidden_rss_bytes));
+    }
+    out
+}
+
+pub fn render_json(report: &MapsReport) -> String {
+    let mut json = serde_json::to_string_pretty(report).expect("maps report serializes to JSON");
+    json.push('\n');
+    json
+}
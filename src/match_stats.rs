@@ -0,0 +1,135 @@
+// How the matcher behaved in one analysis, reported with --verbose
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::time::Duration;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Rule by which a process name matched the search pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchRule {
+    TruncatedName, // Either side may be cut to the 15-character kernel limit
+    Exact,
+    Prefix,
+    Basename, // Path basename with common executable extensions stripped
+    CompactName, // "App Name" searched, "appname" running
+    Title, // A word of a setproctitle title such as "postgres: walwriter"
+    Port, // No name given, listening on a --port
+}
+
+impl MatchRule {
+    // In the order the matcher tries them
+    pub const ALL: [MatchRule; 7] = [MatchRule::TruncatedName, MatchRule::Exact, MatchRule::Prefix, MatchRule::Basename, MatchRule::CompactName, MatchRule::Title, MatchRule::Port];
+}
+
+impl fmt::Display for MatchRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            MatchRule::TruncatedName => "truncated-name",
+            MatchRule::Exact => "exact",
+            MatchRule::Prefix => "prefix",
+            MatchRule::Basename => "basename",
+            MatchRule::CompactName => "compact-name",
+            MatchRule::Title => "title",
+            MatchRule::Port => "port",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// Wall time of each analysis phase, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PhaseTimings {
+    // Reading the process table
+    pub scan_ms: f64,
+    // Trying the pattern against every process
+    pub match_ms: f64,
+    // Picking the roots among the matches
+    pub roots_ms: f64,
+    // Building the trees and their totals
+    pub build_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MatchStats {
+    // Processes the pattern was tried against
+    pub examined: usize,
+    // memon's own processes and those outside --under, never tried
+    pub excluded: usize,
+    pub matched: usize,
+    // Matches per rule, every rule present
+    pub by_rule: BTreeMap<String, usize>,
+    // Matched processes inside the tree of another match
+    pub non_roots: usize,
+    pub roots: usize,
+    pub timings: PhaseTimings,
+}
+
+impl Default for MatchStats {
+    fn default() -> Self {
+        MatchStats {
+            examined: 0,
+            excluded: 0,
+            matched: 0,
+            by_rule: MatchRule::ALL.iter().map(|rule| (rule.to_string(), 0)).collect(),
+            non_roots: 0,
+            roots: 0,
+            timings: PhaseTimings::default(),
+        }
+    }
+}
+
+impl MatchStats {
+    // Count one examined process and the rule that matched it, if any
+    pub fn record(&mut self, rule: Option<MatchRule>) {
+        self.examined += 1;
+        if let Some(rule) = rule {
+            self.matched += 1;
+            *self.by_rule.entry(rule.to_string()).or_default() += 1;
+        }
+    }
+
+    // Split the matches into roots and the rest
+    pub fn set_roots(&mut self, roots: usize) {
+        self.roots = roots;
+        self.non_roots = self.matched.saturating_sub(roots);
+    }
+
+    // Aligned table for stderr, rules in the order they are tried
+    pub fn table(&self, pattern: &str) -> String {
+        let mut rows: Vec<(String, String)> = vec![
+            ("examined".to_string(), self.examined.to_string()),
+            ("excluded".to_string(), self.excluded.to_string()),
+            ("matched".to_string(), self.matched.to_string()),
+        ];
+        for rule in MatchRule::ALL {
+            let label = rule.to_string();
+            let count = self.by_rule.get(&label).copied().unwrap_or(0);
+            rows.push((format!("  {}", label), count.to_string()));
+        }
+        rows.push(("non-roots".to_string(), self.non_roots.to_string()));
+        rows.push(("roots".to_string(), self.roots.to_string()));
+        let timings = self.timings;
+        for (phase, millis) in [("scan", timings.scan_ms), ("match", timings.match_ms), ("roots", timings.roots_ms), ("build", timings.build_ms)] {
+            rows.push((format!("{} time", phase), format!("{:.1}ms", millis)));
+        }
+
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let value_width = rows.iter().map(|(_, value)| value.len()).max().unwrap_or(0);
+        let mut table = format!("Match statistics for '{}':\n", pattern);
+        for (label, value) in rows {
+            let _ = writeln!(table, "  {:<label_width$}  {:>value_width$}", label, value);
+        }
+        table
+    }
+}
+
+// Milliseconds of a phase, keeping sub-millisecond precision
+pub fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,153 @@
+// Where and how a snapshot was taken: embedded in --json, --save baselines,
+// HTML reports and the --db runs table, so a report shared with another team
+// can still be read later. --anonymize replaces the hostname and user names
+// with stable hashes for reports that leave the company.
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::history;
+use crate::record::HostInfo;
+use crate::snapshot::{ProcessNode, Snapshot};
+use crate::units;
+
+// Directories whose next path component is a user name
+const HOME_PREFIXES: [&str; 2] = ["/home/", "/Users/"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RunMetadata {
+    pub memon_version: String,
+    pub hostname: Option<String>,
+    // The account memon ran as
+    pub user: Option<String>,
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub total_memory: u64,
+    // Seconds since the epoch
+    pub timestamp: i64,
+    // The command line as given, program name first
+    pub args: Vec<String>,
+    // Options in effect once the config file's sections were merged in
+    pub options: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anonymized: bool,
+}
+
+impl RunMetadata {
+    // The one place metadata is put together, so every format embeds the same
+    pub fn new(host: &HostInfo, user: Option<&str>, timestamp: i64, args: &[String], options: &[String], anonymize: bool) -> Self {
+        let anonymizer = Anonymizer::new(host.hostname.as_deref(), anonymize);
+        RunMetadata {
+            memon_version: env!("CARGO_PKG_VERSION").to_string(),
+            hostname: host.hostname.as_deref().map(|hostname| anonymizer.hostname(hostname)),
+            user: user.map(|user| anonymizer.user(user)),
+            os: host.os.clone(),
+            kernel: host.kernel.clone(),
+            total_memory: host.total_memory,
+            timestamp,
+            args: args.iter().map(|arg| anonymizer.text(arg)).collect(),
+            options: options.iter().map(|option| anonymizer.text(option)).collect(),
+            anonymized: anonymize,
+        }
+    }
+
+    // Label and value rows for the HTML report
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let unknown = || "unknown".to_string();
+        vec![
+            ("memon", self.memon_version.clone()),
+            ("host", self.hostname.clone().unwrap_or_else(unknown)),
+            ("user", self.user.clone().unwrap_or_else(unknown)),
+            ("OS", self.os.clone().unwrap_or_else(unknown)),
+            ("kernel", self.kernel.clone().unwrap_or_else(unknown)),
+            ("memory", units::format_memory(self.total_memory)),
+            ("time", history::format_timestamp(self.timestamp)),
+            ("command", self.args.join(" ")),
+            ("options", self.options.join(" ")),
+        ]
+    }
+}
+
+// Replaces the hostname and user names in text when --anonymize is on, and
+// leaves it untouched otherwise
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    hostname: Option<String>,
+    enabled: bool,
+}
+
+impl Anonymizer {
+    pub fn new(hostname: Option<&str>, enabled: bool) -> Self {
+        Anonymizer { hostname: hostname.filter(|hostname| !hostname.is_empty()).map(str::to_string), enabled }
+    }
+
+    pub fn hostname(&self, hostname: &str) -> String {
+        if self.enabled { stable_hash("host", hostname) } else { hostname.to_string() }
+    }
+
+    pub fn user(&self, user: &str) -> String {
+        if self.enabled { stable_hash("user", user) } else { user.to_string() }
+    }
+
+    // `text` with the hostname and the user of each home directory replaced
+    pub fn text(&self, text: &str) -> String {
+        if !self.enabled {
+            return text.to_string();
+        }
+        let mut text = match &self.hostname {
+            Some(hostname) => text.replace(hostname.as_str(), &stable_hash("host", hostname)),
+            None => text.to_string(),
+        };
+        for prefix in HOME_PREFIXES {
+            let mut out = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find(prefix) {
+                let (before, after) = rest.split_at(start + prefix.len());
+                let end = after.find(|c: char| c == '/' || c.is_whitespace() || c == '"' || c == '\'').unwrap_or(after.len());
+                out.push_str(before);
+                if end > 0 {
+                    out.push_str(&stable_hash("user", &after[..end]));
+                }
+                rest = &after[end..];
+            }
+            out.push_str(rest);
+            text = out;
+        }
+        text
+    }
+
+    // Command lines and executable paths of every process in `snapshot`
+    pub fn apply(&self, snapshot: &mut Snapshot) {
+        if !self.enabled {
+            return;
+        }
+        for tree in &mut snapshot.trees {
+            self.apply_node(&mut tree.root);
+        }
+    }
+
+    fn apply_node(&self, node: &mut ProcessNode) {
+        node.args = node.args.as_deref().map(|args| self.text(args));
+        // A name taken from the command line may hold a home directory too
+        if node.raw_name.is_some() {
+            node.name = self.text(&node.name);
+        }
+        node.exe = node.exe.as_deref().map(|exe| self.text(exe));
+        for child in &mut node.children {
+            self.apply_node(child);
+        }
+    }
+}
+
+// "host-1c9a8f03": a 32-bit FNV-1a hash, the same on every run and build
+pub fn stable_hash(kind: &str, value: &str) -> String {
+    let hash = value.bytes().fold(0x811c_9dc5_u32, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+    format!("{}-{:08x}", kind, hash)
+}
+
+// The user memon runs as, from the environment
+pub fn current_user(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["USER", "LOGNAME", "USERNAME"].iter().find_map(|name| lookup(name).filter(|user| !user.is_empty()))
+}
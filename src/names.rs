@@ -0,0 +1,66 @@
+// Process names beyond the kernel's 15-character comm. Processes that rewrite
+// their argv with setproctitle, like "postgres: walwriter", and those whose
+// executable name was cut short are shown by their command line instead.
+
+// Longest name the kernel keeps in comm, which is what sysinfo reports
+pub const COMM_LIMIT: usize = 15;
+
+// The name a process is shown and matched by, and the reported name it
+// replaced, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedName {
+    pub display: String,
+    pub raw: Option<String>,
+}
+
+// Whether a reported name may be empty or cut short, so the command line is
+// worth reading
+pub fn needs_resolution(name: &str) -> bool {
+    name.chars().count() <= COMM_LIMIT
+}
+
+// Pick the most informative of the reported name, the comm file and the
+// command line (arguments joined with spaces): a setproctitle title whole,
+// else the executable basename when the reported name is missing or cut at
+// COMM_LIMIT and the basename continues it, else the reported name or comm
+pub fn resolve(reported: &str, comm: Option<&str>, cmdline: Option<&str>) -> ResolvedName {
+    let comm = comm.map(str::trim).filter(|comm| !comm.is_empty());
+    let short = if reported.is_empty() { comm.unwrap_or("") } else { reported };
+    let cmdline = cmdline.map(str::trim).filter(|cmdline| !cmdline.is_empty());
+    let cut = reported.is_empty() || reported.chars().count() == COMM_LIMIT;
+    let display = match cmdline {
+        Some(title) if title_rest(title).is_some() && (short.is_empty() || title.starts_with(short)) => title.to_string(),
+        Some(cmdline) => {
+            let first = cmdline.split_whitespace().next().unwrap_or("");
+            let basename = first.rsplit('/').next().unwrap_or(first);
+            if cut && basename.len() > short.len() && basename.starts_with(short) {
+                basename.to_string()
+            } else {
+                short.to_string()
+            }
+        }
+        None => short.to_string(),
+    };
+    let raw = (display != reported).then(|| reported.to_string());
+    ResolvedName { display, raw }
+}
+
+// What follows the "name: " prefix of a setproctitle title, such as
+// "walwriter" for "postgres: walwriter"; None for any other name
+pub fn title_rest(name: &str) -> Option<&str> {
+    let (prefix, rest) = name.split_once(": ")?;
+    let rest = rest.trim();
+    (!prefix.is_empty() && !prefix.contains(char::is_whitespace) && !rest.is_empty()).then_some(rest)
+}
+
+// Whether `pattern` names a title's process: one of the words after the
+// prefix, or their start ("logical replication" for "postgres: logical
+// replication launcher"). Both are compared ignoring case.
+pub fn title_matches(name: &str, pattern: &str) -> bool {
+    let Some(rest) = title_rest(name) else {
+        return false;
+    };
+    let rest = rest.to_lowercase();
+    let pattern = pattern.trim().to_lowercase();
+    !pattern.is_empty() && (rest.starts_with(&pattern) || rest.split_whitespace().any(|word| word == pattern))
+}
@@ -0,0 +1,85 @@
+// --only-nice: keep the processes whose nice value passes a comparison
+
+use std::collections::{HashMap, HashSet};
+
+use crate::process::ProcessInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NiceOp {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NiceFilter {
+    pub op: NiceOp,
+    pub value: i32,
+}
+
+impl NiceFilter {
+    // A process without a known nice value never passes
+    pub fn matches(self, nice: Option<i32>) -> bool {
+        let Some(nice) = nice else {
+            return false;
+        };
+        match self.op {
+            NiceOp::Less => nice < self.value,
+            NiceOp::LessOrEqual => nice <= self.value,
+            NiceOp::Greater => nice > self.value,
+            NiceOp::GreaterOrEqual => nice >= self.value,
+            NiceOp::Equal => nice == self.value,
+        }
+    }
+}
+
+// --only-nice value: an operator (<, <=, >, >=, =) followed by a nice value,
+// such as "<0" or ">=10"
+pub fn parse_filter(text: &str) -> Result<NiceFilter, String> {
+    let text = text.trim();
+    let (op, value) = [("<=", NiceOp::LessOrEqual), (">=", NiceOp::GreaterOrEqual), ("<", NiceOp::Less), (">", NiceOp::Greater), ("=", NiceOp::Equal)]
+        .into_iter()
+        .find_map(|(symbol, op)| text.strip_prefix(symbol).map(|value| (op, value)))
+        .ok_or_else(|| format!("invalid nice filter '{}': expected <, <=, >, >= or = and a value, e.g. '<0'", text))?;
+    let value: i32 = value.trim().parse().map_err(|_| format!("invalid nice value '{}'", value.trim()))?;
+    if !(-20..=19).contains(&value) {
+        return Err(format!("nice value {} is outside -20..19", value));
+    }
+    Ok(NiceFilter { op, value })
+}
+
+// Unlink every process of the tree below `root_pid` that neither passes the
+// filter nor has a descendant that does, so the tree shows the passing
+// processes and the ancestors that connect them. Returns whether anything
+// in the tree passed. The table must be linked.
+pub fn prune(processes: &mut HashMap<u32, ProcessInfo>, root_pid: u32, filter: NiceFilter) -> bool {
+    let mut keep = HashSet::new();
+    let mut visited = HashSet::new();
+    keep_passing(processes, root_pid, filter, &mut keep, &mut visited);
+    for pid in &visited {
+        if let Some(proc_info) = processes.get_mut(pid) {
+            proc_info.children.retain(|child| keep.contains(child));
+        }
+    }
+    keep.contains(&root_pid)
+}
+
+// Post-order walk marking the processes to keep; `visited` guards against loops
+fn keep_passing(processes: &HashMap<u32, ProcessInfo>, pid: u32, filter: NiceFilter, keep: &mut HashSet<u32>, visited: &mut HashSet<u32>) -> bool {
+    if !visited.insert(pid) {
+        return false;
+    }
+    let Some(proc_info) = processes.get(&pid) else {
+        return false;
+    };
+    let mut kept = filter.matches(proc_info.nice);
+    for &child in &proc_info.children {
+        kept |= keep_passing(processes, child, filter, keep, visited);
+    }
+    if kept {
+        keep.insert(pid);
+    }
+    kept
+}
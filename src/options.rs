@@ -0,0 +1,307 @@
+// Monitor options resolved once from the command line
+
+use crate::cli::{Args, ColorWhen, GroupBy, TreeStyle};
+use crate::collect;
+use crate::colors::{self, ColorEnv};
+use crate::format::OutputFormat;
+use crate::labels::Labels;
+use crate::render::Decorations;
+use crate::nice::NiceFilter;
+use crate::platform;
+use crate::redact::Redactions;
+use crate::roots::RootPolicy;
+use crate::sort::SortKey;
+use crate::source::SelfExclusion;
+use crate::threshold::Thresholds;
+use crate::units::{self, SizeFormat};
+use std::time::Duration;
+
+// Final color decision. Auto records what auto-detection settled on so
+// diagnostics can tell a forced choice from a detected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    Auto { enabled: bool },
+}
+
+impl ColorChoice {
+    // --no-color wins over --color; auto follows the environment and
+    // whether the output is a terminal (see colors::should_use_colors)
+    pub fn resolve(when: ColorWhen, no_color_flag: bool, is_terminal: bool, env: ColorEnv) -> ColorChoice {
+        if no_color_flag {
+            return ColorChoice::Never;
+        }
+        match when {
+            ColorWhen::Always => ColorChoice::Always,
+            ColorWhen::Never => ColorChoice::Never,
+            ColorWhen::Auto => ColorChoice::Auto { enabled: colors::should_use_colors(None, env, is_terminal) },
+        }
+    }
+    
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto { enabled } => enabled,
+        }
+    }
+}
+
+// Optional columns of the text tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Columns {
+    pub args: bool,
+    pub counts: bool,
+    pub percent: bool,
+    pub percent_of_parent: bool,
+    pub mem_details: bool,
+    pub hugepages: bool,
+    pub limits: bool,
+    pub ports: bool,
+    pub jvm: bool,
+    pub gpu: bool,
+    pub tty: bool,
+    pub peak: bool,
+    pub stability: bool,
+    pub churn: bool,
+    pub k8s: bool,
+    pub nice: bool,
+    pub pgid: bool,
+    pub privs: bool,
+    pub history: bool,
+}
+
+// A single tree picked with --tree or --tree-root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSelection {
+    // 1-based position among the roots, which are in PID order
+    Index(usize),
+    RootPid(u32),
+}
+
+impl TreeSelection {
+    // The selected root, if it is one of `roots`
+    pub fn select(self, roots: &[u32]) -> Option<u32> {
+        match self {
+            TreeSelection::Index(index) => index.checked_sub(1).and_then(|i| roots.get(i)).copied(),
+            TreeSelection::RootPid(pid) => roots.contains(&pid).then_some(pid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorOptions {
+    pub color: ColorChoice,
+    // Color of the progress banners, which go to stderr and so are decided
+    // by whether stderr is a terminal
+    pub banner_color: ColorChoice,
+    pub columns: Columns,
+    // Hide subtrees below this share of the tree total
+    pub min_percent: Option<f64>,
+    // Weight of the newest RSS reading for --smooth
+    pub smooth: Option<f64>,
+    // Wrap PIDs in OSC 8 hyperlinks; only honoured on a supporting terminal
+    pub hyperlinks: bool,
+    // Rank marks and the --show-args markers of the text tree
+    pub decorations: Decorations,
+    // Print names whole instead of eliding their middle
+    pub full_names: bool,
+    // Command lines hidden with --redact-args; secret flag values are always hidden
+    pub redact: Redactions,
+    // Hostname and user names hashed in the metadata and command lines
+    pub anonymize: bool,
+    pub tree_style: TreeStyle,
+    pub indent: usize,
+    // Per-depth totals after each tree
+    pub by_depth: bool,
+    // Root's own memory against its descendants' in a header per tree
+    pub breakdown: bool,
+    // Totals per group over every tree, after the last one
+    pub group_by: Option<GroupBy>,
+    // Second tree total with shared mappings counted once
+    pub dedupe_shared: bool,
+    // Keep only processes passing this nice comparison, and their ancestors
+    pub only_nice: Option<NiceFilter>,
+    // Fold identical sibling subtrees into one
+    pub dedupe_subtrees: bool,
+    // Ask jcmd for the heap usage of each JVM
+    pub jvm_probe: bool,
+    // Friendly names for --labels, built-ins plus the config file
+    pub labels: Option<Labels>,
+    pub sizes: SizeFormat,
+    // Print only this many of the largest trees
+    pub max_trees: Option<usize>,
+    // One line per tree instead of the report
+    pub overview: bool,
+    // KEY=value lines for the shell instead of the report
+    pub shell_vars: bool,
+    // Print only this many children per process, the largest
+    pub max_children: Option<usize>,
+    // Cap the children of JSON output as well
+    pub max_children_json: bool,
+    pub tree: Option<TreeSelection>,
+    // Which matches become tree roots
+    pub roots: RootPolicy,
+    // Order of trees and children
+    pub sort: SortKey,
+    // One tree under a synthetic root instead of one per root
+    pub merge_roots: bool,
+    // Second summary over the matched processes alone
+    pub matched_only_totals: bool,
+    // The root's parent and its other children below each tree
+    pub siblings: bool,
+    // Top-3 ranks among the matched processes alone
+    pub highlight_matched: bool,
+    // Only match within the subtree of this PID
+    pub under: Option<u32>,
+    // Only match processes listening on one of these TCP ports (--port)
+    pub listen_ports: Vec<u16>,
+    // Drop trees outside memon's own session
+    pub current_session_only: bool,
+    // Keep matches of another user's login in the matched tree above them
+    pub cross_user_trees: bool,
+    pub exclude_self: SelfExclusion,
+    pub thresholds: Thresholds,
+    pub budget: Option<u64>,
+    pub format: OutputFormat,
+    pub jobs: usize,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for MonitorOptions {
+    fn default() -> Self {
+        MonitorOptions {
+            color: ColorChoice::Never,
+            banner_color: ColorChoice::Never,
+            columns: Columns::default(),
+            min_percent: None,
+            smooth: None,
+            hyperlinks: false,
+            decorations: Decorations::default(),
+            full_names: false,
+            redact: Redactions::default(),
+            anonymize: false,
+            tree_style: TreeStyle::Unicode,
+            indent: 2,
+            by_depth: false,
+            breakdown: false,
+            group_by: None,
+            dedupe_shared: false,
+            only_nice: None,
+            dedupe_subtrees: false,
+            jvm_probe: false,
+            labels: None,
+            sizes: SizeFormat::default(),
+            max_trees: None,
+            overview: false,
+            shell_vars: false,
+            max_children: None,
+            max_children_json: false,
+            tree: None,
+            merge_roots: false,
+            roots: RootPolicy::Strict,
+            sort: SortKey::Pid,
+            matched_only_totals: false,
+            siblings: false,
+            highlight_matched: false,
+            under: None,
+            listen_ports: Vec::new(),
+            current_session_only: false,
+            cross_user_trees: false,
+            exclude_self: SelfExclusion::Process,
+            thresholds: Thresholds::default(),
+            budget: None,
+            format: OutputFormat::Text,
+            jobs: collect::default_jobs(),
+            timeout: None,
+        }
+    }
+}
+
+impl MonitorOptions {
+    // The one place command line flags are mapped onto monitor options;
+    // `format` is the already resolved output format, and the last two tell
+    // whether stdout and stderr are terminals
+    pub fn from_args(args: &Args, format: OutputFormat, color_env: ColorEnv, stdout_is_terminal: bool, stderr_is_terminal: bool) -> Self {
+        MonitorOptions {
+            color: ColorChoice::resolve(args.color, args.no_color, stdout_is_terminal && args.output.is_none(), color_env),
+            banner_color: ColorChoice::resolve(args.color, args.no_color, stderr_is_terminal, color_env),
+            // --overview and --shell-vars show no columns, so nothing is read for them
+            columns: if args.overview || args.shell_vars {
+                Columns::default()
+            } else {
+                Columns {
+                    args: args.show_args,
+                    counts: args.counts,
+                    percent: args.percent,
+                    percent_of_parent: args.percent_of_parent,
+                    mem_details: args.mem_details,
+                    hugepages: args.hugepages,
+                    limits: args.limits,
+                    ports: args.ports,
+                    jvm: args.jvm || args.jvm_probe,
+                    gpu: args.gpu,
+                    tty: args.show_tty,
+                    peak: args.peak,
+                    stability: args.stability,
+                    churn: args.churn,
+                    k8s: args.k8s,
+                    nice: args.nice,
+                    pgid: args.pgid,
+                    privs: args.privs,
+                    history: args.history_cols,
+                }
+            },
+            min_percent: args.min_percent,
+            smooth: args.smooth,
+            hyperlinks: args.hyperlinks,
+            decorations: Decorations { marker: args.marker, args: args.decorations },
+            full_names: args.full_names,
+            redact: Redactions::new(&args.redact_args),
+            anonymize: args.anonymize,
+            tree_style: args.tree_style,
+            indent: args.indent,
+            by_depth: args.by_depth,
+            breakdown: args.breakdown,
+            group_by: args.group_by,
+            dedupe_shared: args.dedupe_shared,
+            only_nice: args.only_nice,
+            dedupe_subtrees: args.dedupe_subtrees,
+            jvm_probe: args.jvm_probe,
+            // Filled in by the caller, which reads the config file
+            labels: None,
+            sizes: SizeFormat::new(args.unit, !args.no_group, units::numeric_locale().as_deref()).with_page_size(platform::page_size()),
+            tree: match (args.tree, args.tree_root) {
+                (Some(index), _) => Some(TreeSelection::Index(index as usize)),
+                (None, Some(pid)) => Some(TreeSelection::RootPid(pid)),
+                (None, None) => None,
+            },
+            merge_roots: args.merge_roots,
+            roots: args.roots,
+            sort: args.sort,
+            matched_only_totals: args.matched_only_totals,
+            siblings: args.siblings,
+            highlight_matched: args.highlight_matched,
+            under: args.under,
+            listen_ports: args.port.clone(),
+            current_session_only: args.current_session_only,
+            cross_user_trees: args.cross_user_trees,
+            exclude_self: match (args.include_self, args.exclude_self_tree) {
+                (true, _) => SelfExclusion::Include,
+                (false, true) => SelfExclusion::Ancestors,
+                (false, false) => SelfExclusion::Process,
+            },
+            max_trees: if args.first { Some(1) } else { args.max_trees.map(|n| n as usize) },
+            overview: args.overview,
+            shell_vars: args.shell_vars,
+            max_children: (args.max_children > 0).then_some(args.max_children),
+            max_children_json: args.max_children_json,
+            thresholds: Thresholds { total: args.fail_if_total, process: args.fail_if_process },
+            budget: args.budget,
+            format,
+            jobs: args.jobs.unwrap_or_else(collect::default_jobs),
+            timeout: args.timeout.map(Duration::from_secs),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// Workers that outlive their supervisor in watch mode (--no-follow-orphans turns this off)
+
+use std::collections::{HashMap, HashSet};
+
+use crate::churn::Identity;
+use crate::process::ProcessInfo;
+use crate::source;
+
+// Every process that was ever part of a matched tree during a watch session.
+// When a supervisor dies its workers are re-parented, usually to PID 1, and
+// drop out of the matched trees while still holding their memory; the
+// tracker finds them again by identity, so a reused PID is never mistaken
+// for one of them.
+#[derive(Debug, Clone, Default)]
+pub struct OrphanTracker {
+    members: HashSet<Identity>,
+}
+
+impl OrphanTracker {
+    // Record the processes of one sample, `reachable` being every PID of the
+    // matched trees, and return the roots of the remembered processes that
+    // are alive outside them, in PID order. Their descendants are remembered
+    // too. Exited processes are forgotten. The table must be linked.
+    pub fn update(&mut self, processes: &HashMap<u32, ProcessInfo>, reachable: &HashSet<u32>) -> Vec<u32> {
+        let alive = |&(pid, start_time): &Identity| processes.get(&pid).is_some_and(|proc_info| proc_info.start_time == start_time);
+        self.members.retain(alive);
+
+        let mut orphans = HashSet::new();
+        for &(pid, _) in &self.members {
+            if !reachable.contains(&pid) && !orphans.contains(&pid) {
+                orphans.extend(source::descendant_set(processes, pid).into_iter().filter(|pid| !reachable.contains(pid)));
+            }
+        }
+        for pid in reachable.iter().chain(&orphans) {
+            if let Some(proc_info) = processes.get(pid) {
+                self.members.insert((*pid, proc_info.start_time));
+            }
+        }
+
+        let mut roots: Vec<u32> = orphans
+            .iter()
+            .copied()
+            .filter(|pid| processes[pid].parent_pid.is_none_or(|parent| !orphans.contains(&parent)))
+            .collect();
+        roots.sort_unstable();
+        roots
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
@@ -0,0 +1,71 @@
+// Destination for rendered output: stdout or the file given with --output
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct OutputTarget {
+    path: Option<PathBuf>,
+    append: bool,
+    mkdir: bool,
+}
+
+// Failure to write the --output file, carrying the path for the message
+#[derive(Debug)]
+pub struct OutputError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot write output file {}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for OutputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl OutputTarget {
+    pub fn new(path: Option<PathBuf>, append: bool, mkdir: bool) -> Self {
+        OutputTarget { path, append, mkdir }
+    }
+    
+    pub fn is_stdout(&self) -> bool {
+        self.path.is_none()
+    }
+    
+    // Write one complete rendering. Files are truncated and rewritten on every
+    // call, so in watch mode the file holds the latest snapshot, unless
+    // appending was requested.
+    pub fn write(&self, text: &str) -> Result<(), OutputError> {
+        let Some(path) = &self.path else {
+            let mut stdout = io::stdout().lock();
+            return stdout
+                .write_all(text.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|source| OutputError { path: PathBuf::from("<stdout>"), source });
+        };
+        
+        let with_path = |source| OutputError { path: path.clone(), source };
+        if self.mkdir
+            && let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent).map_err(with_path)?;
+        }
+        
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .open(path)
+            .map_err(with_path)?;
+        file.write_all(text.as_bytes()).map_err(with_path)
+    }
+}
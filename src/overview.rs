@@ -0,0 +1,97 @@
+// --overview: one line per matched tree, for the lay of the land before
+// looking at any tree in full
+
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::snapshot::{ProcessTree, Snapshot};
+use crate::units::SizeFormat;
+
+// The largest process below a tree's root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Descendant {
+    pub pid: u32,
+    pub name: String,
+    pub rss_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreeOverview {
+    pub root_pid: u32,
+    pub root_name: String,
+    pub process_count: usize,
+    pub total_bytes: u64,
+    // None for a tree that is only its root
+    pub largest_descendant: Option<Descendant>,
+}
+
+impl TreeOverview {
+    pub fn new(tree: &ProcessTree) -> Self {
+        let largest_descendant = tree
+            .iter_preorder()
+            .skip(1)
+            .map(|(node, _)| node)
+            .filter(|node| !node.is_virtual)
+            // The first in tree order on a tie
+            .min_by_key(|node| Reverse(node.rss_bytes))
+            .map(|node| Descendant { pid: node.pid, name: node.name.clone(), rss_bytes: node.rss_bytes });
+        TreeOverview {
+            root_pid: tree.root_pid,
+            root_name: tree.root.name.clone(),
+            process_count: tree.process_count,
+            total_bytes: tree.total_bytes,
+            largest_descendant,
+        }
+    }
+}
+
+// --overview --json document
+#[derive(Debug, Clone, Serialize)]
+pub struct Overview {
+    pub version: String,
+    pub pattern: String,
+    pub matched: usize,
+    // Largest total first
+    pub trees: Vec<TreeOverview>,
+}
+
+impl Overview {
+    // The trees of a snapshot, largest total first, keeping at most `max_trees`
+    pub fn new(snapshot: &Snapshot, max_trees: Option<usize>) -> Self {
+        let mut trees: Vec<TreeOverview> = snapshot.trees.iter().map(TreeOverview::new).collect();
+        trees.sort_by_key(|tree| Reverse(tree.total_bytes));
+        trees.truncate(max_trees.unwrap_or(usize::MAX));
+        Overview { version: snapshot.version.clone(), pattern: snapshot.pattern.clone(), matched: snapshot.matched, trees }
+    }
+}
+
+// One aligned line per tree, such as
+// "nginx  1234  12 procs  340.0MB  largest nginx (1240) 120.0MB"
+pub fn render_text(overview: &Overview, sizes: &SizeFormat) -> String {
+    let rows: Vec<[String; 5]> = overview
+        .trees
+        .iter()
+        .map(|tree| {
+            let largest = match &tree.largest_descendant {
+                Some(largest) => format!("largest {} ({}) {}", largest.name, largest.pid, sizes.memory(largest.rss_bytes)),
+                None => "largest -".to_string(),
+            };
+            [tree.root_name.clone(), tree.root_pid.to_string(), sizes.count(tree.process_count as u64), sizes.memory(tree.total_bytes), largest]
+        })
+        .collect();
+    let width = |column: usize| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0);
+    let widths = [width(0), width(1), width(2), width(3)];
+
+    let mut out = String::new();
+    for [name, pid, count, total, largest] in &rows {
+        let _ = writeln!(out, "{:<w0$}  {:>w1$}  {:>w2$} procs  {:>w3$}  {}", name, pid, count, total, largest,
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    }
+    out
+}
+
+pub fn render_json(overview: &Overview) -> String {
+    serde_json::to_string_pretty(overview).expect("overview serializes to JSON") + "\n"
+}
@@ -0,0 +1,95 @@
+// Long one-shot text reports go through a pager on a terminal, as git does:
+// $MEMON_PAGER, then $PAGER, then `less -RFX`, which keeps the colors and
+// quits right away when the output fits on one screen
+
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub const DEFAULT_PAGER: &str = "less -RFX";
+
+// Exit status of `sh -c` for a command it could not find or execute
+const SHELL_NOT_FOUND: i32 = 127;
+const SHELL_NOT_EXECUTABLE: i32 = 126;
+
+// A pager that could not be run, so nothing was shown
+#[derive(Debug)]
+pub struct PagerError(String);
+
+impl fmt::Display for PagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PagerError {}
+
+// The pager command from the environment; None when it is set to "" or
+// "cat", which turn paging off as with git
+pub fn command(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let command = lookup("MEMON_PAGER").or_else(|| lookup("PAGER")).unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let command = command.trim();
+    (!command.is_empty() && command != "cat").then(|| command.to_string())
+}
+
+// Whether `text` is taller than a terminal of `height` rows; never when the
+// height is unknown
+pub fn needs_paging(text: &str, height: Option<usize>) -> bool {
+    height.is_some_and(|height| text.lines().count() > height)
+}
+
+// Whether the program `command` starts with exists, as a path or in one of
+// the `path` directories; the shell would otherwise report it missing on
+// top of the warning
+pub fn program_exists(command: &str, path: Option<&OsStr>) -> bool {
+    let Some(program) = command.split_whitespace().next() else {
+        return false;
+    };
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    path.is_some_and(|path| env::split_paths(path).any(|dir| dir.join(program).is_file()))
+}
+
+// Pipe `text` into `command`, run through the shell, and wait until the
+// user quits it
+pub fn page(command: &str, text: &str) -> Result<(), PagerError> {
+    if !cfg!(windows) && !program_exists(command, env::var_os("PATH").as_deref()) {
+        return Err(PagerError(format!("pager '{}' not found", command)));
+    }
+    let mut shell = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let mut child = shell
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| PagerError(format!("cannot run pager '{}': {}", command, err)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager before the end closes the pipe, which is fine
+        match stdin.write_all(text.as_bytes()) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+                // Let the pager see the end of its input and give the terminal
+                // back before the caller writes the output directly
+                drop(stdin);
+                let _ = child.wait();
+                return Err(PagerError(format!("cannot write to pager '{}': {}", command, err)));
+            }
+            _ => {}
+        }
+    }
+    let status = child.wait().map_err(|err| PagerError(format!("pager '{}' failed: {}", command, err)))?;
+    match status.code() {
+        Some(SHELL_NOT_FOUND | SHELL_NOT_EXECUTABLE) if !cfg!(windows) => Err(PagerError(format!("cannot run pager '{}': {}", command, status))),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,43 @@
+// Path arguments such as --output and --from-file: a leading ~ and ${VAR}
+// references are expanded and relative paths made absolute, as a shell would
+// have left them. cron jobs and systemd units pass them on unexpanded.
+
+use std::path::{Path, PathBuf};
+
+// `text` with a leading "~" or "~/" replaced by $HOME and every ${VAR} by its
+// value from `lookup`, made absolute against `cwd` when relative and a
+// working directory is known. An unset variable is an error naming it.
+pub fn expand(text: &str, lookup: impl Fn(&str) -> Option<String>, cwd: Option<&Path>) -> Result<PathBuf, String> {
+    let unset = |name: &str| format!("environment variable {} is not set, needed for '{}'", name, text);
+    let mut expanded = String::new();
+    let mut rest = text;
+    if text == "~" || text.starts_with("~/") {
+        expanded.push_str(&lookup("HOME").ok_or_else(|| unset("HOME"))?);
+        rest = &text[1..];
+    }
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let end = reference.find('}').ok_or_else(|| format!("unclosed '${{' in '{}'", text))?;
+        let name = &reference[..end];
+        if name.is_empty() {
+            return Err(format!("empty '${{}}' in '{}'", text));
+        }
+        expanded.push_str(&lookup(name).ok_or_else(|| unset(name))?);
+        rest = &reference[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    let path = PathBuf::from(expanded);
+    Ok(match cwd {
+        Some(cwd) if path.is_relative() => cwd.join(path),
+        _ => path,
+    })
+}
+
+// Value parser of the path flags, with this process's environment and
+// working directory
+pub fn parse_path(text: &str) -> Result<PathBuf, String> {
+    let cwd = std::env::current_dir().ok();
+    expand(text, |name| std::env::var(name).ok(), cwd.as_deref())
+}
@@ -0,0 +1,167 @@
+// Options from the [pattern."..."] sections of the config file, merged into
+// the command line. Matching sections fill in the options that were not
+// given, a later section winning over an earlier one; the command line wins
+// over every section, including where it sets an option that conflicts with
+// a section's.
+
+use std::ffi::OsString;
+use std::fmt::Write as _;
+
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches};
+
+use crate::cli::Args;
+use crate::config::{OptionValue, PatternSection};
+
+// Where an effective option came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    CommandLine,
+    // The header of the section, such as [pattern."postgres"]
+    Section(String),
+    // A section's option the command line took precedence over
+    Overridden(String),
+}
+
+// One option as --show-config lists it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    // Such as "--group-by"
+    pub flag: String,
+    // Empty for a switch
+    pub values: Vec<String>,
+    pub source: Source,
+}
+
+#[derive(Debug)]
+pub struct Resolved {
+    pub args: Args,
+    // Headers of the matching sections, in file order
+    pub sections: Vec<String>,
+    // Options given on the command line or by a section, in --help order,
+    // then the overridden section options
+    pub settings: Vec<Setting>,
+}
+
+// Parse `argv` (program name first) with the options of `sections`, the
+// sections matching its pattern, filled in
+pub fn resolve(argv: &[OsString], sections: &[&PatternSection]) -> Result<Resolved, String> {
+    let command = Args::command();
+    let given = command.clone().try_get_matches_from(argv).map_err(|err| err.to_string())?;
+    let on_command_line = |id: &str| given.value_source(id) == Some(ValueSource::CommandLine);
+
+    // One value per option, from the last section that sets it
+    let mut chosen: Vec<(&str, &OptionValue, String)> = Vec::new();
+    for section in sections {
+        for (long, value) in &section.options {
+            chosen.retain(|(other, ..)| other != long);
+            chosen.push((long, value, section.header()));
+        }
+    }
+
+    let mut extra = Vec::new();
+    let mut from_sections = Vec::new();
+    let mut overridden = Vec::new();
+    for (long, value, header) in chosen {
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long))
+            .ok_or_else(|| format!("unknown option \"{}\" in {}", long, header))?;
+        let values = match value {
+            OptionValue::Switch(_) if arg.get_action().takes_values() => return Err(format!("\"{}\" in {} needs a value", long, header)),
+            OptionValue::Values(_) if !arg.get_action().takes_values() => return Err(format!("\"{}\" in {} is a switch; use true or false", long, header)),
+            OptionValue::Switch(false) => continue,
+            OptionValue::Switch(true) => Vec::new(),
+            OptionValue::Values(values) => values.clone(),
+        };
+        let flag = format!("--{}", long);
+        let id = arg.get_id().as_str();
+        // Conflicts are declared on one side only, so look both ways
+        let conflicts = command.get_arguments().filter(|other| on_command_line(other.get_id().as_str())).any(|other| {
+            command.get_arg_conflicts_with(arg).contains(&other) || command.get_arg_conflicts_with(other).contains(&arg)
+        });
+        if on_command_line(id) || conflicts {
+            overridden.push(Setting { flag, values, source: Source::Overridden(header) });
+            continue;
+        }
+        if values.is_empty() {
+            extra.push(flag);
+        } else {
+            extra.extend(values.iter().map(|value| format!("{}={}", flag, value)));
+        }
+        from_sections.push((id.to_string(), header));
+    }
+
+    // The section options go before the command line's own
+    let mut merged: Vec<OsString> = argv.iter().take(1).cloned().collect();
+    merged.extend(extra.into_iter().map(OsString::from));
+    merged.extend(argv.iter().skip(1).cloned());
+    let matches = command.clone().try_get_matches_from(&merged).map_err(|err| {
+        // clap's message without the usage lines, on one line
+        let message = err.to_string();
+        let message: Vec<&str> = message.split("\n\nUsage").next().unwrap_or_default().lines().map(str::trim).collect();
+        format!("with the options of the config file: {}", message.join(" ").trim_start_matches("error: "))
+    })?;
+    let args = Args::from_arg_matches(&matches).map_err(|err| err.to_string())?;
+
+    let mut settings: Vec<Setting> = command
+        .get_arguments()
+        .filter(|arg| arg.get_id().as_str() != "show_config" && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .filter_map(|arg| {
+            let long = arg.get_long()?;
+            let id = arg.get_id().as_str();
+            let values = if arg.get_action().takes_values() {
+                matches.get_raw(id).map_or(Vec::new(), |raw| raw.map(|value| value.to_string_lossy().into_owned()).collect())
+            } else {
+                Vec::new()
+            };
+            let source = from_sections
+                .iter()
+                .find(|(section_id, _)| section_id == id)
+                .map_or(Source::CommandLine, |(_, header)| Source::Section(header.clone()));
+            Some(Setting { flag: format!("--{}", long), values, source })
+        })
+        .collect();
+    settings.extend(overridden);
+    Ok(Resolved { args, sections: sections.iter().map(|section| section.header()).collect(), settings })
+}
+
+// The options in effect, as "--flag value" in --help order, leaving out the
+// section options the command line took precedence over
+pub fn effective_options(resolved: &Resolved) -> Vec<String> {
+    resolved
+        .settings
+        .iter()
+        .filter(|setting| !matches!(setting.source, Source::Overridden(_)))
+        .map(|setting| std::iter::once(setting.flag.clone()).chain(setting.values.iter().cloned()).collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+// --show-config: the sections matching `pattern` and the merged options, one
+// per line with where it came from
+pub fn render(pattern: &str, resolved: &Resolved) -> String {
+    let mut out = format!("Pattern: {}\n", pattern);
+    if resolved.sections.is_empty() {
+        out.push_str("Sections: none\n");
+    } else {
+        let _ = writeln!(out, "Sections: {}", resolved.sections.join(", "));
+    }
+    let options: Vec<(String, String)> = resolved
+        .settings
+        .iter()
+        .map(|setting| {
+            let option = std::iter::once(setting.flag.clone()).chain(setting.values.iter().cloned()).collect::<Vec<_>>().join(" ");
+            let source = match &setting.source {
+                Source::CommandLine => "command line".to_string(),
+                Source::Section(header) => header.clone(),
+                Source::Overridden(header) => format!("{}, overridden by the command line", header),
+            };
+            (option, source)
+        })
+        .collect();
+    let width = options.iter().map(|(option, _)| option.chars().count()).max().unwrap_or(0);
+    for (option, source) in options {
+        let _ = writeln!(out, "{:<width$}  {}", option, source, width = width);
+    }
+    out
+}
@@ -0,0 +1,109 @@
+// Peak (high-water-mark) memory per process and per tree (--peak)
+
+use std::collections::HashMap;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::process::ProcessInfo;
+
+// Where a peak value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum PeakSource {
+    // VmHWM: the kernel's lifetime peak, available on Linux even for one run
+    Kernel,
+    // The highest RSS memon itself saw across its samples
+    Observed,
+}
+
+impl PeakSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            PeakSource::Kernel => "kernel",
+            PeakSource::Observed => "observed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Peak {
+    pub bytes: u64,
+    pub source: PeakSource,
+}
+
+// Largest process seen over a whole run, for the watch recap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessPeak {
+    pub pid: u32,
+    pub name: String,
+    pub bytes: u64,
+}
+
+// Highest values seen so far. Process and tree peaks are keyed by
+// (pid, start_time) so a reused PID starts afresh.
+#[derive(Debug, Clone, Default)]
+pub struct PeakTracker {
+    processes: HashMap<(u32, u64), u64>,
+    trees: HashMap<(u32, u64), u64>,
+    // Over the whole run, kept after the processes exit
+    pub max_total: u64,
+    pub max_process: Option<ProcessPeak>,
+}
+
+impl PeakTracker {
+    pub fn new() -> Self {
+        PeakTracker::default()
+    }
+
+    // Record the current RSS of the processes in `pids` and set their peak:
+    // the kernel value from `kernel` where it is at least the observed one,
+    // otherwise the observed maximum. Processes that have exited are forgotten.
+    pub fn observe(&mut self, processes: &mut HashMap<u32, ProcessInfo>, pids: &[u32], kernel: &HashMap<u32, u64>) {
+        let alive = |&(pid, start_time): &(u32, u64), _: &mut u64| {
+            processes.get(&pid).is_some_and(|proc_info: &ProcessInfo| proc_info.start_time == start_time)
+        };
+        self.processes.retain(alive);
+        self.trees.retain(alive);
+        for pid in pids {
+            let Some(proc_info) = processes.get_mut(pid) else {
+                continue;
+            };
+            let observed = self.processes.entry((proc_info.pid, proc_info.start_time)).or_default();
+            *observed = (*observed).max(proc_info.rss);
+            let peak = match kernel.get(&proc_info.pid) {
+                Some(&bytes) if bytes >= *observed => Peak { bytes, source: PeakSource::Kernel },
+                _ => Peak { bytes: *observed, source: PeakSource::Observed },
+            };
+            if self.max_process.as_ref().is_none_or(|max| peak.bytes > max.bytes) {
+                self.max_process = Some(ProcessPeak { pid: proc_info.pid, name: proc_info.name.clone(), bytes: peak.bytes });
+            }
+            proc_info.peak = Some(peak);
+        }
+    }
+
+    // Record the total of the tree rooted at `root` and return its peak
+    pub fn observe_tree(&mut self, root: (u32, u64), total: u64) -> u64 {
+        let peak = self.trees.entry(root).or_default();
+        *peak = (*peak).max(total);
+        *peak
+    }
+
+    // Continue the process and tree peaks of a restarted root under its new
+    // identity
+    pub fn carry_over(&mut self, from: (u32, u64), to: (u32, u64)) {
+        for peaks in [&mut self.processes, &mut self.trees] {
+            if let Some(peak) = peaks.remove(&from) {
+                let carried = peaks.entry(to).or_default();
+                *carried = (*carried).max(peak);
+            }
+        }
+    }
+
+    // Record the combined total of all trees in one sample
+    pub fn observe_total(&mut self, total: u64) {
+        self.max_total = self.max_total.max(total);
+    }
+}
@@ -0,0 +1,279 @@
+// Linux readers backed by /proc
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::privs::{self, Privileges};
+use crate::process::{HugePages, MemDetails, MemoryLimits, Session};
+use crate::threads::ThreadStat;
+
+// Read /proc/<pid>/cmdline, whose arguments are NUL-separated
+pub fn read_cmdline(pid: u32) -> Option<String> {
+    let raw = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    Some(args.join(" "))
+}
+
+// Contents of /proc/<pid>/comm without the trailing newline
+pub fn read_comm(pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end_matches('\n').to_string())
+}
+
+// Read VmLck from /proc/<pid>/status and the dirty page total from
+// /proc/<pid>/smaps_rollup; smaps_rollup needs ptrace access to the process
+pub fn read_mem_details(pid: u32) -> Option<MemDetails> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let rollup = fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok()?;
+    Some(MemDetails { locked: parse_status_locked(&status)?, dirty: parse_rollup_dirty(&rollup)? })
+}
+
+// VmLck in bytes from the contents of /proc/<pid>/status
+pub fn parse_status_locked(status: &str) -> Option<u64> {
+    status.lines().find_map(|line| kb_field(line, "VmLck:"))
+}
+
+// Shared_Dirty plus Private_Dirty in bytes from the contents of smaps_rollup
+pub fn parse_rollup_dirty(rollup: &str) -> Option<u64> {
+    let mut found = false;
+    let mut dirty = 0;
+    for line in rollup.lines() {
+        if let Some(bytes) = kb_field(line, "Shared_Dirty:").or_else(|| kb_field(line, "Private_Dirty:")) {
+            found = true;
+            dirty += bytes;
+        }
+    }
+    found.then_some(dirty)
+}
+
+// /proc/<pid>/statm is hidden from other users with hidepid and in some
+// containers; kernel threads can read it and really have no memory
+pub fn memory_readable(pid: u32) -> bool {
+    fs::read_to_string(format!("/proc/{}/statm", pid)).is_ok()
+}
+
+// Lifetime peak RSS (VmHWM) from /proc/<pid>/status
+pub fn read_peak_rss(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    parse_status_hwm(&status)
+}
+
+pub fn parse_status_hwm(status: &str) -> Option<u64> {
+    status.lines().find_map(|line| kb_field(line, "VmHWM:"))
+}
+
+// Huge pages from /proc/<pid>/status (HugetlbPages) and smaps_rollup
+// (AnonHugePages); a field the kernel does not expose, or a file that cannot
+// be read, leaves that value as None
+pub fn read_huge_pages(pid: u32) -> Option<HugePages> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let rollup = fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).unwrap_or_default();
+    Some(parse_huge_pages(&status, &rollup))
+}
+
+pub fn parse_huge_pages(status: &str, rollup: &str) -> HugePages {
+    let field = |text: &str, key: &str| text.lines().find_map(|line| kb_field(line, key));
+    HugePages { anon: field(rollup, "AnonHugePages:"), hugetlb: field(status, "HugetlbPages:") }
+}
+
+// Memory rlimits from /proc/<pid>/limits and VmSize from /proc/<pid>/status
+pub fn read_limits(pid: u32) -> Option<MemoryLimits> {
+    let limits = fs::read_to_string(format!("/proc/{}/limits", pid)).ok()?;
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+    let mut parsed = parse_limits(&limits)?;
+    parsed.virtual_bytes = status.lines().find_map(|line| kb_field(line, "VmSize:"));
+    Some(parsed)
+}
+
+// "Max address space" and "Max resident set" rows of /proc/<pid>/limits:
+// soft and hard limit in bytes, or "unlimited"
+pub fn parse_limits(limits: &str) -> Option<MemoryLimits> {
+    let row = |name: &str| -> Option<(Option<u64>, Option<u64>)> {
+        let mut values = limits.lines().find_map(|line| line.strip_prefix(name))?.split_whitespace();
+        let mut value = || match values.next()? {
+            "unlimited" => Some(None),
+            number => number.parse().ok().map(Some),
+        };
+        Some((value()?, value()?))
+    };
+    let (address_space, address_space_hard) = row("Max address space")?;
+    let (resident, resident_hard) = row("Max resident set")?;
+    Some(MemoryLimits { address_space, address_space_hard, resident, resident_hard, virtual_bytes: None })
+}
+
+// Listening TCP sockets of the whole system from /proc/net/tcp and
+// /proc/net/tcp6, as socket inode to local port
+pub fn read_listening_ports() -> HashMap<u64, u16> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|table| parse_net_tcp(&table))
+        .collect()
+}
+
+// (inode, port) of the sockets in LISTEN state (st 0A) of a /proc/net/tcp
+// table; the local address is hex "ADDR:PORT"
+pub fn parse_net_tcp(table: &str) -> Vec<(u64, u16)> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (local, state, inode) = (fields.get(1)?, fields.get(3)?, fields.get(9)?);
+            if *state != "0A" {
+                return None;
+            }
+            let port = u16::from_str_radix(local.rsplit(':').next()?, 16).ok()?;
+            Some((inode.parse().ok()?, port))
+        })
+        .collect()
+}
+
+// Inodes of the sockets a process holds open, from the /proc/<pid>/fd links.
+// None when the directory is unreadable, as it is for other users' processes.
+pub fn read_socket_inodes(pid: u32) -> Option<Vec<u64>> {
+    let entries = fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+    Some(entries
+        .filter_map(|entry| fs::read_link(entry.ok()?.path()).ok())
+        .filter_map(|link| parse_socket_link(&link.to_string_lossy()))
+        .collect())
+}
+
+// The inode of an fd link such as "socket:[123456]"
+pub fn parse_socket_link(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+// Raw /proc/<pid>/smaps for `memon maps`; the error says why it is unreadable
+pub fn read_smaps(pid: u32) -> io::Result<String> {
+    fs::read_to_string(format!("/proc/{}/smaps", pid))
+}
+
+// Raw /proc/<pid>/maps for the thread stacks of `memon threads`
+pub fn read_maps(pid: u32) -> io::Result<String> {
+    fs::read_to_string(format!("/proc/{}/maps", pid))
+}
+
+// Every thread in /proc/<pid>/task for `memon threads`, in TID order.
+// Threads that exit while being read are left out.
+pub fn read_threads(pid: u32) -> io::Result<Vec<ThreadStat>> {
+    let mut threads = Vec::new();
+    for entry in fs::read_dir(format!("/proc/{}/task", pid))? {
+        let Ok(tid) = entry?.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let dir = format!("/proc/{}/task/{}", pid, tid);
+        let Some(cpu_ticks) = fs::read_to_string(format!("{}/stat", dir)).ok().and_then(|stat| parse_stat_cpu_ticks(&stat)) else {
+            continue;
+        };
+        let name = fs::read_to_string(format!("{}/comm", dir)).map(|comm| comm.trim_end().to_string()).unwrap_or_default();
+        let stack_pointer = fs::read_to_string(format!("{}/syscall", dir)).ok().and_then(|syscall| parse_syscall_sp(&syscall));
+        threads.push(ThreadStat { tid, name, cpu_ticks, stack_pointer });
+    }
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
+}
+
+// Fields 14 (utime) and 15 (stime) of /proc/<pid>/task/<tid>/stat summed,
+// counted after the command name like parse_stat_session
+pub fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+    let utime: u64 = fields.nth(11)?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+// Stack pointer from /proc/<pid>/task/<tid>/syscall, the second to last of
+// "nr arg1 ... arg6 sp pc" (or "-1 sp pc" when blocked outside a system
+// call). A running thread shows just "running". Reading it needs ptrace access.
+pub fn parse_syscall_sp(syscall: &str) -> Option<u64> {
+    let fields: Vec<&str> = syscall.split_whitespace().collect();
+    if fields.len() < 3 {
+        return None;
+    }
+    u64::from_str_radix(fields[fields.len() - 2].strip_prefix("0x")?, 16).ok()
+}
+
+pub fn read_cgroup(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()
+}
+
+// Target of /proc/<pid>/exe; unreadable for other users' processes without
+// root, and for kernel threads
+pub fn read_exe(pid: u32) -> Option<String> {
+    fs::read_link(format!("/proc/{}/exe", pid)).ok().map(|path| path.to_string_lossy().into_owned())
+}
+
+// Session ID and controlling terminal from /proc/<pid>/stat
+pub fn read_session(pid: u32) -> Option<Session> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (id, tty_nr) = parse_stat_session(&stat)?;
+    Some(Session { id, tty: tty_name(tty_nr) })
+}
+
+// Fields 6 (session) and 7 (tty_nr) of /proc/<pid>/stat. The command name in
+// field 2 may contain spaces and parentheses, so counting starts after its
+// closing parenthesis.
+pub fn parse_stat_session(stat: &str) -> Option<(u32, u32)> {
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+    let session = fields.nth(3)?.parse().ok()?;
+    let tty_nr = fields.next()?.parse::<i64>().ok()?;
+    Some((session, tty_nr as u32))
+}
+
+// Process group from /proc/<pid>/stat
+pub fn read_pgid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_pgid(&stat)
+}
+
+// Field 5 (pgrp) of /proc/<pid>/stat, counted after the command name like
+// parse_stat_session
+pub fn parse_stat_pgid(stat: &str) -> Option<u32> {
+    stat.get(stat.rfind(')')? + 1..)?.split_whitespace().nth(2)?.parse().ok()
+}
+
+// UIDs and CapEff from /proc/<pid>/status
+pub fn read_privileges(pid: u32) -> Option<Privileges> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    privs::parse_status(&status)
+}
+
+// Nice value from /proc/<pid>/stat
+pub fn read_nice(pid: u32) -> Option<i32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_nice(&stat)
+}
+
+// Field 19 (nice) of /proc/<pid>/stat, counted after the command name like
+// parse_stat_session
+pub fn parse_stat_nice(stat: &str) -> Option<i32> {
+    stat.get(stat.rfind(')')? + 1..)?.split_whitespace().nth(16)?.parse().ok()
+}
+
+// Device name for a tty_nr; 0 means no controlling terminal
+pub fn tty_name(tty_nr: u32) -> Option<String> {
+    if tty_nr == 0 {
+        return None;
+    }
+    // Major in bits 8-15, minor in bits 0-7 and 20-31
+    let major = (tty_nr >> 8) & 0xfff;
+    let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+    Some(match major {
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        4 if minor < 64 => format!("tty{}", minor),
+        4 => format!("ttyS{}", minor - 64),
+        _ => format!("{}:{}", major, minor),
+    })
+}
+
+// Parse a "Key:   1234 kB" line into bytes
+fn kb_field(line: &str, key: &str) -> Option<u64> {
+    let value = line.strip_prefix(key)?.trim().strip_suffix("kB")?.trim();
+    value.parse::<u64>().ok().map(|kb| kb * 1024)
+}
@@ -0,0 +1,10 @@
+// macOS readers
+
+// Process group from getpgid(2), which reports the same group as libproc's
+// proc_pidinfo and works for other users' processes too
+pub fn read_pgid(pid: u32) -> Option<u32> {
+    let pid = libc::pid_t::try_from(pid).ok()?;
+    // SAFETY: getpgid takes a plain PID and only reads the process table
+    let pgid = unsafe { libc::getpgid(pid) };
+    u32::try_from(pgid).ok()
+}
@@ -0,0 +1,303 @@
+// Platform-specific per-process readers
+//
+// Readers return None when the data is unavailable on this platform or not
+// readable for the given process, so callers can fall back to sysinfo.
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+
+use std::collections::HashMap;
+
+use crate::maps::Sharing;
+use crate::privs::Privileges;
+use crate::process::{HugePages, MemDetails, MemoryLimits, Session};
+use crate::threads::ThreadStat;
+use crate::units::DEFAULT_PAGE_SIZE;
+
+// System memory page size for --unit pages and "p" sizes: sysconf on Unix,
+// DEFAULT_PAGE_SIZE where it is not available
+pub fn page_size() -> u64 {
+    #[cfg(unix)]
+    {
+        // SAFETY: sysconf only reads a system configuration value
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        u64::try_from(size).ok().filter(|&size| size > 0).unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+    #[cfg(not(unix))]
+    {
+        DEFAULT_PAGE_SIZE
+    }
+}
+
+// Whether a process with this PID still exists: a signal 0 on Unix, which
+// fails with EPERM for another user's process. None where it cannot tell
+pub fn process_exists(pid: u32) -> Option<bool> {
+    #[cfg(unix)]
+    {
+        let pid = libc::pid_t::try_from(pid).ok()?;
+        // SAFETY: signal 0 sends nothing, it only checks the PID
+        let result = unsafe { libc::kill(pid, 0) };
+        Some(result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Command line of a process joined with spaces
+pub fn read_cmdline(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_cmdline(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Name the kernel keeps for a process, at most 15 characters; Linux only
+pub fn read_comm(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_comm(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Locked and dirty memory of a process; Linux only
+pub fn read_mem_details(pid: u32) -> Option<MemDetails> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_mem_details(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Whether the memory of a process can be read at all; None where the
+// platform cannot tell, so callers fall back to comparing owners. Linux only
+pub fn memory_readable(pid: u32) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(linux::memory_readable(pid))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Highest RSS a process ever had, as tracked by the kernel; Linux only
+pub fn read_peak_rss(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_peak_rss(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Private and shared memory from /proc/<pid>/smaps for --dedupe-shared; Linux only
+pub fn read_sharing(pid: u32) -> Option<Sharing> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_smaps(pid).ok().map(|smaps| crate::maps::parse_sharing(&smaps))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Nice value of a process, -20 (highest priority) to 19; Linux only
+pub fn read_nice(pid: u32) -> Option<i32> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_nice(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Contents of /proc/<pid>/cgroup, for the pod of a process; Linux only
+pub fn read_cgroup(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_cgroup(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Process group of a process, for --pgid and --group-by pgid; Linux and macOS
+pub fn read_pgid(pid: u32) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_pgid(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::read_pgid(pid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Real and effective user and capabilities of a process for --privs, from
+// /proc/<pid>/status; Linux only
+pub fn read_privileges(pid: u32) -> Option<Privileges> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_privileges(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Path of a process's executable, for --group-by exe; Linux only
+pub fn read_exe(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_exe(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Transparent and explicit huge pages of a process; Linux only
+pub fn read_huge_pages(pid: u32) -> Option<HugePages> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_huge_pages(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Session and controlling terminal of a process; Linux only, elsewhere the
+// caller falls back to sysinfo for the session ID
+pub fn read_session(pid: u32) -> Option<Session> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_session(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Memory rlimits and virtual size of a process; Linux only
+pub fn read_limits(pid: u32) -> Option<MemoryLimits> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_limits(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Listening TCP ports of the whole system keyed by socket inode; Linux
+// only, empty elsewhere
+pub fn read_listening_ports() -> HashMap<u64, u16> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_listening_ports()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        HashMap::new()
+    }
+}
+
+// Socket inodes held open by a process; Linux only
+pub fn read_socket_inodes(pid: u32) -> Option<Vec<u64>> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_socket_inodes(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+// Contents of /proc/<pid>/smaps; Linux only
+pub fn read_smaps(pid: u32) -> std::io::Result<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_smaps(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "memory maps are only available on Linux"))
+    }
+}
+
+// Threads of a process for `memon threads`; Linux only
+pub fn read_threads(pid: u32) -> std::io::Result<Vec<ThreadStat>> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_threads(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "threads are only listed on Linux"))
+    }
+}
+
+// Contents of /proc/<pid>/maps; Linux only
+pub fn read_maps(pid: u32) -> std::io::Result<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_maps(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "memory maps are only available on Linux"))
+    }
+}
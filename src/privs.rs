@@ -0,0 +1,113 @@
+// Privileges of the processes in a tree for --privs: real and effective user,
+// and on Linux the effective capability set, compared with the tree's root
+
+use std::collections::HashMap;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::{ProcessNode, ProcessTree};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Privileges {
+    pub uid: u32,
+    pub euid: u32,
+    // Name of the effective user, where the user database has it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    // CapEff from /proc/<pid>/status; Linux only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<u64>,
+}
+
+impl Privileges {
+    // Real and effective user differ, as for a setuid program
+    pub fn is_setuid(&self) -> bool {
+        self.uid != self.euid
+    }
+
+    // Capabilities held without being root, which has them all anyway
+    pub fn extra_capabilities(&self) -> Option<u64> {
+        self.capabilities.filter(|&caps| caps != 0 && self.euid != 0)
+    }
+
+    // Whether this process runs with other privileges than a root running as
+    // `root_euid`: another effective user, setuid or extra capabilities
+    pub fn differs_from(&self, root_euid: Option<u32>) -> bool {
+        root_euid.is_some_and(|euid| euid != self.euid) || self.is_setuid() || self.extra_capabilities().is_some()
+    }
+
+    // The marks of the text tree, such as "↑root setuid" or "↓www-data";
+    // empty when nothing differs from the root. `ascii` writes ^ and v.
+    pub fn label(&self, root_euid: Option<u32>, ascii: bool) -> String {
+        let mut marks = Vec::new();
+        if let Some(root_euid) = root_euid && root_euid != self.euid {
+            let arrow = match (self.euid == 0, ascii) {
+                (true, false) => '\u{2191}',
+                (true, true) => '^',
+                (false, false) => '\u{2193}',
+                (false, true) => 'v',
+            };
+            marks.push(format!("{}{}", arrow, self.user_label()));
+        }
+        if self.is_setuid() {
+            marks.push("setuid".to_string());
+        }
+        if let Some(caps) = self.extra_capabilities() {
+            marks.push(format!("caps {:#x}", caps));
+        }
+        marks.join(" ")
+    }
+
+    fn user_label(&self) -> String {
+        self.user.clone().unwrap_or_else(|| self.euid.to_string())
+    }
+}
+
+// Real and effective UID and CapEff from the contents of /proc/<pid>/status
+pub fn parse_status(status: &str) -> Option<Privileges> {
+    let mut ids = None;
+    let mut capabilities = None;
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("Uid:") {
+            let mut fields = value.split_whitespace().map(|field| field.parse::<u32>().ok());
+            ids = Some((fields.next()??, fields.next()??));
+        } else if let Some(value) = line.strip_prefix("CapEff:") {
+            capabilities = u64::from_str_radix(value.trim(), 16).ok();
+        }
+    }
+    let (uid, euid) = ids?;
+    Some(Privileges { uid, euid, user: None, capabilities })
+}
+
+// User names by UID from an /etc/passwd style listing
+pub fn parse_passwd(passwd: &str) -> HashMap<u32, String> {
+    passwd
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+// Processes of a tree with other privileges than its root, in tree order,
+// and how many could not be read
+pub fn privileged(tree: &ProcessTree) -> (Vec<&ProcessNode>, usize) {
+    let root_euid = tree.root.privs.as_ref().map(|privs| privs.euid);
+    let mut found = Vec::new();
+    let mut unreadable = 0;
+    for (node, _) in tree.iter_preorder().filter(|(node, _)| !node.is_virtual) {
+        match &node.privs {
+            Some(privs) if privs.differs_from(root_euid) => found.push(node),
+            Some(_) => {}
+            None => unreadable += 1,
+        }
+    }
+    (found, unreadable)
+}
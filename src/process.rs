@@ -0,0 +1,138 @@
+// Process information model
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::history_cols::RssHistory;
+use crate::jvm::JvmHeap;
+use crate::k8s::PodRef;
+use crate::maps::Sharing;
+use crate::peak::Peak;
+use crate::privs::Privileges;
+use crate::stability::Stability;
+
+// Process information structure
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String, // The most informative name, see names.rs
+    pub raw_name: Option<String>, // The name the system reported, when the command line gave a better one
+    pub rss: u64, // Resident Set Size in bytes; smoothed with --smooth
+    pub rss_raw: Option<u64>, // The unsmoothed reading, only set with --smooth
+    pub parent_pid: Option<u32>,
+    pub start_time: u64, // Seconds since epoch; (pid, start_time) identifies a process across refreshes
+    pub uid: Option<u32>, // Real user; only known for the live table on Unix
+    pub children: Vec<u32>,
+    pub is_max_memory: bool,
+    pub is_second_max_memory: bool,
+    pub is_third_max_memory: bool,
+    pub args: Option<String>, // Command line arguments
+    pub descendant_count: usize, // Total processes below this one in the tree
+    pub cumulative_rss: u64, // RSS of this process plus all its descendants
+    pub mem_details: Option<MemDetails>, // Only collected with --mem-details
+    pub huge_pages: Option<HugePages>, // Only collected with --hugepages
+    pub gpu_bytes: Option<u64>, // Only collected with --gpu
+    pub session: Option<Session>, // Only collected with --show-tty
+    pub peak: Option<Peak>, // Only tracked with --peak
+    pub history: Option<RssHistory>, // Only tracked with --history-cols, after MIN_SAMPLES samples
+    pub limits: Option<MemoryLimits>, // Only collected with --limits
+    pub jvm: Option<JvmHeap>, // Only collected with --jvm, for JVMs
+    pub stability: Option<Stability>, // Only tracked with --stability
+    pub ports: Option<Vec<u16>>, // Only collected with --ports, for processes listening on TCP
+    pub sharing: Option<Sharing>, // Only collected with --dedupe-shared, where smaps is readable
+    pub nice: Option<i32>, // Only collected with --nice or --only-nice
+    pub pod: Option<PodRef>, // Only collected with --k8s, for processes in a Kubernetes pod
+    pub exec: Option<String>, // Only collected with --exec-column, where the command succeeded
+    pub exe: Option<String>, // Only collected with --group-by exe, where the executable link is readable
+    pub pgid: Option<u32>, // Only collected with --pgid or --group-by pgid
+    pub privs: Option<Privileges>, // Only collected with --privs, where readable
+    pub readable: bool, // False when the memory was hidden from memon, e.g. another user's; rss is 0 then
+}
+
+// Memory that RSS alone does not break down, read from /proc on Linux
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemDetails {
+    pub locked: u64, // VmLck: pages pinned with mlock, never reclaimed
+    pub dirty: u64, // Shared_Dirty + Private_Dirty: must be written back before reclaim
+}
+
+impl ProcessInfo {
+    pub fn new(pid: u32, name: String, rss: u64, parent_pid: Option<u32>) -> Self {
+        ProcessInfo {
+            pid,
+            name,
+            raw_name: None,
+            rss,
+            rss_raw: None,
+            parent_pid,
+            start_time: 0,
+            uid: None,
+            children: Vec::new(),
+            is_max_memory: false,
+            is_second_max_memory: false,
+            is_third_max_memory: false,
+            args: None,
+            descendant_count: 0,
+            cumulative_rss: 0,
+            mem_details: None,
+            huge_pages: None,
+            gpu_bytes: None,
+            session: None,
+            peak: None,
+            history: None,
+            limits: None,
+            jvm: None,
+            stability: None,
+            ports: None,
+            sharing: None,
+            nice: None,
+            pod: None,
+            exec: None,
+            exe: None,
+            pgid: None,
+            privs: None,
+            readable: true,
+        }
+    }
+    
+    pub fn add_child(&mut self, child_pid: u32) {
+        self.children.push(child_pid);
+    }
+}
+
+// Huge page usage; each field is None when the kernel does not expose it,
+// which is not the same as using none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HugePages {
+    pub anon: Option<u64>, // AnonHugePages: transparent huge pages
+    pub hugetlb: Option<u64>, // HugetlbPages: explicit hugetlbfs pages
+}
+
+// Memory rlimits from /proc/<pid>/limits, None meaning unlimited, and the
+// virtual size RLIMIT_AS is measured against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MemoryLimits {
+    pub address_space: Option<u64>, // RLIMIT_AS soft limit: mappings beyond it fail
+    pub address_space_hard: Option<u64>,
+    pub resident: Option<u64>, // RLIMIT_RSS soft limit; Linux no longer enforces it
+    pub resident_hard: Option<u64>,
+    pub virtual_bytes: Option<u64>, // VmSize
+}
+
+impl MemoryLimits {
+    // Virtual size as a percentage of the address-space limit, when both are known
+    pub fn address_space_percent(&self) -> Option<f64> {
+        let limit = self.address_space?;
+        let used = self.virtual_bytes?;
+        Some(if limit == 0 { 100.0 } else { used as f64 / limit as f64 * 100.0 })
+    }
+}
+
+// Session and controlling terminal of a process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: u32, // Session ID: the PID of the session leader
+    pub tty: Option<String>, // Controlling terminal such as "pts/3"; None for daemons
+}
@@ -0,0 +1,71 @@
+// Progress spinner for slow process scans
+
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Scans shorter than this never show the spinner
+const SHOW_AFTER: Duration = Duration::from_millis(300);
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// Spinner drawn on stderr while a scan is running. Nothing is drawn unless
+// stderr is a terminal and the scan outlives SHOW_AFTER; stop() (or drop)
+// erases the line so it never interleaves with the final output.
+pub struct Spinner {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(message: &str) -> Spinner {
+        let stop = Arc::new(AtomicBool::new(false));
+        if !std::io::stderr().is_terminal() {
+            return Spinner { stop, handle: None };
+        }
+        
+        let message = message.to_string();
+        let stop_flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let started = Instant::now();
+            while started.elapsed() < SHOW_AFTER {
+                if stop_flag.load(Ordering::Relaxed) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            
+            let mut stderr = std::io::stderr();
+            let mut frame = 0;
+            while !stop_flag.load(Ordering::Relaxed) {
+                let _ = write!(stderr, "\r{} {} ({:.1}s)", FRAMES[frame % FRAMES.len()], message, started.elapsed().as_secs_f64());
+                let _ = stderr.flush();
+                frame += 1;
+                thread::sleep(FRAME_INTERVAL);
+            }
+            let _ = write!(stderr, "\r\x1b[K");
+            let _ = stderr.flush();
+        });
+        
+        Spinner { stop, handle: Some(handle) }
+    }
+    
+    pub fn stop(mut self) {
+        self.finish();
+    }
+    
+    fn finish(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
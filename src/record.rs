@@ -0,0 +1,284 @@
+// Recording files (--record) and the offline report (`memon report`)
+//
+// A recording is NDJSON: a header line with the memon version and host
+// metadata, then one line per sample. Every line is written as its own zstd
+// frame, so a recording cut short by a crash still holds every complete sample.
+
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "record")]
+use std::fs::{File, OpenOptions};
+use std::io;
+#[cfg(feature = "record")]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(feature = "record")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chart;
+use crate::history::format_timestamp;
+use crate::snapshot::Snapshot;
+use crate::units::format_memory;
+
+// Identifies the file type in the header line
+pub const FORMAT: &str = "memon-record";
+pub const FORMAT_VERSION: u32 = 1;
+
+// Rows and largest number of columns of the growth chart
+const CHART_HEIGHT: usize = 8;
+const CHART_WIDTH: usize = 60;
+
+// Processes listed in the peak table
+const PEAK_ROWS: usize = 10;
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(PathBuf, io::Error),
+    Format(PathBuf, String),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            RecordError::Format(path, message) => write!(f, "{}: not a memon recording: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+// The machine a recording was made on
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostInfo {
+    pub hostname: Option<String>,
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub cpus: usize,
+    pub total_memory: u64,
+}
+
+// First line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordHeader {
+    pub format: String,
+    pub format_version: u32,
+    pub memon_version: String,
+    pub pattern: String,
+    pub interval_secs: u64,
+    // Seconds since the epoch
+    pub started: i64,
+    pub host: HostInfo,
+}
+
+impl RecordHeader {
+    pub fn new(pattern: &str, interval_secs: u64, started: i64, host: HostInfo) -> Self {
+        RecordHeader {
+            format: FORMAT.to_string(),
+            format_version: FORMAT_VERSION,
+            memon_version: env!("CARGO_PKG_VERSION").to_string(),
+            pattern: pattern.to_string(),
+            interval_secs,
+            started,
+            host,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordSample {
+    pub timestamp: i64,
+    pub snapshot: Snapshot,
+}
+
+impl RecordSample {
+    pub fn total_bytes(&self) -> u64 {
+        self.snapshot.trees.iter().map(|tree| tree.total_bytes).sum()
+    }
+}
+
+#[cfg(feature = "record")]
+pub struct RecordWriter {
+    path: PathBuf,
+    file: File,
+}
+
+#[cfg(feature = "record")]
+impl RecordWriter {
+    // Create (or replace) the recording at `path` and write its header
+    pub fn create(path: &Path, header: &RecordHeader) -> Result<RecordWriter, RecordError> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|err| RecordError::Io(path.to_path_buf(), err))?;
+        let mut writer = RecordWriter { path: path.to_path_buf(), file };
+        writer.write_line(header)?;
+        Ok(writer)
+    }
+
+    pub fn append(&mut self, sample: &RecordSample) -> Result<(), RecordError> {
+        self.write_line(sample)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<(), RecordError> {
+        let mut line = serde_json::to_string(value).expect("recording line serializes to JSON");
+        line.push('\n');
+        let frame = zstd::encode_all(line.as_bytes(), 3)
+            .and_then(|frame| self.file.write_all(&frame))
+            .and_then(|_| self.file.flush());
+        frame.map_err(|err| RecordError::Io(self.path.clone(), err))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: RecordHeader,
+    pub samples: Vec<RecordSample>,
+    // The file ends in an incomplete frame, as after a crash
+    pub truncated: bool,
+}
+
+// Read a recording. A damaged tail is tolerated and reported in `truncated`;
+// a missing or foreign header is an error.
+#[cfg(feature = "record")]
+pub fn read_recording(path: &Path) -> Result<Recording, RecordError> {
+    let file = File::open(path).map_err(|err| RecordError::Io(path.to_path_buf(), err))?;
+    let decoder = zstd::Decoder::new(file).map_err(|err| RecordError::Io(path.to_path_buf(), err))?;
+    let mut lines = BufReader::new(decoder).lines();
+    let format_error = |message: String| RecordError::Format(path.to_path_buf(), message);
+
+    let first = lines.next()
+        .ok_or_else(|| format_error("the file is empty".to_string()))?
+        .map_err(|err| format_error(err.to_string()))?;
+    let header: RecordHeader = serde_json::from_str(&first).map_err(|err| format_error(err.to_string()))?;
+    if header.format != FORMAT {
+        return Err(format_error(format!("unexpected format '{}'", header.format)));
+    }
+    if header.format_version > FORMAT_VERSION {
+        return Err(format_error(format!("format version {} needs a newer memon", header.format_version)));
+    }
+
+    let mut samples = Vec::new();
+    let mut truncated = false;
+    for line in lines {
+        match line.map_err(|err| err.to_string()).and_then(|line| serde_json::from_str(&line).map_err(|err| err.to_string())) {
+            Ok(sample) => samples.push(sample),
+            Err(err) => {
+                log::warn!("{}: stopped reading at a damaged sample: {}", path.display(), err);
+                truncated = true;
+                break;
+            }
+        }
+    }
+    Ok(Recording { header, samples, truncated })
+}
+
+// --record value: seconds, or a number with an s, m or h suffix
+pub fn parse_duration(text: &str) -> Result<u64, String> {
+    let trimmed = text.trim();
+    let split = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+    let (number, suffix) = trimmed.split_at(split);
+    let value: u64 = number.parse().map_err(|_| format!("invalid duration '{}'", text))?;
+    let seconds = match suffix {
+        "" | "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => return Err(format!("invalid duration unit in '{}' (use s, m or h)", text)),
+    };
+    if seconds == 0 {
+        return Err("duration must be longer than zero".to_string());
+    }
+    Ok(seconds)
+}
+
+// Highest RSS of one process over the recording
+struct ProcessPeak<'a> {
+    pid: u32,
+    name: &'a str,
+    peak: u64,
+    last: u64,
+    samples: usize,
+}
+
+// Offline report: header, total memory summary, peak table and growth chart
+pub fn render_report(recording: &Recording) -> String {
+    let mut out = String::new();
+    let header = &recording.header;
+    let host = &header.host;
+    out.push_str(&format!("Recording of '{}' made by memon {}\n", header.pattern, header.memon_version));
+    let mut host_line = host.hostname.clone().unwrap_or_else(|| "unknown host".to_string());
+    if let Some(os) = &host.os {
+        host_line.push_str(&format!(", {}", os));
+    }
+    if let Some(kernel) = &host.kernel {
+        host_line.push_str(&format!(" (kernel {})", kernel));
+    }
+    out.push_str(&format!("Host: {}, {} CPUs, {} memory\n", host_line, host.cpus, format_memory(host.total_memory)));
+
+    let samples = &recording.samples;
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        out.push_str("No samples recorded\n");
+        return out;
+    };
+    out.push_str(&format!("Samples: {} every {}s, {} to {} ({}s){}\n",
+                          samples.len(), header.interval_secs,
+                          format_timestamp(first.timestamp), format_timestamp(last.timestamp),
+                          last.timestamp - first.timestamp,
+                          if recording.truncated { ", file truncated" } else { "" }));
+
+    let totals: Vec<u64> = samples.iter().map(RecordSample::total_bytes).collect();
+    let min = totals.iter().copied().min().unwrap_or(0);
+    let max = totals.iter().copied().max().unwrap_or(0);
+    let average = totals.iter().sum::<u64>() / totals.len() as u64;
+    let (start, end) = (totals[0], totals[totals.len() - 1]);
+    let sign = if end >= start { '+' } else { '-' };
+    out.push_str("\nTotal memory\n");
+    out.push_str(&format!("  min {} | avg {} | max {}\n", format_memory(min), format_memory(average), format_memory(max)));
+    out.push_str(&format!("  first {} -> last {} ({}{})\n", format_memory(start), format_memory(end), sign, format_memory(start.abs_diff(end))));
+
+    out.push_str("\nLargest process peaks\n");
+    out.push_str(&peak_table(samples));
+
+    out.push_str("\nTotal memory over time\n");
+    out.push_str(&chart::render(&totals, CHART_HEIGHT, CHART_WIDTH));
+    out
+}
+
+// The PEAK_ROWS processes with the highest RSS in any sample
+fn peak_table(samples: &[RecordSample]) -> String {
+    let mut peaks: HashMap<(u32, &str), ProcessPeak> = HashMap::new();
+    for sample in samples {
+        for (node, _) in sample.snapshot.trees.iter().flat_map(|tree| tree.iter_preorder()) {
+            let entry = peaks.entry((node.pid, node.name.as_str()))
+                .or_insert(ProcessPeak { pid: node.pid, name: &node.name, peak: 0, last: 0, samples: 0 });
+            entry.peak = entry.peak.max(node.rss_bytes);
+            entry.last = node.rss_bytes;
+            entry.samples += 1;
+        }
+    }
+    let mut peaks: Vec<ProcessPeak> = peaks.into_values().collect();
+    peaks.sort_by(|a, b| b.peak.cmp(&a.peak).then(a.pid.cmp(&b.pid)));
+    peaks.truncate(PEAK_ROWS);
+
+    let header = ["PID", "NAME", "PEAK", "LAST", "SAMPLES"].map(str::to_string);
+    let rows: Vec<[String; 5]> = peaks
+        .iter()
+        .map(|peak| [peak.pid.to_string(), peak.name.to_string(), format_memory(peak.peak), format_memory(peak.last), peak.samples.to_string()])
+        .collect();
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        out.push_str(&format!("  {:>w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}\n", row[0], row[1], row[2], row[3], row[4],
+                              w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4]));
+    }
+    out
+}
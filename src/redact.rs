@@ -0,0 +1,68 @@
+// --redact-args: keep credentials on command lines out of every output
+
+use crate::process::ProcessInfo;
+
+// Shown instead of a redacted command line or flag value
+pub const REDACTED: &str = "[redacted]";
+
+// Flags whose value is always redacted, as `--token VALUE` or `--token=VALUE`
+pub const SECRET_FLAGS: [&str; 3] = ["--password", "--token", "--secret"];
+
+// Processes whose whole command line is hidden
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redactions {
+    patterns: Vec<String>,
+}
+
+impl Redactions {
+    pub fn new(patterns: &[String]) -> Self {
+        Redactions { patterns: patterns.iter().map(|pattern| pattern.to_lowercase()).collect() }
+    }
+
+    // A pattern matches when it is part of the name or the command line,
+    // ignoring case
+    pub fn matches(&self, name: &str, args: &str) -> bool {
+        let name = name.to_lowercase();
+        let args = args.to_lowercase();
+        self.patterns.iter().any(|pattern| name.contains(pattern.as_str()) || args.contains(pattern.as_str()))
+    }
+
+    // Redact the command line of one process before anything renders it:
+    // all of it for a matching process, otherwise the secret flag values
+    pub fn apply(&self, proc_info: &mut ProcessInfo) {
+        let Some(args) = proc_info.args.as_deref() else {
+            return;
+        };
+        let redacted = if self.matches(&proc_info.name, args) {
+            REDACTED.to_string()
+        } else {
+            redact_secret_flags(args)
+        };
+        proc_info.args = Some(redacted);
+    }
+}
+
+// The command line with the values of SECRET_FLAGS replaced; spacing is kept
+pub fn redact_secret_flags(args: &str) -> String {
+    let mut words = Vec::new();
+    let mut value_follows = false;
+    for word in args.split(' ') {
+        if value_follows && !word.is_empty() {
+            words.push(REDACTED.to_string());
+            value_follows = false;
+            continue;
+        }
+        match word.split_once('=') {
+            Some((flag, _)) if is_secret_flag(flag) => words.push(format!("{}={}", flag, REDACTED)),
+            _ => {
+                value_follows = value_follows || is_secret_flag(word);
+                words.push(word.to_string());
+            }
+        }
+    }
+    words.join(" ")
+}
+
+fn is_secret_flag(word: &str) -> bool {
+    SECRET_FLAGS.iter().any(|flag| word.eq_ignore_ascii_case(flag))
+}
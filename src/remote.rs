@@ -0,0 +1,113 @@
+// --ssh: the process table of a remote host, collected by a shell one-liner
+// over the user's own ssh client. Nothing is installed remotely; matching and
+// rendering run locally as for any other ProcessSource.
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use log::debug;
+
+use crate::process::ProcessInfo;
+use crate::source::{ProcessSource, SourceError};
+
+// Run by `sh -c` on the remote side so the user's login shell does not matter.
+// Prints the OS name, and on Linux the page size followed by every
+// /proc/<pid>/stat line; processes that exit meanwhile are skipped.
+pub const REMOTE_SCRIPT: &str = "uname -s; [ \"$(uname -s)\" = Linux ] || exit 0; getconf PAGESIZE; cat /proc/[0-9]*/stat 2>/dev/null; exit 0";
+
+// ssh exits with 255 for its own errors: unknown host, refused connection, failed authentication
+const SSH_ERROR_STATUS: i32 = 255;
+
+pub struct SshHost {
+    destination: String,
+}
+
+impl SshHost {
+    // `destination` is anything ssh accepts, such as `user@host` or a Host alias
+    pub fn new(destination: impl Into<String>) -> Self {
+        SshHost { destination: destination.into() }
+    }
+}
+
+impl ProcessSource for SshHost {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+        let host = self.destination.clone();
+        // A leading dash would be taken as an ssh option
+        if host.is_empty() || host.starts_with('-') {
+            return Err(SourceError::Ssh { host, message: "not a valid ssh destination".to_string() });
+        }
+
+        // Authentication, ports, jump hosts and multiplexing all come from the
+        // user's ssh config and agent
+        let output = Command::new("ssh")
+            .arg(&host)
+            .arg(format!("sh -c '{}'", REMOTE_SCRIPT))
+            .output()
+            .map_err(|err| SourceError::Ssh { host: host.clone(), message: format!("cannot run ssh: {}", err) })?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if output.status.code() == Some(SSH_ERROR_STATUS) || output.status.code().is_none() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().rev().find(|line| !line.trim().is_empty())
+                .map_or_else(|| format!("ssh failed ({})", output.status), |line| line.trim().to_string());
+            return Err(SourceError::Ssh { host, message });
+        }
+
+        let processes = parse_remote_table(&stdout).map_err(|err| match err {
+            RemoteError::Unsupported(os) => SourceError::UnsupportedRemote { host: host.clone(), os },
+            RemoteError::Malformed(message) => SourceError::Ssh { host: host.clone(), message },
+        })?;
+        debug!("read {} processes from {}", processes.len(), host);
+        Ok(processes)
+    }
+}
+
+// Why the output of REMOTE_SCRIPT could not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteError {
+    // The remote OS, or a description when uname did not answer
+    Unsupported(String),
+    Malformed(String),
+}
+
+// Parse the output of REMOTE_SCRIPT into a process table
+pub fn parse_remote_table(output: &str) -> Result<HashMap<u32, ProcessInfo>, RemoteError> {
+    let mut lines = output.lines();
+    let os = lines.next().map(str::trim).unwrap_or_default();
+    match os {
+        "Linux" => {}
+        "" => return Err(RemoteError::Unsupported("a system without uname".to_string())),
+        os => return Err(RemoteError::Unsupported(os.to_string())),
+    }
+    let page_size: u64 = lines.next()
+        .and_then(|line| line.trim().parse().ok())
+        .ok_or_else(|| RemoteError::Malformed("missing page size in the remote output".to_string()))?;
+
+    let mut processes = HashMap::new();
+    for line in lines {
+        match parse_stat_line(line, page_size) {
+            Some(proc_info) => {
+                processes.insert(proc_info.pid, proc_info);
+            }
+            None => debug!("skipping unparsable stat line: {}", line),
+        }
+    }
+    if processes.is_empty() {
+        return Err(RemoteError::Malformed("no processes in the remote output".to_string()));
+    }
+    Ok(processes)
+}
+
+// One /proc/<pid>/stat line. The command name in field 2 may contain spaces
+// and parentheses, so it runs to the last closing parenthesis; PPID is field 4
+// and RSS, in pages, field 24.
+fn parse_stat_line(line: &str, page_size: u64) -> Option<ProcessInfo> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let pid = line[..open].trim().parse().ok()?;
+    let name = line.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+    let ppid: u32 = fields.get(1)?.parse().ok()?;
+    let rss_pages: u64 = fields.get(21)?.parse().ok()?;
+    let parent_pid = (ppid != 0).then_some(ppid);
+    Some(ProcessInfo::new(pid, name, rss_pages * page_size, parent_pid))
+}
@@ -0,0 +1,301 @@
+// Output renderers. Every format implements Renderer and writes one tree at a
+// time to any io::Write, so the same traversal serves stdout, files and tests.
+
+mod text;
+mod widths;
+
+pub use text::{banner, elide_middle, is_significant, Decorations, Prefix, TextRenderer, Theme};
+pub use widths::{StickyWidths, DECAY_SAMPLES};
+
+use std::cell::Cell;
+use std::io::{self, Write};
+
+use crate::format::OutputFormat;
+use crate::snapshot::{ProcessTree, Snapshot};
+use crate::stats::TreeStats;
+use crate::units::format_memory;
+
+pub trait Renderer {
+    // Called once before the first tree, e.g. for a document header
+    fn begin(&self, _snapshot: &Snapshot, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+    
+    fn render(&self, tree: &ProcessTree, stats: &TreeStats, w: &mut dyn Write) -> io::Result<()>;
+    
+    // Called once after the last tree
+    fn finish(&self, _snapshot: &Snapshot, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Renderer for a machine-readable or document format; text output needs a
+// configured TextRenderer and gets a plain one here
+pub fn renderer_for(format: OutputFormat) -> Box<dyn Renderer> {
+    match format {
+        OutputFormat::Text => Box::new(TextRenderer::plain()),
+        OutputFormat::Json => Box::new(JsonRenderer::default()),
+        OutputFormat::Csv => Box::new(CsvRenderer),
+        OutputFormat::Html => Box::new(HtmlRenderer),
+        OutputFormat::Markdown => Box::new(MarkdownRenderer),
+        OutputFormat::Dot => Box::new(DotRenderer),
+    }
+}
+
+// Drive a renderer over every tree of a snapshot
+pub fn render_document(renderer: &dyn Renderer, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+    renderer.begin(snapshot, w)?;
+    for tree in &snapshot.trees {
+        renderer.render(tree, &TreeStats::from_tree(tree), w)?;
+    }
+    renderer.finish(snapshot, w)
+}
+
+// Render a snapshot into a string
+pub fn render_to_string(renderer: &dyn Renderer, snapshot: &Snapshot) -> String {
+    let mut buffer = Vec::new();
+    render_document(renderer, snapshot, &mut buffer).expect("writing to a Vec cannot fail");
+    String::from_utf8(buffer).expect("renderers write UTF-8")
+}
+
+pub fn render_snapshot(format: OutputFormat, snapshot: &Snapshot) -> String {
+    render_to_string(&*renderer_for(format), snapshot)
+}
+
+pub fn render_json(snapshot: &Snapshot) -> String {
+    render_snapshot(OutputFormat::Json, snapshot)
+}
+
+// The whole snapshot as one pretty-printed JSON document, identical to
+// serializing the Snapshot in one go but written tree by tree
+#[derive(Default)]
+pub struct JsonRenderer {
+    trees_written: Cell<usize>,
+}
+
+impl Renderer for JsonRenderer {
+    fn begin(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        self.trees_written.set(0);
+        writeln!(w, "{{")?;
+        writeln!(w, "  \"version\": {},", serde_json::to_string(&snapshot.version)?)?;
+        writeln!(w, "  \"pattern\": {},", serde_json::to_string(&snapshot.pattern)?)?;
+        writeln!(w, "  \"matched\": {},", snapshot.matched)?;
+        write!(w, "  \"trees\": [")
+    }
+    
+    fn render(&self, tree: &ProcessTree, _stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        let written = self.trees_written.get();
+        w.write_all(if written == 0 { b"\n" } else { b",\n" })?;
+        let json = serde_json::to_string_pretty(tree)?;
+        for (i, line) in json.lines().enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            write!(w, "    {}", line)?;
+        }
+        self.trees_written.set(written + 1);
+        Ok(())
+    }
+    
+    fn finish(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        if self.trees_written.get() > 0 {
+            write!(w, "\n  ")?;
+        }
+        write!(w, "]")?;
+        if let Some(budget) = &snapshot.budget {
+            let json = serde_json::to_string_pretty(budget)?;
+            write!(w, ",\n  \"budget\": {}", json.replace('\n', "\n  "))?;
+        }
+        if let Some(match_stats) = &snapshot.match_stats {
+            let json = serde_json::to_string_pretty(match_stats)?;
+            write!(w, ",\n  \"match_stats\": {}", json.replace('\n', "\n  "))?;
+        }
+        if let Some(groups) = &snapshot.groups {
+            let json = serde_json::to_string_pretty(groups)?;
+            write!(w, ",\n  \"groups\": {}", json.replace('\n', "\n  "))?;
+        }
+        if let Some(age) = snapshot.since_last_age_secs {
+            write!(w, ",\n  \"since_last_age_secs\": {}", age)?;
+        }
+        if let Some(metadata) = &snapshot.metadata {
+            let json = serde_json::to_string_pretty(metadata)?;
+            write!(w, ",\n  \"metadata\": {}", json.replace('\n', "\n  "))?;
+        }
+        writeln!(w, "\n}}")
+    }
+}
+
+// One row per process with its tree root, parent and depth
+pub struct CsvRenderer;
+
+impl Renderer for CsvRenderer {
+    fn begin(&self, _snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "tree_root,pid,parent_pid,depth,name,rss_bytes,cumulative_bytes,args")
+    }
+    
+    fn render(&self, tree: &ProcessTree, _stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        let mut ancestors = Vec::new();
+        for (node, depth) in tree.iter_preorder() {
+            let parent_pid = parent_of(&mut ancestors, node.pid, depth);
+            writeln!(w, "{},{},{},{},{},{},{},{}",
+                     tree.root_pid,
+                     node.pid,
+                     parent_pid.map(|pid| pid.to_string()).unwrap_or_default(),
+                     depth,
+                     csv_field(&node.name),
+                     node.rss_bytes,
+                     node.cumulative_bytes,
+                     csv_field(node.args.as_deref().unwrap_or("")))?;
+        }
+        Ok(())
+    }
+}
+
+// Graphviz digraph with one cluster per tree
+pub struct DotRenderer;
+
+impl Renderer for DotRenderer {
+    fn begin(&self, _snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "digraph memon {{\n    node [shape=box];\n")
+    }
+    
+    fn render(&self, tree: &ProcessTree, stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "    subgraph cluster_{} {{", tree.root_pid)?;
+        writeln!(w, "        label=\"{} ({} procs, {})\";",
+                 dot_escape(&tree.root.name), stats.count, format_memory(stats.total_rss))?;
+        let mut ancestors = Vec::new();
+        for (node, depth) in tree.iter_preorder() {
+            let style = if node.rank.is_some() { ", style=filled, fillcolor=lightgray" } else { "" };
+            writeln!(w, "        p{} [label=\"{}\\n{}\\n{}\"{}];",
+                     node.pid, dot_escape(&node.name), node.pid, format_memory(node.rss_bytes), style)?;
+            if let Some(parent_pid) = parent_of(&mut ancestors, node.pid, depth) {
+                writeln!(w, "        p{} -> p{};", parent_pid, node.pid)?;
+            }
+        }
+        writeln!(w, "    }}")
+    }
+    
+    fn finish(&self, _snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "}}")
+    }
+}
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn begin(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "# memon: {}", snapshot.pattern)
+    }
+    
+    fn render(&self, tree: &ProcessTree, stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "\n## {} (PID {})\n", markdown_escape(&tree.root.name), tree.root_pid)?;
+        write!(w, "| PID | Process | Memory | Rank |\n|---:|---|---:|:---:|\n")?;
+        for (node, depth) in tree.iter_preorder() {
+            writeln!(w, "| {} | {}{} | {} | {} |",
+                     node.pid,
+                     "&nbsp;&nbsp;".repeat(depth),
+                     markdown_escape(&node.name),
+                     format_memory(node.rss_bytes),
+                     rank_marker(node.rank))?;
+        }
+        writeln!(w, "\n{} procs | {} avg | {} total",
+                 stats.count, format_memory(stats.average_rss()), format_memory(stats.total_rss))
+    }
+}
+
+// Standalone HTML page with one table per tree
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn begin(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>memon: {}</title>", html_escape(&snapshot.pattern))?;
+        write!(w, "<style>body{{font-family:sans-serif}}td,th{{padding:2px 8px}}td.num{{text-align:right}}tr.top{{background:#ddd}}</style>\n</head>\n<body>\n")?;
+        writeln!(w, "<h1>memon: {}</h1>", html_escape(&snapshot.pattern))?;
+        if let Some(metadata) = &snapshot.metadata {
+            writeln!(w, "<table class=\"metadata\">")?;
+            for (label, value) in metadata.rows() {
+                writeln!(w, "<tr><th>{}</th><td>{}</td></tr>", label, html_escape(&value))?;
+            }
+            writeln!(w, "</table>")?;
+        }
+        Ok(())
+    }
+    
+    fn render(&self, tree: &ProcessTree, stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<h2>{} (PID {})</h2>", html_escape(&tree.root.name), tree.root_pid)?;
+        if let Some(line) = breakdown_line(tree, format_memory) {
+            writeln!(w, "<p class=\"breakdown\">{}</p>", html_escape(&line))?;
+        }
+        write!(w, "<table>\n<tr><th>PID</th><th>Process</th><th>Memory</th><th>Rank</th></tr>\n")?;
+        for (node, depth) in tree.iter_preorder() {
+            let class = if node.rank.is_some() { " class=\"top\"" } else { "" };
+            writeln!(w, "<tr{}><td class=\"num\">{}</td><td style=\"padding-left:{}em\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                     class, node.pid, depth + 1, html_escape(&node.name), format_memory(node.rss_bytes), rank_marker(node.rank))?;
+        }
+        writeln!(w, "</table>\n<p>{} procs | {} avg | {} total</p>",
+                 stats.count, format_memory(stats.average_rss()), format_memory(stats.total_rss))
+    }
+    
+    fn finish(&self, _snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        write!(w, "</body>\n</html>\n")
+    }
+}
+
+// Header of a tree with --breakdown, such as
+// "nginx (pid 811): 8.0MB self + 740.0MB descendants = 748.0MB". When the
+// root is the largest process the descendants come first, so the header
+// still says something the tree does not show at a glance.
+pub fn breakdown_line(tree: &ProcessTree, memory: impl Fn(u64) -> String) -> Option<String> {
+    let (own, below) = (tree.self_bytes?, tree.descendants_bytes?);
+    let root = format!("{} (pid {})", tree.root.name, tree.root_pid);
+    Some(if tree.root.descendants == 0 {
+        format!("{}: {} self, no descendants", root, memory(own))
+    } else if tree.root_is_largest() {
+        format!("{}: {} descendants + {} self (largest process) = {}", root, memory(below), memory(own), memory(own + below))
+    } else {
+        format!("{}: {} self + {} descendants = {}", root, memory(own), memory(below), memory(own + below))
+    })
+}
+
+// Track the path from the root during a pre-order walk and return the
+// parent of the node at `depth`
+fn parent_of(ancestors: &mut Vec<u32>, pid: u32, depth: usize) -> Option<u32> {
+    ancestors.truncate(depth);
+    let parent = ancestors.last().copied();
+    ancestors.push(pid);
+    parent
+}
+
+fn rank_marker(rank: Option<u8>) -> &'static str {
+    match rank {
+        Some(1) => "🥇",
+        Some(2) => "🥈",
+        Some(3) => "🥉",
+        _ => "",
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
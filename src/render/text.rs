@@ -0,0 +1,924 @@
+// Text tree renderer: the default terminal output
+
+use std::cell::Cell;
+use std::io::{self, Write};
+
+use super::{breakdown_line, rank_marker, Renderer};
+use crate::budget::{self, BudgetLevel};
+use crate::cli::{GroupBy, Marker, TreeStyle};
+use crate::colors;
+use crate::options::Columns;
+use crate::privs;
+use crate::stability;
+use crate::snapshot::{self, DepthTotal, GroupTotal, ProcessNode, ProcessTree, Sibling, SiblingContext, Snapshot};
+use crate::stats::TreeStats;
+use crate::units::SizeFormat;
+
+// Escape sequences used by the text output; the plain theme leaves them empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    // Applied to the summary totals
+    pub highlight: &'static str,
+    // Applied to the memory of the processes ranked 1, 2 and 3; the colors
+    // differ in both hue and lightness so they stay apart with color-vision
+    // deficiency
+    pub ranks: [&'static str; 3],
+    pub reset: &'static str,
+    // Line printed between two trees
+    pub separator: &'static str,
+    // Budget usage above budget::WARNING_PERCENT, and above 100%
+    pub warning: &'static str,
+    pub alert: &'static str,
+    // Applied to --labels annotations
+    pub dim: &'static str,
+    // Applied to the progress banners, such as "Searching: nginx"
+    pub banner: &'static str,
+}
+
+impl Theme {
+    pub const PLAIN: Theme = Theme {
+        highlight: "",
+        ranks: ["", "", ""],
+        reset: "",
+        separator: "\n============================================================",
+        warning: "",
+        alert: "",
+        dim: "",
+        banner: "",
+    };
+    
+    pub const ANSI: Theme = Theme {
+        highlight: "\x1b[30m\x1b[47m", // colors::DARK_GRAY on colors::BG_LIGHT_GRAY
+        // Black on the Okabe-Ito orange and sky blue, then on light gray
+        ranks: ["\x1b[30m\x1b[48;5;214m", "\x1b[30m\x1b[48;5;117m", "\x1b[30m\x1b[48;5;252m"],
+        reset: colors::RESET,
+        separator: "",
+        warning: colors::AMBER,
+        alert: colors::RED,
+        dim: colors::DIM,
+        banner: colors::CYAN,
+    };
+    
+    pub fn new(color: bool) -> Theme {
+        if color { Theme::ANSI } else { Theme::PLAIN }
+    }
+    
+    pub fn is_plain(&self) -> bool {
+        self.reset.is_empty()
+    }
+}
+
+// A progress banner such as "Found 3 procs" in the banner color; a plain
+// theme leaves the message as it is
+pub fn banner(theme: &Theme, message: &str) -> String {
+    if theme.banner.is_empty() {
+        message.to_string()
+    } else {
+        format!("{}{}{}", theme.banner, message, theme.reset)
+    }
+}
+
+// Emoji the text tree adds around the data, in one place so each can be
+// turned off without touching what is shown. The top-three marks follow
+// --marker; the markers around --show-args output are opt-in with
+// --decorations, since they throw the alignment off and look out of place in
+// logs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decorations {
+    pub marker: Marker,
+    // 🟢 before the PID and 🔍 before the arguments
+    pub args: bool,
+}
+
+impl Decorations {
+    // Medal at the end of a ranked process's line
+    fn rank_suffix(&self, rank: Option<u8>) -> &'static str {
+        if self.marker == Marker::Emoji { rank_marker(rank) } else { "" }
+    }
+    
+    fn pid_prefix(&self) -> &'static str {
+        if self.args { "🟢" } else { "" }
+    }
+    
+    fn args_prefix(&self) -> &'static str {
+        if self.args { "🔍" } else { "" }
+    }
+}
+
+pub struct TextRenderer {
+    pub theme: Theme,
+    pub columns: Columns,
+    // Hide subtrees below this share of the tree total
+    pub min_percent: Option<f64>,
+    // Fixed (pid, name) column widths; sized to each tree when None
+    pub widths: Option<(usize, usize)>,
+    // Wrap PIDs in OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    pub decorations: Decorations,
+    // Never cut names; long ones push the rest of the line out of the column
+    pub full_names: bool,
+    pub prefix: Prefix,
+    // Unit and digit grouping of sizes and counts
+    pub sizes: SizeFormat,
+    // Name of the --exec-column column
+    pub exec_column: Option<String>,
+    // What the rows of the group table are, for its header
+    pub group_by: Option<GroupBy>,
+    // Children shown per parent, the largest by RSS; the rest share one line
+    pub max_children: Option<usize>,
+    // Physical memory of the host, for the summary highlight
+    pub system_memory: Option<u64>,
+    trees_written: Cell<usize>,
+    // --budget of the snapshot being rendered, for the per-tree share
+    budget: Cell<Option<u64>>,
+    // Age of the --since-last run the snapshot was compared with
+    since_last_age: Cell<Option<u64>>,
+}
+
+// Share of the address-space limit above which --limits flags a process
+const LIMIT_WARNING_PERCENT: f64 = 80.0;
+
+// Names longer than this are cut in the middle, unless --full-names is given
+const MAX_NAME_WIDTH: usize = 40;
+
+// Group table keys, such as executable paths, longer than this are cut in the middle
+const MAX_GROUP_KEY_WIDTH: usize = 60;
+
+// The start and end of `name` left around an ellipsis of `ellipsis_width`
+// characters so the whole fits `width`, or None when it already fits. The end
+// gets two thirds of the room: it is where worker-7 and worker-8 differ.
+fn elision_parts(name: &str, width: usize, ellipsis_width: usize) -> Option<(&str, &str)> {
+    let count = name.chars().count();
+    if count <= width {
+        return None;
+    }
+    let room = width.saturating_sub(ellipsis_width);
+    let head = room / 3;
+    let tail = room - head;
+    // Byte offsets at character boundaries, so multi-byte names are never split
+    let head_end = name.char_indices().nth(head).map_or(name.len(), |(index, _)| index);
+    let tail_start = name.char_indices().nth(count - tail).map_or(name.len(), |(index, _)| index);
+    Some((&name[..head_end], &name[tail_start..]))
+}
+
+/// Cuts `name` to at most `width` characters by replacing its middle with
+/// `ellipsis`, keeping more of the end than the start.
+///
+/// ```
+/// use memon::render::elide_middle;
+///
+/// assert_eq!(elide_middle("com.example.service.worker-7", 16, "\u{2026}"), "com.e\u{2026}e.worker-7");
+/// assert_eq!(elide_middle("nginx", 16, "\u{2026}"), "nginx");
+/// ```
+pub fn elide_middle(name: &str, width: usize, ellipsis: &str) -> String {
+    match elision_parts(name, width, ellipsis.chars().count()) {
+        Some((head, tail)) => format!("{}{}{}", head, ellipsis, tail),
+        None => name.to_string(),
+    }
+}
+
+// Characters of the name column: the name, cut to MAX_NAME_WIDTH, and its
+// --labels annotation
+fn name_column_width(node: &ProcessNode) -> usize {
+    let label_width = node.label.as_ref().map_or(0, |label| label.chars().count() + 3);
+    node.name.chars().count().min(MAX_NAME_WIDTH) + label_width
+}
+
+// A process whose memory was hidden, and all of its descendants too
+fn is_unreadable_subtree(node: &ProcessNode) -> bool {
+    !node.readable && node.children.iter().all(is_unreadable_subtree)
+}
+
+// Per-tree values shared by every line of the tree
+struct Layout {
+    total_memory: u64,
+    pid_width: usize,
+    name_width: usize,
+    // Widths of the --history-cols min, avg and max; None until some
+    // process of the tree has a history
+    history_widths: Option<[usize; 3]>,
+    // Effective user of the root for --privs, which the others are compared with
+    root_euid: Option<u32>,
+}
+
+impl TextRenderer {
+    pub fn new(theme: Theme, columns: Columns, min_percent: Option<f64>) -> Self {
+        TextRenderer { theme, columns, min_percent, widths: None, hyperlinks: false, decorations: Decorations::default(), full_names: false, prefix: Prefix::default(), sizes: SizeFormat::default(), exec_column: None, group_by: None, max_children: None, system_memory: None, trees_written: Cell::new(0), budget: Cell::new(None), since_last_age: Cell::new(None) }
+    }
+    
+    pub fn plain() -> Self {
+        TextRenderer::new(Theme::PLAIN, Columns::default(), None)
+    }
+    
+    pub fn with_widths(mut self, pid_width: usize, name_width: usize) -> Self {
+        self.widths = Some((pid_width, name_width));
+        self
+    }
+    
+    pub fn with_hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+    
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.decorations.marker = marker;
+        self
+    }
+    
+    pub fn with_decorations(mut self, decorations: Decorations) -> Self {
+        self.decorations = decorations;
+        self
+    }
+    
+    pub fn with_full_names(mut self, full_names: bool) -> Self {
+        self.full_names = full_names;
+        self
+    }
+    
+    pub fn with_tree_style(mut self, style: TreeStyle, indent: usize) -> Self {
+        self.prefix = Prefix { style, indent };
+        self
+    }
+    
+    pub fn with_sizes(mut self, sizes: SizeFormat) -> Self {
+        self.sizes = sizes;
+        self
+    }
+    
+    pub fn with_exec_column(mut self, name: Option<String>) -> Self {
+        self.exec_column = name;
+        self
+    }
+    
+    pub fn with_group_by(mut self, group_by: Option<GroupBy>) -> Self {
+        self.group_by = group_by;
+        self
+    }
+    
+    pub fn with_max_children(mut self, max_children: Option<usize>) -> Self {
+        self.max_children = max_children;
+        self
+    }
+    
+    pub fn with_system_memory(mut self, system_memory: Option<u64>) -> Self {
+        self.system_memory = system_memory;
+        self
+    }
+    
+    // Calculate column widths for proper alignment
+    pub fn column_widths(tree: &ProcessTree) -> (usize, usize) {
+        let mut max_pid_width = 0;
+        let mut max_name_width = MAX_NAME_WIDTH; // Default minimum width
+        
+        // Walk all processes in the tree
+        for (node, _) in tree.iter_preorder() {
+            max_pid_width = max_pid_width.max(node.pid.to_string().len());
+            max_name_width = max_name_width.max(name_column_width(node));
+        }
+        
+        (max_pid_width, max_name_width)
+    }
+    
+    // Widths of the min, avg and max history columns, each sized to its
+    // widest value in the tree
+    fn history_widths(&self, tree: &ProcessTree) -> Option<[usize; 3]> {
+        let mut widths = None;
+        for (node, _) in tree.iter_preorder() {
+            if let Some(history) = node.history {
+                let widths = widths.get_or_insert([1; 3]);
+                for (width, bytes) in widths.iter_mut().zip([history.min_bytes, history.avg_bytes, history.max_bytes]) {
+                    *width = (*width).max(self.sizes.memory(bytes).len());
+                }
+            }
+        }
+        widths
+    }
+    
+    fn memory(&self, bytes: u64, color: &str) -> String {
+        if self.theme.is_plain() {
+            return self.sizes.memory(bytes);
+        }
+        format!("{}{}{}", color, self.sizes.memory(bytes), self.theme.reset)
+    }
+    
+    // A value the kernel may not expose: "-" keeps absence apart from zero
+    fn optional_memory(&self, bytes: Option<u64>) -> String {
+        bytes.map_or("-".to_string(), |bytes| self.sizes.memory(bytes))
+    }
+    
+    // Signed size change: "+212.0MB", "-5.0MB", "+0B"
+    fn growth(&self, bytes: i64) -> String {
+        let sign = if bytes < 0 { '-' } else { '+' };
+        format!("{}{}", sign, self.sizes.memory(bytes.unsigned_abs()))
+    }
+    
+    // An rlimit, None being unlimited
+    fn optional_limit(&self, bytes: Option<u64>) -> String {
+        match bytes {
+            Some(bytes) => self.sizes.memory(bytes),
+            None if self.prefix.style == TreeStyle::Unicode => "\u{221e}".to_string(),
+            None => "unl".to_string(),
+        }
+    }
+    
+    // Memory of a process, colored and marked by its rank
+    fn ranked_memory(&self, bytes: u64, rank: Option<u8>) -> String {
+        let index = rank.map(|rank| usize::from(rank.clamp(1, 3)) - 1);
+        let memory = self.memory(bytes, index.map_or("", |index| self.theme.ranks[index]));
+        match self.decorations.marker {
+            Marker::Emoji => memory,
+            Marker::Rank => match rank {
+                Some(rank) => format!("{} #{}", memory, rank),
+                None => memory,
+            },
+            Marker::Ascii => format!("{}{}", index.map_or(" ", |index| ["*", "+", "."][index]), memory),
+        }
+    }
+    
+    // Print process tree with memory information
+    fn render_node(&self, w: &mut dyn Write, node: &ProcessNode, parent_cumulative: u64, level: usize, is_last: bool, layout: &Layout) -> io::Result<()> {
+        let Layout { total_memory, pid_width, name_width, history_widths, root_euid } = *layout;
+        // "?" rather than a misleading 0B where the memory was hidden
+        let memory_str = if node.readable { self.ranked_memory(node.rss_bytes, node.rank) } else { "?".to_string() };
+        
+        // Calculate and format overall percentage if total_memory is provided
+        let percentage_str = if self.columns.percent && total_memory > 0 {
+            let percentage = (node.rss_bytes as f64 / total_memory as f64) * 100.0;
+            format!(" ({:.1}% of tree)", percentage)
+        } else {
+            String::new()
+        };
+        
+        // Share of the parent's cumulative memory taken by this subtree; the root is 100%
+        let parent_percentage_str = if self.columns.percent_of_parent {
+            let percentage = if parent_cumulative > 0 {
+                (node.cumulative_bytes as f64 / parent_cumulative as f64) * 100.0
+            } else {
+                100.0
+            };
+            format!(" ({:.0}% of parent)", percentage)
+        } else {
+            String::new()
+        };
+        
+        // Elide or pad process name to dynamic width, counting characters
+        // rather than bytes so non-ASCII names neither split nor misalign.
+        // A --labels annotation shares the column and shortens the name first.
+        // The ellipsis is dimmed so a cut name does not pass for a real one.
+        let label_str = node.label.as_ref().map_or(String::new(), |label| format!(" ({})", label));
+        let label_width = label_str.chars().count();
+        let name_room = match name_width.saturating_sub(label_width) {
+            room if room > 3 => room,
+            _ => name_width,
+        };
+        let ellipsis = if self.prefix.style == TreeStyle::Unicode { "\u{2026}" } else { "..." };
+        let (name, name_chars) = match elision_parts(&node.name, name_room, ellipsis.chars().count()) {
+            Some((head, tail)) if !self.full_names => {
+                let chars = head.chars().count() + ellipsis.chars().count() + tail.chars().count();
+                if self.theme.is_plain() {
+                    (format!("{}{}{}", head, ellipsis, tail), chars)
+                } else {
+                    (format!("{}{}{}{}{}", head, self.theme.dim, ellipsis, self.theme.reset, tail), chars)
+                }
+            }
+            _ => (node.name.clone(), node.name.chars().count()),
+        };
+        let padding = " ".repeat(name_width.saturating_sub(name_chars + label_width));
+        let display_name = if label_str.is_empty() || self.theme.is_plain() {
+            format!("{}{}{}", name, label_str, padding)
+        } else {
+            format!("{}{}{}{}{}", name, self.theme.dim, label_str, self.theme.reset, padding)
+        };
+        
+        write!(w, "{}", self.prefix.build(level, is_last))?;
+        
+        // Green dot before the PID with --show-args --decorations
+        if self.columns.args {
+            write!(w, "{}", self.decorations.pid_prefix())?;
+        }
+        
+        // Pad before linking so the escape sequence does not count towards the width
+        let mut pid_str = format!("{:width$}", node.pid, width = pid_width);
+        if self.hyperlinks {
+            pid_str = colors::hyperlink(&pid_uri(node.pid), &pid_str);
+        }
+        
+        write!(w, "{} {} {}{}{}", pid_str, display_name, memory_str, percentage_str, parent_percentage_str)?;
+        
+        // The synthetic --merge-roots root has no memory of its own
+        if node.is_virtual {
+            write!(w, " (total {})", self.sizes.memory(node.cumulative_bytes))?;
+        }
+        
+        // Growth since the --since-last run; a process it did not have is new
+        if self.since_last_age.get().is_some() && !node.is_virtual {
+            match node.since_last_bytes {
+                Some(bytes) => write!(w, " {}", self.growth(bytes))?,
+                None => write!(w, " new")?,
+            }
+        }
+        
+        // Range of RSS over the watch session, "-" for a process seen once
+        if self.columns.history && !node.is_virtual && let Some([w0, w1, w2]) = history_widths {
+            let [min, avg, max] = match node.history {
+                Some(history) => [history.min_bytes, history.avg_bytes, history.max_bytes].map(|bytes| self.sizes.memory(bytes)),
+                None => ["-", "-", "-"].map(str::to_string),
+            };
+            write!(w, " min {:>w0$} avg {:>w1$} max {:>w2$}", min, avg, max)?;
+        }
+        
+        // Locked memory and the dirty/clean split of RSS; "-" where /proc
+        // could not be read for this process
+        if self.columns.mem_details {
+            match (node.locked_bytes, node.dirty_bytes) {
+                (Some(locked), Some(dirty)) => write!(w, " lck {} dirty {} clean {}",
+                                                      self.sizes.memory(locked),
+                                                      self.sizes.memory(dirty),
+                                                      self.sizes.memory(node.rss_bytes.saturating_sub(dirty)))?,
+                _ => write!(w, " lck - dirty - clean -")?,
+            }
+        }
+        
+        // Huge pages, with "-" wherever the kernel does not expose the field
+        if self.columns.hugepages {
+            write!(w, " thp {} hugetlb {}", self.optional_memory(node.anon_huge_bytes), self.optional_memory(node.hugetlb_bytes))?;
+        }
+        
+        // Address-space limit and the share of it in use, flagged above LIMIT_WARNING_PERCENT
+        if self.columns.limits {
+            match node.limits {
+                Some(limits) => {
+                    write!(w, " as-limit {}", self.optional_limit(limits.address_space))?;
+                    if let Some(percent) = limits.address_space_percent() {
+                        let color = if percent > LIMIT_WARNING_PERCENT { self.theme.warning } else { "" };
+                        let reset = if color.is_empty() { "" } else { self.theme.reset };
+                        write!(w, " ({}{:.0}%{})", color, percent, reset)?;
+                    }
+                    if limits.resident.is_some() {
+                        write!(w, " rss-limit {}", self.optional_limit(limits.resident))?;
+                    }
+                }
+                None => write!(w, " as-limit -")?,
+            }
+        }
+        
+        if self.columns.gpu {
+            write!(w, " gpu {}", self.optional_memory(node.gpu_bytes))?;
+        }
+        
+        // Listening TCP ports
+        if self.columns.ports && let Some(ports) = &node.ports {
+            let arrow = if self.prefix.style == TreeStyle::Unicode { "\u{21e2}" } else { "->" };
+            write!(w, " {}", arrow)?;
+            for port in ports {
+                write!(w, " :{}", port)?;
+            }
+        }
+        
+        // Heap of a JVM: the -Xmx setting, then what jcmd reported
+        if self.columns.jvm && let Some(heap) = node.jvm {
+            match heap.max_bytes {
+                Some(max) => write!(w, " heap max {}", self.sizes.memory(max))?,
+                None => write!(w, " heap max default")?,
+            }
+            if let (Some(used), Some(committed)) = (heap.used_bytes, heap.committed_bytes) {
+                write!(w, " used {} of {}", self.sizes.memory(used), self.sizes.memory(committed))?;
+            }
+        }
+        
+        // Peak RSS and where it comes from
+        if self.columns.peak {
+            match (node.peak_bytes, node.peak_source) {
+                (Some(bytes), Some(source)) => write!(w, " peak {} ({})", self.sizes.memory(bytes), source.label())?,
+                _ => write!(w, " peak -")?,
+            }
+        }
+        
+        // How long the memory has stood still
+        if self.columns.stability && let Some(stability) = node.stability {
+            write!(w, " {}", stability.label())?;
+        }
+        
+        // Restarts in this process's role, when there were any
+        if self.columns.churn && let Some(restarts) = node.restarts.filter(|&restarts| restarts > 0) {
+            write!(w, " restarts: {}", restarts)?;
+        }
+        
+        if self.columns.tty {
+            write!(w, " tty {}", node.tty.as_deref().unwrap_or("-"))?;
+        }
+        
+        if self.columns.nice {
+            match node.nice {
+                Some(nice) => write!(w, " nice {}", nice)?,
+                None => write!(w, " nice -")?,
+            }
+        }
+        
+        if self.columns.pgid && !node.is_virtual {
+            match node.pgid {
+                Some(pgid) => write!(w, " pgid {}", pgid)?,
+                None => write!(w, " pgid -")?,
+            }
+        }
+        
+        // Privileges other than the root's; "?" where they could not be read
+        if self.columns.privs && !node.is_virtual {
+            match &node.privs {
+                Some(privs) => {
+                    let label = privs.label(root_euid, self.prefix.style != TreeStyle::Unicode);
+                    if !label.is_empty() {
+                        write!(w, " {}", label)?;
+                    }
+                }
+                None => write!(w, " privs ?")?,
+            }
+        }
+        
+        // The --exec-column output; the synthetic root ran nothing
+        if let Some(name) = &self.exec_column && !node.is_virtual {
+            write!(w, " {} {}", name, node.exec.as_deref().unwrap_or("-"))?;
+        }
+        
+        // Kubernetes pod, by name once the kubelet resolved it
+        if self.columns.k8s && let Some(pod) = &node.pod {
+            write!(w, " pod {}", pod.label())?;
+        }
+        
+        // Display direct children / total descendants if requested
+        if self.columns.counts {
+            write!(w, " [{}/{}]", node.children.len(), node.descendants)?;
+        }
+        
+        // A subtree standing for similar siblings
+        if let Some(group) = node.similar {
+            let times = if self.prefix.style == TreeStyle::Unicode { '×' } else { 'x' };
+            write!(w, " {}{} similar, totals aggregated: {} (min {}, median {}, max {})", times, group.count,
+                   self.sizes.memory(group.total_bytes), self.sizes.memory(group.min_bytes),
+                   self.sizes.memory(group.median_bytes), self.sizes.memory(group.max_bytes))?;
+        }
+        
+        // Display arguments if available
+        if let Some(ref args) = node.args {
+            write!(w, " {}{}", self.decorations.args_prefix(), args)?;
+        }
+        
+        writeln!(w, "{}", self.decorations.rank_suffix(node.rank))?;
+        
+        // Split children into those shown, subtrees whose memory was hidden
+        // throughout, and those pruned by --min-percent
+        let (unreadable, readable): (Vec<&ProcessNode>, Vec<&ProcessNode>) = node.children
+            .iter()
+            .partition(|child| is_unreadable_subtree(child));
+        let (mut visible, hidden): (Vec<&ProcessNode>, Vec<&ProcessNode>) = readable
+            .into_iter()
+            .partition(|child| self.is_significant(child, total_memory));
+        // --max-children: a fork bomb's thousands of siblings would flood the terminal
+        let mut more = None;
+        if let Some(max_children) = self.max_children
+            && let Some((kept, omitted)) = snapshot::capped_children(&visible, max_children) {
+            visible = kept.into_iter().map(|i| visible[i]).collect();
+            more = Some(omitted);
+        }
+        
+        let child_count = visible.len();
+        for (i, child) in visible.into_iter().enumerate() {
+            let is_last_child = i == child_count - 1 && more.is_none() && unreadable.is_empty() && hidden.is_empty();
+            self.render_node(w, child, node.cumulative_bytes, level + 1, is_last_child, layout)?;
+        }
+        
+        if let Some(more) = more {
+            writeln!(w, "{}(+{} more, {})",
+                     self.prefix.build(level + 1, unreadable.is_empty() && hidden.is_empty()),
+                     self.sizes.count(more.processes as u64),
+                     self.sizes.memory(more.bytes))?;
+        }
+        
+        // Roll hidden subtrees into one line instead of a run of 0B processes
+        if !unreadable.is_empty() {
+            let unreadable_count: usize = unreadable.iter().map(|child| child.descendants + 1).sum();
+            writeln!(w, "{}unreadable (other users): {} procs, memory unknown",
+                     self.prefix.build(level + 1, hidden.is_empty()),
+                     self.sizes.count(unreadable_count as u64))?;
+        }
+        
+        // Roll pruned children into a single summary line
+        if !hidden.is_empty() {
+            let hidden_count: usize = hidden.iter().map(|child| child.descendants + 1).sum();
+            let hidden_memory: u64 = hidden.iter().map(|child| child.cumulative_bytes).sum();
+            writeln!(w, "{}({} procs below {}%, {})",
+                     self.prefix.build(level + 1, true),
+                     self.sizes.count(hidden_count as u64),
+                     self.min_percent.unwrap_or(0.0),
+                     self.sizes.memory(hidden_memory))?;
+        }
+        
+        Ok(())
+    }
+    
+    // --by-depth: one aligned row per depth with its share of the tree total
+    fn render_depth_table(&self, w: &mut dyn Write, by_depth: &[DepthTotal], total: u64) -> io::Result<()> {
+        let header = ["depth", "procs", "rss", "share"].map(str::to_string);
+        let rows: Vec<[String; 4]> = by_depth
+            .iter()
+            .map(|row| {
+                let share = if total > 0 { row.bytes as f64 / total as f64 * 100.0 } else { 0.0 };
+                [row.depth.to_string(), self.sizes.count(row.processes as u64), self.sizes.memory(row.bytes), format!("{:.1}%", share)]
+            })
+            .collect();
+        let mut widths = [0; 4];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        for row in std::iter::once(&header).chain(&rows) {
+            writeln!(w, "{:>w0$}  {:>w1$}  {:>w2$}  {:>w3$}", row[0], row[1], row[2], row[3],
+                     w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3])?;
+        }
+        Ok(())
+    }
+    
+    // --group-by: one aligned row per group with its share of all trees
+    fn render_group_table(&self, w: &mut dyn Write, key: &str, groups: &[GroupTotal]) -> io::Result<()> {
+        let total: u64 = groups.iter().map(|group| group.bytes).sum();
+        let header = [key, "procs", "rss", "share"].map(str::to_string);
+        let rows: Vec<[String; 4]> = groups
+            .iter()
+            .map(|group| {
+                let share = if total > 0 { group.bytes as f64 / total as f64 * 100.0 } else { 0.0 };
+                [self.group_key(&group.key), self.sizes.count(group.processes as u64), self.sizes.memory(group.bytes), format!("{:.1}%", share)]
+            })
+            .collect();
+        let mut widths = [0; 4];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        writeln!(w)?;
+        for row in std::iter::once(&header).chain(&rows) {
+            writeln!(w, "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}", row[0], row[1], row[2], row[3],
+                     w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3])?;
+        }
+        Ok(())
+    }
+    
+    // --siblings: the root's parent, then each of its children on one line,
+    // the matched root marked and the rest collapsed to their subtree totals
+    // --privs: the processes running with other privileges than the root
+    fn render_privileged(&self, w: &mut dyn Write, tree: &ProcessTree, root_euid: Option<u32>) -> io::Result<()> {
+        let (found, unreadable) = privs::privileged(tree);
+        let ascii = self.prefix.style != TreeStyle::Unicode;
+        let listed: Vec<String> = found
+            .iter()
+            .filter_map(|node| Some(format!("{} {} {}", node.pid, node.name, node.privs.as_ref()?.label(root_euid, ascii))))
+            .collect();
+        if listed.is_empty() {
+            write!(w, "privileged: none")?;
+        } else {
+            write!(w, "privileged: {} procs: {}", self.sizes.count(listed.len() as u64), listed.join(", "))?;
+        }
+        if unreadable > 0 {
+            write!(w, " ({} unreadable)", self.sizes.count(unreadable as u64))?;
+        }
+        writeln!(w)
+    }
+    
+    fn render_siblings(&self, w: &mut dyn Write, context: &SiblingContext) -> io::Result<()> {
+        writeln!(w, "siblings under {} {} ({}):", context.parent_pid, context.parent_name, self.sizes.memory(context.parent_rss_bytes))?;
+        // --max-children keeps the largest, and always the matched root
+        let max = self.max_children.unwrap_or(usize::MAX);
+        let mut shown: Vec<&Sibling> = Vec::new();
+        let mut omitted: Vec<&Sibling> = Vec::new();
+        for (i, sibling) in context.siblings.iter().enumerate() {
+            if i < max || sibling.matched_root {
+                shown.push(sibling);
+            } else {
+                omitted.push(sibling);
+            }
+        }
+        let count = shown.len();
+        for (i, sibling) in shown.into_iter().enumerate() {
+            write!(w, "{}{} {} [{} procs] {}",
+                   self.prefix.build(1, i == count - 1 && omitted.is_empty()),
+                   sibling.pid, sibling.name,
+                   self.sizes.count(sibling.process_count as u64),
+                   self.sizes.memory(sibling.total_bytes))?;
+            if sibling.matched_root {
+                write!(w, " (this tree)")?;
+            }
+            writeln!(w)?;
+        }
+        if !omitted.is_empty() {
+            let processes: usize = omitted.iter().map(|sibling| sibling.process_count).sum();
+            let bytes: u64 = omitted.iter().map(|sibling| sibling.total_bytes).sum();
+            writeln!(w, "{}(+{} more, {})", self.prefix.build(1, true), self.sizes.count(processes as u64), self.sizes.memory(bytes))?;
+        }
+        Ok(())
+    }
+    
+    // A group key cut in the middle to MAX_GROUP_KEY_WIDTH, keeping the file
+    // name at the end of a path, unless --full-names is given
+    fn group_key(&self, key: &str) -> String {
+        let ellipsis = if self.prefix.style == TreeStyle::Unicode { "\u{2026}" } else { "..." };
+        match elision_parts(key, MAX_GROUP_KEY_WIDTH, ellipsis.chars().count()) {
+            Some((head, tail)) if !self.full_names => format!("{}{}{}", head, ellipsis, tail),
+            _ => key.to_string(),
+        }
+    }
+    
+    // Whether a subtree's cumulative memory reaches the --min-percent threshold
+    fn is_significant(&self, node: &ProcessNode, total_memory: u64) -> bool {
+        match self.min_percent {
+            Some(min_percent) if total_memory > 0 => {
+                (node.cumulative_bytes as f64 / total_memory as f64) * 100.0 >= min_percent
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn begin(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        self.trees_written.set(0);
+        self.budget.set(snapshot.budget.map(|budget| budget.budget_bytes));
+        self.since_last_age.set(snapshot.since_last_age_secs);
+        if let Some(note) = self.sizes.page_note() {
+            writeln!(w, "{}", note)?;
+        }
+        Ok(())
+    }
+    
+    fn render(&self, tree: &ProcessTree, stats: &TreeStats, w: &mut dyn Write) -> io::Result<()> {
+        if self.trees_written.get() > 0 {
+            writeln!(w, "{}", self.theme.separator)?;
+        }
+        self.trees_written.set(self.trees_written.get() + 1);
+        
+        let (pid_width, name_width) = self.widths.unwrap_or_else(|| TextRenderer::column_widths(tree));
+        let history_widths = if self.columns.history { self.history_widths(tree) } else { None };
+        let root_euid = tree.root.privs.as_ref().map(|privs| privs.euid);
+        let layout = Layout { total_memory: stats.total_rss, pid_width, name_width, history_widths, root_euid };
+        if let Some(line) = breakdown_line(tree, |bytes| self.sizes.memory(bytes)) {
+            writeln!(w, "{}", line)?;
+        }
+        self.render_node(w, &tree.root, tree.root.cumulative_bytes, 0, false, &layout)?;
+        
+        // Summary line; the total is highlighted only when it is significant,
+        // and marked as a lower bound when some of it could not be read
+        let summary_memory = |bytes| self.sizes.memory(bytes);
+        let total = if is_significant(stats.total_rss, self.system_memory, self.budget.get()) {
+            self.memory(stats.total_rss, self.theme.highlight)
+        } else {
+            self.sizes.memory(stats.total_rss)
+        };
+        let at_least = match (tree.lower_bound, self.prefix.style) {
+            (false, _) => "",
+            (true, TreeStyle::Unicode) => "\u{2265} ",
+            (true, _) => ">= ",
+        };
+        write!(w, "{} procs | {} avg | {}{} total",
+               self.sizes.count(stats.count as u64),
+               summary_memory(stats.average_rss()),
+               at_least,
+               total)?;
+        if let Some(deduped) = tree.shared_deduped_bytes {
+            write!(w, " | total (shared-deduped): {}", summary_memory(deduped))?;
+        }
+        if let Some(age) = self.since_last_age.get() {
+            match tree.since_last_bytes {
+                Some(bytes) => write!(w, " | {} since {} ago", self.growth(bytes), stability::format_age(age))?,
+                None => write!(w, " | new since {} ago", stability::format_age(age))?,
+            }
+        }
+        if self.columns.mem_details {
+            let locked: u64 = tree.iter_preorder().filter_map(|(node, _)| node.locked_bytes).sum();
+            write!(w, " | {} locked", self.sizes.memory(locked))?;
+        }
+        if self.columns.hugepages {
+            write!(w, " | thp {} hugetlb {}",
+                   self.optional_memory(sum_known(tree, |node| node.anon_huge_bytes)),
+                   self.optional_memory(sum_known(tree, |node| node.hugetlb_bytes)))?;
+        }
+        if self.columns.gpu {
+            write!(w, " | gpu {}", self.optional_memory(sum_known(tree, |node| node.gpu_bytes)))?;
+        }
+        if let Some(budget) = self.budget.get() {
+            write!(w, " | {:.0}% of budget", budget::share(stats.total_rss, budget))?;
+        }
+        if self.columns.peak && let Some(peak) = tree.peak_bytes {
+            write!(w, " | peak {}", self.sizes.memory(peak))?;
+        }
+        if tree.current_session {
+            write!(w, " (current session)")?;
+        }
+        if tree.reparented {
+            write!(w, " (reparented)")?;
+        }
+        writeln!(w)?;
+        
+        // --matched-only-totals: the processes actually named like the pattern
+        if let Some(matched) = tree.matched_total {
+            writeln!(w, "matched: {} procs {} / tree: {} procs {}",
+                     self.sizes.count(matched.processes as u64), summary_memory(matched.bytes),
+                     self.sizes.count(stats.count as u64), summary_memory(stats.total_rss))?;
+        }
+        
+        if self.columns.privs {
+            self.render_privileged(w, tree, root_euid)?;
+        }
+        
+        if let Some(context) = &tree.siblings {
+            self.render_siblings(w, context)?;
+        }
+        
+        if let Some(by_depth) = &tree.by_depth {
+            self.render_depth_table(w, by_depth, stats.total_rss)?;
+        }
+        Ok(())
+    }
+    
+    // Re-parented workers, group totals and the overall gauge once every tree is written
+    fn finish(&self, snapshot: &Snapshot, w: &mut dyn Write) -> io::Result<()> {
+        let orphaned: Vec<&ProcessTree> = snapshot.trees.iter().filter(|tree| tree.reparented).collect();
+        if !orphaned.is_empty() {
+            let processes: usize = orphaned.iter().map(|tree| tree.process_count).sum();
+            let bytes: u64 = orphaned.iter().map(|tree| tree.total_bytes).sum();
+            writeln!(w, "\norphaned workers: {} procs | {} total", self.sizes.count(processes as u64), self.sizes.memory(bytes))?;
+        }
+        if let Some(groups) = &snapshot.groups {
+            self.render_group_table(w, self.group_by.map_or("group", GroupBy::label), groups)?;
+        }
+        let Some(budget) = snapshot.budget else {
+            return Ok(());
+        };
+        let color = match budget.level() {
+            BudgetLevel::Normal => "",
+            BudgetLevel::Warning => self.theme.warning,
+            BudgetLevel::Over => self.theme.alert,
+        };
+        let reset = if color.is_empty() { "" } else { self.theme.reset };
+        writeln!(w, "\nused {} of {} budget ({}{:.0}%{})",
+                 self.sizes.memory(budget.used_bytes),
+                 self.sizes.memory(budget.budget_bytes),
+                 color, budget.percent, reset)
+    }
+}
+
+// Share of system memory above which a tree total is highlighted
+pub const SIGNIFICANT_PERCENT: f64 = 10.0;
+
+// Whether a tree total deserves the summary highlight: more than
+// SIGNIFICANT_PERCENT of system memory, or more than the --budget
+pub fn is_significant(total: u64, system_memory: Option<u64>, budget: Option<u64>) -> bool {
+    let of_system = system_memory.is_some_and(|memory| memory > 0 && total as f64 * 100.0 / memory as f64 > SIGNIFICANT_PERCENT);
+    of_system || budget.is_some_and(|budget| total > budget)
+}
+
+// Link target for a PID: its /proc directory on Linux, a memon:// URI elsewhere
+fn pid_uri(pid: u32) -> String {
+    if cfg!(target_os = "linux") {
+        format!("file:///proc/{}", pid)
+    } else {
+        format!("memon://pid/{}", pid)
+    }
+}
+
+// Tree total of an optional per-process value; None when no process has it
+fn sum_known(tree: &ProcessTree, field: fn(&ProcessNode) -> Option<u64>) -> Option<u64> {
+    tree.iter_preorder()
+        .filter_map(|(node, _)| field(node))
+        .fold(None, |sum, bytes| Some(sum.unwrap_or(0) + bytes))
+}
+
+// Builds the part of a line in front of the PID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prefix {
+    pub style: TreeStyle,
+    // Columns per level of depth
+    pub indent: usize,
+}
+
+impl Default for Prefix {
+    fn default() -> Self {
+        Prefix { style: TreeStyle::Unicode, indent: 2 }
+    }
+}
+
+impl Prefix {
+    // Prefix for a line at the given depth; the root has none
+    pub fn build(&self, level: usize, is_last: bool) -> String {
+        if level == 0 {
+            return String::new();
+        }
+        let connector = match (self.style, is_last) {
+            (TreeStyle::Unicode, false) => "├─",
+            (TreeStyle::Unicode, true) => "└─",
+            (TreeStyle::Ascii, false) => "|-",
+            (TreeStyle::Ascii, true) => "`-",
+            (TreeStyle::Indent, _) => return format!("{}{} ", " ".repeat(self.indent * level), level),
+        };
+        format!("{}{} ", " ".repeat(self.indent * (level - 1)), connector)
+    }
+}
@@ -0,0 +1,56 @@
+// Column widths of the text tree kept across watch samples, so a long name
+// that comes and goes does not shift every column on each refresh
+
+// Samples a column must be wider than needed before it starts to shrink
+pub const DECAY_SAMPLES: usize = 10;
+
+// Room on a line besides the PID and name columns: the tree prefix of a
+// shallow process, the separating spaces and the memory with its marker
+const LINE_ROOM: usize = 16;
+
+// Narrowest name column that clamping to the terminal leaves
+const MIN_NAME_WIDTH: usize = 12;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct StickyColumn {
+    width: usize,
+    // Consecutive samples that needed less than `width`
+    narrower: usize,
+}
+
+impl StickyColumn {
+    // Widen at once, shrink one character per sample after DECAY_SAMPLES
+    fn observe(&mut self, needed: usize) -> usize {
+        if needed >= self.width {
+            self.width = needed;
+            self.narrower = 0;
+        } else {
+            self.narrower += 1;
+            if self.narrower > DECAY_SAMPLES {
+                self.width -= 1;
+            }
+        }
+        self.width
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StickyWidths {
+    pid: StickyColumn,
+    name: StickyColumn,
+}
+
+impl StickyWidths {
+    // PID and name widths for a sample that needs `needed`. With the width of
+    // the terminal, the name column never grows past the line; it comes back
+    // once the terminal is wide enough again.
+    pub fn observe(&mut self, needed: (usize, usize), terminal_columns: Option<usize>) -> (usize, usize) {
+        let pid_width = self.pid.observe(needed.0);
+        let name_width = self.name.observe(needed.1);
+        let name_width = match terminal_columns {
+            Some(columns) => name_width.min(columns.saturating_sub(pid_width + LINE_ROOM).max(MIN_NAME_WIDTH)),
+            None => name_width,
+        };
+        (pid_width, name_width)
+    }
+}
@@ -0,0 +1,111 @@
+// Following a watched tree when its root is restarted under a new PID
+// (--no-reroot turns this off)
+
+use std::collections::{HashMap, HashSet};
+
+use crate::churn::Identity;
+use crate::process::ProcessInfo;
+
+// A watched root that exited, and the new roots of the same name that took
+// its place in the same sample
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootChange {
+    pub name: String,
+    pub from: Identity,
+    pub to: Vec<Identity>,
+}
+
+impl RootChange {
+    // "root changed: 1234 → 9876 (restart detected)"
+    pub fn label(&self) -> String {
+        let to: Vec<String> = self.to.iter().map(|(pid, _)| pid.to_string()).collect();
+        format!("root changed: {} \u{2192} {} (restart detected)", self.from.0, to.join(", "))
+    }
+}
+
+// The roots of the previous watch sample. Matching runs on every sample, so a
+// supervisor's restarted service is found again by name; the tracker pairs
+// the new root with the one that exited, so the session can report the
+// restart and carry the old root's history over. With `pinned`
+// (--no-reroot) only the roots of the first sample are kept, and the session
+// shows them exiting instead.
+#[derive(Debug, Clone, Default)]
+pub struct RootTracker {
+    pinned: bool,
+    roots: Vec<(Identity, String)>,
+    // Changes of the latest sample, until taken
+    changes: Vec<RootChange>,
+    total: usize,
+}
+
+impl RootTracker {
+    pub fn new(pinned: bool) -> Self {
+        RootTracker { pinned, ..RootTracker::default() }
+    }
+
+    // Record the roots of one sample, in PID order, and return the ones to
+    // show: all of them, or with `pinned` those of the first sample that had any
+    pub fn update(&mut self, processes: &HashMap<u32, ProcessInfo>, roots: Vec<u32>) -> Vec<u32> {
+        let identity = |pid: u32| processes.get(&pid).map(|proc_info| ((pid, proc_info.start_time), proc_info.name.clone()));
+        let current: Vec<(Identity, String)> = roots.iter().filter_map(|&pid| identity(pid)).collect();
+
+        if self.pinned {
+            if self.roots.is_empty() {
+                self.roots = current;
+            }
+            let watched: HashSet<Identity> = self.roots.iter().map(|(identity, _)| *identity).collect();
+            return roots
+                .into_iter()
+                .filter(|&pid| identity(pid).is_some_and(|(identity, _)| watched.contains(&identity)))
+                .collect();
+        }
+
+        let alive = |&(pid, start_time): &Identity| processes.get(&pid).is_some_and(|proc_info| proc_info.start_time == start_time);
+        let previous: HashSet<Identity> = self.roots.iter().map(|(identity, _)| *identity).collect();
+        let mut new_roots: Vec<&(Identity, String)> = current.iter().filter(|(identity, _)| !previous.contains(identity)).collect();
+        let gone: Vec<&(Identity, String)> = self.roots.iter().filter(|(identity, _)| !alive(identity)).collect();
+        for (index, (from, name)) in gone.iter().enumerate() {
+            // One new root per exited one; the last exited root of a name
+            // takes whatever is left of it
+            let last_of_name = gone[index + 1..].iter().all(|(_, other)| other != name);
+            let mut to = Vec::new();
+            new_roots.retain(|(identity, new_name)| {
+                let take = new_name == name && (to.is_empty() || last_of_name);
+                if take {
+                    to.push(*identity);
+                }
+                !take
+            });
+            if !to.is_empty() {
+                self.changes.push(RootChange { name: name.clone(), from: *from, to });
+                self.total += 1;
+            }
+        }
+        self.roots = current;
+        roots
+    }
+
+    // Root changes not taken yet
+    pub fn pending(&self) -> &[RootChange] {
+        &self.changes
+    }
+
+    // Root changes since the last call
+    pub fn take_changes(&mut self) -> Vec<RootChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    // Root changes over the whole session
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    // With `pinned`, the PIDs of the first roots that have exited
+    pub fn exited(&self, processes: &HashMap<u32, ProcessInfo>) -> Vec<u32> {
+        self.roots
+            .iter()
+            .filter(|((pid, start_time), _)| processes.get(pid).is_none_or(|proc_info| proc_info.start_time != *start_time))
+            .map(|((pid, _), _)| *pid)
+            .collect()
+    }
+}
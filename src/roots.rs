@@ -0,0 +1,92 @@
+// Which matched processes become tree roots (--roots)
+
+use std::collections::{HashMap, HashSet};
+
+use crate::process::ProcessInfo;
+
+// Values accepted by --roots
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RootPolicy {
+    // A match whose parent is not matched, is PID 1 or is unknown
+    #[default]
+    Strict,
+    // The highest matched ancestor of each match, even across unmatched
+    // processes in between, such as a launcher that re-execs
+    MatchedTop,
+    // Every match is a root of its own, its matched descendants cut off
+    AllMatched,
+}
+
+// Whether `child` runs in another user's login than `parent`: both the
+// users and the sessions, looked up in `sessions` by PID, differ. A daemon
+// dropping privileges in its own session, such as nginx's workers, stays
+// with its parent, and so does any process whose user or session is unknown.
+pub fn other_login(parent: &ProcessInfo, child: &ProcessInfo, sessions: &HashMap<u32, u32>) -> bool {
+    let differ = |a: Option<u32>, b: Option<u32>| matches!((a, b), (Some(a), Some(b)) if a != b);
+    differ(parent.uid, child.uid) && differ(sessions.get(&parent.pid).copied(), sessions.get(&child.pid).copied())
+}
+
+// The roots among `matching_pids`, in their order. Given the session IDs of
+// `logins`, a match under a matched parent of another login is a root of its
+// own, so a user's instance is not counted in a system daemon's tree of the
+// same name.
+pub fn find_roots(processes: &HashMap<u32, ProcessInfo>, matching_pids: &[u32], policy: RootPolicy, logins: Option<&HashMap<u32, u32>>) -> Vec<u32> {
+    let matching: HashSet<u32> = matching_pids.iter().copied().collect();
+    let known = |pid: &u32| processes.contains_key(pid);
+    match policy {
+        RootPolicy::Strict => matching_pids
+            .iter()
+            .copied()
+            .filter(known)
+            .filter(|pid| {
+                // Processes under PID 1 (init or launchd) are never nested in it
+                processes[pid].parent_pid.is_none_or(|parent_pid| {
+                    !matching.contains(&parent_pid) || parent_pid == 1 || !known(&parent_pid)
+                        || logins.is_some_and(|sessions| other_login(&processes[&parent_pid], &processes[pid], sessions))
+                })
+            })
+            .collect(),
+        RootPolicy::MatchedTop => {
+            let mut seen = HashSet::new();
+            matching_pids
+                .iter()
+                .copied()
+                .filter(known)
+                .map(|pid| matched_top(processes, &matching, pid, logins))
+                .filter(|&root| seen.insert(root))
+                .collect()
+        }
+        RootPolicy::AllMatched => matching_pids.iter().copied().filter(known).collect(),
+    }
+}
+
+// The highest matched process on the way from `pid` up to PID 1, or up to
+// the first login boundary when `logins` is given
+fn matched_top(processes: &HashMap<u32, ProcessInfo>, matching: &HashSet<u32>, pid: u32, logins: Option<&HashMap<u32, u32>>) -> u32 {
+    let mut top = pid;
+    let mut visited = HashSet::from([pid]);
+    let mut current = pid;
+    while let Some(parent_pid) = processes.get(&current).and_then(|proc_info| proc_info.parent_pid) {
+        // Stop at PID 1, and at a loop left by a reused PID
+        if parent_pid == 1 || !visited.insert(parent_pid) || !processes.contains_key(&parent_pid) {
+            break;
+        }
+        if logins.is_some_and(|sessions| other_login(&processes[&parent_pid], &processes[&current], sessions)) {
+            break;
+        }
+        if matching.contains(&parent_pid) {
+            top = parent_pid;
+        }
+        current = parent_pid;
+    }
+    top
+}
+
+// --roots all-matched: cut every matched process off its parent's children,
+// so each tree ends where another match's tree starts
+pub fn detach_matched(processes: &mut HashMap<u32, ProcessInfo>, matching_pids: &[u32]) {
+    let matching: HashSet<u32> = matching_pids.iter().copied().collect();
+    for proc_info in processes.values_mut() {
+        proc_info.children.retain(|child| !matching.contains(child));
+    }
+}
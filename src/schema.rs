@@ -0,0 +1,21 @@
+// --schema: a JSON Schema of the --json snapshot, generated from the serde
+// model so it cannot drift from the code. It carries the same version as the
+// snapshots it describes.
+
+use schemars::Schema;
+
+use crate::snapshot::Snapshot;
+
+pub fn schema() -> Schema {
+    let mut schema = schemars::schema_for!(Snapshot);
+    schema.insert("title".to_string(), "memon snapshot".into());
+    schema.insert("version".to_string(), env!("CARGO_PKG_VERSION").into());
+    schema
+}
+
+// The schema as printed by --schema
+pub fn render() -> String {
+    let mut text = serde_json::to_string_pretty(&schema()).expect("schema serializes to JSON");
+    text.push('\n');
+    text
+}
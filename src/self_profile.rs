@@ -0,0 +1,155 @@
+// --self-profile: where the time of a run went, for reports of memon being
+// slow on a machine. Nothing leaves the machine: the report is printed on
+// stderr at exit and, with --output, also written as JSON next to the output
+// file. It holds no pattern, process names, command lines or host names, so
+// it can be pasted into a bug report as it is.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::match_stats;
+use crate::snapshot::Snapshot;
+
+// Bumped when a field of the report changes meaning or goes away; new
+// fields may be added without a bump
+pub const REPORT_VERSION: u32 = 1;
+
+// Wall time of one phase over the whole run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseTime {
+    pub phase: String,
+    // Once per sample for most phases
+    pub runs: usize,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+// Sizes of the work, the largest over the samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WorkCounts {
+    pub samples: usize,
+    pub processes_scanned: usize,
+    pub matched: usize,
+    pub trees: usize,
+    // Processes of the largest tree
+    pub largest_tree: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelfProfile {
+    pub report_version: u32,
+    pub memon_version: String,
+    pub os: String,
+    pub arch: String,
+    pub cpus: usize,
+    // Parallel reads (--jobs)
+    pub jobs: usize,
+    // The flags in effect, without their values
+    pub flags: Vec<String>,
+    pub wall_ms: f64,
+    // In the order they first ran
+    pub phases: Vec<PhaseTime>,
+    pub counts: WorkCounts,
+}
+
+impl SelfProfile {
+    // `options` are the effective options of the run metadata
+    pub fn new(options: &[String], jobs: usize) -> Self {
+        SelfProfile {
+            report_version: REPORT_VERSION,
+            memon_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpus: std::thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+            jobs,
+            flags: flag_names(options),
+            wall_ms: 0.0,
+            phases: Vec::new(),
+            counts: WorkCounts::default(),
+        }
+    }
+
+    // Add one run of `phase`
+    pub fn record(&mut self, phase: &str, elapsed: Duration) {
+        let millis = match_stats::millis(elapsed);
+        match self.phases.iter_mut().find(|time| time.phase == phase) {
+            Some(time) => {
+                time.runs += 1;
+                time.total_ms += millis;
+                time.max_ms = time.max_ms.max(millis);
+            }
+            None => self.phases.push(PhaseTime { phase: phase.to_string(), runs: 1, total_ms: millis, max_ms: millis }),
+        }
+    }
+
+    // Count one analysis of `scanned` processes that ended in `snapshot`
+    pub fn count_sample(&mut self, scanned: usize, snapshot: &Snapshot) {
+        let counts = &mut self.counts;
+        counts.samples += 1;
+        counts.processes_scanned = counts.processes_scanned.max(scanned);
+        counts.matched = counts.matched.max(snapshot.matched);
+        counts.trees = counts.trees.max(snapshot.trees.len());
+        let largest = snapshot.trees.iter().map(|tree| tree.process_count).max().unwrap_or(0);
+        counts.largest_tree = counts.largest_tree.max(largest);
+    }
+
+    // Time outside the recorded phases, such as start-up and writing the output
+    pub fn other_ms(&self) -> f64 {
+        (self.wall_ms - self.phases.iter().map(|time| time.total_ms).sum::<f64>()).max(0.0)
+    }
+
+    // Phase table for stderr, such as
+    // "scan          1    35.2ms    35.2ms"
+    pub fn table(&self) -> String {
+        let mut rows: Vec<[String; 4]> = vec![["phase".to_string(), "runs".to_string(), "total".to_string(), "max".to_string()]];
+        for time in &self.phases {
+            rows.push([time.phase.clone(), time.runs.to_string(), format!("{:.1}ms", time.total_ms), format!("{:.1}ms", time.max_ms)]);
+        }
+        rows.push(["other".to_string(), String::new(), format!("{:.1}ms", self.other_ms()), String::new()]);
+        rows.push(["wall".to_string(), String::new(), format!("{:.1}ms", self.wall_ms), String::new()]);
+        let width = |column: usize| rows.iter().map(|row| row[column].len()).max().unwrap_or(0);
+        let widths = [width(0), width(1), width(2), width(3)];
+
+        let mut out = String::new();
+        let _ = writeln!(out, "Self-profile (report {}, memon {}, {} {}, {} cpus, {} jobs):",
+            self.report_version, self.memon_version, self.os, self.arch, self.cpus, self.jobs);
+        for [phase, runs, total, max] in &rows {
+            let line = format!("  {:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}", phase, runs, total, max,
+                w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+            let _ = writeln!(out, "{}", line.trim_end());
+        }
+        let counts = self.counts;
+        let _ = writeln!(out, "  samples {} | processes scanned {} | matched {} | trees {} | largest tree {} procs",
+            counts.samples, counts.processes_scanned, counts.matched, counts.trees, counts.largest_tree);
+        if !self.flags.is_empty() {
+            let _ = writeln!(out, "  flags: {}", self.flags.join(" "));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("self-profile serializes to JSON") + "\n"
+    }
+}
+
+// The flags of options such as "--from-file host.table", without their
+// values, which may hold paths or patterns
+pub fn flag_names(options: &[String]) -> Vec<String> {
+    options
+        .iter()
+        .filter_map(|option| option.split([' ', '=']).next())
+        .filter(|flag| flag.starts_with('-'))
+        .map(str::to_string)
+        .collect()
+}
+
+// Where the JSON report goes for an --output file: "report.html" gets
+// "report.html.profile.json"
+pub fn json_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".profile.json");
+    PathBuf::from(path)
+}
@@ -0,0 +1,136 @@
+// Minimal HTTP server for --http: GET /snapshot, /healthz and /stream (SSE)
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Produces the JSON snapshot document served to clients
+pub type Collect = dyn Fn() -> Result<String, String> + Send + Sync;
+
+// Header block larger than this is rejected
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+
+#[derive(Debug)]
+pub enum ServerError {
+    InvalidAddress(String),
+    AddressInUse(SocketAddr),
+    Bind(SocketAddr, io::Error),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::InvalidAddress(addr) => {
+                write!(f, "invalid --http address '{}': expected HOST:PORT such as 127.0.0.1:8765", addr)
+            }
+            ServerError::AddressInUse(addr) => {
+                write!(f, "cannot listen on {}: address already in use (is another memon running?)", addr)
+            }
+            ServerError::Bind(addr, err) => write!(f, "cannot listen on {}: {}", addr, err),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+// Resolve and bind the --http address
+pub fn bind(addr: &str) -> Result<TcpListener, ServerError> {
+    let socket_addr = addr
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| ServerError::InvalidAddress(addr.to_string()))?;
+    TcpListener::bind(socket_addr).map_err(|err| match err.kind() {
+        io::ErrorKind::AddrInUse => ServerError::AddressInUse(socket_addr),
+        _ => ServerError::Bind(socket_addr, err),
+    })
+}
+
+// Accept connections forever, one thread per client
+pub fn serve(listener: TcpListener, collect: Arc<Collect>, stream_interval: Duration) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let collect = Arc::clone(&collect);
+        thread::spawn(move || {
+            let _ = handle(stream, &*collect, stream_interval);
+        });
+    }
+}
+
+fn handle(mut stream: TcpStream, collect: &Collect, stream_interval: Duration) -> io::Result<()> {
+    let Some((method, path)) = read_request(&stream)? else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", "bad request\n");
+    };
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", "only GET is supported\n");
+    }
+    
+    match path.split('?').next().unwrap_or("") {
+        "/healthz" => respond(&mut stream, "200 OK", "text/plain", "ok\n"),
+        "/snapshot" => match collect() {
+            Ok(json) => respond(&mut stream, "200 OK", "application/json", &json),
+            Err(err) => respond(&mut stream, "500 Internal Server Error", "text/plain", &format!("{}\n", err)),
+        },
+        "/stream" => stream_events(&mut stream, collect, stream_interval),
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "not found\n"),
+    }
+}
+
+// Read the request line and skip the headers; None for malformed requests
+fn read_request(stream: &TcpStream) -> io::Result<Option<(String, String)>> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    
+    let mut header_bytes = request_line.len();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)?;
+        header_bytes += read;
+        if read == 0 || line == "\r\n" || line == "\n" || header_bytes > MAX_HEADER_BYTES {
+            break;
+        }
+    }
+    
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) if header_bytes <= MAX_HEADER_BYTES => Ok(Some((method.to_string(), path.to_string()))),
+        _ => Ok(None),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, content_type, body.len(), body)?;
+    stream.flush()
+}
+
+// Server-sent events: one "snapshot" event every interval until the client goes away
+fn stream_events(stream: &mut TcpStream, collect: &Collect, interval: Duration) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n")?;
+    stream.flush()?;
+    loop {
+        let event = match collect() {
+            Ok(json) => sse_event("snapshot", &json),
+            Err(err) => sse_event("error", &err),
+        };
+        stream.write_all(event.as_bytes())?;
+        stream.flush()?;
+        thread::sleep(interval);
+    }
+}
+
+// Multi-line payloads become one data: line per line
+pub fn sse_event(event: &str, data: &str) -> String {
+    let mut out = format!("event: {}\n", event);
+    for line in data.trim_end().lines() {
+        out.push_str("data: ");
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push('\n');
+    out
+}
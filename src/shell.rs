@@ -0,0 +1,93 @@
+// Finding the terminal session memon runs in, for `memon shell`
+
+use std::collections::HashMap;
+
+use crate::process::ProcessInfo;
+use crate::source;
+
+// Terminal emulators, by process name as the kernel reports it (cut to 15
+// characters on Linux)
+const TERMINALS: &[&str] = &[
+    "alacritty", "foot", "gnome-terminal-", "gnome-terminal-server", "iTerm2", "kitty", "konsole",
+    "mate-terminal", "ptyxis", "rxvt", "st", "terminator", "Terminal", "tilix", "urxvt",
+    "wezterm-gui", "xfce4-terminal", "xterm", "yakuake",
+];
+
+// Processes that start sessions without being part of one: init systems,
+// systemd --user and display managers. Their children are session roots.
+pub const SESSION_STARTERS: &[&str] = &[
+    "gdm", "gdm-session-wor", "gdm-wayland-ses", "gdm-x-session", "init", "launchd", "lightdm",
+    "sddm", "sddm-helper", "systemd", "xdm",
+];
+
+// Why the walk stopped where it did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootKind {
+    Terminal,
+    Ssh,
+    Tmux,
+    // Child of PID 1 or of a session starter, with no better marker on the way
+    TopLevel,
+}
+
+impl RootKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            RootKind::Terminal => "terminal emulator",
+            RootKind::Ssh => "ssh session",
+            RootKind::Tmux => "tmux server",
+            RootKind::TopLevel => "session leader",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellRoot {
+    pub pid: u32,
+    pub kind: RootKind,
+    // Inside tmux: the attached clients, which live in another terminal's tree
+    pub tmux_clients: Vec<u32>,
+}
+
+fn is_tmux_server(name: &str) -> bool {
+    name == "tmux: server" || name == "tmux"
+}
+
+// Clients keep the plain name where the platform cannot retitle them
+fn is_tmux_client(proc_info: &ProcessInfo, server: u32) -> bool {
+    proc_info.name == "tmux: client" || (proc_info.name == "tmux" && proc_info.pid != server)
+}
+
+// Walk up from `pid` to the process that owns its terminal session: the
+// nearest terminal emulator, sshd or tmux server, or failing those the first
+// ancestor started by PID 1 or a session starter. The table must be linked.
+pub fn find_root(processes: &HashMap<u32, ProcessInfo>, pid: u32) -> Option<ShellRoot> {
+    let chain = source::ancestor_chain(processes, pid);
+    let name = |pid: &u32| processes.get(pid).map_or("", |proc_info| proc_info.name.as_str());
+    // Skip memon itself
+    for (position, ancestor) in chain.iter().enumerate().skip(1) {
+        let ancestor_name = name(ancestor);
+        let kind = if is_tmux_server(ancestor_name) {
+            Some(RootKind::Tmux)
+        } else if ancestor_name == "sshd" || ancestor_name == "sshd-session" {
+            Some(RootKind::Ssh)
+        } else if TERMINALS.contains(&ancestor_name) {
+            Some(RootKind::Terminal)
+        } else {
+            match chain.get(position + 1) {
+                None | Some(1) => Some(RootKind::TopLevel),
+                Some(parent) if SESSION_STARTERS.contains(&name(parent)) => Some(RootKind::TopLevel),
+                Some(_) => None,
+            }
+        };
+        if let Some(kind) = kind {
+            let mut tmux_clients: Vec<u32> = match kind {
+                RootKind::Tmux => processes.values().filter(|proc_info| is_tmux_client(proc_info, *ancestor)).map(|proc_info| proc_info.pid).collect(),
+                _ => Vec::new(),
+            };
+            tmux_clients.sort_unstable();
+            return Some(ShellRoot { pid: *ancestor, kind, tmux_clients });
+        }
+    }
+    None
+}
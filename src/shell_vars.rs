@@ -0,0 +1,70 @@
+// --shell-vars: the tree totals as KEY=value lines for `eval $(memon nginx
+// --shell-vars)`. Every value is quoted for POSIX sh unless it is made only
+// of characters the shell never treats specially.
+
+use std::fmt::Write as _;
+
+use crate::snapshot::{ProcessTree, Snapshot};
+
+// Totals of one tree, or of all of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Totals {
+    total_bytes: u64,
+    process_count: usize,
+    // Largest process by RSS, the lowest PID on a tie
+    max: Option<(u32, u64)>,
+}
+
+impl Totals {
+    fn of(trees: &[&ProcessTree]) -> Self {
+        let max = trees
+            .iter()
+            .flat_map(|tree| tree.iter_preorder())
+            .filter(|(node, _)| !node.is_virtual)
+            .max_by_key(|(node, _)| (node.rss_bytes, std::cmp::Reverse(node.pid)))
+            .map(|(node, _)| (node.pid, node.rss_bytes));
+        Totals {
+            total_bytes: trees.iter().map(|tree| tree.total_bytes).sum(),
+            process_count: trees.iter().map(|tree| tree.process_count).sum(),
+            max,
+        }
+    }
+
+    fn write(&self, out: &mut String, prefix: &str) {
+        let max_pid = self.max.map_or(String::new(), |(pid, _)| pid.to_string());
+        let max_bytes = self.max.map_or(0, |(_, bytes)| bytes);
+        let _ = writeln!(out, "{}TOTAL_BYTES={}", prefix, self.total_bytes);
+        let _ = writeln!(out, "{}PROC_COUNT={}", prefix, self.process_count);
+        let _ = writeln!(out, "{}MAX_PID={}", prefix, quote(&max_pid));
+        let _ = writeln!(out, "{}MAX_BYTES={}", prefix, max_bytes);
+    }
+}
+
+// MEMON_PATTERN, MEMON_TREE_COUNT and the combined totals; with several
+// trees also MEMON_TREE_<N>_* for each, numbered from 1 in root PID order
+// as --tree counts them. MEMON_MAX_PID is empty without a match.
+pub fn render(snapshot: &Snapshot) -> String {
+    let trees: Vec<&ProcessTree> = snapshot.trees.iter().collect();
+    let mut out = String::new();
+    let _ = writeln!(out, "MEMON_PATTERN={}", quote(&snapshot.pattern));
+    let _ = writeln!(out, "MEMON_TREE_COUNT={}", trees.len());
+    Totals::of(&trees).write(&mut out, "MEMON_");
+    if trees.len() > 1 {
+        for (index, tree) in trees.iter().enumerate() {
+            let prefix = format!("MEMON_TREE_{}_", index + 1);
+            let _ = writeln!(out, "{}ROOT_PID={}", prefix, tree.root_pid);
+            Totals::of(&[tree]).write(&mut out, &prefix);
+        }
+    }
+    out
+}
+
+// `value` as one POSIX sh word: bare when every character is safe, otherwise
+// in single quotes, with each ' written as '\''
+pub fn quote(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:,+@%".contains(c);
+    if !value.is_empty() && value.chars().all(safe) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
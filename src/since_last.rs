@@ -0,0 +1,186 @@
+// --since-last: every run's per-process numbers are cached by pattern and
+// host, and the next run shows what changed since
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::{ProcessNode, ProcessTree};
+
+// Cached runs older than this are ignored unless --since-last-ttl says otherwise
+pub const DEFAULT_TTL: &str = "24h";
+
+// A process or tree as one run saw it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedProcess {
+    pub pid: u32,
+    pub start_time: u64,
+    pub name: String,
+    pub bytes: u64,
+}
+
+// What the cache keeps of a run: its time, the tree totals keyed by their
+// root, and the RSS of every process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub timestamp: i64,
+    pub trees: Vec<SavedProcess>,
+    pub processes: Vec<SavedProcess>,
+}
+
+impl SavedRun {
+    // `start_time` looks up the start time of a PID, 0 when unknown
+    pub fn from_trees(trees: &[ProcessTree], timestamp: i64, start_time: impl Fn(u32) -> u64) -> SavedRun {
+        let saved = |pid: u32, name: &str, bytes: u64| SavedProcess { pid, start_time: start_time(pid), name: name.to_string(), bytes };
+        SavedRun {
+            timestamp,
+            trees: trees.iter().map(|tree| saved(tree.root_pid, &tree.root.name, tree.total_bytes)).collect(),
+            processes: trees
+                .iter()
+                .flat_map(ProcessTree::iter_preorder)
+                .filter(|(node, _)| !node.is_virtual)
+                .map(|(node, _)| saved(node.pid, &node.name, node.rss_bytes))
+                .collect(),
+        }
+    }
+}
+
+// The saved bytes of each current entry, keyed by PID. The same PID, start
+// time and name is the same process; the rest are paired with unclaimed
+// saved entries of the same name in PID order, so a restarted process is
+// compared with the one it replaced. Entries without a partner are new.
+pub fn match_saved(saved: &[SavedProcess], current: &[SavedProcess]) -> HashMap<u32, u64> {
+    let mut matched = HashMap::new();
+    let mut claimed = HashSet::new();
+    for entry in current {
+        if let Some(index) = saved.iter().position(|old| old.pid == entry.pid && old.start_time == entry.start_time && old.name == entry.name) {
+            matched.insert(entry.pid, saved[index].bytes);
+            claimed.insert(index);
+        }
+    }
+    let mut unclaimed: BTreeMap<&str, Vec<&SavedProcess>> = BTreeMap::new();
+    let mut saved_order: Vec<(usize, &SavedProcess)> = saved.iter().enumerate().filter(|(index, _)| !claimed.contains(index)).collect();
+    saved_order.sort_by_key(|(_, old)| old.pid);
+    for (_, old) in saved_order.into_iter().rev() {
+        unclaimed.entry(old.name.as_str()).or_default().push(old);
+    }
+    let mut rest: Vec<&SavedProcess> = current.iter().filter(|entry| !matched.contains_key(&entry.pid)).collect();
+    rest.sort_by_key(|entry| entry.pid);
+    for entry in rest {
+        if let Some(old) = unclaimed.get_mut(entry.name.as_str()).and_then(Vec::pop) {
+            matched.insert(entry.pid, old.bytes);
+        }
+    }
+    matched
+}
+
+// Set the growth since `previous` on every process and tree of `trees`
+pub fn apply(trees: &mut [ProcessTree], previous: &SavedRun, start_time: impl Fn(u32) -> u64) {
+    let current = SavedRun::from_trees(trees, 0, start_time);
+    let tree_bytes = match_saved(&previous.trees, &current.trees);
+    let process_bytes = match_saved(&previous.processes, &current.processes);
+    for tree in trees {
+        tree.since_last_bytes = tree_bytes.get(&tree.root_pid).map(|&before| growth(before, tree.total_bytes));
+        set_growth(&mut tree.root, &process_bytes);
+    }
+}
+
+fn set_growth(node: &mut ProcessNode, before: &HashMap<u32, u64>) {
+    if !node.is_virtual {
+        node.since_last_bytes = before.get(&node.pid).map(|&bytes| growth(bytes, node.rss_bytes));
+    }
+    for child in &mut node.children {
+        set_growth(child, before);
+    }
+}
+
+fn growth(before: u64, after: u64) -> i64 {
+    after as i64 - before as i64
+}
+
+// $XDG_CACHE_HOME/memon/runs, or ~/.cache/memon/runs; `lookup` returns the
+// value of an environment variable, if set
+pub fn cache_dir(lookup: impl Fn(&str) -> Option<PathBuf>) -> Option<PathBuf> {
+    let base = lookup("XDG_CACHE_HOME")
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| lookup("HOME").map(|home| home.join(".cache")))?;
+    Some(base.join("memon").join("runs"))
+}
+
+// Cache file of a pattern on a host: the pattern made file-name safe, plus a
+// hash of host and pattern so patterns differing in other characters do not
+// share an entry
+pub fn entry_path(dir: &Path, host: &str, pattern: &str) -> PathBuf {
+    let readable: String = pattern
+        .chars()
+        .take(40)
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    // FNV-1a, stable across builds unlike the std hasher
+    let hash = format!("{}\0{}", host, pattern)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+    dir.join(format!("{}-{:016x}.json", readable, hash))
+}
+
+// The cached run of one pattern, read once and saved after every analysis
+#[derive(Debug)]
+pub struct RunCache {
+    path: PathBuf,
+    ttl_secs: u64,
+    previous: Option<Option<SavedRun>>,
+}
+
+impl RunCache {
+    pub fn new(path: PathBuf, ttl_secs: u64) -> Self {
+        RunCache { path, ttl_secs, previous: None }
+    }
+    
+    // The run cached before this session, unless it is older than the TTL
+    // or unreadable. Watch samples all compare against it.
+    pub fn previous(&mut self, now: i64) -> Option<&SavedRun> {
+        let (path, ttl_secs) = (&self.path, self.ttl_secs);
+        self.previous
+            .get_or_insert_with(|| {
+                let text = match fs::read_to_string(path) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        if err.kind() != io::ErrorKind::NotFound {
+                            log::warn!("cannot read the last run from {}: {}", path.display(), err);
+                        }
+                        return None;
+                    }
+                };
+                let run: SavedRun = serde_json::from_str(&text)
+                    .map_err(|err| log::warn!("ignoring the last run in {}: {}", path.display(), err))
+                    .ok()?;
+                let age = now.saturating_sub(run.timestamp);
+                if age > ttl_secs as i64 {
+                    log::debug!("last run in {} is {}s old, past the {}s TTL", path.display(), age, ttl_secs);
+                    return None;
+                }
+                Some(run)
+            })
+            .as_ref()
+    }
+    
+    pub fn save(&self, run: &SavedRun) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(run).map_err(io::Error::other)?;
+        fs::write(&self.path, json)
+    }
+}
+
+// --forget: drop the cached run; false when there was none
+pub fn forget(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
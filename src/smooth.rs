@@ -0,0 +1,62 @@
+// Exponential smoothing of RSS readings across watch samples (--smooth)
+
+use std::collections::HashMap;
+
+use crate::process::ProcessInfo;
+
+// Moving average per (pid, start_time), so a reused PID starts afresh
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    alpha: f64,
+    averages: HashMap<(u32, u64), f64>,
+}
+
+impl Smoother {
+    // `alpha` is the weight of the newest reading: 1 disables smoothing,
+    // values near 0 smooth heavily
+    pub fn new(alpha: f64) -> Self {
+        Smoother { alpha, averages: HashMap::new() }
+    }
+
+    // Feed one reading and return the smoothed value. The first reading of a
+    // process is taken as is.
+    pub fn update(&mut self, key: (u32, u64), raw: u64) -> u64 {
+        let average = self.averages
+            .entry(key)
+            .and_modify(|average| *average += self.alpha * (raw as f64 - *average))
+            .or_insert(raw as f64);
+        average.round() as u64
+    }
+
+    // Replace the RSS of every process with its smoothed value, keeping the
+    // reading in rss_raw, and forget processes that have exited
+    pub fn apply(&mut self, processes: &mut HashMap<u32, ProcessInfo>) {
+        self.averages.retain(|&(pid, start_time), _| {
+            processes.get(&pid).is_some_and(|proc_info| proc_info.start_time == start_time)
+        });
+        for proc_info in processes.values_mut() {
+            let raw = proc_info.rss_raw.unwrap_or(proc_info.rss);
+            proc_info.rss = self.update((proc_info.pid, proc_info.start_time), raw);
+            proc_info.rss_raw = Some(raw);
+        }
+    }
+
+    // Number of processes with smoothing state
+    pub fn len(&self) -> usize {
+        self.averages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.averages.is_empty()
+    }
+}
+
+// --smooth value: a weight in (0, 1]
+pub fn parse_alpha(text: &str) -> Result<f64, String> {
+    let alpha: f64 = text.trim().parse().map_err(|_| format!("invalid smoothing factor '{}'", text))?;
+    if alpha > 0.0 && alpha <= 1.0 {
+        Ok(alpha)
+    } else {
+        Err("smoothing factor must be above 0 and at most 1".to_string())
+    }
+}
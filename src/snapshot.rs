@@ -0,0 +1,740 @@
+// Serializable snapshot of the matched process trees, shared by every
+// machine-readable output format
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::budget::Budget;
+use crate::consistency;
+use crate::dedupe::SimilarGroup;
+use crate::history_cols::RssHistory;
+use crate::jvm::JvmHeap;
+use crate::k8s::PodRef;
+use crate::match_stats::MatchStats;
+use crate::metadata::RunMetadata;
+use crate::peak::PeakSource;
+use crate::privs::Privileges;
+use crate::process::{MemoryLimits, ProcessInfo};
+use crate::source;
+use crate::stability::Stability;
+use crate::stats::{TreeStats, TOP_RANKS};
+
+// Process count and memory of one --group-by group over every tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct GroupTotal {
+    pub key: String,
+    pub processes: usize,
+    pub bytes: u64,
+}
+
+// Children a --max-children cap left out of a parent: their subtrees' process
+// count and memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MoreChildren {
+    pub processes: usize,
+    pub bytes: u64,
+}
+
+// Which of `children` a cap of `max` keeps: the `max` largest by RSS, in
+// their original order, with what the rest add up to. None when all fit.
+pub fn capped_children(children: &[&ProcessNode], max: usize) -> Option<(Vec<usize>, MoreChildren)> {
+    if children.len() <= max {
+        return None;
+    }
+    let mut kept: Vec<usize> = (0..children.len()).collect();
+    kept.sort_by_key(|&i| Reverse(children[i].rss_bytes));
+    let omitted = kept.split_off(max);
+    kept.sort_unstable();
+    let more = MoreChildren {
+        processes: omitted.iter().map(|&i| children[i].descendants + 1).sum(),
+        bytes: omitted.iter().map(|&i| children[i].cumulative_bytes).sum(),
+    };
+    Some((kept, more))
+}
+
+// Group of --group-by exe for processes whose executable could not be read
+pub const UNKNOWN_EXE: &str = "(unknown)";
+
+// Per-group totals over every process of every tree, largest first; `key`
+// names the group of a process, and those without one share the `missing` row
+pub fn group_totals(trees: &[ProcessTree], missing: &str, key: impl Fn(&ProcessNode) -> Option<String>) -> Vec<GroupTotal> {
+    let mut totals: BTreeMap<String, GroupTotal> = BTreeMap::new();
+    for (node, _) in trees.iter().flat_map(ProcessTree::iter_preorder) {
+        if node.is_virtual {
+            continue;
+        }
+        let key = key(node).unwrap_or_else(|| missing.to_string());
+        let total = totals.entry(key.clone()).or_insert(GroupTotal { key, processes: 0, bytes: 0 });
+        total.processes += 1;
+        total.bytes += node.rss_bytes;
+    }
+    let mut totals: Vec<GroupTotal> = totals.into_values().collect();
+    totals.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
+    totals
+}
+
+// --group-by exe: one row per executable path, so same-named binaries from
+// different virtualenvs or installs stay apart
+pub fn exe_totals(trees: &[ProcessTree]) -> Vec<GroupTotal> {
+    group_totals(trees, UNKNOWN_EXE, |node| node.exe.clone())
+}
+
+// Row of --group-by pgid for processes whose group could not be read
+pub const UNKNOWN_PGID: &str = "(unknown)";
+
+// --group-by pgid: one row per process group, such as a shell pipeline or a
+// background job, named after its leader when the leader is in the trees
+pub fn pgid_totals(trees: &[ProcessTree]) -> Vec<GroupTotal> {
+    let names: HashMap<u32, &str> = trees.iter()
+        .flat_map(ProcessTree::iter_preorder)
+        .map(|(node, _)| (node.pid, node.name.as_str()))
+        .collect();
+    group_totals(trees, UNKNOWN_PGID, |node| {
+        let pgid = node.pgid?;
+        Some(match names.get(&pgid) {
+            Some(leader) => format!("{} ({})", pgid, leader),
+            None => pgid.to_string(),
+        })
+    })
+}
+
+// Table key of the synthetic --merge-roots process while its tree is built;
+// no real PID gets this high
+const MERGED_ROOT_PID: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Snapshot {
+    pub version: String,
+    pub pattern: String,
+    pub matched: usize,
+    pub trees: Vec<ProcessTree>,
+    // Only present with --budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+    // Only present with --verbose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_stats: Option<MatchStats>,
+    // Only present with --group-by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<GroupTotal>>,
+    // Only present with --since-last when an earlier run was cached: its age in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_last_age_secs: Option<u64>,
+    // Where and how the snapshot was taken; absent from tables and tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RunMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ProcessTree {
+    pub root_pid: u32,
+    pub process_count: usize,
+    pub total_bytes: u64,
+    pub average_bytes: u64,
+    pub root: ProcessNode,
+    // Only present with --by-depth
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by_depth: Option<Vec<DepthTotal>>,
+    // The tree holds one of memon's own ancestors, such as the shell it runs in
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub current_session: bool,
+    // Highest tree total seen, only present with --peak
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_bytes: Option<u64>,
+    // Processes whose own name matched the pattern, only present with --matched-only-totals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_total: Option<MatchedTotal>,
+    // Some processes' memory could not be read, so the totals are at least this much
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lower_bound: bool,
+    // Total with each shared mapping counted once, only present with --dedupe-shared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_deduped_bytes: Option<u64>,
+    // Only present with --since-last: growth of the total since the earlier
+    // run's tree with the same root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_last_bytes: Option<i64>,
+    // Processes that were in a matched tree earlier in a watch session and
+    // were re-parented out of it when their parent died
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reparented: bool,
+    // Only present with --siblings: the root's parent and its other children,
+    // which the tree totals leave out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub siblings: Option<SiblingContext>,
+    // Only present with --breakdown: the root's own memory and that of
+    // everything below it, which add up to its cumulative total
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descendants_bytes: Option<u64>,
+}
+
+// A child of the matched root's parent for --siblings, collapsed to the
+// process count and memory of its subtree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Sibling {
+    pub pid: u32,
+    pub name: String,
+    pub process_count: usize,
+    pub total_bytes: u64,
+    // The matched root itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub matched_root: bool,
+}
+
+// --siblings: the parent of a tree's root with all of its children, the
+// largest subtree first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SiblingContext {
+    pub parent_pid: u32,
+    pub parent_name: String,
+    pub parent_rss_bytes: u64,
+    pub siblings: Vec<Sibling>,
+}
+
+impl SiblingContext {
+    // The context of the tree rooted at `root_pid` in a linked table; None
+    // when its parent is not in the table
+    pub fn collect(processes: &HashMap<u32, ProcessInfo>, root_pid: u32) -> Option<SiblingContext> {
+        let parent_pid = *source::ancestor_chain(processes, root_pid).get(1)?;
+        let parent = processes.get(&parent_pid)?;
+        let mut siblings: Vec<Sibling> = parent
+            .children
+            .iter()
+            .filter_map(|&pid| {
+                let proc_info = processes.get(&pid)?;
+                let subtree = source::descendant_set(processes, pid);
+                Some(Sibling {
+                    pid,
+                    name: proc_info.name.clone(),
+                    process_count: subtree.len(),
+                    total_bytes: subtree.iter().filter_map(|pid| processes.get(pid)).map(|proc_info| proc_info.rss).sum(),
+                    matched_root: pid == root_pid,
+                })
+            })
+            .collect();
+        siblings.sort_by_key(|sibling| (Reverse(sibling.total_bytes), sibling.pid));
+        Some(SiblingContext { parent_pid, parent_name: parent.name.clone(), parent_rss_bytes: parent.rss, siblings })
+    }
+}
+
+// Count and memory of the matched processes of a tree, leaving out the
+// unrelated children they spawned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MatchedTotal {
+    pub processes: usize,
+    pub bytes: u64,
+}
+
+// Process count and memory at one depth below a root (0 is the root itself)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct DepthTotal {
+    pub depth: usize,
+    pub processes: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub name: String,
+    // The name the system reported, where the command line gave a more
+    // informative one such as a setproctitle title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_name: Option<String>,
+    pub rss_bytes: u64,
+    // Reading before --smooth; rss_bytes then holds the smoothed value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rss_raw: Option<u64>,
+    pub cumulative_bytes: u64,
+    pub descendants: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<String>,
+    // 1, 2 or 3 for the top memory ranks of the tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u8>,
+    // Only present with --mem-details, and only where /proc was readable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dirty_bytes: Option<u64>,
+    // Only present with --hugepages, and only where the kernel exposes them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anon_huge_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hugetlb_bytes: Option<u64>,
+    // Only present with --gpu when NVML is available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_bytes: Option<u64>,
+    // Only present with --show-tty, for processes with a controlling terminal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tty: Option<String>,
+    // Only present with --peak
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_source: Option<PeakSource>,
+    // Only present with --history-cols, once the process was seen twice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<RssHistory>,
+    // Only present with --limits, where /proc was readable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<MemoryLimits>,
+    // Only present with --jvm, for processes that look like a JVM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jvm: Option<JvmHeap>,
+    // Only present with --labels, for well-known process names
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // Only present with --stability
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stability: Option<Stability>,
+    // Only present with --ports, for processes listening on TCP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<Vec<u16>>,
+    // Only present with --churn: replacements seen in this process's slot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restarts: Option<usize>,
+    // Only present with --nice or --only-nice, where the platform provides it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    // Only present with --k8s, for processes in a Kubernetes pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<PodRef>,
+    // Only present with --exec-column, where the command succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    // Only present with --since-last: RSS growth since the earlier run's
+    // instance of this process; absent for a new process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_last_bytes: Option<i64>,
+    // Only present with --group-by exe, where the executable could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    // Only present with --pgid or --group-by pgid, where the platform provides it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pgid: Option<u32>,
+    // Only present with --privs, where the process could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privs: Option<Privileges>,
+    // The synthetic root of --merge-roots, standing for no process
+    #[serde(rename = "virtual", default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_virtual: bool,
+    // False when the memory was hidden from memon, e.g. another user's process
+    #[serde(default = "readable_default", skip_serializing_if = "is_readable")]
+    pub readable: bool,
+    // Only present with --dedupe-subtrees, on a subtree standing for similar siblings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar: Option<SimilarGroup>,
+    // Only present with --max-children-json, on a parent whose smallest
+    // children were left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub more_children: Option<MoreChildren>,
+    pub children: Vec<ProcessNode>,
+}
+
+// A readable process with no memory and none of the optional columns, to be
+// filled in with struct update syntax
+impl Default for ProcessNode {
+    fn default() -> Self {
+        ProcessNode {
+            pid: 0,
+            name: String::new(),
+            raw_name: None,
+            rss_bytes: 0,
+            rss_raw: None,
+            cumulative_bytes: 0,
+            descendants: 0,
+            args: None,
+            rank: None,
+            locked_bytes: None,
+            dirty_bytes: None,
+            anon_huge_bytes: None,
+            hugetlb_bytes: None,
+            gpu_bytes: None,
+            tty: None,
+            peak_bytes: None,
+            peak_source: None,
+            history: None,
+            limits: None,
+            jvm: None,
+            label: None,
+            stability: None,
+            ports: None,
+            restarts: None,
+            nice: None,
+            pod: None,
+            exec: None,
+            since_last_bytes: None,
+            exe: None,
+            pgid: None,
+            privs: None,
+            is_virtual: false,
+            readable: true,
+            similar: None,
+            more_children: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Snapshot {
+    // A copy holding only the `count` largest trees by total memory, largest
+    // first, plus the trees left out. Ties keep their original order.
+    pub fn split_largest(&self, count: usize) -> (Snapshot, Vec<ProcessTree>) {
+        let mut trees = self.trees.clone();
+        trees.sort_by_key(|tree| Reverse(tree.total_bytes));
+        let omitted = trees.split_off(count.min(trees.len()));
+        (Snapshot { trees, ..self.clone() }, omitted)
+    }
+    
+    // A copy where no process has more than `max` children, for
+    // --max-children-json; the left-out ones are summed up in `more_children`.
+    // Tree totals and counts still cover every process.
+    pub fn cap_children(&self, max: usize) -> Snapshot {
+        let mut capped = self.clone();
+        for tree in &mut capped.trees {
+            cap_node(&mut tree.root, max);
+        }
+        capped
+    }
+}
+
+// Caps every process below `root`, with an explicit stack so deep chains
+// cannot overflow
+fn cap_node(root: &mut ProcessNode, max: usize) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let children: Vec<&ProcessNode> = node.children.iter().collect();
+        if let Some((kept, more)) = capped_children(&children, max) {
+            let mut index = 0;
+            node.children.retain(|_| {
+                index += 1;
+                kept.binary_search(&(index - 1)).is_ok()
+            });
+            node.more_children = Some(more);
+        }
+        stack.extend(node.children.iter_mut());
+    }
+}
+
+impl ProcessTree {
+    /// Visits every process of the tree in pre-order, together with its depth
+    /// below the root.
+    ///
+    /// ```
+    /// use memon::snapshot::{ProcessNode, ProcessTree};
+    ///
+    /// let leaf = |pid, name: &str| ProcessNode {
+    ///     pid, name: name.to_string(), rss_bytes: 1024, cumulative_bytes: 1024, ..Default::default()
+    /// };
+    /// let mut root = leaf(1, "server");
+    /// let mut worker = leaf(2, "worker");
+    /// worker.children.push(leaf(3, "helper"));
+    /// root.children.push(worker);
+    /// root.children.push(leaf(4, "logger"));
+    /// let tree = ProcessTree::new(root);
+    ///
+    /// let order: Vec<(u32, usize)> = tree.iter_preorder().map(|(node, depth)| (node.pid, depth)).collect();
+    /// assert_eq!(order, vec![(1, 0), (2, 1), (3, 2), (4, 1)]);
+    /// assert_eq!(tree.len(), 4);
+    /// assert_eq!(tree.total_rss(), 4096);
+    /// assert_eq!(tree.find(3).map(|node| node.name.as_str()), Some("helper"));
+    /// assert!(tree.find(99).is_none());
+    /// ```
+    pub fn iter_preorder(&self) -> Preorder<'_> {
+        Preorder { stack: vec![(&self.root, 0)] }
+    }
+    
+    /// Builds a tree from its root node, deriving the summary fields.
+    pub fn new(root: ProcessNode) -> Self {
+        let mut tree = ProcessTree {
+            root_pid: root.pid,
+            process_count: 0,
+            total_bytes: 0,
+            average_bytes: 0,
+            root,
+            by_depth: None,
+            current_session: false,
+            peak_bytes: None,
+            matched_total: None,
+            lower_bound: false,
+            shared_deduped_bytes: None,
+            since_last_bytes: None,
+            reparented: false,
+            siblings: None,
+            self_bytes: None,
+            descendants_bytes: None,
+        };
+        tree.process_count = tree.len();
+        tree.lower_bound = tree.has_unreadable();
+        tree.total_bytes = tree.total_rss();
+        tree.average_bytes = tree.total_bytes / tree.process_count as u64;
+        tree
+    }
+    
+    /// Builds the tree rooted at `root_pid` from a linked process table. One
+    /// TreeStats pass fills in the cumulative values, then the top three
+    /// memory ranks are marked on the table and copied into the nodes.
+    pub fn from_processes(processes: &mut HashMap<u32, ProcessInfo>, root_pid: u32) -> Option<ProcessTree> {
+        let stats = TreeStats::collect(processes, root_pid);
+        mark_memory_highlights(processes, &stats);
+        let root = node_from(processes, root_pid)?;
+        let mut tree = ProcessTree {
+            root_pid,
+            process_count: stats.count,
+            total_bytes: stats.total_rss,
+            average_bytes: stats.average_rss(),
+            root,
+            by_depth: None,
+            current_session: false,
+            peak_bytes: None,
+            matched_total: None,
+            lower_bound: false,
+            shared_deduped_bytes: None,
+            since_last_bytes: None,
+            reparented: false,
+            siblings: None,
+            self_bytes: None,
+            descendants_bytes: None,
+        };
+        tree.lower_bound = tree.has_unreadable();
+        Some(tree)
+    }
+    
+    /// Builds one tree holding the trees of all `root_pids` under a synthetic
+    /// "<pattern> (merged)" root (--merge-roots). The root is shown as PID 0,
+    /// has no memory of its own and is left out of the process count and the
+    /// ranks, which are shared by all the merged trees.
+    pub fn merged(processes: &mut HashMap<u32, ProcessInfo>, pattern: &str, root_pids: &[u32]) -> Option<ProcessTree> {
+        let mut merged = ProcessInfo::new(MERGED_ROOT_PID, format!("{} (merged)", pattern), 0, None);
+        merged.children = root_pids.to_vec();
+        processes.insert(MERGED_ROOT_PID, merged);
+        let tree = ProcessTree::from_processes(processes, MERGED_ROOT_PID);
+        processes.remove(&MERGED_ROOT_PID);
+        let mut tree = tree?;
+        tree.root_pid = 0;
+        tree.root.pid = 0;
+        tree.root.rank = None;
+        tree.root.is_virtual = true;
+        tree.process_count = tree.process_count.saturating_sub(1);
+        tree.average_bytes = tree.total_bytes / tree.process_count.max(1) as u64;
+        Some(tree)
+    }
+    
+    /// Number of processes in the tree, root included.
+    pub fn len(&self) -> usize {
+        self.iter_preorder().count()
+    }
+    
+    /// A tree always holds at least its root.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+    
+    /// Sum of the resident memory of every process in the tree.
+    pub fn total_rss(&self) -> u64 {
+        self.iter_preorder().map(|(node, _)| node.rss_bytes).sum()
+    }
+    
+    /// Fills in `self_bytes` and `descendants_bytes` (--breakdown) from the
+    /// root's cumulative total. A merged tree's root has no memory of its own
+    /// and gets neither.
+    pub fn set_breakdown(&mut self) {
+        if self.root.is_virtual {
+            return;
+        }
+        self.self_bytes = Some(self.root.rss_bytes);
+        self.descendants_bytes = Some(self.root.cumulative_bytes.saturating_sub(self.root.rss_bytes));
+    }
+    
+    /// Whether no process below the root uses more memory than the root.
+    pub fn root_is_largest(&self) -> bool {
+        self.iter_preorder().all(|(node, _)| node.rss_bytes <= self.root.rss_bytes)
+    }
+    
+    /// Process count and memory at each depth, from the root down.
+    pub fn depth_totals(&self) -> Vec<DepthTotal> {
+        let mut totals: Vec<DepthTotal> = Vec::new();
+        for (node, depth) in self.iter_preorder() {
+            while totals.len() <= depth {
+                totals.push(DepthTotal { depth: totals.len(), processes: 0, bytes: 0 });
+            }
+            totals[depth].processes += 1;
+            totals[depth].bytes += node.rss_bytes;
+        }
+        totals
+    }
+    
+    /// Count and memory of the processes in `matched` (--matched-only-totals).
+    pub fn matched_total(&self, matched: &HashSet<u32>) -> MatchedTotal {
+        self.iter_preorder()
+            .filter(|(node, _)| !node.is_virtual && matched.contains(&node.pid))
+            .fold(MatchedTotal { processes: 0, bytes: 0 }, |total, (node, _)| {
+                MatchedTotal { processes: total.processes + 1, bytes: total.bytes + node.rss_bytes }
+            })
+    }
+    
+    /// Moves the top three memory ranks onto the processes in `matched`
+    /// (--highlight-matched), so spawned helpers cannot take them. Ranks go to
+    /// distinct RSS values, as for the whole tree.
+    pub fn rank_matched(&mut self, matched: &HashSet<u32>) {
+        let mut ranks: Vec<u64> = self.iter_preorder()
+            .filter(|(node, _)| !node.is_virtual && matched.contains(&node.pid))
+            .map(|(node, _)| node.rss_bytes)
+            .collect();
+        ranks.sort_unstable_by_key(|&rss| Reverse(rss));
+        ranks.dedup();
+        ranks.truncate(TOP_RANKS);
+        let mut stack = vec![&mut self.root];
+        while let Some(node) = stack.pop() {
+            node.rank = match ranks.iter().position(|&rss| rss == node.rss_bytes) {
+                Some(index) if matched.contains(&node.pid) && !node.is_virtual && (index == 0 || node.rss_bytes > 0) => Some(index as u8 + 1),
+                _ => None,
+            };
+            stack.extend(node.children.iter_mut());
+        }
+    }
+    
+    /// Whether the memory of any process in the tree could not be read.
+    pub fn has_unreadable(&self) -> bool {
+        self.iter_preorder().any(|(node, _)| !node.readable)
+    }
+    
+    /// The process with the given PID, if it is part of the tree.
+    pub fn find(&self, pid: u32) -> Option<&ProcessNode> {
+        self.iter_preorder().map(|(node, _)| node).find(|node| node.pid == pid)
+    }
+}
+
+// Pre-order traversal with an explicit stack, so deep chains cannot overflow
+pub struct Preorder<'a> {
+    stack: Vec<(&'a ProcessNode, usize)>,
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = (&'a ProcessNode, usize);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.stack.pop()?;
+        // Push children in reverse so the first child is visited next
+        self.stack.extend(node.children.iter().rev().map(|child| (child, depth + 1)));
+        Some((node, depth))
+    }
+}
+
+// Mark processes with max, second max, and third max memory
+fn mark_memory_highlights(processes: &mut HashMap<u32, ProcessInfo>, stats: &TreeStats) {
+    for &(rss, pid) in &stats.top {
+        if let Some(proc_info) = processes.get_mut(&pid) {
+            match stats.rank_of(rss) {
+                Some(0) => proc_info.is_max_memory = true,
+                Some(1) if rss > 0 => proc_info.is_second_max_memory = true,
+                Some(2) if rss > 0 => proc_info.is_third_max_memory = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+fn readable_default() -> bool {
+    true
+}
+
+fn is_readable(readable: &bool) -> bool {
+    *readable
+}
+
+// Copy of the subtree rooted at root_pid, children in table order. Built in
+// an iterative post-order like TreeStats::collect, so deep chains cannot
+// overflow: on its second visit a node takes its children off the top of
+// `built`, where they were finished in order.
+fn node_from(processes: &HashMap<u32, ProcessInfo>, root_pid: u32) -> Option<ProcessNode> {
+    let mut built: Vec<ProcessNode> = Vec::new();
+    let mut stack = vec![(root_pid, false)];
+    while let Some((pid, children_done)) = stack.pop() {
+        let Some(proc_info) = processes.get(&pid) else {
+            continue;
+        };
+        if children_done {
+            let count = proc_info.children.iter().filter(|child_pid| processes.contains_key(child_pid)).count();
+            let children = built.split_off(built.len() - count);
+            built.push(copy_node(proc_info, pid, children));
+        } else {
+            stack.push((pid, true));
+            // Reversed, so the first child is finished first
+            for &child_pid in proc_info.children.iter().rev() {
+                // link_children leaves out parents younger than their child
+                debug_assert!(processes.get(&child_pid).is_none_or(|child| consistency::plausible_parent(proc_info, child)),
+                              "PID {} is attached to PID {}, which started after it", child_pid, pid);
+                stack.push((child_pid, false));
+            }
+        }
+    }
+    built.pop()
+}
+
+// The node of one process above its already copied children
+fn copy_node(proc_info: &ProcessInfo, pid: u32, children: Vec<ProcessNode>) -> ProcessNode {
+    let rank = if proc_info.is_max_memory {
+        Some(1)
+    } else if proc_info.is_second_max_memory {
+        Some(2)
+    } else if proc_info.is_third_max_memory {
+        Some(3)
+    } else {
+        None
+    };
+    ProcessNode {
+        pid,
+        name: proc_info.name.clone(),
+        raw_name: proc_info.raw_name.clone(),
+        rss_bytes: proc_info.rss,
+        rss_raw: proc_info.rss_raw,
+        cumulative_bytes: proc_info.cumulative_rss,
+        descendants: proc_info.descendant_count,
+        args: proc_info.args.clone(),
+        rank,
+        locked_bytes: proc_info.mem_details.map(|details| details.locked),
+        dirty_bytes: proc_info.mem_details.map(|details| details.dirty),
+        anon_huge_bytes: proc_info.huge_pages.and_then(|pages| pages.anon),
+        hugetlb_bytes: proc_info.huge_pages.and_then(|pages| pages.hugetlb),
+        gpu_bytes: proc_info.gpu_bytes,
+        tty: proc_info.session.as_ref().and_then(|session| session.tty.clone()),
+        peak_bytes: proc_info.peak.map(|peak| peak.bytes),
+        peak_source: proc_info.peak.map(|peak| peak.source),
+        history: proc_info.history,
+        limits: proc_info.limits,
+        jvm: proc_info.jvm,
+        label: None,
+        stability: proc_info.stability,
+        ports: proc_info.ports.clone(),
+        restarts: None,
+        nice: proc_info.nice,
+        pod: proc_info.pod.clone(),
+        exec: proc_info.exec.clone(),
+        since_last_bytes: None,
+        exe: proc_info.exe.clone(),
+        pgid: proc_info.pgid,
+        privs: proc_info.privs.clone(),
+        is_virtual: false,
+        readable: proc_info.readable,
+        similar: None,
+        more_children: None,
+        children,
+    }
+}
@@ -0,0 +1,66 @@
+// Order of trees and children (--sort)
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::changes::Change;
+use crate::process::ProcessInfo;
+use crate::snapshot::{ProcessNode, ProcessTree};
+
+// Values accepted by --sort
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    // Trees by root PID, children by PID
+    #[default]
+    Pid,
+    // Fastest growing first: the change of each subtree total since the
+    // previous watch sample, largest growth first
+    Delta,
+}
+
+// Subtree totals of the previous watch sample for --sort delta, keyed by
+// (pid, start_time) so a reused PID starts afresh
+#[derive(Debug, Clone, Default)]
+pub struct DeltaSort {
+    previous: Option<HashMap<(u32, u64), u64>>,
+}
+
+impl DeltaSort {
+    // Order `trees` and every node's children by the change of their
+    // cumulative bytes since the last call, ties keeping PID order. A process
+    // new since then grew by all of its memory; on the first call nothing has
+    // changed yet. `processes` is the table the trees were built from.
+    pub fn sort(&mut self, trees: &mut [ProcessTree], processes: &HashMap<u32, ProcessInfo>) {
+        let identity = |pid: u32| (pid, processes.get(&pid).map_or(0, |proc_info| proc_info.start_time));
+        let current: HashMap<(u32, u64), u64> = trees
+            .iter()
+            .flat_map(|tree| tree.iter_preorder())
+            .map(|(node, _)| (identity(node.pid), node.cumulative_bytes))
+            .collect();
+        let deltas: HashMap<u32, i64> = match &self.previous {
+            Some(previous) => current
+                .iter()
+                .map(|(&(pid, start_time), &bytes)| (pid, bytes as i64 - previous.get(&(pid, start_time)).map_or(0, |&before| before as i64)))
+                .collect(),
+            None => HashMap::new(),
+        };
+        let delta = |pid: u32| deltas.get(&pid).copied().unwrap_or(0);
+        trees.sort_by_key(|tree| (Reverse(delta(tree.root.pid)), tree.root_pid));
+        for tree in trees.iter_mut() {
+            sort_children(&mut tree.root, &delta);
+        }
+        self.previous = Some(current);
+    }
+}
+
+fn sort_children(node: &mut ProcessNode, delta: &impl Fn(u32) -> i64) {
+    node.children.sort_by_key(|child| (Reverse(delta(child.pid)), child.pid));
+    for child in &mut node.children {
+        sort_children(child, delta);
+    }
+}
+
+// --changes-only with --sort delta: the largest growth first, exits last
+pub fn sort_changes(changes: &mut [Change]) {
+    changes.sort_by_key(|change| (Reverse(change.delta_bytes), change.pid));
+}
@@ -0,0 +1,216 @@
+// Where the process table comes from: the live system, a saved table file or
+// a remote host (see remote.rs)
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::consistency;
+use crate::process::ProcessInfo;
+use crate::units::parse_size;
+
+// A provider of process tables. The live scan lives in the CLI; TableFile
+// replays a table saved to disk, which keeps tests and bug reports reproducible.
+pub trait ProcessSource {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError>;
+}
+
+#[derive(Debug)]
+pub enum SourceError {
+    Io(PathBuf, io::Error),
+    Parse { path: PathBuf, line: usize, message: String },
+    // ssh could not reach the host, or the remote collection failed
+    Ssh { host: String, message: String },
+    UnsupportedRemote { host: String, os: String },
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Io(path, err) => write!(f, "cannot read process table {}: {}", path.display(), err),
+            SourceError::Parse { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            SourceError::Ssh { host, message } => write!(f, "{}: {}", host, message),
+            SourceError::UnsupportedRemote { host, os } => write!(f, "{} runs {}; --ssh needs a Linux host with /proc", host, os),
+        }
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+// Process table file: one process per line as `PID PPID RSS NAME`, where PPID
+// is `-` for processes without a parent, RSS is a size such as 12M or a byte
+// count, and NAME is the rest of the line. Blank lines and `#` comments are skipped.
+pub struct TableFile {
+    path: PathBuf,
+}
+
+impl TableFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TableFile { path: path.into() }
+    }
+}
+
+impl ProcessSource for TableFile {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+        let text = fs::read_to_string(&self.path).map_err(|err| SourceError::Io(self.path.clone(), err))?;
+        parse_table(&text).map_err(|(line, message)| SourceError::Parse { path: self.path.clone(), line, message })
+    }
+}
+
+// Parse a process table; errors carry the 1-based line number
+pub fn parse_table(text: &str) -> Result<HashMap<u32, ProcessInfo>, (usize, String)> {
+    let mut processes = HashMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        
+        // Columns are separated by any run of whitespace; the name is the
+        // rest of the line, so it may contain spaces itself
+        let (pid, rest) = next_field(line);
+        let (ppid, rest) = next_field(rest);
+        let (rss, name) = next_field(rest);
+        if pid.is_empty() || ppid.is_empty() || rss.is_empty() {
+            return Err((line_number, "expected PID PPID RSS NAME".to_string()));
+        }
+        let pid: u32 = pid.parse().map_err(|_| (line_number, format!("invalid PID '{}'", pid)))?;
+        let parent_pid = match ppid {
+            "-" => None,
+            _ => Some(ppid.parse().map_err(|_| (line_number, format!("invalid PPID '{}'", ppid)))?),
+        };
+        // "?" stands for memory that could not be read, like another user's
+        let readable = rss != "?";
+        let rss = if readable { parse_size(rss).map_err(|err| (line_number, err))? } else { 0 };
+        let name = name.to_string();
+        
+        let mut proc_info = ProcessInfo::new(pid, name, rss, parent_pid);
+        proc_info.readable = readable;
+        if processes.insert(pid, proc_info).is_some() {
+            return Err((line_number, format!("duplicate PID {}", pid)));
+        }
+    }
+    Ok(processes)
+}
+
+fn next_field(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    (&text[..end], text[end..].trim_start())
+}
+
+// Load a table file directly
+pub fn load_table(path: &Path) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+    TableFile::new(path).load()
+}
+
+// Rebuild the parent/child links of a process table. Children are kept in
+// PID order so the tree prints the same way on every run.
+pub fn link_children(processes: &mut HashMap<u32, ProcessInfo>) {
+    for proc_info in processes.values_mut() {
+        proc_info.children.clear();
+    }
+    
+    // A parent PID held by a process younger than the child was reused after
+    // the real parent exited; the child is left without a parent instead
+    let stale: Vec<(u32, u32)> = processes
+        .values()
+        .filter_map(|proc_info| {
+            let parent = processes.get(&proc_info.parent_pid?)?;
+            (!consistency::plausible_parent(parent, proc_info)).then_some((proc_info.pid, parent.pid))
+        })
+        .collect();
+    for (pid, parent_pid) in stale {
+        debug!("PID {} names PID {} as its parent, which started after it; not linking them", pid, parent_pid);
+        if let Some(proc_info) = processes.get_mut(&pid) {
+            proc_info.parent_pid = None;
+        }
+    }
+    
+    let mut links: Vec<(u32, u32)> = processes
+        .values()
+        .filter_map(|proc_info| proc_info.parent_pid.map(|parent_pid| (parent_pid, proc_info.pid)))
+        .filter(|(parent_pid, _)| processes.contains_key(parent_pid))
+        .collect();
+    links.sort_unstable();
+    for (parent_pid, pid) in links {
+        if let Some(parent) = processes.get_mut(&parent_pid) {
+            parent.add_child(pid);
+        }
+    }
+    
+    let orphans = processes
+        .values()
+        .filter(|proc_info| proc_info.parent_pid.is_none_or(|parent_pid| !processes.contains_key(&parent_pid)))
+        .count();
+    debug!("linked {} processes, {} without a known parent", processes.len(), orphans);
+}
+
+// PIDs whose parent is not part of the table, in PID order
+pub fn table_roots(processes: &HashMap<u32, ProcessInfo>) -> Vec<u32> {
+    let mut roots: Vec<u32> = processes
+        .values()
+        .filter(|proc_info| proc_info.parent_pid.is_none_or(|parent_pid| !processes.contains_key(&parent_pid)))
+        .map(|proc_info| proc_info.pid)
+        .collect();
+    roots.sort_unstable();
+    roots
+}
+
+// Which of memon's own processes are left out of the match candidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfExclusion {
+    // --include-self: memon matches like any other process
+    Include,
+    // memon's own process and the tasks below it
+    #[default]
+    Process,
+    // --exclude-self-tree: also every ancestor, such as the launching shell
+    Ancestors,
+}
+
+// `pid` followed by its ancestors, nearest first; stops at a parent missing
+// from the table or at a loop
+pub fn ancestor_chain(processes: &HashMap<u32, ProcessInfo>, pid: u32) -> Vec<u32> {
+    let mut chain = Vec::new();
+    let mut next = Some(pid);
+    while let Some(current) = next {
+        if chain.contains(&current) {
+            break;
+        }
+        chain.push(current);
+        next = processes.get(&current).and_then(|proc_info| proc_info.parent_pid);
+    }
+    chain
+}
+
+// `pid` and every process below it. The table must be linked; `pid` is
+// included even when it is not in the table.
+pub fn descendant_set(processes: &HashMap<u32, ProcessInfo>, pid: u32) -> HashSet<u32> {
+    let mut descendants = HashSet::new();
+    let mut stack = vec![pid];
+    while let Some(pid) = stack.pop() {
+        if descendants.insert(pid) {
+            stack.extend(processes.get(&pid).map_or(&[][..], |proc_info| &proc_info.children));
+        }
+    }
+    descendants
+}
+
+// PIDs to drop from the match candidates for memon running as `self_pid`.
+// The table must be linked; on Linux memon's threads show up as its children.
+pub fn self_pids(processes: &HashMap<u32, ProcessInfo>, self_pid: u32, exclusion: SelfExclusion) -> HashSet<u32> {
+    if exclusion == SelfExclusion::Include {
+        return HashSet::new();
+    }
+    let mut excluded = descendant_set(processes, self_pid);
+    if exclusion == SelfExclusion::Ancestors {
+        excluded.extend(ancestor_chain(processes, self_pid));
+    }
+    excluded
+}
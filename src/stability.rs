@@ -0,0 +1,111 @@
+// Time since each process's memory last moved, across watch samples (--stability)
+
+use std::collections::HashMap;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::process::ProcessInfo;
+
+// RSS moves smaller than this are page-level noise, not a change
+pub const EPSILON_BYTES: u64 = 256 * 1024;
+
+// A change more recent than this shows as "changed Ns ago", older as "stable"
+pub const RECENT_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Stability {
+    // Seconds since the epoch of the last change, or of the first sample when
+    // none was seen
+    pub last_change_epoch: i64,
+    pub stable_secs: u64,
+    // Whether a change was seen at all; without one memon only knows the
+    // process has been stable at least since it was first sampled
+    pub observed_change: bool,
+}
+
+impl Stability {
+    // "changed 4s ago" or "stable 32m"
+    pub fn label(&self) -> String {
+        if self.observed_change && self.stable_secs < RECENT_SECS {
+            format!("changed {} ago", format_age(self.stable_secs))
+        } else {
+            format!("stable {}", format_age(self.stable_secs))
+        }
+    }
+}
+
+// Where a process stood when it last changed
+#[derive(Debug, Clone, Copy)]
+struct Reference {
+    rss: u64,
+    epoch: i64,
+    observed_change: bool,
+}
+
+// Last change per (pid, start_time), so a reused PID starts afresh
+#[derive(Debug, Clone)]
+pub struct StabilityTracker {
+    epsilon: u64,
+    references: HashMap<(u32, u64), Reference>,
+}
+
+impl Default for StabilityTracker {
+    fn default() -> Self {
+        StabilityTracker::new(EPSILON_BYTES)
+    }
+}
+
+impl StabilityTracker {
+    pub fn new(epsilon: u64) -> Self {
+        StabilityTracker { epsilon, references: HashMap::new() }
+    }
+
+    // Feed one reading taken at `now`. RSS is compared with the reading at
+    // the last change rather than the previous sample, so slow creep still
+    // counts once it adds up to more than the epsilon.
+    pub fn update(&mut self, key: (u32, u64), rss: u64, now: i64) -> Stability {
+        let reference = self.references.entry(key).or_insert(Reference { rss, epoch: now, observed_change: false });
+        if reference.rss.abs_diff(rss) > self.epsilon {
+            *reference = Reference { rss, epoch: now, observed_change: true };
+        }
+        Stability {
+            last_change_epoch: reference.epoch,
+            stable_secs: now.saturating_sub(reference.epoch).max(0) as u64,
+            observed_change: reference.observed_change,
+        }
+    }
+
+    // Set the stability of every process from its unsmoothed RSS, and forget
+    // processes that have exited
+    pub fn apply(&mut self, processes: &mut HashMap<u32, ProcessInfo>, now: i64) {
+        self.references.retain(|&(pid, start_time), _| {
+            processes.get(&pid).is_some_and(|proc_info| proc_info.start_time == start_time)
+        });
+        for proc_info in processes.values_mut() {
+            let rss = proc_info.rss_raw.unwrap_or(proc_info.rss);
+            proc_info.stability = Some(self.update((proc_info.pid, proc_info.start_time), rss, now));
+        }
+    }
+
+    // Number of processes being tracked
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+}
+
+// Compact age in the largest fitting unit: 45s, 32m, 5h, 3d
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
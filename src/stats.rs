@@ -0,0 +1,130 @@
+// Per-tree statistics computed in a single post-order traversal
+
+use std::collections::HashMap;
+
+use crate::process::ProcessInfo;
+use crate::snapshot::ProcessTree;
+
+// Number of distinct memory ranks highlighted per tree (gold, silver, bronze)
+pub const TOP_RANKS: usize = 3;
+
+// Aggregates for one process tree, shared by highlighting and the summary line
+#[derive(Debug, Clone, Default)]
+pub struct TreeStats {
+    pub count: usize,
+    pub total_rss: u64,
+    // (rss, pid) of every process holding one of the TOP_RANKS largest distinct
+    // RSS values, sorted by RSS descending then PID ascending
+    pub top: Vec<(u64, u32)>,
+    // The TOP_RANKS largest distinct RSS values, descending
+    ranks: Vec<u64>,
+}
+
+impl TreeStats {
+    // Walk the tree rooted at root_pid once, filling in each node's descendant
+    // count and cumulative RSS and accumulating the tree-wide aggregates
+    pub fn collect(processes: &mut HashMap<u32, ProcessInfo>, root_pid: u32) -> TreeStats {
+        let mut stats = TreeStats::default();
+        if !processes.contains_key(&root_pid) {
+            return stats;
+        }
+        
+        // Iterative post-order: each pid is visited twice, children are
+        // finalised before their parent on the second visit
+        let mut stack = vec![(root_pid, false)];
+        while let Some((pid, children_done)) = stack.pop() {
+            if children_done {
+                let (descendants, cumulative_rss) = match processes.get(&pid) {
+                    Some(proc_info) => proc_info.children
+                        .iter()
+                        .filter_map(|child_pid| processes.get(child_pid))
+                        .fold((0, proc_info.rss), |(count, rss), child| {
+                            (count + child.descendant_count + 1, rss + child.cumulative_rss)
+                        }),
+                    None => continue,
+                };
+                if let Some(proc_info) = processes.get_mut(&pid) {
+                    proc_info.descendant_count = descendants;
+                    proc_info.cumulative_rss = cumulative_rss;
+                    stats.count += 1;
+                    stats.total_rss += proc_info.rss;
+                    stats.offer(proc_info.rss, pid);
+                }
+            } else if let Some(proc_info) = processes.get(&pid) {
+                stack.push((pid, true));
+                for &child_pid in &proc_info.children {
+                    stack.push((child_pid, false));
+                }
+            }
+        }
+        
+        stats.finish();
+        stats
+    }
+    
+    // Aggregates of an already built tree, e.g. one loaded from a snapshot. A
+    // subtree folded by --dedupe-subtrees counts for its whole group: members
+    // share its shape, so each holds as many processes.
+    pub fn from_tree(tree: &ProcessTree) -> TreeStats {
+        let mut stats = TreeStats::default();
+        // (node, inside a folded subtree already counted)
+        let mut stack = vec![(&tree.root, false)];
+        while let Some((node, counted)) = stack.pop() {
+            let counted = counted || match node.similar {
+                Some(group) => {
+                    stats.count += group.count * (node.descendants + 1);
+                    stats.total_rss += group.total_bytes;
+                    true
+                }
+                None => false,
+            };
+            if !counted && !node.is_virtual {
+                stats.count += 1;
+                stats.total_rss += node.rss_bytes;
+            }
+            if !node.is_virtual {
+                stats.offer(node.rss_bytes, node.pid);
+            }
+            stack.extend(node.children.iter().map(|child| (child, counted)));
+        }
+        stats.finish();
+        stats
+    }
+    
+    // Consider a process for the top ranks; candidates are pruned and sorted in finish()
+    fn offer(&mut self, rss: u64, pid: u32) {
+        if self.ranks.len() == TOP_RANKS && rss < self.ranks[TOP_RANKS - 1] {
+            return;
+        }
+        if !self.ranks.contains(&rss) {
+            let position = self.ranks.partition_point(|&rank_rss| rank_rss > rss);
+            self.ranks.insert(position, rss);
+            self.ranks.truncate(TOP_RANKS);
+        }
+        self.top.push((rss, pid));
+    }
+    
+    fn finish(&mut self) {
+        if let Some(&cutoff) = self.ranks.last() {
+            self.top.retain(|&(rss, _)| rss >= cutoff);
+        }
+        self.top.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    }
+    
+    // Zero-based rank of an RSS value, or None when it is outside the top ranks
+    pub fn rank_of(&self, rss: u64) -> Option<usize> {
+        self.ranks.iter().position(|&rank_rss| rank_rss == rss)
+    }
+    
+    pub fn max_rss(&self) -> u64 {
+        self.ranks.first().copied().unwrap_or(0)
+    }
+    
+    pub fn average_rss(&self) -> u64 {
+        if self.count > 0 {
+            self.total_rss / self.count as u64
+        } else {
+            0
+        }
+    }
+}
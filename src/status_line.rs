@@ -0,0 +1,44 @@
+// --status-line: one key=value summary on stderr at exit, for wrappers that
+// want a parsable result without JSON output. New keys are only ever appended.
+
+use std::fmt;
+
+use crate::snapshot::Snapshot;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusLine {
+    pub matched: usize,
+    pub trees: usize,
+    pub total_bytes: u64,
+    // Largest process by RSS, none without a match
+    pub max_pid: Option<u32>,
+    pub duration_ms: u128,
+    pub exit: i32,
+}
+
+impl StatusLine {
+    // Take the figures of the latest snapshot; duration and exit are set at exit
+    pub fn observe(&mut self, snapshot: &Snapshot) {
+        self.matched = snapshot.matched;
+        self.trees = snapshot.trees.len();
+        self.total_bytes = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+        self.max_pid = snapshot.trees.iter()
+            .flat_map(|tree| tree.iter_preorder())
+            .filter(|(node, _)| !node.is_virtual)
+            .max_by_key(|(node, _)| (node.rss_bytes, std::cmp::Reverse(node.pid)))
+            .map(|(node, _)| node.pid);
+    }
+}
+
+// `memon: matched=14 trees=2 total_bytes=4831838208 max_pid=4312 duration_ms=123 exit=0`,
+// with `max_pid=-` when nothing matched
+impl fmt::Display for StatusLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "memon: matched={} trees={} total_bytes={} max_pid=", self.matched, self.trees, self.total_bytes)?;
+        match self.max_pid {
+            Some(pid) => write!(f, "{}", pid)?,
+            None => write!(f, "-")?,
+        }
+        write!(f, " duration_ms={} exit={}", self.duration_ms, self.exit)
+    }
+}
@@ -0,0 +1,193 @@
+// Per-thread listing of one process (`memon threads <pid>`), for services
+// whose tree is a single process full of threads
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::render::Theme;
+use crate::units::format_memory;
+
+// /proc reports CPU time in USER_HZ ticks, which is 100 on every Linux ABI
+pub const TICKS_PER_SEC: u64 = 100;
+
+// One thread as read from /proc/<pid>/task/<tid>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadStat {
+    pub tid: u32,
+    pub name: String,
+    // User plus system time
+    pub cpu_ticks: u64,
+    // Only known for a thread blocked in a system call, and with ptrace access
+    pub stack_pointer: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThreadRow {
+    pub tid: u32,
+    pub name: String,
+    pub cpu_secs: f64,
+    // Size of the mapping holding the thread's stack pointer
+    pub stack_bytes: Option<u64>,
+}
+
+// Threads whose names differ only in a trailing number, such as the
+// members of "pool-1-thread-7"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThreadPool {
+    pub name: String,
+    pub threads: usize,
+    pub cpu_secs: f64,
+}
+
+// --json document for `memon threads`
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadsReport {
+    pub pid: u32,
+    pub name: Option<String>,
+    // RSS belongs to the whole process; it cannot be split per thread
+    pub process_rss_bytes: u64,
+    pub thread_count: usize,
+    // Most threads first
+    pub pools: Vec<ThreadPool>,
+    // The shown threads, most CPU time first
+    pub threads: Vec<ThreadRow>,
+    // Threads left out by --top
+    pub hidden_threads: usize,
+}
+
+impl ThreadsReport {
+    // Keep the `top` threads with the most CPU time; `maps` is the contents
+    // of /proc/<pid>/maps, used to size the thread stacks
+    pub fn new(pid: u32, name: Option<String>, process_rss_bytes: u64, threads: Vec<ThreadStat>, maps: &str, top: usize) -> Self {
+        let ranges = parse_map_ranges(maps);
+        let mut rows: Vec<ThreadRow> = threads
+            .into_iter()
+            .map(|thread| ThreadRow {
+                tid: thread.tid,
+                cpu_secs: cpu_secs(thread.cpu_ticks),
+                stack_bytes: thread.stack_pointer.and_then(|sp| stack_size(&ranges, sp)),
+                name: thread.name,
+            })
+            .collect();
+        rows.sort_by(|a, b| b.cpu_secs.total_cmp(&a.cpu_secs).then(a.tid.cmp(&b.tid)));
+
+        let mut pools: HashMap<String, ThreadPool> = HashMap::new();
+        for row in &rows {
+            let name = pool_name(&row.name);
+            let pool = pools.entry(name.clone()).or_insert_with(|| ThreadPool { name, threads: 0, cpu_secs: 0.0 });
+            pool.threads += 1;
+            pool.cpu_secs += row.cpu_secs;
+        }
+        let mut pools: Vec<ThreadPool> = pools.into_values().collect();
+        pools.sort_by(|a, b| b.threads.cmp(&a.threads).then(b.cpu_secs.total_cmp(&a.cpu_secs)).then_with(|| a.name.cmp(&b.name)));
+
+        let thread_count = rows.len();
+        let hidden_threads = thread_count.saturating_sub(top);
+        rows.truncate(top);
+        ThreadsReport { pid, name, process_rss_bytes, thread_count, pools, threads: rows, hidden_threads }
+    }
+}
+
+fn cpu_secs(ticks: u64) -> f64 {
+    ticks as f64 / TICKS_PER_SEC as f64
+}
+
+// "pool-1-thread-12" -> "pool-1-thread-*"; names without a trailing number
+// stay as they are
+pub fn pool_name(name: &str) -> String {
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if stem.len() == name.len() || stem.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}*", stem)
+    }
+}
+
+// Address ranges of /proc/<pid>/maps lines such as
+// "7f1c2b000000-7f1c2b800000 rw-p 00000000 00:00 0"
+pub fn parse_map_ranges(maps: &str) -> Vec<(u64, u64)> {
+    maps.lines()
+        .filter_map(|line| {
+            let (start, end) = line.split_whitespace().next()?.split_once('-')?;
+            Some((u64::from_str_radix(start, 16).ok()?, u64::from_str_radix(end, 16).ok()?))
+        })
+        .collect()
+}
+
+// Size of the mapping that contains `address`
+pub fn stack_size(ranges: &[(u64, u64)], address: u64) -> Option<u64> {
+    ranges.iter().find(|(start, end)| (*start..*end).contains(&address)).map(|(start, end)| end - start)
+}
+
+// 0.42s, 12.30s, 3m07s, 2h15m
+pub fn format_cpu(secs: f64) -> String {
+    let whole = secs as u64;
+    match whole {
+        0..60 => format!("{:.2}s", secs),
+        60..3600 => format!("{}m{:02}s", whole / 60, whole % 60),
+        _ => format!("{}h{:02}m", whole / 3600, whole % 3600 / 60),
+    }
+}
+
+// Pools, then the busiest threads; the largest pool's count is highlighted
+pub fn render_text(report: &ThreadsReport, theme: Theme) -> String {
+    let mut out = String::new();
+    let title = match &report.name {
+        Some(name) => format!("{} ({})", report.pid, name),
+        None => report.pid.to_string(),
+    };
+    let _ = writeln!(out, "Threads of {}: {} threads, {} RSS", title, report.thread_count, format_memory(report.process_rss_bytes));
+    let _ = writeln!(out, "Memory is process-wide: RSS cannot be split per thread");
+
+    let rows: Vec<[String; 3]> = report.pools
+        .iter()
+        .map(|pool| [pool.threads.to_string(), format_cpu(pool.cpu_secs), pool.name.clone()])
+        .collect();
+    let header = ["THREADS", "CPU", "POOL"].map(str::to_string);
+    let widths = column_widths(&header, &rows);
+    let _ = writeln!(out, "\n{:>w0$}  {:>w1$}  {}", header[0], header[1], header[2], w0 = widths[0], w1 = widths[1]);
+    for (index, row) in rows.iter().enumerate() {
+        // Pad before coloring so escape sequences do not count towards the width
+        let mut threads = format!("{:>width$}", row[0], width = widths[0]);
+        if index == 0 && !theme.is_plain() {
+            threads = format!("{}{}{}", theme.highlight, threads, theme.reset);
+        }
+        let _ = writeln!(out, "{}  {:>w1$}  {}", threads, row[1], row[2], w1 = widths[1]);
+    }
+
+    let rows: Vec<[String; 4]> = report.threads
+        .iter()
+        .map(|thread| [thread.tid.to_string(), format_cpu(thread.cpu_secs), thread.stack_bytes.map_or("-".to_string(), format_memory), thread.name.clone()])
+        .collect();
+    let header = ["TID", "CPU", "STACK", "NAME"].map(str::to_string);
+    let widths = column_widths(&header, &rows);
+    let _ = writeln!(out, "\n{:>w0$}  {:>w1$}  {:>w2$}  {}", header[0], header[1], header[2], header[3],
+                     w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+    for row in &rows {
+        let _ = writeln!(out, "{:>w0$}  {:>w1$}  {:>w2$}  {}", row[0], row[1], row[2], row[3],
+                         w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+    }
+    if report.hidden_threads > 0 {
+        let _ = writeln!(out, "({} more threads)", report.hidden_threads);
+    }
+    out
+}
+
+// Widths of every column but the last, which is left unpadded
+fn column_widths<const N: usize>(header: &[String; N], rows: &[[String; N]]) -> Vec<usize> {
+    let mut widths = vec![0; N - 1];
+    for row in std::iter::once(header).chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    widths
+}
+
+pub fn render_json(report: &ThreadsReport) -> String {
+    let mut json = serde_json::to_string_pretty(report).expect("threads report serializes to JSON");
+    json.push('\n');
+    json
+}
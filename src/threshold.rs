@@ -0,0 +1,92 @@
+// Memory thresholds (--fail-if-total / --fail-if-process) and edge-triggered alerting
+
+use crate::snapshot::{ProcessNode, Snapshot};
+use std::time::{Duration, Instant};
+
+// Limits checked against every snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Thresholds {
+    // Combined memory of all matched trees
+    pub total: Option<u64>,
+    // Memory of any single process
+    pub process: Option<u64>,
+}
+
+// Which limit was crossed, with its configured value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exceeded {
+    Total(u64),
+    Process(u64),
+}
+
+// The process that represents a crossed threshold, plus the overall total
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breach {
+    pub pid: u32,
+    pub name: String,
+    pub rss: u64,
+    pub total: u64,
+    pub exceeded: Exceeded,
+}
+
+impl Thresholds {
+    pub fn is_empty(&self) -> bool {
+        self.total.is_none() && self.process.is_none()
+    }
+    
+    // Return the breach for this snapshot, if any limit is exceeded. The
+    // reported process is the largest one, which is also the one over the
+    // per-process limit whenever that limit is what tripped.
+    pub fn check(&self, snapshot: &Snapshot) -> Option<Breach> {
+        let total: u64 = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+        let mut largest: Option<&ProcessNode> = None;
+        for (node, _) in snapshot.trees.iter().flat_map(|tree| tree.iter_preorder()) {
+            if largest.is_none_or(|max| node.rss_bytes > max.rss_bytes) {
+                largest = Some(node);
+            }
+        }
+        let largest = largest?;
+        let (pid, name, rss) = (largest.pid, largest.name.clone(), largest.rss_bytes);
+        
+        // The tree total is reported first when both limits are crossed
+        let exceeded = match (self.total, self.process) {
+            (Some(limit), _) if total > limit => Exceeded::Total(limit),
+            (_, Some(limit)) if rss > limit => Exceeded::Process(limit),
+            _ => return None,
+        };
+        Some(Breach { pid, name, rss, total, exceeded })
+    }
+}
+
+// Fires once when a condition becomes true, then stays quiet until it has
+// cleared again and the cooldown since the last firing has passed
+#[derive(Debug)]
+pub struct Trigger {
+    cooldown: Duration,
+    active: bool,
+    last_fired: Option<Instant>,
+}
+
+impl Trigger {
+    pub fn new(cooldown: Duration) -> Self {
+        Trigger { cooldown, active: false, last_fired: None }
+    }
+    
+    // Feed the current condition; returns true when the hooks should run
+    pub fn update(&mut self, breached: bool, now: Instant) -> bool {
+        if !breached {
+            self.active = false;
+            return false;
+        }
+        if self.active {
+            return false;
+        }
+        if let Some(last) = self.last_fired
+            && now.duration_since(last) < self.cooldown {
+            return false;
+        }
+        self.active = true;
+        self.last_fired = Some(now);
+        true
+    }
+}
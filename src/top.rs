@@ -0,0 +1,132 @@
+// `memon top`: the applications of the whole system ranked by the memory of
+// their process trees, for finding out what to pass to the regular flow.
+// An application is a top-level process, named after its root; the trees of
+// roots with the same name are added up.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::process::ProcessInfo;
+use crate::shell::SESSION_STARTERS;
+use crate::source;
+use crate::units::format_memory;
+
+// Applications listed when --limit is not given
+pub const DEFAULT_LIMIT: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppTotal {
+    pub name: String,
+    // Roots of this name that were merged
+    pub trees: usize,
+    pub processes: usize,
+    pub bytes: u64,
+}
+
+// `memon top --json` document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopReport {
+    // Largest total first
+    pub applications: Vec<AppTotal>,
+    // Applications past the limit, and their memory
+    pub omitted: usize,
+    pub omitted_bytes: u64,
+    // Every process of the system and its memory
+    pub processes: usize,
+    pub total_bytes: u64,
+}
+
+// Whether the children of a process are applications of their own: init
+// systems, systemd --user and display managers, as for `memon shell`
+fn starts_applications(proc_info: &ProcessInfo) -> bool {
+    SESSION_STARTERS.contains(&proc_info.name.as_str())
+}
+
+// Roots of the application trees in PID order: processes without a known
+// parent and the children of a process that starts applications. A process
+// starting applications is an application of its own as well, holding only
+// itself. The table must be linked.
+pub fn app_roots(processes: &HashMap<u32, ProcessInfo>) -> Vec<u32> {
+    let mut roots = source::table_roots(processes);
+    roots.extend(processes
+        .values()
+        .filter(|proc_info| starts_applications(proc_info))
+        .flat_map(|proc_info| proc_info.children.iter().copied()));
+    roots.sort_unstable();
+    roots.dedup();
+    roots
+}
+
+impl TopReport {
+    // Cut the forest into application trees, merge them by root name and keep
+    // the `limit` largest; ties go by name
+    pub fn new(processes: &HashMap<u32, ProcessInfo>, limit: usize) -> Self {
+        let roots = app_roots(processes);
+        let is_root: HashSet<u32> = roots.iter().copied().collect();
+        let mut by_name: HashMap<&str, AppTotal> = HashMap::new();
+        for &root_pid in &roots {
+            let Some(root) = processes.get(&root_pid) else {
+                continue;
+            };
+            let app = by_name.entry(root.name.as_str())
+                .or_insert_with(|| AppTotal { name: root.name.clone(), trees: 0, processes: 0, bytes: 0 });
+            app.trees += 1;
+            // The subtree down to where other applications start
+            let mut stack = vec![root_pid];
+            while let Some(pid) = stack.pop() {
+                let Some(proc_info) = processes.get(&pid) else {
+                    continue;
+                };
+                app.processes += 1;
+                app.bytes += proc_info.rss;
+                stack.extend(proc_info.children.iter().filter(|child| !is_root.contains(child)));
+            }
+        }
+        let mut applications: Vec<AppTotal> = by_name.into_values().collect();
+        applications.sort_by(|a, b| (Reverse(a.bytes), &a.name).cmp(&(Reverse(b.bytes), &b.name)));
+        let omitted: Vec<AppTotal> = applications.split_off(limit.min(applications.len()));
+        TopReport {
+            applications,
+            omitted: omitted.len(),
+            omitted_bytes: omitted.iter().map(|app| app.bytes).sum(),
+            processes: processes.len(),
+            total_bytes: processes.values().map(|proc_info| proc_info.rss).sum(),
+        }
+    }
+}
+
+// Ranked table with a header line, such as
+// " 1  postgres  1 tree   12 procs  1.2GB  37.5%"
+pub fn render_text(report: &TopReport) -> String {
+    let percent = |bytes: u64| if report.total_bytes == 0 { 0.0 } else { bytes as f64 / report.total_bytes as f64 * 100.0 };
+    let rows: Vec<[String; 6]> = report
+        .applications
+        .iter()
+        .enumerate()
+        .map(|(index, app)| {
+            let trees = if app.trees == 1 { "1 tree".to_string() } else { format!("{} trees", app.trees) };
+            [(index + 1).to_string(), app.name.clone(), trees, app.processes.to_string(), format_memory(app.bytes), format!("{:.1}%", percent(app.bytes))]
+        })
+        .collect();
+    let width = |column: usize| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0);
+    let widths = [width(0), width(1), width(2), width(3), width(4), width(5)];
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Top {} applications by tree memory ({} processes, {} total):",
+        report.applications.len(), report.processes, format_memory(report.total_bytes));
+    for [rank, name, trees, processes, bytes, share] in &rows {
+        let _ = writeln!(out, "{:>w0$}  {:<w1$}  {:<w2$}  {:>w3$} procs  {:>w4$}  {:>w5$}", rank, name, trees, processes, bytes, share,
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4], w5 = widths[5]);
+    }
+    if report.omitted > 0 {
+        let _ = writeln!(out, "\u{2026} {} more applications, {} total", report.omitted, format_memory(report.omitted_bytes));
+    }
+    out
+}
+
+pub fn render_json(report: &TopReport) -> String {
+    serde_json::to_string_pretty(report).expect("top report serializes to JSON") + "\n"
+}
@@ -0,0 +1,192 @@
+// Squarified treemap of the process forest as SVG (--svg)
+//
+// Rectangle area follows cumulative RSS and nesting follows the process
+// hierarchy. The layout only depends on the snapshot: siblings are placed by
+// size, then PID, so the same snapshot always renders to the same file.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::snapshot::{ProcessNode, Snapshot};
+use crate::units::format_memory;
+
+pub const WIDTH: f64 = 1200.0;
+pub const HEIGHT: f64 = 800.0;
+
+// Room for a label above a process's children, and the gap around them
+const HEADER: f64 = 16.0;
+const PADDING: f64 = 2.0;
+
+// Rough width of one character at the font size used for labels
+const CHAR_WIDTH: f64 = 6.6;
+
+// Fills of ranks 1, 2 and 3: the orange, sky blue and light gray of the
+// terminal highlights
+const RANK_FILLS: [&str; 3] = ["#ffaf00", "#87d7ff", "#d0d0d0"];
+
+// Fills of other processes, by depth
+const DEPTH_FILLS: [&str; 4] = ["#eef2f7", "#dfe7f0", "#d0dbe9", "#c1cfe2"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Rect {
+    fn area(&self) -> f64 {
+        self.w * self.h
+    }
+
+    // The rectangle shrunk by the given margins, never below zero size
+    fn inset(&self, top: f64, side: f64) -> Rect {
+        Rect {
+            x: self.x + side,
+            y: self.y + top,
+            w: (self.w - 2.0 * side).max(0.0),
+            h: (self.h - top - side).max(0.0),
+        }
+    }
+}
+
+// Worst aspect ratio of a row of `areas` laid along a side of length `side`
+fn worst_ratio(areas: &[f64], side: f64) -> f64 {
+    let sum: f64 = areas.iter().sum();
+    let (side2, sum2) = (side * side, sum * sum);
+    areas.iter().fold(0.0, |worst: f64, &area| worst.max((side2 * area / sum2).max(sum2 / (side2 * area))))
+}
+
+// Squarified layout (Bruls, Huizing and van Wijk) of `values` in `rect`,
+// which should be sorted largest first. Returns one rectangle per value, in
+// the same order; values of zero or less get an empty rectangle.
+pub fn squarify(values: &[f64], rect: Rect) -> Vec<Rect> {
+    let total: f64 = values.iter().filter(|value| **value > 0.0).sum();
+    let mut rects = vec![Rect { x: rect.x, y: rect.y, w: 0.0, h: 0.0 }; values.len()];
+    if total <= 0.0 || rect.area() <= 0.0 {
+        return rects;
+    }
+    let scale = rect.area() / total;
+    let items: Vec<(usize, f64)> = values
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value > 0.0)
+        .map(|(index, value)| (index, value * scale))
+        .collect();
+    let areas: Vec<f64> = items.iter().map(|(_, area)| *area).collect();
+    let mut remaining = rect;
+    let mut start = 0;
+    while start < items.len() {
+        let side = remaining.w.min(remaining.h);
+        let mut end = start + 1;
+        while end < items.len() && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side) {
+            end += 1;
+        }
+        let row_area: f64 = areas[start..end].iter().sum();
+        if remaining.w >= remaining.h {
+            // A column on the left
+            let thickness = row_area / remaining.h;
+            let mut y = remaining.y;
+            for &(index, area) in &items[start..end] {
+                let h = area / thickness;
+                rects[index] = Rect { x: remaining.x, y, w: thickness, h };
+                y += h;
+            }
+            remaining.x += thickness;
+            remaining.w = (remaining.w - thickness).max(0.0);
+        } else {
+            // A row along the top
+            let thickness = row_area / remaining.w;
+            let mut x = remaining.x;
+            for &(index, area) in &items[start..end] {
+                let w = area / thickness;
+                rects[index] = Rect { x, y: remaining.y, w, h: thickness };
+                x += w;
+            }
+            remaining.y += thickness;
+            remaining.h = (remaining.h - thickness).max(0.0);
+        }
+        start = end;
+    }
+    rects
+}
+
+// Escape text for XML content and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn fill(node: &ProcessNode, depth: usize) -> &'static str {
+    match node.rank {
+        Some(rank @ 1..=3) => RANK_FILLS[usize::from(rank) - 1],
+        _ => DEPTH_FILLS[depth % DEPTH_FILLS.len()],
+    }
+}
+
+// Lay out `nodes` largest first, by cumulative RSS and then PID
+fn layout<'a>(nodes: impl Iterator<Item = &'a ProcessNode>, own_bytes: u64, rect: Rect) -> Vec<(&'a ProcessNode, Rect)> {
+    let mut sorted: Vec<&ProcessNode> = nodes.collect();
+    sorted.sort_by(|a, b| b.cumulative_bytes.cmp(&a.cumulative_bytes).then(a.pid.cmp(&b.pid)));
+    // The parent's own RSS takes the last slot and stays uncovered
+    let mut values: Vec<f64> = sorted.iter().map(|node| node.cumulative_bytes as f64).collect();
+    values.push(own_bytes as f64);
+    let rects = squarify(&values, rect);
+    sorted.into_iter().zip(rects).collect()
+}
+
+fn render_node(out: &mut String, node: &ProcessNode, rect: Rect, depth: usize) {
+    if rect.w < 1.0 || rect.h < 1.0 {
+        return;
+    }
+    let _ = write!(out, r#"<g><title>{} ({}): {} own, {} with children</title>"#,
+                   escape(&node.name), node.pid, format_memory(node.rss_bytes), format_memory(node.cumulative_bytes));
+    let stroke = if node.rank.is_some() { "#000000" } else { "#7f8c99" };
+    let _ = writeln!(out, r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" stroke="{}" stroke-width="0.5"/>"#,
+                     rect.x, rect.y, rect.w, rect.h, fill(node, depth), stroke);
+    let label = format!("{} {}", node.name, format_memory(node.cumulative_bytes));
+    let labelled = rect.h >= HEADER && rect.w >= label.chars().count() as f64 * CHAR_WIDTH + 2.0 * PADDING;
+    if labelled {
+        let weight = if node.rank == Some(1) { r#" font-weight="bold""# } else { "" };
+        let _ = writeln!(out, r#"<text x="{:.1}" y="{:.1}"{}>{}</text>"#, rect.x + 3.0, rect.y + 12.0, weight, escape(&label));
+    }
+    let _ = writeln!(out, "</g>");
+    let inner = rect.inset(if labelled { HEADER } else { PADDING }, PADDING);
+    for (child, child_rect) in layout(node.children.iter(), node.rss_bytes, inner) {
+        render_node(out, child, child_rect, depth + 1);
+    }
+}
+
+// The whole snapshot as an SVG document; each tree gets an area of the
+// canvas proportional to its total
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="monospace" font-size="11">"#,
+                     w = WIDTH, h = HEIGHT);
+    let total: u64 = snapshot.trees.iter().map(|tree| tree.total_bytes).sum();
+    let _ = writeln!(out, "<title>memon: {}, {} in {} trees</title>", escape(&snapshot.pattern), format_memory(total), snapshot.trees.len());
+    let canvas = Rect { x: 0.0, y: 0.0, w: WIDTH, h: HEIGHT };
+    for (root, rect) in layout(snapshot.trees.iter().map(|tree| &tree.root), 0, canvas) {
+        render_node(&mut out, root, rect, 0);
+    }
+    let _ = writeln!(out, "</svg>");
+    out
+}
+
+pub fn write(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    fs::write(path, render(snapshot))
+}
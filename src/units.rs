@@ -0,0 +1,149 @@
+// Memory size formatting shared by all renderers
+
+// Convert bytes to human readable format (MB/GB)
+pub fn format_memory(bytes_value: u64) -> String {
+    if bytes_value == 0 {
+        return "0B".to_string();
+    }
+    
+    let mb = bytes_value as f64 / (1024.0 * 1024.0);
+    let gb = mb / 1024.0;
+    
+    if gb >= 1.0 {
+        format!("{:.1}GB", gb)
+    } else {
+        format!("{:.1}MB", mb)
+    }
+}
+
+// Page size assumed where the system does not report one
+pub const DEFAULT_PAGE_SIZE: u64 = 4096;
+
+// Parse a memory size such as "512MB", "1.5G", "4096" (bytes), "64k" or
+// "300p" (pages of the system page size)
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let trimmed = text.trim();
+    let split = trimmed.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(trimmed.len());
+    let (number, suffix) = trimmed.split_at(split);
+    let value: f64 = number.parse().map_err(|_| format!("invalid size '{}'", text))?;
+    
+    let multiplier = match suffix.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" | "KB" | "KIB" => 1024.0,
+        "M" | "MB" | "MIB" => 1024.0 * 1024.0,
+        "G" | "GB" | "GIB" => 1024.0 * 1024.0 * 1024.0,
+        "P" | "PAGES" => crate::platform::page_size() as f64,
+        _ => return Err(format!("invalid size unit in '{}' (use B, K, M, G or P for pages)", text)),
+    };
+    Ok((value * multiplier) as u64)
+}
+
+// Parse a percentage from 0 to 100 such as "2.5". NaN and infinities are
+// refused: no comparison with NaN holds, so it would hide everything.
+pub fn parse_percent(text: &str) -> Result<f64, String> {
+    let percent: f64 = text.trim().parse().map_err(|_| format!("invalid percentage '{}'", text))?;
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!("percentage must be from 0 to 100, not '{}'", text))
+    }
+}
+
+// Values accepted by --unit
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unit {
+    // MB below 1GB, GB above, one decimal
+    #[default]
+    Auto,
+    // Exact byte counts
+    B,
+    // Exact page counts, for comparing with /proc and kernel counters
+    Pages,
+}
+
+// How the text output prints sizes and counts: the unit, the digit
+// grouping separator for exact numbers (None with --no-group) and the page
+// size for Unit::Pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeFormat {
+    pub unit: Unit,
+    pub separator: Option<char>,
+    pub page_size: u64,
+}
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        SizeFormat { unit: Unit::Auto, separator: Some(','), page_size: DEFAULT_PAGE_SIZE }
+    }
+}
+
+impl SizeFormat {
+    // The separator comes from the numeric locale unless grouping is off
+    pub fn new(unit: Unit, group: bool, locale: Option<&str>) -> Self {
+        SizeFormat { unit, separator: group.then(|| locale.map_or(',', locale_separator)), page_size: DEFAULT_PAGE_SIZE }
+    }
+    
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+}
+
+impl SizeFormat {
+    pub fn memory(&self, bytes: u64) -> String {
+        match self.unit {
+            Unit::Auto => format_memory(bytes),
+            Unit::B => format!("{}B", self.count(bytes)),
+            // RSS is whole pages; smoothed values round to the nearest
+            Unit::Pages => format!("{}p", self.count(bytes.saturating_add(self.page_size / 2) / self.page_size)),
+        }
+    }
+    
+    // "Sizes in pages of 4,096B" above a report in pages, so readers can
+    // convert; None for the other units
+    pub fn page_note(&self) -> Option<String> {
+        (self.unit == Unit::Pages).then(|| format!("Sizes in pages of {}B", self.count(self.page_size)))
+    }
+    
+    // A plain number such as a process count, grouped unless --no-group
+    pub fn count(&self, value: u64) -> String {
+        match self.separator {
+            Some(separator) => group_digits(value, separator),
+            None => value.to_string(),
+        }
+    }
+}
+
+// 48211234816 -> "48,211,234,816" with ',' as the separator
+pub fn group_digits(value: u64, separator: char) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// Thousands separator for a POSIX locale name such as "de_DE.UTF-8", from the
+// language and territory alone; unknown and C/POSIX locales use ','
+pub fn locale_separator(locale: &str) -> char {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let language = name.split('_').next().unwrap_or("");
+    match (language, name) {
+        (_, "de_CH" | "it_CH" | "fr_CH") => '\'',
+        ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => '.',
+        ("fr" | "ru" | "sv" | "fi" | "nb" | "no" | "pl" | "cs" | "sk" | "uk" | "hu", _) => ' ',
+        _ => ',',
+    }
+}
+
+// The numeric locale from LC_ALL, LC_NUMERIC or LANG, in that order
+pub fn numeric_locale() -> Option<String> {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
@@ -0,0 +1,29 @@
+#!/bin/bash
+
+# Memon Static Build Script
+# Builds the core tool (--features cli) for musl and checks that the
+# binary is statically linked
+
+set -e
+
+TARGET="x86_64-unknown-linux-musl"
+BINARY="target/$TARGET/release/memon"
+
+echo "Building memon for $TARGET..."
+
+if ! rustup target list --installed | grep -qx "$TARGET"; then
+    echo "Error: the $TARGET target is not installed"
+    echo "Install it with: rustup target add $TARGET"
+    exit 1
+fi
+
+cargo build --release --target "$TARGET" --no-default-features --features cli
+
+# Check that no shared library is needed
+if ldd "$BINARY" 2>&1 | grep -Eq "not a dynamic executable|statically linked"; then
+    echo "$BINARY is statically linked ($(du -h "$BINARY" | cut -f1))"
+else
+    echo "Error: $BINARY is not statically linked:"
+    ldd "$BINARY"
+    exit 1
+fi
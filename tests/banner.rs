@@ -0,0 +1,44 @@
+// Progress banners: one helper for their color, printed on stderr
+
+use std::process::{Command, Output};
+
+use memon::render::{self, Theme};
+
+#[test]
+fn plain_banners_have_no_escape_codes() {
+    assert_eq!(render::banner(&Theme::PLAIN, "Found 3 procs"), "Found 3 procs");
+    assert_eq!(render::banner(&Theme::ANSI, "Found 3 procs"), "\x1b[36mFound 3 procs\x1b[0m");
+}
+
+fn memon(args: &[&str]) -> Output {
+    let table = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/small_tree.table");
+    Command::new(env!("CARGO_BIN_EXE_memon")).args(["--from-file", table]).args(args).env_remove("CLICOLOR_FORCE").env_remove("FORCE_COLOR").output().unwrap()
+}
+
+#[test]
+fn banners_go_to_stderr_and_the_report_to_stdout() {
+    let output = memon(&["nginx", "--color", "never"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "Searching: nginx\nFound 5 procs\nFound 1 trees\n");
+    assert!(stdout.starts_with("100 nginx"), "{}", stdout);
+    assert!(!stdout.contains('\x1b') && !stderr.contains('\x1b'), "{:?} {:?}", stdout, stderr);
+    
+    let output = memon(&["apache"]);
+    assert_eq!(output.stdout, b"");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "Searching: apache\nNo processes found matching 'apache'\n");
+}
+
+#[test]
+fn banners_follow_color_settings() {
+    let output = memon(&["nginx", "--color", "always"]);
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("\x1b[36mSearching: nginx\x1b[0m\n"));
+    
+    // Not a terminal, so auto means no color on either stream
+    let output = memon(&["nginx"]);
+    assert!(!output.stdout.contains(&0x1b) && !output.stderr.contains(&0x1b));
+    
+    // Only text output has banners
+    let output = memon(&["nginx", "--json"]);
+    assert_eq!(output.stderr, b"");
+}
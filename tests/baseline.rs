@@ -0,0 +1,74 @@
+// Baseline comparison: tolerance parsing, regression detection and the diff table
+
+mod common;
+
+use memon::baseline::{compare, render_table, Tolerance};
+use memon::snapshot::{ProcessNode, Snapshot, ProcessTree};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, rss: u64) -> ProcessNode {
+    common::node(pid, name, rss, Vec::new())
+}
+
+fn snapshot(server: u64, workers: &[u64]) -> Snapshot {
+    let mut root = node(1, "server", server);
+    root.children = workers.iter().enumerate().map(|(i, rss)| node(i as u32 + 2, "worker", *rss)).collect();
+    let total = server + workers.iter().sum::<u64>();
+    Snapshot {
+        version: "test".to_string(),
+        pattern: "server".to_string(),
+        matched: 1,
+        trees: vec![ProcessTree { root_pid: 1, process_count: workers.len() + 1, total_bytes: total, average_bytes: 0, root, ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn parses_percent_and_absolute_tolerances() {
+    assert_eq!(Tolerance::parse("10%"), Ok(Tolerance::Percent(10.0)));
+    assert_eq!(Tolerance::parse("+200M"), Ok(Tolerance::Bytes(200 * MB)));
+    assert_eq!(Tolerance::parse("64MB"), Ok(Tolerance::Bytes(64 * MB)));
+    assert!(Tolerance::parse("-5%").is_err());
+    assert!(Tolerance::parse("ten%").is_err());
+}
+
+#[test]
+fn flags_growth_beyond_tolerance() {
+    let before = snapshot(100 * MB, &[100 * MB]);
+    let after = snapshot(100 * MB, &[125 * MB]);
+    
+    let rows = compare(&before, &after, Tolerance::Percent(10.0), false);
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].regressed);
+    
+    assert!(!compare(&before, &after, Tolerance::Percent(15.0), false)[0].regressed);
+    assert!(!compare(&before, &after, Tolerance::Bytes(25 * MB), false)[0].regressed);
+    assert!(compare(&before, &after, Tolerance::Bytes(24 * MB), false)[0].regressed);
+    assert!(!compare(&after, &before, Tolerance::Percent(0.0), false)[0].regressed);
+}
+
+#[test]
+fn groups_by_process_name() {
+    let before = snapshot(100 * MB, &[50 * MB, 50 * MB]);
+    let after = snapshot(100 * MB, &[60 * MB, 60 * MB]);
+    let rows = compare(&before, &after, Tolerance::Percent(10.0), true);
+    let summary: Vec<(&str, u64, u64, bool)> = rows.iter().map(|row| (row.label.as_str(), row.baseline, row.current, row.regressed)).collect();
+    assert_eq!(summary, vec![
+        ("total", 200 * MB, 220 * MB, false),
+        ("server", 100 * MB, 100 * MB, false),
+        ("worker", 100 * MB, 120 * MB, true),
+    ]);
+}
+
+#[test]
+fn renders_aligned_table() {
+    let before = snapshot(100 * MB, &[100 * MB]);
+    let after = snapshot(100 * MB, &[150 * MB]);
+    let table = render_table(&compare(&before, &after, Tolerance::Percent(10.0), false), Tolerance::Percent(10.0));
+    assert_eq!(table, "\
+Group  Baseline  Current            Change
+total   200.0MB  250.0MB  +50.0MB (+25.0%)  REGRESSION
+1 of 1 rows grew more than the tolerance (10%)
+");
+}
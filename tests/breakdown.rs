@@ -0,0 +1,48 @@
+// --breakdown: the root's own memory against its descendants' in each tree's
+// header, in text, JSON and HTML
+
+use std::process::Command;
+
+use serde_json::Value;
+
+fn memon(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_memon"))
+        .args(args)
+        .args(["--from-file", "tests/fixtures/forest.table", "--breakdown"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn header_splits_the_total_at_the_root() {
+    let output = memon(&["postgres"]);
+    assert!(output.starts_with("postgres (pid 10): 8.0MB self + 184.0MB descendants = 192.0MB\n10 postgres"), "{}", output);
+    
+    let output = memon(&["redis-server"]);
+    assert!(output.starts_with("redis-server (pid 2000): 300.0MB self, no descendants\n"), "{}", output);
+}
+
+#[test]
+fn a_root_that_is_the_largest_process_comes_last() {
+    let output = memon(&["worker"]);
+    assert!(output.contains("worker (pid 31): 1.0MB descendants + 16.0MB self (largest process) = 17.0MB\n"), "{}", output);
+}
+
+#[test]
+fn json_and_html_carry_the_breakdown() {
+    let snapshot: Value = serde_json::from_str(&memon(&["postgres", "--json"])).unwrap();
+    let tree = &snapshot["trees"][0];
+    assert_eq!(tree["self_bytes"], 8 * 1024 * 1024);
+    assert_eq!(tree["descendants_bytes"], 184 * 1024 * 1024);
+    
+    let html = memon(&["postgres", "--format", "html"]);
+    assert!(html.contains("<h2>postgres (PID 10)</h2>\n<p class=\"breakdown\">postgres (pid 10): 8.0MB self + 184.0MB descendants = 192.0MB</p>"), "{}", html);
+}
+
+#[test]
+fn merged_roots_have_no_breakdown() {
+    let snapshot: Value = serde_json::from_str(&memon(&["worker", "--merge-roots", "--json"])).unwrap();
+    assert!(snapshot["trees"][0].get("self_bytes").is_none());
+}
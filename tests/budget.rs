@@ -0,0 +1,90 @@
+// --budget gauge: levels, summary lines and the JSON section, and when the
+// summary total is highlighted
+
+use memon::budget::{self, Budget, BudgetLevel};
+use memon::format::OutputFormat;
+use memon::options::Columns;
+use memon::render::{self, render_snapshot, render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+
+const MB: u64 = 1024 * 1024;
+
+fn snapshot(budget: Option<u64>) -> Snapshot {
+    let tree = |pid: u32, rss: u64| ProcessTree::new(ProcessNode {
+        pid, name: "frontend".to_string(), rss_bytes: rss, cumulative_bytes: rss, rank: Some(1), ..Default::default()
+    });
+    let trees = vec![tree(10, 300 * MB), tree(20, 100 * MB)];
+    let used = trees.iter().map(|tree| tree.total_bytes).sum();
+    Snapshot {
+        version: "0.1.0".to_string(),
+        pattern: "frontend".to_string(),
+        matched: 2,
+        trees,
+        budget: budget.map(|budget| Budget::new(budget, used)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn levels_switch_above_eighty_and_one_hundred_percent() {
+    assert_eq!(Budget::new(1000, 800).level(), BudgetLevel::Normal);
+    assert_eq!(Budget::new(1000, 801).level(), BudgetLevel::Warning);
+    assert_eq!(Budget::new(1000, 1000).level(), BudgetLevel::Warning);
+    assert!(!Budget::new(1000, 1000).exceeded());
+    assert_eq!(Budget::new(1000, 1001).level(), BudgetLevel::Over);
+    assert!(Budget::new(1000, 1001).exceeded());
+    assert_eq!(budget::parse_budget("6G"), Ok(6 * 1024 * MB));
+    assert!(budget::parse_budget("0").is_err());
+}
+
+#[test]
+fn text_summary_shows_tree_shares_and_overall_gauge() {
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(2, 8);
+    let text = render_to_string(&renderer, &snapshot(Some(500 * MB)));
+    assert!(text.contains("1 procs | 300.0MB avg | 300.0MB total | 60% of budget\n"));
+    assert!(text.contains("1 procs | 100.0MB avg | 100.0MB total | 20% of budget\n"));
+    assert!(text.ends_with("\nused 400.0MB of 500.0MB budget (80%)\n"));
+    
+    let renderer = TextRenderer::new(Theme::ANSI, Columns::default(), None);
+    assert!(render_to_string(&renderer, &snapshot(Some(450 * MB))).contains("budget (\x1b[33m89%\x1b[0m)"));
+    assert!(render_to_string(&renderer, &snapshot(Some(300 * MB))).contains("budget (\x1b[31m133%\x1b[0m)"));
+    
+    let renderer = TextRenderer::plain();
+    assert!(!render_to_string(&renderer, &snapshot(None)).contains("budget"));
+}
+
+#[test]
+fn only_significant_totals_are_highlighted() {
+    const GB: u64 = 1024 * MB;
+    assert!(render::is_significant(2 * GB, Some(16 * GB), None));
+    assert!(!render::is_significant(GB, Some(16 * GB), None));
+    assert!(!render::is_significant(GB, None, None));
+    assert!(render::is_significant(GB, None, Some(GB - 1)));
+    assert!(!render::is_significant(GB, Some(0), Some(GB)));
+    
+    // The 300MB tree is over a 200MB budget, the 100MB one is not; averages never are
+    let highlighted = |bytes: &str| format!("{}{}{}", Theme::ANSI.highlight, bytes, Theme::ANSI.reset);
+    let renderer = TextRenderer::new(Theme::ANSI, Columns::default(), None);
+    let text = render_to_string(&renderer, &snapshot(Some(200 * MB)));
+    assert!(text.contains(&format!("300.0MB avg | {} total", highlighted("300.0MB"))), "{:?}", text);
+    assert!(text.contains("100.0MB avg | 100.0MB total"), "{:?}", text);
+    
+    // Without a budget, a share of system memory decides: 300MB is over 10% of 2GB
+    let renderer = TextRenderer::new(Theme::ANSI, Columns::default(), None).with_system_memory(Some(2 * GB));
+    let text = render_to_string(&renderer, &snapshot(None));
+    assert!(text.contains(&highlighted("300.0MB")), "{:?}", text);
+    assert!(!text.contains(&highlighted("100.0MB")), "{:?}", text);
+    assert!(!render_to_string(&TextRenderer::new(Theme::ANSI, Columns::default(), None), &snapshot(None)).contains(Theme::ANSI.highlight));
+}
+
+#[test]
+fn json_includes_budget_and_usage() {
+    let snapshot = snapshot(Some(500 * MB));
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert_eq!(json, serde_json::to_string_pretty(&snapshot).unwrap() + "\n");
+    assert!(json.contains("\"budget_bytes\": 524288000"));
+    assert!(json.contains("\"used_bytes\": 419430400"));
+    
+    let parsed: Snapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.budget, snapshot.budget);
+}
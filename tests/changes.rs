@@ -0,0 +1,48 @@
+// Watch-mode change detection for --changes-only
+
+mod common;
+
+use memon::changes::{self, Change, ChangeKind};
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+use memon::units::SizeFormat;
+use common::node;
+
+const MB: u64 = 1024 * 1024;
+
+fn snapshot(root: ProcessNode) -> Snapshot {
+    Snapshot { version: "0.1.0".to_string(), pattern: "server".to_string(), matched: 0, trees: vec![ProcessTree::new(root)], ..Default::default() }
+}
+
+#[test]
+fn reports_started_exited_and_changes_above_the_threshold() {
+    let before = snapshot(node(1, "server", 10 * MB, vec![node(2, "worker", 20 * MB, Vec::new()), node(3, "worker", 5 * MB, Vec::new())]));
+    let after = snapshot(node(1, "server", 10 * MB + 1000, vec![node(2, "worker", 24 * MB, Vec::new()), node(4, "worker", 6 * MB, Vec::new())]));
+    let changes = changes::diff(&before, &after, MB);
+    let summary: Vec<(ChangeKind, u32, i64)> = changes.iter().map(|change| (change.kind, change.pid, change.delta_bytes)).collect();
+    assert_eq!(summary, vec![
+        (ChangeKind::Changed, 2, 4 * MB as i64),
+        (ChangeKind::Exited, 3, -5 * MB as i64),
+        (ChangeKind::Started, 4, 6 * MB as i64),
+    ]);
+}
+
+#[test]
+fn reused_pid_is_an_exit_and_a_start() {
+    let before = snapshot(node(7, "worker", MB, Vec::new()));
+    let after = snapshot(node(7, "compactor", MB, Vec::new()));
+    let kinds: Vec<ChangeKind> = changes::diff(&before, &after, MB).iter().map(|change| change.kind).collect();
+    assert_eq!(kinds, vec![ChangeKind::Exited, ChangeKind::Started]);
+}
+
+#[test]
+fn renders_text_and_ndjson() {
+    let changes = vec![
+        Change { kind: ChangeKind::Changed, pid: 2, name: "worker".to_string(), rss_bytes: 24 * MB, delta_bytes: -4 * MB as i64, last_change_epoch: None },
+        Change { kind: ChangeKind::Started, pid: 4, name: "worker".to_string(), rss_bytes: 6 * MB, delta_bytes: 6 * MB as i64, last_change_epoch: None },
+    ];
+    assert_eq!(changes::render_text("2026-01-02 03:04:05 UTC", &changes, &SizeFormat::default()),
+               "[2026-01-02 03:04:05 UTC] 2 changes\n~ 2 worker 24.0MB (-4.0MB)\n+ 4 worker started at 6.0MB\n");
+    assert_eq!(changes::render_ndjson(1700000000, &changes[1..]),
+               "{\"timestamp\":1700000000,\"kind\":\"started\",\"pid\":4,\"name\":\"worker\",\"rss_bytes\":6291456,\"delta_bytes\":6291456}\n");
+    assert_eq!(changes::render_ndjson(1700000000, &[]), "");
+}
@@ -0,0 +1,52 @@
+// Tree-total charts for watch mode and `memon report`
+
+use memon::chart::{self, TotalHistory};
+
+const MB: u64 = 1024 * 1024;
+
+#[test]
+fn renders_a_known_series() {
+    let series = [10, 12, 11, 15, 20, 18].map(|mb| mb * MB);
+    let expected = [
+        "  20.0MB |    #",
+        "         |    ##",
+        "         |   ###",
+        "         |   ###",
+        "         | #####",
+        "  10.0MB |######",
+        "         +------",
+    ];
+    assert_eq!(chart::render(&series, 6, 60).lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn buckets_long_series_into_the_width() {
+    let series: Vec<u64> = (1..=8).map(|mb| mb * MB).collect();
+    let rows: Vec<String> = chart::render(&series, 2, 4).lines().map(str::to_string).collect();
+    // Each column is the larger of two samples: 2, 4, 6 and 8MB, on a 1-8MB axis
+    assert_eq!(rows, vec!["  8.0MB |  ##", "  1.0MB |####", "        +----"]);
+}
+
+#[test]
+fn flat_series_fills_every_row() {
+    let chart = chart::render(&[5 * MB; 3], 3, 60);
+    assert!(chart.lines().take(3).all(|row| row.ends_with("|###")));
+}
+
+#[test]
+fn history_keeps_the_latest_samples() {
+    let mut history = TotalHistory::new(3);
+    assert!(history.is_empty());
+    for total in 1..=5 {
+        history.push(total);
+    }
+    assert_eq!(history.totals(), vec![3, 4, 5]);
+    assert_eq!(history.len(), 3);
+}
+
+#[test]
+fn parses_chart_height() {
+    assert_eq!(chart::parse_height("8"), Ok(8));
+    assert!(chart::parse_height("1").is_err());
+    assert!(chart::parse_height("tall").is_err());
+}
@@ -0,0 +1,61 @@
+// --check exit statuses, probing a real child process
+
+#![cfg(unix)]
+
+mod common;
+
+use std::process::{Command, Output};
+
+use memon::check::{self, CheckOutcome, EXIT_EXCEEDED, EXIT_NO_MATCH, EXIT_OK};
+use memon::snapshot::Snapshot;
+use common::Probe;
+
+fn memon(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_memon")).args(args).output().unwrap()
+}
+
+fn stdout_lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()
+}
+
+#[test]
+fn healthy_process_exits_zero_with_one_line() {
+    let probe = Probe::start("a");
+    let output = memon(&["--check", &probe.name, "--fail-if-total", "1G"]);
+    assert_eq!(output.status.code(), Some(EXIT_OK));
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].starts_with(&format!("OK {}: 1 procs in 1 trees, ", probe.name)), "{}", lines[0]);
+}
+
+#[test]
+fn missing_process_exits_one() {
+    let output = memon(&["--check", "memon-no-such-process"]);
+    assert_eq!(output.status.code(), Some(EXIT_NO_MATCH));
+    assert_eq!(stdout_lines(&output), vec!["NO MATCH memon-no-such-process: no matching process"]);
+}
+
+#[test]
+fn exceeded_threshold_exits_two() {
+    let probe = Probe::start("b");
+    let output = memon(&["--check", &probe.name, "--fail-if-process", "1K"]);
+    assert_eq!(output.status.code(), Some(EXIT_EXCEEDED));
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].starts_with(&format!("EXCEEDED {}: {} (", probe.name, probe.name)), "{}", lines[0]);
+    assert!(lines[0].ends_with("per-process limit"), "{}", lines[0]);
+}
+
+#[test]
+fn usage_errors_are_not_mistaken_for_exceeded_limits() {
+    assert_eq!(memon(&["--check", "sleep", "--watch", "5"]).status.code(), Some(1));
+    assert_eq!(memon(&["--help"]).status.code(), Some(0));
+}
+
+#[test]
+fn empty_snapshot_is_no_match() {
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "nginx".to_string(), matched: 0, trees: Vec::new(), ..Default::default() };
+    let outcome = check::evaluate(&snapshot, &Default::default(), false);
+    assert_eq!(outcome, CheckOutcome::NoMatch);
+    assert_eq!(outcome.exit_code(), EXIT_NO_MATCH);
+}
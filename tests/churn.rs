@@ -0,0 +1,89 @@
+// Restarts per role across watch samples (--churn)
+
+use std::collections::HashMap;
+
+use memon::churn::{self, ChurnTracker};
+use memon::process::ProcessInfo;
+use memon::snapshot::ProcessTree;
+use memon::source;
+
+fn sample(slots: &[(&str, u32, u64)]) -> Vec<(String, (u32, u64))> {
+    slots.iter().map(|&(path, pid, start_time)| (path.to_string(), (pid, start_time))).collect()
+}
+
+#[test]
+fn steady_pool_has_no_restarts() {
+    let mut tracker = ChurnTracker::default();
+    let pool = sample(&[("app", 1, 0), ("app > worker", 2, 0), ("app > worker", 3, 0), ("app > worker", 4, 0)]);
+    for _ in 0..3 {
+        tracker.observe(pool.clone());
+    }
+    assert_eq!(tracker.restarts("app > worker"), 0);
+    assert_eq!(tracker.total(), 0);
+    assert!(tracker.busiest().is_empty());
+}
+
+#[test]
+fn replaced_worker_counts_once_per_new_pid() {
+    let mut tracker = ChurnTracker::default();
+    tracker.observe(sample(&[("app", 1, 0), ("app > worker", 2, 10), ("app > worker", 3, 10)]));
+    tracker.observe(sample(&[("app", 1, 0), ("app > worker", 2, 10), ("app > worker", 4, 20)]));
+    tracker.observe(sample(&[("app", 1, 0), ("app > worker", 2, 10), ("app > worker", 5, 30)]));
+    // Back to an earlier PID is not a new process
+    tracker.observe(sample(&[("app", 1, 0), ("app > worker", 2, 10), ("app > worker", 5, 30)]));
+    assert_eq!(tracker.restarts("app > worker"), 2);
+    assert_eq!(tracker.restarts("app"), 0);
+    assert_eq!(tracker.total(), 2);
+}
+
+#[test]
+fn reused_pid_with_a_new_start_time_is_a_restart() {
+    let mut tracker = ChurnTracker::default();
+    tracker.observe(sample(&[("app > worker", 7, 100)]));
+    tracker.observe(sample(&[("app > worker", 7, 160)]));
+    assert_eq!(tracker.restarts("app > worker"), 1);
+}
+
+#[test]
+fn busiest_lists_slots_with_restarts_most_first() {
+    let mut tracker = ChurnTracker::default();
+    tracker.observe(sample(&[("app > beat", 2, 0), ("app > worker", 3, 0)]));
+    tracker.observe(sample(&[("app > beat", 4, 0), ("app > worker", 5, 0)]));
+    tracker.observe(sample(&[("app > beat", 4, 0), ("app > worker", 6, 0)]));
+    assert_eq!(tracker.busiest(), vec![("app > worker", 2), ("app > beat", 1)]);
+    assert_eq!(tracker.total(), 3);
+}
+
+#[test]
+fn slot_paths_join_names() {
+    assert_eq!(churn::slot_path("", "app"), "app");
+    assert_eq!(churn::slot_path("app", "worker"), "app > worker");
+}
+
+// supervisor 10 with workers under it, reading a fresh table per sample
+fn tree(workers: &[(u32, u64)]) -> (Vec<ProcessTree>, HashMap<u32, ProcessInfo>) {
+    let mut processes: HashMap<u32, ProcessInfo> = HashMap::new();
+    processes.insert(10, ProcessInfo::new(10, "supervisor".to_string(), 1024, None));
+    for &(pid, start_time) in workers {
+        let mut worker = ProcessInfo::new(pid, "worker".to_string(), 1024, Some(10));
+        worker.start_time = start_time;
+        processes.insert(pid, worker);
+    }
+    source::link_children(&mut processes);
+    let trees = ProcessTree::from_processes(&mut processes, 10).into_iter().collect();
+    (trees, processes)
+}
+
+#[test]
+fn apply_annotates_nodes_with_their_slot() {
+    let mut tracker = ChurnTracker::default();
+    let (mut trees, processes) = tree(&[(11, 5), (12, 5)]);
+    tracker.apply(&mut trees, &processes, false);
+    assert!(trees[0].root.restarts.is_none());
+    
+    let (mut trees, processes) = tree(&[(11, 5), (13, 9)]);
+    tracker.apply(&mut trees, &processes, true);
+    assert_eq!(trees[0].root.restarts, Some(0));
+    assert!(trees[0].root.children.iter().all(|worker| worker.restarts == Some(1)));
+    assert_eq!(tracker.busiest(), vec![("supervisor > worker", 1)]);
+}
@@ -0,0 +1,53 @@
+// Color resolution: --color flag, CLICOLOR_FORCE/FORCE_COLOR, NO_COLOR/CLICOLOR=0, terminal
+
+use memon::colors::{should_use_colors, ColorEnv};
+
+fn env(vars: &[(&str, &str)]) -> ColorEnv {
+    ColorEnv::from_lookup(|name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string()))
+}
+
+#[test]
+fn reads_force_and_disable_variables() {
+    assert_eq!(env(&[]), ColorEnv { force: false, disable: false });
+    assert!(env(&[("CLICOLOR_FORCE", "1")]).force);
+    assert!(!env(&[("CLICOLOR_FORCE", "0")]).force);
+    assert!(!env(&[("CLICOLOR_FORCE", "")]).force);
+    assert!(env(&[("FORCE_COLOR", "")]).force);
+    assert!(env(&[("FORCE_COLOR", "3")]).force);
+    assert_eq!(env(&[("FORCE_COLOR", "0")]), ColorEnv { force: false, disable: true });
+    assert_eq!(env(&[("FORCE_COLOR", "false")]), ColorEnv { force: false, disable: true });
+    assert!(env(&[("NO_COLOR", "1")]).disable);
+    assert!(!env(&[("NO_COLOR", "")]).disable);
+    assert!(env(&[("CLICOLOR", "0")]).disable);
+    assert!(!env(&[("CLICOLOR", "1")]).disable);
+}
+
+#[test]
+fn every_combination_follows_the_precedence() {
+    for flag in [None, Some(true), Some(false)] {
+        for force in [false, true] {
+            for disable in [false, true] {
+                for is_terminal in [false, true] {
+                    let expected = match flag {
+                        Some(enabled) => enabled,
+                        None if force => true,
+                        None if disable => false,
+                        None => is_terminal,
+                    };
+                    assert_eq!(should_use_colors(flag, ColorEnv { force, disable }, is_terminal), expected,
+                               "flag {:?}, force {}, disable {}, terminal {}", flag, force, disable, is_terminal);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn force_beats_no_color_and_flag_beats_both() {
+    let both = env(&[("FORCE_COLOR", "1"), ("NO_COLOR", "1")]);
+    assert!(should_use_colors(None, both, false));
+    assert!(!should_use_colors(Some(false), both, true));
+    assert!(should_use_colors(Some(true), env(&[("NO_COLOR", "1")]), false));
+    assert!(!should_use_colors(None, env(&[("CLICOLOR", "0")]), true));
+    assert!(should_use_colors(None, env(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")]), false));
+}
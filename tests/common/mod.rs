@@ -0,0 +1,48 @@
+// Builders and fixtures shared by the integration tests. Each test file uses
+// only some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+
+use memon::snapshot::ProcessNode;
+
+// A readable process of `rss` bytes above `children`, with its cumulative
+// memory and descendant count filled in. Other fields are set with struct
+// update syntax: `ProcessNode { rank: Some(1), ..node(...) }`
+pub fn node(pid: u32, name: &str, rss: u64, children: Vec<ProcessNode>) -> ProcessNode {
+    let cumulative_bytes = rss + children.iter().map(|child| child.cumulative_bytes).sum::<u64>();
+    let descendants = children.iter().map(|child| child.descendants + 1).sum();
+    ProcessNode { pid, name: name.to_string(), rss_bytes: rss, cumulative_bytes, descendants, children, ..Default::default() }
+}
+
+// A copy of `sleep` under a name no other process uses, killed on drop
+pub struct Probe {
+    pub name: String,
+    pub child: Child,
+    dir: PathBuf,
+}
+
+impl Probe {
+    pub fn start(tag: &str) -> Probe {
+        let name = format!("mprobe{}{}", tag, std::process::id() % 100_000);
+        let dir = std::env::temp_dir().join(&name);
+        fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join(&name);
+        let sleep = ["/bin/sleep", "/usr/bin/sleep"].into_iter().find(|path| fs::metadata(path).is_ok()).expect("sleep binary");
+        fs::copy(sleep, &binary).unwrap();
+        let child = Command::new(&binary).arg("30").spawn().unwrap();
+        // Give the process a moment to show up in the process table
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        Probe { name, child, dir }
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,65 @@
+// `memon compare`: per-forest summaries, the b - a diff and the aligned table
+
+mod common;
+
+use memon::compare::{diff, render_text, DepthTotal, ForestSummary};
+use memon::snapshot::{ProcessNode, Snapshot, ProcessTree};
+use common::node;
+
+const MB: u64 = 1024 * 1024;
+
+fn forest(pattern: &str, root: ProcessNode) -> Snapshot {
+    let total = root.cumulative_bytes;
+    Snapshot {
+        version: "test".to_string(),
+        pattern: pattern.to_string(),
+        matched: 1,
+        trees: vec![ProcessTree { root_pid: root.pid, process_count: root.descendants + 1, total_bytes: total, average_bytes: 0, root, ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+fn nginx() -> Snapshot {
+    forest("nginx", node(100, "nginx", 10 * MB, vec![node(101, "nginx", 30 * MB, Vec::new()), node(102, "nginx", 20 * MB, Vec::new())]))
+}
+
+fn caddy() -> Snapshot {
+    forest("caddy", node(200, "caddy", 40 * MB, Vec::new()))
+}
+
+#[test]
+fn summarizes_a_forest() {
+    let summary = ForestSummary::of(&nginx());
+    assert_eq!((summary.processes, summary.total_bytes, summary.average_bytes), (3, 60 * MB, 20 * MB));
+    assert_eq!(summary.max_process.map(|max| max.pid), Some(101));
+    assert_eq!(summary.by_depth, vec![
+        DepthTotal { depth: 0, processes: 1, bytes: 10 * MB },
+        DepthTotal { depth: 1, processes: 2, bytes: 50 * MB },
+    ]);
+}
+
+#[test]
+fn diff_is_b_minus_a() {
+    let d = diff(&ForestSummary::of(&nginx()), &ForestSummary::of(&caddy()));
+    assert_eq!(d.processes, -2);
+    assert_eq!(d.total_bytes, -20 * MB as i64);
+    assert_eq!(d.average_bytes, 20 * MB as i64);
+    assert_eq!(d.max_process_bytes, 10 * MB as i64);
+    let depths: Vec<(usize, i64)> = d.by_depth.iter().map(|depth| (depth.depth, depth.processes)).collect();
+    assert_eq!(depths, vec![(0, 0), (1, -2)]);
+}
+
+#[test]
+fn renders_aligned_columns() {
+    let expected = [
+        "                          nginx               caddy     diff",
+        "processes                     3                   1       -2",
+        "total                    60.0MB              40.0MB  -20.0MB",
+        "average                  20.0MB              40.0MB  +20.0MB",
+        "max process  nginx (101) 30.0MB  caddy (200) 40.0MB  +10.0MB",
+        "depth 0          1 procs 10.0MB      1 procs 40.0MB  +30.0MB",
+        "depth 1          2 procs 50.0MB                   -  -50.0MB",
+    ];
+    let text = render_text(&nginx(), &caddy());
+    assert_eq!(text.lines().collect::<Vec<_>>(), expected);
+}
@@ -0,0 +1,79 @@
+// The config file: location, parsing and load errors
+
+use std::path::{Path, PathBuf};
+
+use memon::config::{self, Config, ConfigError, OptionValue, PatternSection};
+
+#[test]
+fn labels_are_read_in_file_order() {
+    let config = Config::parse("[labels]\n\"Web Content\" = \"browser tab\"\nworker = \"queue worker\"\n").unwrap();
+    assert_eq!(config.labels, vec![
+        ("Web Content".to_string(), "browser tab".to_string()),
+        ("worker".to_string(), "queue worker".to_string()),
+    ]);
+    
+    // Inline tables work too, and unknown tables are ignored
+    let config = Config::parse("labels = { sleep = \"idle\" }\n[display]\ncolor = true\n").unwrap();
+    assert_eq!(config.labels, vec![("sleep".to_string(), "idle".to_string())]);
+    
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+}
+
+#[test]
+fn invalid_config_is_reported() {
+    assert!(Config::parse("[labels\n").is_err());
+    assert_eq!(Config::parse("labels = 3\n").unwrap_err(), "labels must be a table");
+    assert_eq!(Config::parse("[labels]\nworker = 1\n").unwrap_err(), "label for \"worker\" must be a string");
+}
+
+#[test]
+fn config_path_follows_xdg() {
+    let lookup = |xdg: Option<&str>, home: Option<&str>| config::config_path(|name| match name {
+        "XDG_CONFIG_HOME" => xdg.map(PathBuf::from),
+        "HOME" => home.map(PathBuf::from),
+        _ => None,
+    });
+    assert_eq!(lookup(Some("/xdg"), Some("/home/me")), Some(PathBuf::from("/xdg/memon/config.toml")));
+    assert_eq!(lookup(None, Some("/home/me")), Some(PathBuf::from("/home/me/.config/memon/config.toml")));
+    // A relative XDG_CONFIG_HOME is invalid and ignored, as the spec says
+    assert_eq!(lookup(Some("xdg"), Some("/home/me")), Some(PathBuf::from("/home/me/.config/memon/config.toml")));
+    assert_eq!(lookup(None, None), None);
+}
+
+#[test]
+fn load_reads_an_explicit_file() {
+    let path = std::env::temp_dir().join(format!("memon-config-{}.toml", std::process::id()));
+    std::fs::write(&path, "[labels]\nsleep = \"idle\"\n").unwrap();
+    let config = Config::load(Some(&path)).unwrap();
+    assert_eq!(config.labels, vec![("sleep".to_string(), "idle".to_string())]);
+    
+    std::fs::write(&path, "[labels]\nsleep = 1\n").unwrap();
+    assert!(matches!(Config::load(Some(&path)), Err(ConfigError::Parse(..))));
+    std::fs::remove_file(&path).unwrap();
+    
+    // A missing explicit file is an error
+    assert!(matches!(Config::load(Some(Path::new("/nonexistent/memon.toml"))), Err(ConfigError::Io(..))));
+}
+
+#[test]
+fn pattern_sections_are_read_in_file_order() {
+    let config = Config::parse("[pattern.\"postgres\"]\npeak = true\ngroup_by = \"exe\"\nmax-trees = 2\nredact-args = [\"secret\", \"token\"]\n\n[pattern.\"post*\"]\nfirst = false\n").unwrap();
+    assert_eq!(config.patterns, vec![
+        PatternSection { pattern: "postgres".to_string(), options: vec![
+            ("peak".to_string(), OptionValue::Switch(true)),
+            // Underscores are read as the dashes of the long option
+            ("group-by".to_string(), OptionValue::Values(vec!["exe".to_string()])),
+            ("max-trees".to_string(), OptionValue::Values(vec!["2".to_string()])),
+            ("redact-args".to_string(), OptionValue::Values(vec!["secret".to_string(), "token".to_string()])),
+        ] },
+        PatternSection { pattern: "post*".to_string(), options: vec![("first".to_string(), OptionValue::Switch(false))] },
+    ]);
+    let headers: Vec<String> = config.sections_for("postgres").iter().map(|section| section.header()).collect();
+    assert_eq!(headers, ["[pattern.\"postgres\"]", "[pattern.\"post*\"]"]);
+    assert_eq!(config.sections_for("postmaster").len(), 1);
+    assert!(config.sections_for("nginx").is_empty());
+    
+    assert_eq!(Config::parse("pattern = 1\n").unwrap_err(), "pattern must be a table");
+    assert_eq!(Config::parse("[pattern]\nnginx = 1\n").unwrap_err(), "pattern \"nginx\" must be a table");
+    assert_eq!(Config::parse("[pattern.nginx]\npeak = { on = true }\n").unwrap_err(), "option \"peak\" of pattern \"nginx\" must be a boolean, number, string or array");
+}
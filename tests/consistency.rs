@@ -0,0 +1,96 @@
+// Reused PIDs and re-parented processes across refreshes
+
+use std::collections::{HashMap, VecDeque};
+
+use memon::consistency::{TableCheck, TableEvent};
+use memon::process::ProcessInfo;
+use memon::snapshot::ProcessTree;
+use memon::source::{self, ProcessSource, SourceError};
+
+// (pid, ppid, start_time, name)
+type Row = (u32, Option<u32>, u64, &'static str);
+
+// One table per refresh
+struct ScriptedSource {
+    refreshes: VecDeque<Vec<Row>>,
+}
+
+impl ProcessSource for ScriptedSource {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+        let table = self.refreshes.pop_front().expect("a table for every refresh");
+        Ok(table
+            .into_iter()
+            .map(|(pid, ppid, start_time, name)| {
+                let mut proc_info = ProcessInfo::new(pid, name.to_string(), 1024, ppid);
+                proc_info.start_time = start_time;
+                (pid, proc_info)
+            })
+            .collect())
+    }
+}
+
+// nginx 20 with worker 30; then nginx exits, the worker is adopted by init
+// and PID 20 goes to a new shell that started after the worker
+fn reused_parent() -> ScriptedSource {
+    ScriptedSource { refreshes: VecDeque::from([
+        vec![(1, None, 10, "init"), (20, Some(1), 100, "nginx"), (30, Some(20), 110, "nginx")],
+        vec![(1, None, 10, "init"), (20, Some(1), 200, "bash"), (30, Some(1), 110, "nginx")],
+    ]) }
+}
+
+#[test]
+fn reused_and_reparented_pids_are_reported_in_pid_order() {
+    let mut source = reused_parent();
+    let mut check = TableCheck::default();
+    assert!(check.check(&source.load().unwrap()).is_empty());
+    
+    let events = check.check(&source.load().unwrap());
+    assert_eq!(events, [
+        TableEvent::Reused { pid: 20, old_start: 100, new_start: 200 },
+        TableEvent::Reparented { pid: 30, old_parent: Some(20), new_parent: Some(1) },
+    ]);
+    assert_eq!(events[0].stale_identity(), Some((20, 100)));
+    assert_eq!(events[1].stale_identity(), None);
+    assert_eq!(events[0].to_string(), "PID 20 was reused: started at 200, before at 100");
+    assert_eq!(events[1].to_string(), "PID 30 changed parent from 20 to 1");
+}
+
+#[test]
+fn a_child_is_never_linked_under_a_younger_parent() {
+    // The table was read before the kernel re-parented the worker, so it
+    // still names PID 20, which now belongs to the shell
+    let mut source = ScriptedSource { refreshes: VecDeque::from([
+        vec![(1, None, 10, "init"), (20, Some(1), 200, "bash"), (30, Some(20), 110, "nginx")],
+    ]) };
+    let mut processes = source.load().unwrap();
+    source::link_children(&mut processes);
+    assert!(processes[&20].children.is_empty());
+    assert_eq!(processes[&30].parent_pid, None);
+    assert_eq!(source::table_roots(&processes), [1, 30]);
+    
+    let tree = ProcessTree::from_processes(&mut processes, 20).unwrap();
+    assert_eq!(tree.process_count, 1);
+}
+
+#[test]
+fn unknown_start_times_are_linked_as_listed() {
+    let mut source = ScriptedSource { refreshes: VecDeque::from([
+        vec![(20, None, 0, "bash"), (30, Some(20), 110, "nginx")],
+    ]) };
+    let mut processes = source.load().unwrap();
+    source::link_children(&mut processes);
+    assert_eq!(processes[&20].children, [30]);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "which started after it")]
+fn building_a_tree_checks_the_start_times() {
+    let mut source = ScriptedSource { refreshes: VecDeque::from([
+        vec![(20, None, 200, "bash"), (30, Some(20), 110, "nginx")],
+    ]) };
+    // Linked by hand, skipping link_children
+    let mut processes = source.load().unwrap();
+    processes.get_mut(&20).unwrap().add_child(30);
+    ProcessTree::from_processes(&mut processes, 20);
+}
@@ -0,0 +1,87 @@
+// Folding identical sibling subtrees (--dedupe-subtrees)
+
+mod common;
+
+use memon::dedupe::{self, SimilarGroup};
+use memon::snapshot::{ProcessNode, ProcessTree};
+use memon::stats::TreeStats;
+use common::node;
+
+const MB: u64 = 1024 * 1024;
+
+fn worker(pid: u32, rss: u64, helpers: &[&str]) -> ProcessNode {
+    let children = helpers.iter().enumerate().map(|(i, name)| node(pid + 1 + i as u32, name, MB, Vec::new())).collect();
+    node(pid, "worker", rss, children)
+}
+
+#[test]
+fn fingerprint_ignores_pids_memory_and_child_order() {
+    let a = worker(10, 5 * MB, &["helper", "logger"]);
+    let mut b = worker(20, 50 * MB, &["logger", "helper"]);
+    b.children[0].rss_bytes = 9 * MB;
+    assert_eq!(dedupe::fingerprint(&a), dedupe::fingerprint(&b));
+}
+
+#[test]
+fn almost_identical_subtrees_keep_distinct_fingerprints() {
+    let base = dedupe::fingerprint(&worker(10, MB, &["helper", "helper"]));
+    // One helper more
+    assert_ne!(base, dedupe::fingerprint(&worker(10, MB, &["helper", "helper", "helper"])));
+    // Same count, one name differs
+    assert_ne!(base, dedupe::fingerprint(&worker(10, MB, &["helper", "helpers"])));
+    // Same names, one helper a level deeper
+    let deeper = node(10, "worker", MB, vec![node(11, "helper", MB, vec![node(12, "helper", MB, Vec::new())])]);
+    assert_ne!(base, dedupe::fingerprint(&deeper));
+    // Same shape under a differently named root
+    let mut renamed = worker(10, MB, &["helper", "helper"]);
+    renamed.name = "worker2".to_string();
+    assert_ne!(base, dedupe::fingerprint(&renamed));
+}
+
+#[test]
+fn folds_groups_into_the_first_member() {
+    let mut root = node(1, "pool", MB, vec![
+        worker(10, 10 * MB, &["helper", "helper"]),
+        worker(20, 30 * MB, &["helper", "helper"]),
+        worker(30, 20 * MB, &["helper"]),
+        worker(40, 40 * MB, &["helper", "helper"]),
+    ]);
+    dedupe::dedupe(&mut root);
+    let pids: Vec<u32> = root.children.iter().map(|child| child.pid).collect();
+    assert_eq!(pids, vec![10, 30]);
+    assert_eq!(root.children[0].similar, Some(SimilarGroup {
+        count: 3,
+        total_bytes: 86 * MB,
+        min_bytes: 12 * MB,
+        median_bytes: 32 * MB,
+        max_bytes: 42 * MB,
+    }));
+    assert_eq!(root.children[1].similar, None);
+    // The helpers inside the representative fold in turn
+    assert_eq!(root.children[0].children.len(), 1);
+    assert_eq!(root.children[0].children[0].similar.map(|group| group.count), Some(2));
+}
+
+#[test]
+fn median_of_an_even_group_is_the_mean_of_the_middle_two() {
+    let mut root = node(1, "pool", MB, vec![node(2, "worker", 4 * MB, Vec::new()), node(3, "worker", 2 * MB, Vec::new())]);
+    dedupe::dedupe(&mut root);
+    assert_eq!(root.children[0].similar.map(|group| group.median_bytes), Some(3 * MB));
+}
+
+#[test]
+fn tree_totals_still_count_every_process() {
+    let root = node(1, "pool", MB, vec![
+        worker(10, 10 * MB, &["helper", "helper"]),
+        worker(20, 30 * MB, &["helper", "helper"]),
+        node(30, "logger", MB, Vec::new()),
+    ]);
+    let mut tree = ProcessTree::new(root);
+    let before = TreeStats::from_tree(&tree);
+    dedupe::dedupe(&mut tree.root);
+    let after = TreeStats::from_tree(&tree);
+    // pool, one worker with one helper, logger
+    assert_eq!(tree.len(), 4);
+    assert_eq!((after.count, after.total_rss), (before.count, before.total_rss));
+    assert_eq!((after.count, after.total_rss), (8, 46 * MB));
+}
@@ -0,0 +1,67 @@
+// Degraded data collection: the list kept per sample, --strict and --verbose
+
+use std::process::Command;
+
+use memon::degradation::{self, Degradation};
+use memon::platform;
+
+const TABLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/other_users.table");
+
+#[test]
+fn failed_reads_tell_exited_processes_apart() {
+    let alive = |pid: u32| match pid {
+        10 | 11 => Some(true),
+        12 => Some(false),
+        _ => None,
+    };
+    assert_eq!(Degradation::failed_reads("command lines", vec![10, 11, 12, 13], alive), [
+        Degradation::Unreadable { what: "command lines", pids: vec![10, 11, 13] },
+        Degradation::Exited { what: "command lines", pids: vec![12] },
+    ]);
+    assert!(Degradation::failed_reads("memory", Vec::new(), alive).is_empty());
+}
+
+#[test]
+fn degradations_read_as_one_line_each() {
+    let degradations = [
+        Degradation::Unreadable { what: "memory", pids: (1..=12).collect() },
+        Degradation::Exited { what: "cgroups", pids: vec![42] },
+        Degradation::TimedOut { what: "smaps", count: 3 },
+        Degradation::Unavailable { what: "pod names", reason: "connection refused".to_string() },
+    ];
+    assert_eq!(degradation::summary(&degradations), "  memory: unreadable for 12 processes (PIDs 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, \u{2026} and 2 more)
+  cgroups: 1 process exited mid-scan (PID 42)
+  smaps: 3 processes still pending at the timeout
+  pod names: unavailable: connection refused
+");
+}
+
+#[cfg(unix)]
+#[test]
+fn exited_processes_are_detected() {
+    assert_eq!(platform::process_exists(std::process::id()), Some(true));
+    let mut child = Command::new("true").spawn().unwrap();
+    let pid = child.id();
+    child.wait().unwrap();
+    assert_eq!(platform::process_exists(pid), Some(false));
+}
+
+#[test]
+fn strict_fails_on_unreadable_memory() {
+    let memon = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_memon")).args(["--from-file", TABLE]).args(args).output().unwrap();
+    
+    let output = memon(&["sshd", "--strict"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("--strict: data collection was degraded:\n  memory: unreadable for 3 processes (PIDs 301, 302, 303)"), "{}", stderr);
+    
+    // Without it the report stands, with the list only for --verbose
+    let output = memon(&["sshd"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Degraded"));
+    let output = memon(&["sshd", "--verbose"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Degraded data collection:\n  memory: unreadable"));
+    
+    // Readable trees pass
+    assert_eq!(memon(&["bash", "--strict", "--tree-root", "305"]).status.code(), Some(0));
+}
@@ -0,0 +1,67 @@
+// --exec-column: parsing, single runs and the refresh interval
+
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use memon::exec_column::{parse_exec_column, run, ExecColumn, ExecOutcome, ExecRunner, ExecStats};
+
+#[test]
+fn parses_name_and_command() {
+    let column = parse_exec_column("ticket=cat /run/tags/$MEMON_PID").unwrap();
+    assert_eq!(column, ExecColumn { name: "ticket".to_string(), command: "cat /run/tags/$MEMON_PID".to_string() });
+    // Only the first `=` separates the name
+    assert_eq!(parse_exec_column("flag=test $X = 1").unwrap().command, "test $X = 1");
+    assert!(parse_exec_column("ticket").is_err());
+    assert!(parse_exec_column("=echo").is_err());
+    assert!(parse_exec_column("two words=echo").is_err());
+    assert!(parse_exec_column("ticket=  ").is_err());
+}
+
+#[test]
+fn runs_with_memon_pid_and_keeps_the_first_trimmed_line() {
+    let timeout = Duration::from_secs(5);
+    assert_eq!(run("echo \"  pid $MEMON_PID  \"; echo second", 42, timeout), ExecOutcome::Output("pid 42".to_string()));
+    assert_eq!(run("true", 42, timeout), ExecOutcome::Output(String::new()));
+    assert_eq!(run("echo partial; exit 3", 42, timeout), ExecOutcome::Failed);
+}
+
+#[test]
+fn slow_commands_are_killed() {
+    let started = Instant::now();
+    assert_eq!(run("sleep 10", 1, Duration::from_millis(100)), ExecOutcome::TimedOut);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn refresh_counts_failures_and_keys_values_by_start_time() {
+    let column = parse_exec_column("half=[ $((MEMON_PID % 2)) -eq 0 ] && echo even-$MEMON_PID").unwrap();
+    let mut runner = ExecRunner::new(column, Duration::ZERO);
+    let stats = runner.refresh(&[(10, 100), (11, 100), (12, 200)], 8);
+    assert_eq!(stats, ExecStats { ran: 3, failed: 1, timed_out: 0 });
+    assert_eq!(runner.value(10, 100), Some("even-10"));
+    assert_eq!(runner.value(11, 100), None);
+    assert_eq!(runner.value(12, 200), Some("even-12"));
+    // A reused PID is another process
+    assert_eq!(runner.value(12, 300), None);
+    
+    let column = parse_exec_column("slow=sleep 10").unwrap();
+    let mut runner = ExecRunner::new(column, Duration::ZERO).with_timeout(Duration::from_millis(100));
+    assert_eq!(runner.refresh(&[(1, 0)], 1), ExecStats { ran: 1, failed: 0, timed_out: 1 });
+}
+
+#[test]
+fn refreshes_at_most_once_per_interval() {
+    let column = parse_exec_column("x=echo x").unwrap();
+    let mut runner = ExecRunner::new(column, Duration::from_secs(60));
+    let now = Instant::now();
+    assert!(runner.due(now));
+    runner.refresh(&[(1, 0)], 1);
+    assert!(!runner.due(Instant::now()));
+    assert!(runner.due(now + Duration::from_secs(61)));
+    
+    let column = parse_exec_column("x=echo x").unwrap();
+    let mut runner = ExecRunner::new(column, Duration::ZERO);
+    runner.refresh(&[(1, 0)], 1);
+    assert!(runner.due(Instant::now()));
+}
@@ -0,0 +1,60 @@
+// Cargo features: the flags of a compiled-out subsystem are gone, and the
+// feature table agrees with Cargo.toml
+
+use clap::{CommandFactory, Parser};
+use memon::cli::Args;
+use memon::features::{self, FEATURES, MATRIX};
+use toml_edit::DocumentMut;
+
+fn cargo_features() -> Vec<String> {
+    let manifest: DocumentMut = include_str!("../Cargo.toml").parse().unwrap();
+    manifest["features"].as_table().unwrap().iter().map(|(name, _)| name.to_string()).collect()
+}
+
+#[test]
+fn flags_exist_only_with_their_feature() {
+    let help = Args::command().render_long_help().to_string();
+    for feature in FEATURES {
+        for flag in feature.flags {
+            assert_eq!(help.contains(flag), feature.enabled, "{} in --help with `{}` {}", flag, feature.name,
+                       if feature.enabled { "on" } else { "off" });
+            if !feature.enabled {
+                assert!(Args::try_parse_from(["memon", "app", flag]).is_err(), "{} accepted", flag);
+            }
+        }
+    }
+}
+
+#[test]
+fn subcommands_exist_only_with_their_feature() {
+    let command = Args::command();
+    for feature in FEATURES {
+        for name in feature.subcommands {
+            assert_eq!(command.find_subcommand(name).is_some(), feature.enabled, "`memon {}` with `{}` {}", name, feature.name,
+                       if feature.enabled { "on" } else { "off" });
+        }
+    }
+}
+
+#[test]
+fn feature_table_matches_the_manifest() {
+    let declared = cargo_features();
+    for feature in FEATURES {
+        assert!(declared.contains(&feature.name.to_string()), "`{}` is not a cargo feature", feature.name);
+    }
+    // Every cargo feature but the two bundles is listed
+    for name in declared.iter().filter(|name| !["default", "full"].contains(&name.as_str())) {
+        assert!(FEATURES.iter().any(|feature| feature.name == name), "`{}` missing from FEATURES", name);
+    }
+    for combination in MATRIX {
+        assert!(combination.iter().all(|name| declared.contains(&name.to_string())), "{:?}", combination);
+    }
+}
+
+#[test]
+fn this_build_is_listed() {
+    let enabled = features::enabled();
+    assert_eq!(enabled.contains(&"cli"), cfg!(feature = "cli"));
+    assert_eq!(enabled.contains(&"http"), cfg!(feature = "http"));
+    assert_eq!(features::missing("nvml"), "memon was built without the `nvml` feature");
+}
@@ -0,0 +1,66 @@
+// --fine: samples between watch renders catch processes shorter than the interval
+
+use std::collections::{HashMap, HashSet};
+
+use clap::Parser;
+use memon::cli::Args;
+use memon::fine::{self, FineReport, FineWindow, MAX_IDENTITIES};
+use memon::process::ProcessInfo;
+use memon::source;
+use memon::units::SizeFormat;
+
+const MB: u64 = 1024 * 1024;
+
+// make 10 with the compilers alive at one fine sample
+fn table(compilers: &[(u32, u64)]) -> HashMap<u32, ProcessInfo> {
+    let mut processes = HashMap::from([(10, ProcessInfo::new(10, "make".to_string(), 10 * MB, Some(1)))]);
+    for &(pid, rss) in compilers {
+        let mut proc_info = ProcessInfo::new(pid, "cc1".to_string(), rss, Some(10));
+        proc_info.start_time = 1_700_000_000 + pid as u64;
+        processes.insert(pid, proc_info);
+    }
+    source::link_children(&mut processes);
+    processes
+}
+
+fn identities(pids: &[u32]) -> HashSet<(u32, u64)> {
+    pids.iter().map(|&pid| (pid, if pid == 10 { 0 } else { 1_700_000_000 + pid as u64 })).collect()
+}
+
+#[test]
+fn compilers_between_renders_raise_the_peak_and_are_counted() {
+    let mut window = FineWindow::default();
+    // The first render closes an empty window
+    assert_eq!(window.finish(identities(&[10]), 10 * MB), None);
+    
+    window.observe(&table(&[(20, 300 * MB)]), &[10]);
+    window.observe(&table(&[(20, 400 * MB), (21, 200 * MB)]), &[10]);
+    window.observe(&table(&[(22, 100 * MB)]), &[10]);
+    // 22 is still running at the render, so only 20 and 21 were missed
+    let report = window.finish(identities(&[10, 22]), 120 * MB);
+    assert_eq!(report, Some(FineReport { samples: 3, peak_total: 610 * MB, short_lived: 2, saturated: false }));
+    
+    // Processes of the previous render are not new in the next window
+    window.observe(&table(&[(22, 100 * MB)]), &[10]);
+    let report = window.finish(identities(&[10]), 10 * MB).unwrap();
+    assert_eq!((report.samples, report.peak_total, report.short_lived), (1, 110 * MB, 0));
+}
+
+#[test]
+fn the_identity_set_is_bounded() {
+    let mut window = FineWindow::default();
+    let compilers: Vec<(u32, u64)> = (0..MAX_IDENTITIES as u32 + 5).map(|i| (100 + i, MB)).collect();
+    window.observe(&table(&compilers), &[10]);
+    let report = window.finish(HashSet::new(), 0).unwrap();
+    // make itself takes one of the places
+    assert_eq!(report.short_lived, MAX_IDENTITIES);
+    assert!(report.saturated);
+    assert_eq!(fine::line(&report, 200, &SizeFormat::default()), "fine 200ms: peak 9.8GB over 1 samples, at least 10,000 short-lived procs\n");
+}
+
+#[test]
+fn fine_needs_watch_mode() {
+    assert_eq!(Args::try_parse_from(["memon", "make", "--watch", "5", "--fine", "200"]).unwrap().fine, Some(200));
+    assert!(Args::try_parse_from(["memon", "make", "--fine", "200"]).is_err());
+    assert!(Args::try_parse_from(["memon", "make", "--watch", "5", "--fine", "1"]).is_err());
+}
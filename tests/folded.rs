@@ -0,0 +1,47 @@
+// Folded-stack export for flamegraphs (--folded)
+
+mod common;
+
+use memon::folded;
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+use common::node;
+
+const KB: u64 = 1024;
+
+fn snapshot(roots: Vec<ProcessNode>) -> Snapshot {
+    Snapshot { version: String::new(), pattern: "nginx".to_string(), matched: roots.len(), trees: roots.into_iter().map(ProcessTree::new).collect(), ..Default::default() }
+}
+
+#[test]
+fn one_line_per_path_with_own_rss_in_kb() {
+    let tree = node(1, "nginx", 4 * KB, vec![
+        node(2, "worker", 100 * KB, vec![node(4, "helper", 10 * KB + 1023, Vec::new())]),
+        node(3, "cache", 50 * KB, Vec::new()),
+    ]);
+    assert_eq!(folded::render(&snapshot(vec![tree])), "\
+nginx 4
+nginx;worker 100
+nginx;worker;helper 10
+nginx;cache 50
+");
+}
+
+#[test]
+fn same_paths_are_summed_across_siblings_and_trees() {
+    let first = node(1, "nginx", 4 * KB, vec![node(2, "worker", 100 * KB, Vec::new()), node(3, "worker", 120 * KB, Vec::new())]);
+    let second = node(10, "nginx", 6 * KB, vec![node(11, "worker", 80 * KB, Vec::new())]);
+    assert_eq!(folded::render(&snapshot(vec![first, second])), "nginx 10\nnginx;worker 300\n");
+}
+
+#[test]
+fn separators_in_names_are_replaced() {
+    assert_eq!(folded::frame("Web Content"), "Web_Content");
+    assert_eq!(folded::frame("a;b\tc"), "a:b_c");
+    let tree = node(1, "Isolated Web Co", 2 * KB, vec![node(2, "x;y", KB, Vec::new())]);
+    assert_eq!(folded::render(&snapshot(vec![tree])), "Isolated_Web_Co 2\nIsolated_Web_Co;x:y 1\n");
+}
+
+#[test]
+fn no_trees_no_lines() {
+    assert_eq!(folded::render(&snapshot(Vec::new())), "");
+}
@@ -0,0 +1,65 @@
+// Output format precedence: explicit flag > --output extension > text
+
+use memon::format::{resolve_format, OutputFormat};
+use std::path::Path;
+
+#[test]
+fn defaults_to_text_without_flag_or_output() {
+    let resolved = resolve_format(None, None, false).unwrap();
+    assert_eq!(resolved.format, OutputFormat::Text);
+    assert_eq!(resolved.warning, None);
+}
+
+#[test]
+fn picks_format_from_extension() {
+    let cases = [
+        ("out.json", OutputFormat::Json),
+        ("out.CSV", OutputFormat::Csv),
+        ("report.html", OutputFormat::Html),
+        ("report.htm", OutputFormat::Html),
+        ("notes.md", OutputFormat::Markdown),
+        ("graph.dot", OutputFormat::Dot),
+    ];
+    for (path, expected) in cases {
+        let resolved = resolve_format(None, Some(Path::new(path)), false).unwrap();
+        assert_eq!(resolved.format, expected, "{}", path);
+    }
+}
+
+#[test]
+fn txt_and_unknown_extensions_fall_back_to_text() {
+    for path in ["out.txt", "out.log", "out.xyz", "no_extension", ".json"] {
+        let resolved = resolve_format(None, Some(Path::new(path)), false).unwrap();
+        assert_eq!(resolved.format, OutputFormat::Text, "{}", path);
+    }
+}
+
+#[test]
+fn explicit_flag_never_conflicts_with_generic_extensions() {
+    for path in ["out.txt", "out.xyz", "no_extension"] {
+        let resolved = resolve_format(Some(OutputFormat::Json), Some(Path::new(path)), true).unwrap();
+        assert_eq!(resolved.format, OutputFormat::Json, "{}", path);
+        assert_eq!(resolved.warning, None);
+    }
+}
+
+#[test]
+fn explicit_flag_wins_over_conflicting_extension_with_warning() {
+    let resolved = resolve_format(Some(OutputFormat::Dot), Some(Path::new("out.csv")), false).unwrap();
+    assert_eq!(resolved.format, OutputFormat::Dot);
+    assert!(resolved.warning.unwrap().contains("out.csv"));
+}
+
+#[test]
+fn conflicting_extension_is_an_error_when_strict() {
+    let conflict = resolve_format(Some(OutputFormat::Dot), Some(Path::new("out.csv")), true).unwrap_err();
+    assert_eq!(conflict.explicit, OutputFormat::Dot);
+    assert_eq!(conflict.implied, OutputFormat::Csv);
+}
+
+#[test]
+fn matching_flag_and_extension_is_not_a_conflict() {
+    let resolved = resolve_format(Some(OutputFormat::Json), Some(Path::new("out.json")), true).unwrap();
+    assert_eq!(resolved.format, OutputFormat::Json);
+    assert_eq!(resolved.warning, None);
+}
@@ -0,0 +1,160 @@
+// Golden-file tests for the text renderer: fixture process tables are loaded
+// through the --from-file source, rendered with fixed widths and no color, and
+// compared byte for byte against tests/golden/*.txt.
+//
+// Regenerate after an intentional output change with:
+//     MEMON_UPDATE_GOLDEN=1 cargo test --test golden
+
+use std::fs;
+use std::path::PathBuf;
+
+use memon::cli::{Marker, TreeStyle};
+use memon::options::Columns;
+use memon::render::{render_to_string, Decorations, TextRenderer, Theme};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+fn fixture_snapshot(name: &str) -> Snapshot {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(format!("{}.table", name));
+    let mut processes = source::load_table(&path).unwrap_or_else(|err| panic!("{}", err));
+    source::link_children(&mut processes);
+    let roots = source::table_roots(&processes);
+    let trees: Vec<ProcessTree> = roots.iter()
+        .filter_map(|&pid| ProcessTree::from_processes(&mut processes, pid))
+        .collect();
+    let matched = trees.iter().map(|tree| tree.process_count).sum();
+    Snapshot { version: "0.1.0".to_string(), pattern: name.to_string(), matched, trees, ..Default::default() }
+}
+
+fn assert_golden(golden: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.txt", golden));
+    if std::env::var_os("MEMON_UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("{}: {} (set MEMON_UPDATE_GOLDEN=1 to create it)", path.display(), err));
+    if expected != actual {
+        panic!("{} does not match the rendered output (set MEMON_UPDATE_GOLDEN=1 to update)\n--- expected\n{}--- actual\n{}",
+               path.display(), expected, actual);
+    }
+}
+
+fn render(name: &str, columns: Columns, min_percent: Option<f64>) -> String {
+    render_with(name, TextRenderer::new(Theme::PLAIN, columns, min_percent))
+}
+
+fn render_with(name: &str, renderer: TextRenderer) -> String {
+    render_to_string(&renderer.with_widths(6, 40), &fixture_snapshot(name))
+}
+
+#[test]
+fn small_tree() {
+    assert_golden("small_tree", &render("small_tree", Columns::default(), None));
+}
+
+#[test]
+fn small_tree_all_columns() {
+    let columns = Columns { args: false, counts: true, percent: true, percent_of_parent: true, mem_details: false, hugepages: false, limits: false, ports: false, jvm: false, gpu: false, tty: false, peak: false, stability: false, churn: false, k8s: false, nice: false, pgid: false, privs: false, history: false };
+    assert_golden("small_tree_all_columns", &render("small_tree", columns, None));
+}
+
+#[test]
+fn small_tree_rank_markers() {
+    let renderer = TextRenderer::plain().with_marker(Marker::Rank);
+    assert_golden("small_tree_marker_rank", &render_with("small_tree", renderer));
+}
+
+#[test]
+fn small_tree_ascii_markers() {
+    let renderer = TextRenderer::plain().with_marker(Marker::Ascii);
+    assert_golden("small_tree_marker_ascii", &render_with("small_tree", renderer));
+}
+
+// small_tree with command lines, as collected for --show-args
+fn small_tree_with_args(decorations: Decorations) -> String {
+    let mut snapshot = fixture_snapshot("small_tree");
+    let mut stack = vec![&mut snapshot.trees[0].root];
+    while let Some(node) = stack.pop() {
+        node.args = Some(format!("{} -c /etc/nginx/nginx.conf", node.name));
+        stack.extend(node.children.iter_mut());
+    }
+    let columns = Columns { args: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_decorations(decorations).with_widths(6, 40);
+    render_to_string(&renderer, &snapshot)
+}
+
+#[test]
+fn small_tree_args_plain() {
+    assert_golden("small_tree_args", &small_tree_with_args(Decorations::default()));
+}
+
+#[test]
+fn small_tree_args_decorated() {
+    let decorations = Decorations { args: true, ..Decorations::default() };
+    assert_golden("small_tree_args_decorated", &small_tree_with_args(decorations));
+}
+
+#[test]
+fn small_tree_args_rank_marker() {
+    let decorations = Decorations { marker: Marker::Rank, args: false };
+    assert_golden("small_tree_args_marker_rank", &small_tree_with_args(decorations));
+}
+
+#[test]
+fn small_tree_ascii_style() {
+    let renderer = TextRenderer::plain().with_tree_style(TreeStyle::Ascii, 2);
+    assert_golden("small_tree_style_ascii", &render_with("small_tree", renderer));
+}
+
+#[test]
+fn small_tree_indent_style() {
+    let renderer = TextRenderer::plain().with_tree_style(TreeStyle::Indent, 2);
+    assert_golden("small_tree_style_indent", &render_with("small_tree", renderer));
+}
+
+#[test]
+fn deep_chain_wide_indent() {
+    let renderer = TextRenderer::plain().with_tree_style(TreeStyle::Unicode, 4);
+    assert_golden("deep_chain_indent_4", &render_with("deep_chain", renderer));
+}
+
+#[test]
+fn forest_of_three_trees() {
+    assert_golden("forest", &render("forest", Columns::default(), None));
+}
+
+#[test]
+fn forest_by_depth() {
+    let mut snapshot = fixture_snapshot("forest");
+    for tree in &mut snapshot.trees {
+        tree.by_depth = Some(tree.depth_totals());
+    }
+    let renderer = TextRenderer::plain().with_widths(6, 40);
+    assert_golden("forest_by_depth", &render_to_string(&renderer, &snapshot));
+}
+
+#[test]
+fn deep_chain() {
+    assert_golden("deep_chain", &render("deep_chain", Columns::default(), None));
+}
+
+#[test]
+fn unicode_names() {
+    assert_golden("unicode_names", &render("unicode_names", Columns::default(), None));
+}
+
+#[test]
+fn fan_out() {
+    assert_golden("fan_out", &render("fan_out", Columns::default(), None));
+}
+
+#[test]
+fn fan_out_min_percent() {
+    assert_golden("fan_out_min_percent", &render("fan_out", Columns::default(), Some(2.0)));
+}
+
+#[test]
+fn other_users_unreadable() {
+    assert_golden("other_users", &render("other_users", Columns::default(), None));
+}
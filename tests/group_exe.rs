@@ -0,0 +1,82 @@
+// --group-by exe: same-named binaries from different paths are grouped apart
+
+mod common;
+
+use memon::cli::GroupBy;
+use memon::options::Columns;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{self, GroupTotal, ProcessNode, ProcessTree, Snapshot, UNKNOWN_EXE};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, rss: u64, exe: Option<&str>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { exe: exe.map(str::to_string), ..common::node(pid, "python3", rss, children) }
+}
+
+#[test]
+fn same_named_processes_from_different_paths_are_separate_groups() {
+    let system = "/usr/bin/python3.12";
+    let venv = "/srv/projects/analytics-pipeline/.venv-production-2024/bin/python3";
+    let trees = vec![
+        ProcessTree::new(node(10, 4 * MB, Some(venv), vec![node(11, 2 * MB, Some(venv), Vec::new()), node(12, MB, None, Vec::new())])),
+        ProcessTree::new(node(20, 3 * MB, Some(system), Vec::new())),
+    ];
+    let groups = snapshot::exe_totals(&trees);
+    assert_eq!(groups, vec![
+        GroupTotal { key: venv.to_string(), processes: 2, bytes: 6 * MB },
+        GroupTotal { key: system.to_string(), processes: 1, bytes: 3 * MB },
+        GroupTotal { key: UNKNOWN_EXE.to_string(), processes: 1, bytes: MB },
+    ]);
+    
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "python3".to_string(), matched: 4, trees, groups: Some(groups), ..Default::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_group_by(Some(GroupBy::Exe));
+    let output = render_to_string(&renderer, &snapshot);
+    let table: Vec<&str> = output.lines().rev().take(4).collect();
+    // Long paths keep their start and the file name at the end
+    assert_eq!(table, [
+        "(unknown)                                                         1  1.0MB  10.0%",
+        "/usr/bin/python3.12                                               1  3.0MB  30.0%",
+        "/srv/projects/analy\u{2026}peline/.venv-production-2024/bin/python3      2  6.0MB  60.0%",
+        "exe                                                           procs    rss  share",
+    ]);
+    
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_group_by(Some(GroupBy::Exe)).with_full_names(true);
+    assert!(render_to_string(&renderer, &snapshot).contains(venv));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn live_processes_are_grouped_by_their_executable() {
+    use std::fs;
+    use std::process::Command;
+    
+    // Two copies of `sleep` with the same name in different directories
+    let name = format!("mexe{}", std::process::id() % 100_000);
+    let base = std::env::temp_dir().join(&name);
+    let sleep = ["/bin/sleep", "/usr/bin/sleep"].into_iter().find(|path| fs::metadata(path).is_ok()).expect("sleep binary");
+    let mut children = Vec::new();
+    let mut paths = Vec::new();
+    for dir in ["a", "b"] {
+        let dir = base.join(dir);
+        fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join(&name);
+        fs::copy(sleep, &binary).unwrap();
+        children.push(Command::new(&binary).arg("30").spawn().unwrap());
+        paths.push(binary.to_string_lossy().into_owned());
+    }
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args([name.as_str(), "--group-by", "exe", "--full-names"]).output().unwrap();
+    for child in &mut children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let _ = fs::remove_dir_all(&base);
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<&str> = stdout.lines().skip_while(|line| !line.starts_with("exe ")).skip(1).collect();
+    assert_eq!(rows.len(), 2, "{}", stdout);
+    for path in &paths {
+        assert!(rows.iter().any(|row| row.starts_with(path.as_str()) && row.split_whitespace().nth(1) == Some("1")), "{}", stdout);
+    }
+}
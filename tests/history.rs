@@ -0,0 +1,66 @@
+// SQLite history: runs are recorded per snapshot and read back as tree totals
+
+#![cfg(feature = "history")]
+
+use memon::history::{format_timestamp, HistoryDb};
+use memon::snapshot::{ProcessNode, Snapshot, ProcessTree};
+
+fn snapshot(pattern: &str, total: u64) -> Snapshot {
+    let child = ProcessNode {
+        pid: 2,
+        name: "worker".to_string(),
+        rss_bytes: total / 4,
+        cumulative_bytes: total / 4,
+        ..Default::default()
+    };
+    let root = ProcessNode {
+        pid: 1,
+        name: pattern.to_string(),
+        rss_bytes: total - total / 4,
+        cumulative_bytes: total,
+        descendants: 1,
+        rank: Some(1),
+        children: vec![child],
+        ..Default::default()
+    };
+    Snapshot {
+        version: "test".to_string(),
+        pattern: pattern.to_string(),
+        matched: 2,
+        trees: vec![ProcessTree {
+            root_pid: 1,
+            process_count: 2,
+            total_bytes: total,
+            average_bytes: total / 2,
+            root,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn records_runs_and_reads_totals_in_order() {
+    let path = std::env::temp_dir().join(format!("memon-history-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    
+    let mut db = HistoryDb::open(&path).unwrap();
+    db.record(&snapshot("chrome", 4000), 200).unwrap();
+    db.record(&snapshot("chrome", 8000), 100).unwrap();
+    db.record(&snapshot("firefox", 1000), 150).unwrap();
+    
+    let totals = db.tree_totals("chrome").unwrap();
+    let series: Vec<(i64, u64)> = totals.iter().map(|run| (run.timestamp, run.total_bytes)).collect();
+    assert_eq!(series, vec![(100, 8000), (200, 4000)]);
+    assert!(totals.iter().all(|run| run.tree_count == 1));
+    assert!(db.tree_totals("missing").unwrap().is_empty());
+    
+    drop(db);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn formats_timestamps_as_utc() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20 UTC");
+}
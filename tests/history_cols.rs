@@ -0,0 +1,106 @@
+// --history-cols: lowest, average and highest RSS per process over a watch session
+
+mod common;
+
+use std::collections::HashMap;
+
+use memon::history_cols::{self, HistoryTracker, RssHistory};
+use memon::options::Columns;
+use memon::process::ProcessInfo;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+use memon::units::format_memory;
+
+const MB: u64 = 1024 * 1024;
+
+fn table(readings: &[(u32, &str, u64)]) -> HashMap<u32, ProcessInfo> {
+    readings.iter()
+        .map(|&(pid, name, rss)| (pid, ProcessInfo::new(pid, name.to_string(), rss, None)))
+        .collect()
+}
+
+fn node(pid: u32, name: &str, rss: u64, history: Option<RssHistory>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { history, ..common::node(pid, name, rss, children) }
+}
+
+fn snapshot(root: ProcessNode) -> Snapshot {
+    Snapshot { version: "0.1.0".to_string(), pattern: "web".to_string(), matched: 1, trees: vec![ProcessTree::new(root)], ..Default::default() }
+}
+
+fn history(min: u64, avg: u64, max: u64) -> Option<RssHistory> {
+    Some(RssHistory { samples: 3, min_bytes: min, avg_bytes: avg, max_bytes: max })
+}
+
+#[test]
+fn history_appears_from_the_second_sample() {
+    let mut tracker = HistoryTracker::new();
+    let mut processes = table(&[(1, "web", 10 * MB)]);
+    tracker.observe(&mut processes, &[1]);
+    assert_eq!(processes[&1].history, None);
+    
+    for rss in [40, 25] {
+        let mut processes = table(&[(1, "web", rss * MB)]);
+        tracker.observe(&mut processes, &[1]);
+        if rss == 25 {
+            assert_eq!(processes[&1].history, Some(RssHistory { samples: 3, min_bytes: 10 * MB, avg_bytes: 25 * MB, max_bytes: 40 * MB }));
+        }
+    }
+}
+
+#[test]
+fn reused_pids_start_afresh_and_exited_processes_stay_in_the_recap() {
+    let mut tracker = HistoryTracker::new();
+    for rss in [90, 70] {
+        tracker.observe(&mut table(&[(1, "old", rss * MB), (2, "web", 5 * MB)]), &[1, 2]);
+    }
+    
+    // PID 1 exits and comes back as another process
+    let mut processes = table(&[(1, "new", 3 * MB), (2, "web", 7 * MB)]);
+    processes.get_mut(&1).unwrap().start_time = 100;
+    tracker.observe(&mut processes, &[1, 2]);
+    assert_eq!(processes[&1].history, None);
+    assert_eq!(processes[&2].history.map(|history| history.max_bytes), Some(7 * MB));
+    
+    let largest = tracker.largest(history_cols::RECAP_PROCESSES);
+    let names: Vec<&str> = largest.iter().map(|process| process.name.as_str()).collect();
+    assert_eq!(names, ["old", "web"]);
+    assert_eq!(largest[0].history, RssHistory { samples: 2, min_bytes: 70 * MB, avg_bytes: 80 * MB, max_bytes: 90 * MB });
+}
+
+#[test]
+fn columns_are_right_aligned_per_tree() {
+    let snapshot = snapshot(node(1, "web", 120 * MB, history(100 * MB, 110 * MB, 120 * MB), vec![
+        node(2, "worker", 8 * MB, history(4 * MB, 6 * MB, 8 * MB), Vec::new()),
+        node(3, "fresh", MB, None, Vec::new()),
+    ]));
+    let columns = Columns { history: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(1, 6);
+    let output = render_to_string(&renderer, &snapshot);
+    assert!(output.contains("1 web    120.0MB min 100.0MB avg 110.0MB max 120.0MB"), "{}", output);
+    assert!(output.contains("├─ 2 worker 8.0MB min   4.0MB avg   6.0MB max   8.0MB"), "{}", output);
+    assert!(output.contains("└─ 3 fresh  1.0MB min       - avg       - max       -"), "{}", output);
+    
+    // Before any process was seen twice the columns stay out
+    let snapshot = self::snapshot(node(1, "web", 120 * MB, None, Vec::new()));
+    assert!(!render_to_string(&renderer, &snapshot).contains("min"));
+}
+
+#[test]
+fn json_carries_the_history_object() {
+    let snapshot = snapshot(node(1, "web", 3 * MB, history(MB, 2 * MB, 3 * MB), vec![node(2, "fresh", MB, None, Vec::new())]));
+    let json = serde_json::to_value(&snapshot).unwrap();
+    let root = &json["trees"][0]["root"];
+    assert_eq!(root["history"], serde_json::json!({ "samples": 3, "min_bytes": MB, "avg_bytes": 2 * MB, "max_bytes": 3 * MB }));
+    assert!(root["children"][0].get("history").is_none());
+}
+
+#[test]
+fn recap_uses_the_column_aggregates() {
+    let mut tracker = HistoryTracker::new();
+    for (web, worker) in [(100, 4), (120, 8)] {
+        tracker.observe(&mut table(&[(1, "web", web * MB), (22, "worker", worker * MB)]), &[1, 22]);
+    }
+    let recap = history_cols::render_recap(&tracker.largest(history_cols::RECAP_PROCESSES), format_memory);
+    assert_eq!(recap, "RSS min/avg/max:\n   1 web     100.0MB  110.0MB  120.0MB\n  22 worker    4.0MB    6.0MB    8.0MB\n");
+    assert_eq!(history_cols::render_recap(&[], format_memory), "");
+}
@@ -0,0 +1,82 @@
+// JVM detection, heap flags and jcmd GC.heap_info parsing (--jvm, --jvm-probe)
+
+use memon::jvm::{self, JvmHeap};
+
+const MB: u64 = 1024 * 1024;
+
+#[test]
+fn jvms_are_recognised_by_name_or_program() {
+    assert!(jvm::is_jvm("java", "java -jar app.jar"));
+    assert!(jvm::is_jvm("main", "/usr/lib/jvm/bin/java -Xmx1g Main"));
+    assert!(jvm::is_jvm("javaw.exe", ""));
+    assert!(!jvm::is_jvm("javac", "javac Main.java"));
+    assert!(!jvm::is_jvm("python3", "python3 java.py"));
+}
+
+#[test]
+fn heap_flags_follow_jvm_rules() {
+    assert_eq!(jvm::parse_heap_flags("java -Xms32m -Xmx256m Main"), JvmHeap { max_bytes: Some(256 * MB), initial_bytes: Some(32 * MB), ..JvmHeap::default() });
+
+    // The last occurrence wins, in either spelling
+    let heap = jvm::parse_heap_flags("java -Xmx1g -XX:MaxHeapSize=512m -jar app.jar");
+    assert_eq!(heap.max_bytes, Some(512 * MB));
+    assert_eq!(heap.initial_bytes, None);
+
+    // A class path value is not the main class
+    assert_eq!(jvm::parse_heap_flags("java -cp lib/* -Xmx64m Main").max_bytes, Some(64 * MB));
+
+    // Application arguments are not JVM flags
+    assert_eq!(jvm::parse_heap_flags("java -jar app.jar -Xmx8g").max_bytes, None);
+    assert_eq!(jvm::parse_heap_flags("java Main -Xmx8g").max_bytes, None);
+    assert_eq!(jvm::parse_heap_flags("java -m app/app.Main -Xmx8g").max_bytes, None);
+}
+
+#[test]
+fn jvm_sizes_take_binary_suffixes() {
+    assert_eq!(jvm::parse_jvm_size("1048576"), Some(MB));
+    assert_eq!(jvm::parse_jvm_size("512k"), Some(512 * 1024));
+    assert_eq!(jvm::parse_jvm_size("256M"), Some(256 * MB));
+    assert_eq!(jvm::parse_jvm_size("2g"), Some(2048 * MB));
+    assert_eq!(jvm::parse_jvm_size("1T"), Some(1024 * 1024 * MB));
+    assert_eq!(jvm::parse_jvm_size("m"), None);
+    assert_eq!(jvm::parse_jvm_size("1.5g"), None);
+    assert_eq!(jvm::parse_jvm_size(""), None);
+}
+
+#[test]
+fn heap_info_of_g1() {
+    let output = "12345:
+ garbage-first heap   total 31744K, used 8400K [0x00000000f0000000, 0x0000000100000000)
+  region size 1024K, 9 young (9216K), 0 survivors (0K)
+ Metaspace       used 6721K, committed 6912K, reserved 1114112K
+  class space    used 601K, committed 704K, reserved 1048576K
+";
+    assert_eq!(jvm::parse_heap_info(output), Some((8400 * 1024, 31744 * 1024)));
+}
+
+#[test]
+fn heap_info_of_parallel_sums_generations() {
+    let output = "12345:
+ PSYoungGen      total 9216K, used 1024K [0x00000000ff600000, 0x0000000100000000, 0x0000000100000000)
+  eden space 8192K, 12% used [0x00000000ff600000,0x00000000ff700000,0x00000000ffe00000)
+ ParOldGen       total 20480K, used 2048K [0x00000000fe200000, 0x00000000ff600000, 0x00000000ff600000)
+ Metaspace       used 3000K, committed 3200K, reserved 1056768K
+";
+    assert_eq!(jvm::parse_heap_info(output), Some((3072 * 1024, 29696 * 1024)));
+}
+
+#[test]
+fn heap_info_of_zgc() {
+    let output = "12345:
+ ZHeap           used 24M, capacity 64M, max capacity 256M
+ Metaspace       used 512K, committed 640K, reserved 1056768K
+";
+    assert_eq!(jvm::parse_heap_info(output), Some((24 * MB, 64 * MB)));
+}
+
+#[test]
+fn heap_info_without_a_heap_line() {
+    assert_eq!(jvm::parse_heap_info(""), None);
+    assert_eq!(jvm::parse_heap_info("12345:\nCommand not found\n"), None);
+    assert_eq!(jvm::parse_heap_info(" Metaspace       used 512K, committed 640K, reserved 1056768K\n"), None);
+}
@@ -0,0 +1,102 @@
+// --k8s: pods from cgroup paths and the kubelet pod list, and --group-by pod
+
+mod common;
+
+use memon::cli::GroupBy;
+use memon::k8s::{self, PodDirectory, PodRef, NO_POD};
+use memon::options::Columns;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{GroupTotal, ProcessNode, ProcessTree, Snapshot};
+
+const MB: u64 = 1024 * 1024;
+const UID: &str = "0f3c2a1b-9d4e-4c5f-8a7b-6e5d4c3b2a10";
+const CONTAINER: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904e6b1a3f0c5d2e7a9b8c1d0e2";
+
+fn pod(namespace: Option<&str>, name: Option<&str>) -> PodRef {
+    PodRef {
+        uid: UID.to_string(),
+        container_id: Some(CONTAINER.to_string()),
+        namespace: namespace.map(str::to_string),
+        pod: name.map(str::to_string),
+        container: name.map(|_| "app".to_string()),
+    }
+}
+
+#[test]
+fn systemd_driver_cgroup_path() {
+    let cgroup = format!("0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod{}.slice/cri-containerd-{}.scope\n",
+                         UID.replace('-', "_"), CONTAINER);
+    assert_eq!(k8s::parse_cgroup(&cgroup), Some(pod(None, None)));
+}
+
+#[test]
+fn cgroupfs_driver_cgroup_path() {
+    let cgroup = format!("12:memory:/kubepods/besteffort/pod{}/{}\n11:cpu:/kubepods/besteffort/pod{}/{}\n", UID, CONTAINER, UID, CONTAINER);
+    assert_eq!(k8s::parse_cgroup(&cgroup), Some(pod(None, None)));
+}
+
+#[test]
+fn pod_level_cgroup_has_no_container() {
+    let cgroup = format!("0::/kubepods/pod{}\n", UID);
+    let pod = k8s::parse_cgroup(&cgroup).unwrap();
+    assert_eq!((pod.uid.as_str(), pod.container_id), (UID, None));
+}
+
+#[test]
+fn processes_outside_kubepods_have_no_pod() {
+    assert_eq!(k8s::parse_cgroup("0::/system.slice/containerd.service\n"), None);
+    assert_eq!(k8s::parse_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n"), None);
+}
+
+#[test]
+fn kubelet_pod_list_resolves_names() {
+    let json = format!(r#"{{"kind": "PodList", "items": [{{
+        "metadata": {{"name": "web-7d9f", "namespace": "shop", "uid": "{}"}},
+        "status": {{"containerStatuses": [{{"name": "app", "containerID": "containerd://{}"}}]}}
+    }}]}}"#, UID, CONTAINER);
+    let directory = PodDirectory::from_json(&json).unwrap();
+    let mut resolved = pod(None, None);
+    directory.resolve(&mut resolved);
+    assert_eq!(resolved, pod(Some("shop"), Some("web-7d9f")));
+    assert_eq!(resolved.label(), "shop/web-7d9f/app");
+    assert_eq!(resolved.group_key(), "shop/web-7d9f");
+
+    // An unknown pod keeps showing its UID
+    let mut unknown = PodRef { uid: "other".to_string(), ..pod(None, None) };
+    directory.resolve(&mut unknown);
+    assert_eq!(unknown.label(), "other");
+    assert!(PodDirectory::from_json("{}").is_err());
+}
+
+fn node(pid: u32, rss: u64, pod: Option<PodRef>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { pod, ..common::node(pid, "java", rss, children) }
+}
+
+#[test]
+fn group_by_pod_totals_every_tree() {
+    let shop = pod(Some("shop"), Some("web-7d9f"));
+    let trees = vec![
+        ProcessTree::new(node(10, MB, None, vec![node(11, 4 * MB, Some(shop.clone()), Vec::new())])),
+        ProcessTree::new(node(20, 2 * MB, Some(shop), Vec::new())),
+        ProcessTree::new(node(30, MB, Some(pod(None, None)), Vec::new())),
+    ];
+    let groups = k8s::pod_totals(&trees);
+    assert_eq!(groups, vec![
+        GroupTotal { key: "shop/web-7d9f".to_string(), processes: 2, bytes: 6 * MB },
+        GroupTotal { key: NO_POD.to_string(), processes: 1, bytes: MB },
+        GroupTotal { key: UID.to_string(), processes: 1, bytes: MB },
+    ]);
+
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "java".to_string(), matched: 3, trees, groups: Some(groups), ..Default::default() };
+    let columns = Columns { k8s: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(2, 4).with_group_by(Some(GroupBy::Pod));
+    let output = render_to_string(&renderer, &snapshot);
+    assert!(output.contains("└─ 11 java 4.0MB pod shop/web-7d9f/app"), "{}", output);
+    let table: Vec<&str> = output.lines().rev().take(4).collect();
+    assert_eq!(table, [
+        "0f3c2a1b-9d4e-4c5f-8a7b-6e5d4c3b2a10      1  1.0MB  12.5%",
+        "(no pod)                                  1  1.0MB  12.5%",
+        "shop/web-7d9f                             2  6.0MB  75.0%",
+        "pod                                   procs    rss  share",
+    ]);
+}
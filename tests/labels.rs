@@ -0,0 +1,64 @@
+// Friendly labels for well-known process names (--labels)
+
+mod common;
+
+use memon::labels::{self, Labels};
+use memon::snapshot::ProcessNode;
+
+fn node(pid: u32, name: &str, children: Vec<ProcessNode>) -> ProcessNode {
+    common::node(pid, name, 0, children)
+}
+
+#[test]
+fn patterns_match_whole_names() {
+    assert!(labels::matches("Web Content", "Web Content"));
+    assert!(!labels::matches("Web Content", "Web Content 2"));
+    assert!(!labels::matches("Web Content", "Web"));
+    assert!(labels::matches("kworker/*", "kworker/0:1H"));
+    assert!(labels::matches("kworker/*", "kworker/"));
+    assert!(!labels::matches("kworker/*", "xkworker/1"));
+    assert!(labels::matches("*-server", "language-server"));
+    assert!(labels::matches("a*b*c", "aXbYbc"));
+    assert!(!labels::matches("a*b*c", "acb"));
+    assert!(!labels::matches("a*a", "a"));
+    assert!(labels::matches("*", ""));
+}
+
+#[test]
+fn builtins_cover_common_helpers() {
+    let labels = Labels::builtin();
+    assert_eq!(labels.lookup("Web Content"), Some("Firefox tab renderer"));
+    assert_eq!(labels.lookup("Isolated Web Co"), Some("Firefox site-isolated tab"));
+    assert_eq!(labels.lookup("Socket Process"), Some("Firefox network sandbox"));
+    assert_eq!(labels.lookup("mdworker_shared"), Some("Spotlight indexer"));
+    assert_eq!(labels.lookup("kworker/u8:2"), Some("kernel worker thread"));
+    assert_eq!(labels.lookup("postgres"), None);
+}
+
+#[test]
+fn config_entries_extend_and_override() {
+    let overrides = vec![
+        ("Web Content".to_string(), "browser tab".to_string()),
+        ("my-worker*".to_string(), "queue worker".to_string()),
+        ("mdworker_shared".to_string(), String::new()),
+        ("pipe*".to_string(), "pipeline".to_string()),
+    ];
+    let labels = Labels::with_overrides(&overrides);
+    assert_eq!(labels.lookup("Web Content"), Some("browser tab"));
+    assert_eq!(labels.lookup("my-worker-3"), Some("queue worker"));
+    // An empty label hides the built-in one
+    assert_eq!(labels.lookup("mdworker_shared"), None);
+    // Config patterns are tried before every built-in
+    assert_eq!(labels.lookup("pipewire"), Some("pipeline"));
+    assert_eq!(labels.lookup("Socket Process"), Some("Firefox network sandbox"));
+}
+
+#[test]
+fn apply_labels_the_whole_subtree() {
+    let mut root = node(1, "firefox", vec![node(2, "Web Content", Vec::new()), node(3, "Socket Process", vec![node(4, "other", Vec::new())])]);
+    Labels::builtin().apply(&mut root);
+    assert_eq!(root.label, None);
+    assert_eq!(root.children[0].label.as_deref(), Some("Firefox tab renderer"));
+    assert_eq!(root.children[1].label.as_deref(), Some("Firefox network sandbox"));
+    assert_eq!(root.children[1].children[0].label, None);
+}
@@ -0,0 +1,77 @@
+// `memon maps`: smaps parsing, grouping and the --top / --min cut
+
+use memon::maps::{parse_smaps, render_text, MapKind, MapsReport};
+use memon::render::Theme;
+
+const SMAPS: &str = "\
+55d4c0a00000-55d4c0a21000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                 100 kB
+Private_Dirty:       100 kB
+7f1c2a000000-7f1c2a021000 r-xp 00000000 08:01 1234                       /usr/lib/libc.so.6
+Size:               1600 kB
+Rss:                 900 kB
+7f1c2a021000-7f1c2a030000 r--p 00021000 08:01 1234                       /usr/lib/libc.so.6
+Size:                 60 kB
+Rss:                  60 kB
+7f1c2b000000-7f1c2f000000 rw-p 00000000 00:00 0 
+Size:              65536 kB
+Rss:                4096 kB
+7f1c30000000-7f1c30400000 rw-s 00000000 00:05 77                         /dev/shm/cache file (deleted)
+Size:               4096 kB
+Rss:                2048 kB
+7f1c31000000-7f1c31200000 rw-p 00000000 00:00 0 
+Size:               2048 kB
+Rss:                1024 kB
+7ffd1a000000-7ffd1a021000 rw-p 00000000 00:00 0                          [stack]
+Size:                132 kB
+Rss:                  24 kB
+7ffd1a1f0000-7ffd1a1f2000 r-xp 00000000 00:00 0                          [vdso]
+Size:                  8 kB
+Rss:                   4 kB
+";
+
+#[test]
+fn mappings_are_grouped_by_backing_and_sorted_by_rss() {
+    let groups = parse_smaps(SMAPS);
+    let summary: Vec<(&str, MapKind, usize, u64)> = groups
+        .iter()
+        .map(|group| (group.name.as_str(), group.kind, group.regions, group.rss_bytes / 1024))
+        .collect();
+    assert_eq!(summary, vec![
+        ("[anon]", MapKind::Anonymous, 2, 5120),
+        ("/dev/shm/cache file (deleted)", MapKind::File, 1, 2048),
+        ("/usr/lib/libc.so.6", MapKind::Library, 2, 960),
+        ("[heap]", MapKind::Heap, 1, 100),
+        ("[stack]", MapKind::Stack, 1, 24),
+        ("[vdso]", MapKind::Special, 1, 4),
+    ]);
+    assert_eq!(groups[0].size_bytes, (65536 + 2048) * 1024);
+}
+
+#[test]
+fn top_and_min_hide_the_tail() {
+    let report = MapsReport::new(42, Some("server".to_string()), parse_smaps(SMAPS), 4, 512 * 1024);
+    assert_eq!(report.rss_bytes, 8256 * 1024);
+    assert_eq!(report.groups.len(), 3);
+    assert_eq!(report.hidden_groups, 3);
+    assert_eq!(report.hidden_rss_bytes, 128 * 1024);
+    
+    let report = MapsReport::new(42, None, parse_smaps(SMAPS), 2, 0);
+    assert_eq!(report.groups.len(), 2);
+    assert_eq!(report.hidden_groups, 4);
+}
+
+#[test]
+fn text_table_is_aligned() {
+    let report = MapsReport::new(42, Some("server".to_string()), parse_smaps(SMAPS), 3, 0);
+    let lines: Vec<String> = render_text(&report, Theme::PLAIN).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "Memory maps of 42 (server): 8.1MB RSS",
+        "  RSS    SIZE  REGIONS  MAPPING",
+        "5.0MB  66.0MB        2  [anon]",
+        "2.0MB   4.0MB        1  /dev/shm/cache file (deleted)",
+        "0.9MB   1.6MB        2  /usr/lib/libc.so.6",
+        "(3 more mappings, 0.1MB)",
+    ]);
+}
@@ -0,0 +1,49 @@
+// Matcher tally reported with --verbose
+
+use memon::match_stats::{MatchRule, MatchStats, PhaseTimings};
+
+fn stats() -> MatchStats {
+    let mut stats = MatchStats { excluded: 2, ..MatchStats::default() };
+    for rule in [Some(MatchRule::Exact), Some(MatchRule::Exact), Some(MatchRule::Prefix), None, Some(MatchRule::CompactName), None] {
+        stats.record(rule);
+    }
+    stats.set_roots(1);
+    stats.timings = PhaseTimings { scan_ms: 12.34, match_ms: 0.25, roots_ms: 0.0, build_ms: 1.5 };
+    stats
+}
+
+#[test]
+fn counts_matches_per_rule() {
+    let stats = stats();
+    assert_eq!((stats.examined, stats.matched, stats.excluded), (6, 4, 2));
+    assert_eq!((stats.roots, stats.non_roots), (1, 3));
+    assert_eq!(stats.by_rule["exact"], 2);
+    assert_eq!(stats.by_rule["prefix"], 1);
+    assert_eq!(stats.by_rule["compact-name"], 1);
+    // Rules that matched nothing are still listed
+    assert_eq!(stats.by_rule["basename"], 0);
+    assert_eq!(stats.by_rule.len(), MatchRule::ALL.len());
+}
+
+#[test]
+fn table_lists_rules_in_matcher_order() {
+    let table = stats().table("nginx");
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Match statistics for 'nginx':");
+    assert_eq!(lines[1], "  examined               6");
+    assert_eq!(lines[4], "    truncated-name       0");
+    assert_eq!(lines[5], "    exact                2");
+    assert_eq!(lines[9], "    title                0");
+    assert_eq!(lines[10], "    port                 0");
+    assert_eq!(lines[11], "  non-roots              3");
+    assert_eq!(lines[13], "  scan time         12.3ms");
+    assert_eq!(lines.len(), 17);
+}
+
+#[test]
+fn serializes_as_a_match_stats_object() {
+    let json = serde_json::to_value(stats()).unwrap();
+    assert_eq!(json["by_rule"]["exact"], 2);
+    assert_eq!(json["non_roots"], 3);
+    assert_eq!(json["timings"]["build_ms"], 1.5);
+}
@@ -0,0 +1,57 @@
+// Totals and highlights restricted to the matched processes of a tree
+// (--matched-only-totals, --highlight-matched)
+
+mod common;
+
+use std::collections::HashSet;
+
+use memon::options::Columns;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{MatchedTotal, ProcessNode, ProcessTree, Snapshot};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, rss: u64, rank: Option<u8>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { rank, ..common::node(pid, name, rss, children) }
+}
+
+// make 1 -> make 2 -> cc1 3, cc1 4; make 1 -> ld 5. The compilers dwarf make.
+fn tree() -> ProcessTree {
+    ProcessTree::new(node(1, "make", 4 * MB, None, vec![
+        node(2, "make", 3 * MB, None, vec![
+            node(3, "cc1", 300 * MB, Some(1), Vec::new()),
+            node(4, "cc1", 200 * MB, Some(2), Vec::new()),
+        ]),
+        node(5, "ld", 100 * MB, Some(3), Vec::new()),
+    ]))
+}
+
+fn matched() -> HashSet<u32> {
+    [1, 2].into_iter().collect()
+}
+
+#[test]
+fn matched_total_leaves_out_spawned_children() {
+    let tree = tree();
+    assert_eq!(tree.matched_total(&matched()), MatchedTotal { processes: 2, bytes: 7 * MB });
+    assert_eq!(tree.matched_total(&HashSet::new()), MatchedTotal { processes: 0, bytes: 0 });
+}
+
+#[test]
+fn highlights_move_to_matched_processes() {
+    let mut tree = tree();
+    tree.rank_matched(&matched());
+    let ranks: Vec<(u32, Option<u8>)> = tree.iter_preorder().map(|(node, _)| (node.pid, node.rank)).collect();
+    assert_eq!(ranks, vec![(1, Some(1)), (2, Some(2)), (3, None), (4, None), (5, None)]);
+}
+
+#[test]
+fn summary_shows_matched_next_to_tree_total() {
+    let mut tree = tree();
+    tree.matched_total = Some(tree.matched_total(&matched()));
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "make".to_string(), matched: 2, trees: vec![tree], ..Default::default() };
+    let text = render_to_string(&TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(1, 8), &snapshot);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[lines.len() - 2], "5 procs | 121.4MB avg | 607.0MB total");
+    assert_eq!(lines[lines.len() - 1], "matched: 2 procs 7.0MB / tree: 5 procs 607.0MB");
+}
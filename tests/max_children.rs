@@ -0,0 +1,68 @@
+// --max-children: wide fan-out is cut to the largest children in text output
+
+mod common;
+
+use memon::options::Columns;
+use memon::render::{render_json, render_to_string, TextRenderer, Theme};
+use memon::snapshot::{MoreChildren, ProcessNode, ProcessTree, Snapshot};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, rss: u64, children: Vec<ProcessNode>) -> ProcessNode {
+    common::node(pid, "bomb", rss, children)
+}
+
+// A parent with 1000 children of 1MB, except 4MB for PID 1500 and 2MB for
+// PID 1200, which has a child of its own
+fn fork_bomb() -> Snapshot {
+    let children = (1001..=2000)
+        .map(|pid| match pid {
+            1500 => node(pid, 4 * MB, Vec::new()),
+            1200 => node(pid, 2 * MB, vec![node(3000, MB, Vec::new())]),
+            _ => node(pid, MB, Vec::new()),
+        })
+        .collect();
+    let mut tree = ProcessTree::new(node(1000, 10 * MB, children));
+    // Ranks are computed over every child, not just the printed ones
+    tree.root.rank = Some(1);
+    tree.root.children[499].rank = Some(2);
+    tree.root.children[199].rank = Some(3);
+    Snapshot { version: "0.1.0".to_string(), pattern: "bomb".to_string(), matched: 1002, trees: vec![tree], ..Default::default() }
+}
+
+#[test]
+fn prints_the_largest_children_and_one_line_for_the_rest() {
+    let snapshot = fork_bomb();
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { counts: true, ..Columns::default() }, None)
+        .with_widths(4, 4)
+        .with_max_children(Some(2));
+    let output = render_to_string(&renderer, &snapshot);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines, [
+        "1000 bomb 10.0MB [1000/1001]🥇",
+        "├─ 1200 bomb 2.0MB [1/1]🥉",
+        "  └─ 3000 bomb 1.0MB [0/0]",
+        "├─ 1500 bomb 4.0MB [0/0]🥈",
+        "└─ (+998 more, 998.0MB)",
+        "1,002 procs | 1.0MB avg | 1015.0MB total",
+    ]);
+    
+    // 0 lifts the cap
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_max_children(None);
+    assert_eq!(render_to_string(&renderer, &snapshot).lines().count(), 1 + 1000 + 1 + 1);
+}
+
+#[test]
+fn json_keeps_every_child_unless_capped() {
+    let snapshot = fork_bomb();
+    assert_eq!(render_json(&snapshot).matches("\"pid\"").count(), 1002);
+    
+    let capped = snapshot.cap_children(2);
+    let root = &capped.trees[0].root;
+    let pids: Vec<u32> = root.children.iter().map(|child| child.pid).collect();
+    assert_eq!(pids, [1200, 1500]);
+    assert_eq!(root.more_children, Some(MoreChildren { processes: 998, bytes: 998 * MB }));
+    assert_eq!(root.children[0].more_children, None);
+    assert_eq!(capped.trees[0].total_bytes, snapshot.trees[0].total_bytes);
+    assert!(render_json(&capped).contains("\"more_children\""));
+}
@@ -0,0 +1,62 @@
+// One tree for all matched roots under a synthetic node (--merge-roots)
+
+use std::collections::HashMap;
+
+use memon::process::ProcessInfo;
+use memon::snapshot::ProcessTree;
+use memon::source;
+
+const MB: u64 = 1024 * 1024;
+
+// postgres 10 -> 11, 12 and a worker 20 re-parented to init, with child 21
+fn table() -> HashMap<u32, ProcessInfo> {
+    let rows = [(1, None, "init", MB), (10, Some(1), "postgres", 30 * MB), (11, Some(10), "postgres", 20 * MB),
+                (12, Some(10), "postgres", 10 * MB), (20, Some(1), "postgres", 40 * MB), (21, Some(20), "postgres", 5 * MB)];
+    let mut processes: HashMap<u32, ProcessInfo> = rows
+        .into_iter()
+        .map(|(pid, ppid, name, rss)| (pid, ProcessInfo::new(pid, name.to_string(), rss, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    processes
+}
+
+#[test]
+fn roots_share_one_tree_and_summary() {
+    let mut processes = table();
+    let tree = ProcessTree::merged(&mut processes, "postgres", &[10, 20]).unwrap();
+    assert_eq!(tree.root_pid, 0);
+    assert_eq!(tree.root.name, "postgres (merged)");
+    assert!(tree.root.is_virtual);
+    assert_eq!(tree.root.rss_bytes, 0);
+    assert_eq!(tree.root.cumulative_bytes, 105 * MB);
+    assert_eq!(tree.process_count, 5);
+    assert_eq!(tree.total_bytes, 105 * MB);
+    assert_eq!(tree.average_bytes, 21 * MB);
+    let roots: Vec<u32> = tree.root.children.iter().map(|child| child.pid).collect();
+    assert_eq!(roots, vec![10, 20]);
+    // The synthetic entry does not stay in the process table
+    assert!(!processes.contains_key(&u32::MAX));
+}
+
+#[test]
+fn ranks_span_all_merged_trees() {
+    let mut processes = table();
+    let tree = ProcessTree::merged(&mut processes, "postgres", &[10, 20]).unwrap();
+    let mut ranked: Vec<(u32, u8)> = tree.iter_preorder()
+        .filter_map(|(node, _)| node.rank.map(|rank| (node.pid, rank)))
+        .collect();
+    ranked.sort_unstable();
+    assert_eq!(ranked, vec![(10, 2), (11, 3), (20, 1)]);
+    assert_eq!(tree.root.rank, None);
+}
+
+#[test]
+fn json_marks_only_the_synthetic_root() {
+    let mut processes = table();
+    let tree = ProcessTree::merged(&mut processes, "postgres", &[10, 20]).unwrap();
+    let json = serde_json::to_value(&tree).unwrap();
+    assert_eq!(json["root"]["virtual"], true);
+    assert!(json["root"]["children"][0].get("virtual").is_none());
+    let back: ProcessTree = serde_json::from_value(json).unwrap();
+    assert!(back.root.is_virtual && !back.root.children[0].is_virtual);
+}
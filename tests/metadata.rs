@@ -0,0 +1,95 @@
+// Run metadata embedded in every format, and --anonymize
+
+use std::collections::HashMap;
+
+use memon::format::OutputFormat;
+#[cfg(feature = "history")]
+use memon::history::HistoryDb;
+use memon::metadata::{self, Anonymizer, RunMetadata};
+use memon::process::ProcessInfo;
+use memon::record::HostInfo;
+use memon::render::{self, render_snapshot};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+fn host() -> HostInfo {
+    HostInfo { hostname: Some("build-07".to_string()), os: Some("Linux 12 Debian".to_string()), kernel: Some("6.1.0".to_string()), cpus: 8, total_memory: 16 << 30 }
+}
+
+fn run_metadata(anonymize: bool) -> RunMetadata {
+    let args = ["memon", "make", "--from-file", "/home/alice/build-07.table"].map(str::to_string);
+    let options = ["--group-by exe", "--from-file /home/alice/build-07.table"].map(str::to_string);
+    RunMetadata::new(&host(), Some("alice"), 1_700_000_000, &args, &options, anonymize)
+}
+
+fn snapshot() -> Snapshot {
+    let mut processes: HashMap<u32, ProcessInfo> = [(10, None, "make"), (11, Some(10), "cc1")].iter()
+        .map(|&(pid, ppid, name)| (pid, ProcessInfo::new(pid, name.to_string(), 8 << 20, ppid)))
+        .collect();
+    processes.get_mut(&11).unwrap().args = Some("cc1 -o /home/alice/src/main.o".to_string());
+    source::link_children(&mut processes);
+    let trees: Vec<ProcessTree> = ProcessTree::from_processes(&mut processes, 10).into_iter().collect();
+    Snapshot { version: "0.1.0".to_string(), pattern: "make".to_string(), matched: 1, trees, metadata: Some(run_metadata(false)), ..Default::default() }
+}
+
+#[test]
+fn metadata_holds_the_host_and_the_command_line() {
+    let metadata = run_metadata(false);
+    assert_eq!(metadata.memon_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!((metadata.hostname.as_deref(), metadata.user.as_deref()), (Some("build-07"), Some("alice")));
+    assert_eq!(metadata.total_memory, 16 << 30);
+    assert_eq!(metadata.args[3], "/home/alice/build-07.table");
+    assert_eq!(metadata.options[0], "--group-by exe");
+    assert!(!metadata.anonymized);
+    
+    let lookup = |vars: &'static [(&'static str, &'static str)]| move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string());
+    assert_eq!(metadata::current_user(lookup(&[("USER", ""), ("LOGNAME", "bob")])).as_deref(), Some("bob"));
+    assert_eq!(metadata::current_user(lookup(&[])), None);
+}
+
+#[test]
+fn anonymize_hashes_the_hostname_and_user_names_stably() {
+    let metadata = run_metadata(true);
+    let host = metadata::stable_hash("host", "build-07");
+    let user = metadata::stable_hash("user", "alice");
+    assert_eq!(metadata::stable_hash("host", "build-07"), "host-f45490fb");
+    assert_eq!(metadata.hostname.as_deref(), Some(host.as_str()));
+    assert_eq!(metadata.user.as_deref(), Some(user.as_str()));
+    assert_eq!(metadata.args[3], format!("/home/{}/{}.table", user, host));
+    assert_eq!(metadata.options[1], format!("--from-file /home/{}/{}.table", user, host));
+    assert!(metadata.anonymized);
+    assert_eq!(run_metadata(true), metadata);
+    
+    let mut snapshot = snapshot();
+    Anonymizer::new(Some("build-07"), true).apply(&mut snapshot);
+    assert_eq!(snapshot.trees[0].root.children[0].args.as_deref(), Some(format!("cc1 -o /home/{}/src/main.o", user).as_str()));
+    // Without --anonymize nothing changes
+    assert_eq!(Anonymizer::new(Some("build-07"), false).text("/Users/alice on build-07"), "/Users/alice on build-07");
+}
+
+#[test]
+fn every_format_embeds_the_same_metadata() {
+    let snapshot = snapshot();
+    let expected = snapshot.metadata.clone().unwrap();
+    
+    let json: Snapshot = serde_json::from_str(&render::render_json(&snapshot)).unwrap();
+    assert_eq!(json.metadata.as_ref(), Some(&expected));
+    
+    let html = render_snapshot(OutputFormat::Html, &snapshot);
+    assert!(html.contains("<tr><th>host</th><td>build-07</td></tr>"), "{}", html);
+    assert!(html.contains("<tr><th>time</th><td>2023-11-14 22:13:20 UTC</td></tr>"), "{}", html);
+}
+
+#[cfg(feature = "history")]
+#[test]
+fn history_db_keeps_the_metadata() {
+    let snapshot = snapshot();
+    let expected = snapshot.metadata.clone().unwrap();
+    let path = std::env::temp_dir().join(format!("memon-metadata-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut db = HistoryDb::open(&path).unwrap();
+    let run_id = db.record(&snapshot, 100).unwrap();
+    let stored: RunMetadata = serde_json::from_str(&db.run_metadata(run_id).unwrap().unwrap()).unwrap();
+    assert_eq!(stored, expected);
+    let _ = std::fs::remove_file(&path);
+}
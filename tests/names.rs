@@ -0,0 +1,63 @@
+// Names resolved from the command line, and matching setproctitle titles
+
+use std::process::Command;
+
+use serde_json::Value;
+
+use memon::names::{self, ResolvedName};
+
+const TABLE: &str = "tests/fixtures/postgres_titles.table";
+
+fn resolved(display: &str, raw: Option<&str>) -> ResolvedName {
+    ResolvedName { display: display.to_string(), raw: raw.map(str::to_string) }
+}
+
+fn matched_pids(pattern: &str) -> Vec<u64> {
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args(["--from-file", TABLE, pattern, "--json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let snapshot: Value = serde_json::from_slice(&output.stdout).unwrap();
+    snapshot["trees"].as_array().unwrap().iter().map(|tree| tree["root_pid"].as_u64().unwrap()).collect()
+}
+
+#[test]
+fn setproctitle_titles_replace_the_reported_name() {
+    assert_eq!(names::resolve("postgres", Some("postgres"), Some("postgres: walwriter")), resolved("postgres: walwriter", Some("postgres")));
+    assert_eq!(names::resolve("sshd", None, Some("sshd: deploy@pts/0 ")), resolved("sshd: deploy@pts/0", Some("sshd")));
+    // The postmaster keeps its real command line
+    assert_eq!(names::resolve("postgres", Some("postgres"), Some("/usr/lib/postgresql/16/bin/postgres -D /var/lib/postgresql")), resolved("postgres", None));
+}
+
+#[test]
+fn names_cut_at_the_comm_limit_take_the_executable_basename() {
+    assert_eq!(names::resolve("chrome_crashpad", None, Some("/opt/google/chrome/chrome_crashpad_handler --monitor-self")),
+               resolved("chrome_crashpad_handler", Some("chrome_crashpad")));
+    // Shorter names are complete, even when the executable name goes on
+    assert_eq!(names::resolve("python3", None, Some("/usr/bin/python3.12 app.py")), resolved("python3", None));
+    assert!(names::needs_resolution(""));
+    assert!(!names::needs_resolution("a-name-longer-than-15"));
+}
+
+#[test]
+fn empty_names_fall_back_to_comm_then_the_command_line() {
+    assert_eq!(names::resolve("", Some("kworker/0:1\n"), None), resolved("kworker/0:1", Some("")));
+    assert_eq!(names::resolve("", None, Some("/usr/sbin/cron -f")), resolved("cron", Some("")));
+    assert_eq!(names::resolve("", None, None), resolved("", None));
+}
+
+#[test]
+fn title_words_match_the_pattern() {
+    assert!(names::title_matches("postgres: walwriter", "WalWriter"));
+    assert!(names::title_matches("postgres: logical replication launcher", "logical replication"));
+    assert!(names::title_matches("postgres: app orders 10.0.0.7(51234) idle", "orders"));
+    assert!(!names::title_matches("postgres: walwriter", "postgres"));
+    assert!(!names::title_matches("python3 -c print('a: b')", "b"));
+    assert_eq!(names::title_rest("postgres:"), None);
+}
+
+#[test]
+fn titled_workers_are_found_by_their_title() {
+    assert_eq!(matched_pids("walwriter"), vec![303]);
+    assert_eq!(matched_pids("launcher"), vec![304, 305]);
+    // The reported name still finds the whole server
+    assert_eq!(matched_pids("postgres"), vec![300]);
+}
@@ -0,0 +1,65 @@
+// --only-nice: filter parsing and pruning trees to the passing processes
+
+use std::collections::HashMap;
+
+use memon::nice::{self, NiceFilter, NiceOp};
+use memon::process::ProcessInfo;
+use memon::snapshot::ProcessTree;
+use memon::source;
+
+#[test]
+fn filters_parse_operator_and_value() {
+    assert_eq!(nice::parse_filter("<0"), Ok(NiceFilter { op: NiceOp::Less, value: 0 }));
+    assert_eq!(nice::parse_filter("<=-5"), Ok(NiceFilter { op: NiceOp::LessOrEqual, value: -5 }));
+    assert_eq!(nice::parse_filter(" >= 10 "), Ok(NiceFilter { op: NiceOp::GreaterOrEqual, value: 10 }));
+    assert_eq!(nice::parse_filter(">19"), Ok(NiceFilter { op: NiceOp::Greater, value: 19 }));
+    assert_eq!(nice::parse_filter("=0"), Ok(NiceFilter { op: NiceOp::Equal, value: 0 }));
+    assert!(nice::parse_filter("0").is_err());
+    assert!(nice::parse_filter("<x").is_err());
+    assert!(nice::parse_filter("<-21").is_err());
+}
+
+#[test]
+fn unknown_nice_values_never_pass() {
+    let filter = NiceFilter { op: NiceOp::GreaterOrEqual, value: -20 };
+    assert!(filter.matches(Some(-20)));
+    assert!(!filter.matches(None));
+}
+
+// systemd 1 (0) -> pipewire 10 (-11) -> pw-helper 11 (0)
+//               -> cron 20 (0) -> backup 21 (19)
+//               -> sshd 30 (0)
+fn table() -> HashMap<u32, ProcessInfo> {
+    let mut processes = source::parse_table("1 - 8M systemd\n10 1 40M pipewire\n11 10 2M pw-helper\n20 1 4M cron\n21 20 300M backup\n30 1 6M sshd\n").unwrap();
+    for (pid, nice) in [(1, 0), (10, -11), (11, 0), (20, 0), (21, 19), (30, 0)] {
+        processes.get_mut(&pid).unwrap().nice = Some(nice);
+    }
+    source::link_children(&mut processes);
+    processes
+}
+
+fn pids(tree: &ProcessTree) -> Vec<u32> {
+    tree.iter_preorder().map(|(node, _)| node.pid).collect()
+}
+
+#[test]
+fn pruning_keeps_passing_processes_and_their_ancestors() {
+    let mut processes = table();
+    assert!(nice::prune(&mut processes, 1, nice::parse_filter("<0").unwrap()));
+    let tree = ProcessTree::from_processes(&mut processes, 1).unwrap();
+    assert_eq!(pids(&tree), [1, 10]);
+    assert_eq!((tree.process_count, tree.total_bytes), (2, 48 * 1024 * 1024));
+
+    let mut processes = table();
+    assert!(nice::prune(&mut processes, 1, nice::parse_filter(">0").unwrap()));
+    let tree = ProcessTree::from_processes(&mut processes, 1).unwrap();
+    assert_eq!(pids(&tree), [1, 20, 21]);
+    assert_eq!(tree.find(21).and_then(|node| node.nice), Some(19));
+}
+
+#[test]
+fn pruning_reports_trees_without_passing_processes() {
+    let mut processes = table();
+    assert!(!nice::prune(&mut processes, 20, nice::parse_filter("<0").unwrap()));
+    assert!(!nice::prune(&mut processes, 99, nice::parse_filter("<0").unwrap()));
+}
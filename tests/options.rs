@@ -0,0 +1,134 @@
+// Mapping of command line flags onto MonitorOptions
+
+use clap::Parser;
+use memon::cli::{Args, ColorWhen, Marker, TreeStyle};
+use memon::colors::ColorEnv;
+use memon::format::OutputFormat;
+use memon::options::{ColorChoice, Columns, MonitorOptions, TreeSelection};
+use memon::source::SelfExclusion;
+use memon::threshold::Thresholds;
+use std::time::Duration;
+
+fn options(argv: &[&str]) -> MonitorOptions {
+    let args = Args::try_parse_from(argv).unwrap();
+    MonitorOptions::from_args(&args, OutputFormat::Text, ColorEnv::default(), true, true)
+}
+
+#[test]
+fn maps_columns_and_limits() {
+    let opts = options(&["memon", "chrome", "-v", "--counts", "--percent", "--min-percent", "2.5", "--jobs", "3", "--timeout", "7"]);
+    assert_eq!(opts.columns, Columns { args: true, counts: true, percent: true, percent_of_parent: false, mem_details: false, hugepages: false, limits: false, ports: false, jvm: false, gpu: false, tty: false, peak: false, stability: false, churn: false, k8s: false, nice: false, pgid: false, privs: false, history: false });
+    assert_eq!(opts.min_percent, Some(2.5));
+    assert_eq!(opts.jobs, 3);
+    assert_eq!(opts.timeout, Some(Duration::from_secs(7)));
+    assert_eq!(opts.format, OutputFormat::Text);
+}
+
+#[test]
+fn min_percent_must_be_a_percentage() {
+    assert_eq!(options(&["memon", "chrome", "--min-percent", "0"]).min_percent, Some(0.0));
+    assert_eq!(options(&["memon", "chrome", "--min-percent", "100"]).min_percent, Some(100.0));
+    // The = form, so -5 reaches the parser instead of being taken for a flag
+    for value in ["nan", "-5", "inf", "100.5", "2%"] {
+        let flag = format!("--min-percent={}", value);
+        assert!(Args::try_parse_from(["memon", "chrome", flag.as_str()]).is_err(), "{} accepted", value);
+    }
+    let error = Args::try_parse_from(["memon", "chrome", "--min-percent=nan"]).unwrap_err().to_string();
+    assert!(error.contains("percentage must be from 0 to 100, not 'nan'"), "{}", error);
+}
+
+#[test]
+fn maps_tree_limits() {
+    assert_eq!(options(&["memon", "chrome", "--max-trees", "3"]).max_trees, Some(3));
+    assert_eq!(options(&["memon", "chrome", "--first"]).max_trees, Some(1));
+    assert_eq!(options(&["memon", "chrome"]).max_trees, None);
+    assert!(Args::try_parse_from(["memon", "chrome", "--max-trees", "0"]).is_err());
+    assert!(Args::try_parse_from(["memon", "chrome", "--first", "--max-trees", "2"]).is_err());
+}
+
+#[test]
+fn selects_single_tree_by_index_or_root_pid() {
+    assert_eq!(options(&["memon", "chrome", "--tree", "2"]).tree, Some(TreeSelection::Index(2)));
+    assert_eq!(options(&["memon", "chrome", "--tree-root", "1234"]).tree, Some(TreeSelection::RootPid(1234)));
+    assert_eq!(options(&["memon", "chrome"]).tree, None);
+    assert!(Args::try_parse_from(["memon", "chrome", "--tree", "1", "--tree-root", "5"]).is_err());
+    assert!(Args::try_parse_from(["memon", "chrome", "--tree", "1", "--first"]).is_err());
+    
+    let roots = [40, 300, 1234];
+    assert_eq!(TreeSelection::Index(2).select(&roots), Some(300));
+    assert_eq!(TreeSelection::Index(4).select(&roots), None);
+    assert_eq!(TreeSelection::RootPid(1234).select(&roots), Some(1234));
+    assert_eq!(TreeSelection::RootPid(41).select(&roots), None);
+}
+
+#[test]
+fn maps_marker_style() {
+    assert_eq!(options(&["memon", "chrome"]).decorations.marker, Marker::Emoji);
+    assert_eq!(options(&["memon", "chrome", "--marker", "rank"]).decorations.marker, Marker::Rank);
+    assert_eq!(options(&["memon", "chrome", "--marker", "ascii"]).decorations.marker, Marker::Ascii);
+    assert!(Args::try_parse_from(["memon", "chrome", "--marker", "stars"]).is_err());
+}
+
+#[test]
+fn args_markers_need_decorations() {
+    assert!(!options(&["memon", "chrome", "--show-args"]).decorations.args);
+    assert!(options(&["memon", "chrome", "--show-args", "--decorations"]).decorations.args);
+}
+
+#[test]
+fn maps_tree_style_and_indent() {
+    let opts = options(&["memon", "chrome"]);
+    assert_eq!((opts.tree_style, opts.indent), (TreeStyle::Unicode, 2));
+    let opts = options(&["memon", "chrome", "--tree-style", "ascii", "--indent", "4"]);
+    assert_eq!((opts.tree_style, opts.indent), (TreeStyle::Ascii, 4));
+    assert_eq!(options(&["memon", "chrome", "--tree-style", "indent"]).tree_style, TreeStyle::Indent);
+}
+
+#[test]
+fn maps_thresholds() {
+    let opts = options(&["memon", "chrome", "--fail-if-total", "2G", "--fail-if-process", "512M"]);
+    assert_eq!(opts.thresholds, Thresholds { total: Some(2 << 30), process: Some(512 << 20) });
+    assert_eq!(options(&["memon", "chrome"]).thresholds, Thresholds::default());
+}
+
+#[test]
+fn defaults_are_plain() {
+    let opts = options(&["memon", "chrome"]);
+    assert_eq!(opts.columns, Columns::default());
+    assert_eq!(opts.min_percent, None);
+    assert_eq!(opts.timeout, None);
+    assert_eq!(opts.color, ColorChoice::Auto { enabled: true });
+}
+
+#[test]
+fn resolves_color_once() {
+    assert_eq!(options(&["memon", "chrome", "--no-color", "--color", "always"]).color, ColorChoice::Never);
+    assert_eq!(options(&["memon", "chrome", "--color", "never"]).color, ColorChoice::Never);
+    assert_eq!(options(&["memon", "chrome", "--output", "x.txt", "--color", "always"]).color, ColorChoice::Always);
+    assert_eq!(options(&["memon", "chrome", "--output", "x.txt"]).color, ColorChoice::Auto { enabled: false });
+    let no_color = ColorEnv { force: false, disable: true };
+    assert_eq!(ColorChoice::resolve(ColorWhen::Auto, false, true, no_color), ColorChoice::Auto { enabled: false });
+    assert_eq!(ColorChoice::resolve(ColorWhen::Auto, false, false, ColorEnv::default()), ColorChoice::Auto { enabled: false });
+    let forced = ColorEnv { force: true, disable: false };
+    assert_eq!(ColorChoice::resolve(ColorWhen::Auto, false, false, forced), ColorChoice::Auto { enabled: true });
+    assert_eq!(ColorChoice::resolve(ColorWhen::Never, false, true, forced), ColorChoice::Never);
+    assert!(!ColorChoice::Auto { enabled: false }.enabled());
+    assert!(ColorChoice::Always.enabled());
+}
+
+#[test]
+fn maps_self_exclusion() {
+    assert_eq!(options(&["memon", "bash"]).exclude_self, SelfExclusion::Process);
+    assert_eq!(options(&["memon", "bash", "--exclude-self-tree"]).exclude_self, SelfExclusion::Ancestors);
+    assert_eq!(options(&["memon", "bash", "--include-self"]).exclude_self, SelfExclusion::Include);
+    assert!(Args::try_parse_from(["memon", "bash", "--include-self", "--exclude-self-tree"]).is_err());
+}
+
+#[test]
+fn under_makes_the_pattern_optional() {
+    assert_eq!(options(&["memon", "--under", "42"]).under, Some(42));
+    assert_eq!(options(&["memon", "node", "--under", "42"]).under, Some(42));
+    assert_eq!(options(&["memon", "node"]).under, None);
+    assert!(Args::try_parse_from(["memon"]).is_err());
+    assert!(Args::try_parse_from(["memon", "--under", "x"]).is_err());
+}
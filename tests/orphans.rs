@@ -0,0 +1,81 @@
+// Watch mode follows workers re-parented out of the matched trees (--no-follow-orphans)
+
+use std::collections::{HashMap, HashSet};
+
+use memon::options::Columns;
+use memon::orphans::OrphanTracker;
+use memon::process::ProcessInfo;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+// init 1 -> supervisor 10 -> worker 11 -> helper 12
+//                         -> worker 13
+//        -> cron 20
+fn table(supervisor_alive: bool) -> HashMap<u32, ProcessInfo> {
+    let text = if supervisor_alive {
+        "1 - 8M init\n10 1 20M supervisor\n11 10 300M worker\n12 11 4M helper\n13 10 280M worker\n20 1 2M cron\n"
+    } else {
+        "1 - 8M init\n11 1 300M worker\n12 11 4M helper\n13 1 280M worker\n20 1 2M cron\n"
+    };
+    let mut processes = source::parse_table(text).unwrap();
+    for proc_info in processes.values_mut() {
+        proc_info.start_time = 1_700_000_000 + proc_info.pid as u64;
+    }
+    source::link_children(&mut processes);
+    processes
+}
+
+fn reachable(processes: &HashMap<u32, ProcessInfo>, roots: &[u32]) -> HashSet<u32> {
+    roots.iter().flat_map(|&root| source::descendant_set(processes, root)).collect()
+}
+
+#[test]
+fn workers_of_a_dead_supervisor_are_followed() {
+    let mut tracker = OrphanTracker::default();
+    let processes = table(true);
+    assert!(tracker.update(&processes, &reachable(&processes, &[10])).is_empty());
+    assert_eq!(tracker.len(), 4);
+    
+    // The supervisor died; its workers now hang off init and match nothing
+    let processes = table(false);
+    assert_eq!(tracker.update(&processes, &HashSet::new()), [11, 13]);
+    assert_eq!(tracker.len(), 3);
+    
+    // A worker that exits is forgotten, and so is one whose PID was reused
+    let mut processes = table(false);
+    processes.remove(&13);
+    processes.get_mut(&11).unwrap().start_time += 60;
+    assert_eq!(tracker.update(&processes, &HashSet::new()), [12]);
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn workers_back_in_a_matched_tree_are_not_orphans() {
+    let mut tracker = OrphanTracker::default();
+    let processes = table(true);
+    tracker.update(&processes, &reachable(&processes, &[10]));
+    // A pattern matching the workers themselves keeps them in matched trees
+    let processes = table(false);
+    assert!(tracker.update(&processes, &reachable(&processes, &[11, 13])).is_empty());
+}
+
+#[test]
+fn orphaned_workers_get_their_own_subtotal() {
+    let mut processes = table(false);
+    let supervisor = ProcessTree::from_processes(&mut processes, 20).unwrap();
+    let mut trees = vec![supervisor];
+    for pid in [11, 13] {
+        let mut tree = ProcessTree::from_processes(&mut processes, pid).unwrap();
+        tree.reparented = true;
+        trees.push(tree);
+    }
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "cron".to_string(), matched: 1, trees, ..Default::default() };
+    let output = render_to_string(&TextRenderer::new(Theme::PLAIN, Columns::default(), None), &snapshot);
+    assert!(output.contains("2 procs | 152.0MB avg | 304.0MB total (reparented)\n"), "{}", output);
+    assert!(output.contains("1 procs | 2.0MB avg | 2.0MB total\n"), "{}", output);
+    assert!(output.ends_with("\norphaned workers: 3 procs | 584.0MB total\n"), "{}", output);
+    
+    let json = memon::render::render_json(&snapshot);
+    assert_eq!(json.matches("\"reparented\": true").count(), 2, "{}", json);
+}
@@ -0,0 +1,68 @@
+// --overview: one line per tree, largest total first
+
+use std::collections::HashMap;
+
+use clap::Parser;
+use memon::cli::Args;
+use memon::overview::{self, Descendant, Overview};
+use memon::process::ProcessInfo;
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+use memon::units::{SizeFormat, Unit};
+
+const MB: u64 = 1024 * 1024;
+
+// A small nginx tree, a larger postgres one and a lone redis
+fn snapshot() -> Snapshot {
+    let rows = [
+        (10, None, "nginx", 20 * MB), (11, Some(10), "nginx", 30 * MB), (12, Some(10), "nginx", 30 * MB),
+        (20, None, "postgres", 100 * MB), (21, Some(20), "postgres", 50 * MB), (22, Some(21), "autovacuum", 200 * MB),
+        (30, None, "redis", 40 * MB),
+    ];
+    let mut processes: HashMap<u32, ProcessInfo> = rows.iter()
+        .map(|&(pid, ppid, name, rss)| (pid, ProcessInfo::new(pid, name.to_string(), rss, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    let trees: Vec<ProcessTree> = [10, 20, 30].iter().filter_map(|&pid| ProcessTree::from_processes(&mut processes, pid)).collect();
+    Snapshot { version: "0.1.0".to_string(), pattern: "db".to_string(), matched: 5, trees, ..Default::default() }
+}
+
+#[test]
+fn trees_are_sorted_by_total_with_their_largest_descendant() {
+    let overview = Overview::new(&snapshot(), None);
+    let roots: Vec<u32> = overview.trees.iter().map(|tree| tree.root_pid).collect();
+    assert_eq!(roots, [20, 10, 30]);
+    assert_eq!(overview.trees[0].process_count, 3);
+    assert_eq!(overview.trees[0].total_bytes, 350 * MB);
+    assert_eq!(overview.trees[0].largest_descendant, Some(Descendant { pid: 22, name: "autovacuum".to_string(), rss_bytes: 200 * MB }));
+    // A tie goes to the first child; a lone root has no descendant
+    assert_eq!(overview.trees[1].largest_descendant.as_ref().map(|largest| largest.pid), Some(11));
+    assert_eq!(overview.trees[2].largest_descendant, None);
+    
+    let limited = Overview::new(&snapshot(), Some(2));
+    assert_eq!(limited.trees.len(), 2);
+    assert_eq!(limited.matched, 5);
+}
+
+#[test]
+fn text_is_one_aligned_line_per_tree() {
+    let overview = Overview::new(&snapshot(), None);
+    let text = overview::render_text(&overview, &SizeFormat::new(Unit::Auto, true, None));
+    assert_eq!(text, "\
+postgres  20  3 procs  350.0MB  largest autovacuum (22) 200.0MB
+nginx     10  3 procs   80.0MB  largest nginx (11) 30.0MB
+redis     30  1 procs   40.0MB  largest -
+");
+    
+    let json: serde_json::Value = serde_json::from_str(&overview::render_json(&overview)).unwrap();
+    assert_eq!(json["trees"][0]["root_name"], "postgres");
+    assert_eq!(json["trees"][0]["largest_descendant"]["pid"], 22);
+    assert!(json["trees"][2]["largest_descendant"].is_null());
+}
+
+#[test]
+fn overview_is_a_one_shot_report() {
+    assert!(Args::try_parse_from(["memon", "nginx", "--overview", "--max-trees", "2", "--json"]).unwrap().overview);
+    assert!(Args::try_parse_from(["memon", "nginx", "--overview", "--watch", "2"]).is_err());
+    assert!(Args::try_parse_from(["memon", "nginx", "--overview", "--tree", "1"]).is_err());
+}
@@ -0,0 +1,51 @@
+// Long text output goes through $MEMON_PAGER, $PAGER or less (--pager/--no-pager)
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+
+use clap::Parser;
+use memon::cli::Args;
+use memon::pager;
+
+fn command(vars: &[(&str, &str)]) -> Option<String> {
+    let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+    pager::command(|name| vars.get(name).map(|value| value.to_string()))
+}
+
+#[test]
+fn memon_pager_wins_over_pager_and_cat_turns_it_off() {
+    assert_eq!(command(&[]).as_deref(), Some("less -RFX"));
+    assert_eq!(command(&[("PAGER", "more")]).as_deref(), Some("more"));
+    assert_eq!(command(&[("PAGER", "more"), ("MEMON_PAGER", "most -s")]).as_deref(), Some("most -s"));
+    assert_eq!(command(&[("PAGER", "more"), ("MEMON_PAGER", "")]), None);
+    assert_eq!(command(&[("PAGER", "cat")]), None);
+}
+
+#[test]
+fn only_output_taller_than_the_terminal_is_paged() {
+    let text = "a\nb\nc\n";
+    assert!(!pager::needs_paging(text, Some(3)));
+    assert!(pager::needs_paging(text, Some(2)));
+    assert!(!pager::needs_paging(text, None));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_missing_pager_program_is_detected_before_running_it() {
+    let path = OsStr::new("/nonexistent:/bin:/usr/bin");
+    assert!(pager::program_exists("sh -c cat", Some(path)));
+    assert!(pager::program_exists("/bin/sh", None));
+    assert!(!pager::program_exists("no-such-pager -R", Some(path)));
+    assert!(!pager::program_exists("sh", None));
+    assert!(pager::page("no-such-pager -R", "text\n").is_err());
+}
+
+#[test]
+fn the_last_of_pager_and_no_pager_wins() {
+    let args = Args::try_parse_from(["memon", "nginx", "--pager", "--no-pager"]).unwrap();
+    assert!(!args.pager && args.no_pager);
+    let args = Args::try_parse_from(["memon", "nginx", "--no-pager", "--pager"]).unwrap();
+    assert!(args.pager && !args.no_pager);
+    assert!(Args::try_parse_from(["memon", "nginx", "--pager", "--watch", "2"]).is_err());
+    assert!(Args::try_parse_from(["memon", "nginx", "--pager", "--json"]).is_err());
+}
@@ -0,0 +1,73 @@
+// ~ and ${VAR} in path flags, and relative paths made absolute
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use memon::paths;
+
+fn expand(text: &str) -> Result<PathBuf, String> {
+    let lookup = |name: &str| match name {
+        "HOME" => Some("/home/me".to_string()),
+        "CAPTURES" => Some("/srv/captures".to_string()),
+        "EMPTY" => Some(String::new()),
+        _ => None,
+    };
+    paths::expand(text, lookup, Some(Path::new("/work")))
+}
+
+#[test]
+fn home_and_variables_are_expanded() {
+    assert_eq!(expand("~/captures/run.json"), Ok(PathBuf::from("/home/me/captures/run.json")));
+    assert_eq!(expand("~"), Ok(PathBuf::from("/home/me")));
+    assert_eq!(expand("${CAPTURES}/run-${CAPTURES}.json"), Ok(PathBuf::from("/srv/captures/run-/srv/captures.json")));
+    // Set but empty is still set, as in the shell
+    assert_eq!(expand("/tmp/${EMPTY}run.json"), Ok(PathBuf::from("/tmp/run.json")));
+    // Only a leading ~ of this user; $VAR without braces is left alone
+    assert_eq!(expand("/tmp/~/a"), Ok(PathBuf::from("/tmp/~/a")));
+    assert_eq!(expand("~other/a"), Ok(PathBuf::from("/work/~other/a")));
+    assert_eq!(expand("$CAPTURES"), Ok(PathBuf::from("/work/$CAPTURES")));
+}
+
+#[test]
+fn relative_paths_are_made_absolute() {
+    assert_eq!(expand("run.json"), Ok(PathBuf::from("/work/run.json")));
+    assert_eq!(expand("../run.json"), Ok(PathBuf::from("/work/../run.json")));
+    assert_eq!(expand("/abs/run.json"), Ok(PathBuf::from("/abs/run.json")));
+    let lookup = |_: &str| None;
+    assert_eq!(paths::expand("run.json", lookup, None), Ok(PathBuf::from("run.json")));
+}
+
+#[test]
+fn unset_variables_are_errors() {
+    assert_eq!(expand("${NOPE}/run.json"), Err("environment variable NOPE is not set, needed for '${NOPE}/run.json'".to_string()));
+    assert_eq!(expand("${CAPTURES/run.json"), Err("unclosed '${' in '${CAPTURES/run.json'".to_string()));
+    assert_eq!(expand("${}/run.json"), Err("empty '${}' in '${}/run.json'".to_string()));
+    let lookup = |_: &str| None;
+    assert_eq!(paths::expand("~", lookup, None), Err("environment variable HOME is not set, needed for '~'".to_string()));
+}
+
+#[test]
+fn path_flags_expand_on_the_command_line() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let memon = |from_file: &str| {
+        Command::new(env!("CARGO_BIN_EXE_memon"))
+            .args(["nginx", "--from-file", from_file])
+            .env("MEMON_FIXTURES", &fixtures)
+            .env_remove("MEMON_NO_SUCH_DIR")
+            .output()
+            .unwrap()
+    };
+    let output = memon("${MEMON_FIXTURES}/small_tree.table");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("100 nginx"));
+
+    let output = memon("${MEMON_NO_SUCH_DIR}/small_tree.table");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("environment variable MEMON_NO_SUCH_DIR is not set, needed for '${MEMON_NO_SUCH_DIR}/small_tree.table'"), "{}", stderr);
+
+    // Failures name the resolved path
+    let output = memon("missing.table");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let resolved = std::env::current_dir().unwrap().join("missing.table");
+    assert!(stderr.contains(&format!("cannot read process table {}", resolved.display())), "{}", stderr);
+}
@@ -0,0 +1,100 @@
+// [pattern."..."] sections of the config file: resolution order and --show-config
+
+use std::ffi::OsString;
+
+use memon::config::Config;
+use memon::pattern_config::{self, Resolved, Setting, Source};
+use memon::units::Unit;
+
+const CONFIG: &str = "\
+[pattern.\"postgres\"]
+peak = true
+unit = \"b\"
+max-trees = 3
+
+[pattern.\"post*\"]
+max-trees = 2
+group-by = \"exe\"
+";
+
+fn resolve(config: &str, argv: &[&str]) -> Result<Resolved, String> {
+    let config = Config::parse(config).unwrap();
+    let argv: Vec<OsString> = argv.iter().map(OsString::from).collect();
+    let pattern = argv.get(1).and_then(|arg| arg.to_str()).unwrap_or_default().to_string();
+    pattern_config::resolve(&argv, &config.sections_for(&pattern))
+}
+
+fn source(resolved: &Resolved, flag: &str) -> Option<Source> {
+    resolved.settings.iter().find(|setting| setting.flag == flag).map(|setting| setting.source.clone())
+}
+
+#[test]
+fn sections_fill_in_options_and_later_sections_win() {
+    let resolved = resolve(CONFIG, &["memon", "postgres"]).unwrap();
+    assert!(resolved.args.peak);
+    assert_eq!(resolved.args.unit, Unit::B);
+    assert_eq!(resolved.args.max_trees, Some(2));
+    assert_eq!(resolved.sections, ["[pattern.\"postgres\"]", "[pattern.\"post*\"]"]);
+    assert_eq!(source(&resolved, "--max-trees"), Some(Source::Section("[pattern.\"post*\"]".to_string())));
+    
+    // Only the wildcard section matches here, and none at all for nginx
+    let resolved = resolve(CONFIG, &["memon", "postmaster"]).unwrap();
+    assert!(!resolved.args.peak);
+    assert_eq!(resolved.args.max_trees, Some(2));
+    assert!(resolve(CONFIG, &["memon", "nginx"]).unwrap().settings.is_empty());
+}
+
+#[test]
+fn the_command_line_wins_over_every_section() {
+    let resolved = resolve(CONFIG, &["memon", "postgres", "--max-trees", "5", "--unit", "auto"]).unwrap();
+    assert_eq!(resolved.args.max_trees, Some(5));
+    assert_eq!(resolved.args.unit, Unit::Auto);
+    assert_eq!(source(&resolved, "--unit"), Some(Source::CommandLine));
+    assert!(resolved.settings.contains(&Setting { flag: "--max-trees".to_string(), values: vec!["2".to_string()], source: Source::Overridden("[pattern.\"post*\"]".to_string()) }));
+    
+    // Also where the command line sets a conflicting option
+    let resolved = resolve(CONFIG, &["memon", "postgres", "--first"]).unwrap();
+    assert!(resolved.args.first);
+    assert_eq!(resolved.args.max_trees, None);
+    assert!(resolved.args.peak);
+}
+
+#[test]
+fn bad_section_options_are_errors() {
+    assert_eq!(resolve("[pattern.nginx]\nno-such-flag = true\n", &["memon", "nginx"]).unwrap_err(), "unknown option \"no-such-flag\" in [pattern.\"nginx\"]");
+    assert_eq!(resolve("[pattern.nginx]\nmax-trees = true\n", &["memon", "nginx"]).unwrap_err(), "\"max-trees\" in [pattern.\"nginx\"] needs a value");
+    assert_eq!(resolve("[pattern.nginx]\npeak = 1\n", &["memon", "nginx"]).unwrap_err(), "\"peak\" in [pattern.\"nginx\"] is a switch; use true or false");
+    let err = resolve("[pattern.nginx]\nmax-trees = 0\n", &["memon", "nginx"]).unwrap_err();
+    assert!(err.starts_with("with the options of the config file: invalid value '0'"), "{}", err);
+    // A switch set to false is left out
+    assert!(!resolve("[pattern.nginx]\npeak = false\n", &["memon", "nginx"]).unwrap().args.peak);
+}
+
+#[test]
+fn show_config_lists_the_merged_options() {
+    let resolved = resolve(CONFIG, &["memon", "postgres", "--max-trees", "5"]).unwrap();
+    assert_eq!(pattern_config::render("postgres", &resolved), "\
+Pattern: postgres
+Sections: [pattern.\"postgres\"], [pattern.\"post*\"]
+--unit b        [pattern.\"postgres\"]
+--peak          [pattern.\"postgres\"]
+--group-by exe  [pattern.\"post*\"]
+--max-trees 5   command line
+--max-trees 2   [pattern.\"post*\"], overridden by the command line
+");
+}
+
+#[test]
+fn show_config_runs_nothing() {
+    let path = std::env::temp_dir().join(format!("memon-pattern-config-{}.toml", std::process::id()));
+    std::fs::write(&path, CONFIG).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_memon"))
+        .args(["--config", path.to_str().unwrap(), "--show-config", "postgres"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.starts_with(&format!("Config file: {}\nPattern: postgres\n", path.display())), "{}", stdout);
+    assert!(stdout.contains("--peak"));
+}
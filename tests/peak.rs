@@ -0,0 +1,59 @@
+// Peak memory tracking (--peak)
+
+use std::collections::HashMap;
+
+use memon::peak::{Peak, PeakSource, PeakTracker};
+use memon::process::ProcessInfo;
+
+const MB: u64 = 1024 * 1024;
+
+fn table(readings: &[(u32, &str, u64)]) -> HashMap<u32, ProcessInfo> {
+    readings.iter()
+        .map(|&(pid, name, rss)| (pid, ProcessInfo::new(pid, name.to_string(), rss, None)))
+        .collect()
+}
+
+#[test]
+fn observed_peak_survives_a_drop() {
+    let mut peaks = PeakTracker::new();
+    for (rss, peak) in [(10, 10), (40, 40), (25, 40)] {
+        let mut processes = table(&[(1, "job", rss * MB)]);
+        peaks.observe(&mut processes, &[1], &HashMap::new());
+        assert_eq!(processes[&1].peak, Some(Peak { bytes: peak * MB, source: PeakSource::Observed }));
+    }
+    assert_eq!(peaks.max_process.as_ref().map(|max| (max.pid, max.bytes)), Some((1, 40 * MB)));
+}
+
+#[test]
+fn kernel_peak_wins_when_higher() {
+    let mut peaks = PeakTracker::new();
+    let mut processes = table(&[(1, "job", 10 * MB), (2, "helper", 5 * MB)]);
+    peaks.observe(&mut processes, &[1, 2], &HashMap::from([(1, 90 * MB)]));
+    assert_eq!(processes[&1].peak, Some(Peak { bytes: 90 * MB, source: PeakSource::Kernel }));
+    assert_eq!(processes[&2].peak, Some(Peak { bytes: 5 * MB, source: PeakSource::Observed }));
+}
+
+#[test]
+fn only_listed_processes_are_tracked_and_exited_ones_are_forgotten() {
+    let mut peaks = PeakTracker::new();
+    let mut processes = table(&[(1, "job", 10 * MB), (2, "unrelated", 500 * MB)]);
+    peaks.observe(&mut processes, &[1], &HashMap::new());
+    assert_eq!(processes[&2].peak, None);
+    assert_eq!(peaks.max_process.as_ref().map(|max| max.name.as_str()), Some("job"));
+    
+    // PID 1 exits and is reused: the old peak does not carry over
+    peaks.observe(&mut table(&[]), &[], &HashMap::new());
+    let mut processes = table(&[(1, "job", 3 * MB)]);
+    peaks.observe(&mut processes, &[1], &HashMap::new());
+    assert_eq!(processes[&1].peak.map(|peak| peak.bytes), Some(3 * MB));
+}
+
+#[test]
+fn tree_and_run_totals_keep_their_maximum() {
+    let mut peaks = PeakTracker::new();
+    assert_eq!(peaks.observe_tree((1, 100), 30 * MB), 30 * MB);
+    assert_eq!(peaks.observe_tree((1, 100), 20 * MB), 30 * MB);
+    peaks.observe_total(50 * MB);
+    peaks.observe_total(45 * MB);
+    assert_eq!(peaks.max_total, 50 * MB);
+}
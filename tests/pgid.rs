@@ -0,0 +1,89 @@
+// --pgid and --group-by pgid: process groups such as shell pipelines
+
+mod common;
+
+use memon::cli::GroupBy;
+use memon::options::Columns;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{self, GroupTotal, ProcessNode, ProcessTree, Snapshot, UNKNOWN_PGID};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, rss: u64, pgid: Option<u32>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { pgid, ..common::node(pid, name, rss, children) }
+}
+
+// A shell with two background pipelines, `make | tee` and `tail | grep`,
+// whose members are siblings under the shell
+fn shell_with_jobs() -> Snapshot {
+    let tree = ProcessTree::new(node(100, "bash", MB, Some(100), vec![
+        node(201, "make", 8 * MB, Some(201), Vec::new()),
+        node(202, "tee", MB, Some(201), Vec::new()),
+        node(301, "tail", 2 * MB, Some(301), Vec::new()),
+        node(302, "grep", 3 * MB, Some(301), Vec::new()),
+        node(400, "kworker", MB, None, Vec::new()),
+    ]));
+    Snapshot { version: "0.1.0".to_string(), pattern: "bash".to_string(), matched: 1, trees: vec![tree], ..Default::default() }
+}
+
+#[test]
+fn pipelines_are_totalled_under_their_leader() {
+    let mut snapshot = shell_with_jobs();
+    let groups = snapshot::pgid_totals(&snapshot.trees);
+    assert_eq!(groups, vec![
+        GroupTotal { key: "201 (make)".to_string(), processes: 2, bytes: 9 * MB },
+        GroupTotal { key: "301 (tail)".to_string(), processes: 2, bytes: 5 * MB },
+        GroupTotal { key: "(unknown)".to_string(), processes: 1, bytes: MB },
+        GroupTotal { key: "100 (bash)".to_string(), processes: 1, bytes: MB },
+    ]);
+    assert_eq!(groups[2].key, UNKNOWN_PGID);
+    
+    snapshot.groups = Some(groups);
+    let columns = Columns { pgid: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 7).with_group_by(Some(GroupBy::Pgid));
+    let output = render_to_string(&renderer, &snapshot);
+    assert!(output.contains("├─ 202 tee     1.0MB pgid 201"), "{}", output);
+    assert!(output.contains("└─ 400 kworker 1.0MB pgid -"), "{}", output);
+    let table: Vec<&str> = output.lines().rev().take(5).collect();
+    assert_eq!(table, [
+        "100 (bash)      1  1.0MB   6.2%",
+        "(unknown)       1  1.0MB   6.2%",
+        "301 (tail)      2  5.0MB  31.2%",
+        "201 (make)      2  9.0MB  56.2%",
+        "pgid        procs    rss  share",
+    ]);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn live_process_groups_are_read() {
+    use std::fs;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    
+    // Two copies of `sleep` in one new process group, like a pipeline
+    let name = format!("mpgid{}", std::process::id() % 100_000);
+    let dir = std::env::temp_dir().join(&name);
+    fs::create_dir_all(&dir).unwrap();
+    let binary = dir.join(&name);
+    let sleep = ["/bin/sleep", "/usr/bin/sleep"].into_iter().find(|path| fs::metadata(path).is_ok()).expect("sleep binary");
+    fs::copy(sleep, &binary).unwrap();
+    let mut leader = Command::new(&binary).arg("30").process_group(0).spawn().unwrap();
+    let pgid = leader.id();
+    let mut member = Command::new(&binary).arg("30").process_group(pgid as i32).spawn().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args([name.as_str(), "--pgid", "--group-by", "pgid"]).output().unwrap();
+    for child in [&mut leader, &mut member] {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let _ = fs::remove_dir_all(&dir);
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches(&format!(" pgid {}", pgid)).count(), 2, "{}", stdout);
+    let rows: Vec<&str> = stdout.lines().skip_while(|line| !line.starts_with("pgid ")).skip(1).collect();
+    assert_eq!(rows.len(), 1, "{}", stdout);
+    assert!(rows[0].starts_with(&format!("{} ({})", pgid, name)), "{}", stdout);
+    assert_eq!(rows[0].split_whitespace().nth(2), Some("2"), "{}", stdout);
+}
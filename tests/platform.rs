@@ -0,0 +1,138 @@
+// Parsers for the Linux /proc files behind --mem-details, --hugepages, --show-tty, --peak, --limits, --ports, --nice and --pgid, and `memon threads`
+#![cfg(target_os = "linux")]
+
+use memon::platform::linux::{parse_huge_pages, parse_limits, parse_net_tcp, parse_rollup_dirty, parse_socket_link, parse_stat_cpu_ticks, parse_stat_nice, parse_stat_pgid, parse_stat_session, parse_status_hwm, parse_status_locked, parse_syscall_sp, tty_name};
+
+#[test]
+fn status_locked_is_read_from_vmlck() {
+    let status = "Name:\tpostgres\nVmPeak:\t  220000 kB\nVmLck:\t    4096 kB\nVmRSS:\t   51200 kB\n";
+    assert_eq!(parse_status_locked(status), Some(4096 * 1024));
+    assert_eq!(parse_status_locked("Name:\tkthreadd\n"), None);
+}
+
+#[test]
+fn rollup_dirty_sums_shared_and_private() {
+    let rollup = "\
+558cd1b49000-7ffee0b35000 ---p 00000000 00:00 0                          [rollup]
+Rss:                1408 kB
+Shared_Clean:       1256 kB
+Shared_Dirty:         12 kB
+Private_Clean:        52 kB
+Private_Dirty:       100 kB
+";
+    assert_eq!(parse_rollup_dirty(rollup), Some(112 * 1024));
+    assert_eq!(parse_rollup_dirty("Rss: 1408 kB\n"), None);
+}
+
+#[test]
+fn huge_pages_missing_fields_stay_unknown() {
+    let status = "Name:\tpostgres\nHugetlbPages:\t  2097152 kB\n";
+    let rollup = "Rss:  1408 kB\nAnonHugePages:     6144 kB\n";
+    let pages = parse_huge_pages(status, rollup);
+    assert_eq!(pages.anon, Some(6144 * 1024));
+    assert_eq!(pages.hugetlb, Some(2097152 * 1024));
+    
+    // Older kernels have no HugetlbPages line, and smaps_rollup may be unreadable
+    let pages = parse_huge_pages("Name:\tpostgres\n", "");
+    assert_eq!((pages.anon, pages.hugetlb), (None, None));
+}
+
+#[test]
+fn stat_session_skips_names_with_spaces() {
+    let stat = "4242 (tmux: server) S 1 4242 4242 34819 4242 4194560 1 0 0 0";
+    assert_eq!(parse_stat_session(stat), Some((4242, 34819)));
+    let daemon = "812 (sshd) S 1 812 812 0 -1 4194560";
+    assert_eq!(parse_stat_session(daemon), Some((812, 0)));
+}
+
+#[test]
+fn stat_nice_is_field_nineteen() {
+    let stat = "4242 (pg (worker)) S 1 4242 4242 0 -1 4194560 120 0 0 0 5 3 0 0 39 19 1 0 900 15000000 512";
+    assert_eq!(parse_stat_nice(stat), Some(19));
+    let boosted = "77 (pipewire) S 1 77 77 0 -1 4194560 120 0 0 0 5 3 0 0 9 -11 1 0 900 15000000 512";
+    assert_eq!(parse_stat_nice(boosted), Some(-11));
+    assert_eq!(parse_stat_nice("77 (short) S 1"), None);
+}
+
+#[test]
+fn stat_pgid_is_field_five() {
+    // The second member of a pipeline led by 4241
+    let stat = "4242 (grep (x) y) S 4200 4241 4200 34817 4241 4194304 120 0 0 0 5 3 0 0 20 0 1 0 900 15000000 512";
+    assert_eq!(parse_stat_pgid(stat), Some(4241));
+    assert_eq!(parse_stat_pgid("4242 (grep) S"), None);
+}
+
+#[test]
+fn tty_numbers_map_to_device_names() {
+    assert_eq!(tty_name(0), None);
+    assert_eq!(tty_name(34819).as_deref(), Some("pts/3"));
+    assert_eq!(tty_name(1025).as_deref(), Some("tty1"));
+    assert_eq!(tty_name(1088).as_deref(), Some("ttyS0"));
+}
+
+#[test]
+fn status_hwm_is_the_lifetime_peak() {
+    let status = "Name:\tjob\nVmHWM:\t  204800 kB\nVmRSS:\t   51200 kB\n";
+    assert_eq!(parse_status_hwm(status), Some(204800 * 1024));
+    assert_eq!(parse_status_hwm("Name:\tkthreadd\n"), None);
+}
+
+#[test]
+fn limits_read_address_space_and_resident_rows() {
+    let limits = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max resident set          unlimited            unlimited            bytes     
+Max processes             23960                23960                processes 
+Max address space         4294967296           unlimited            bytes     
+Max file locks            unlimited            unlimited            locks     
+";
+    let parsed = parse_limits(limits).unwrap();
+    assert_eq!(parsed.address_space, Some(4 << 30));
+    assert_eq!(parsed.address_space_hard, None);
+    assert_eq!((parsed.resident, parsed.resident_hard), (None, None));
+    assert_eq!(parsed.virtual_bytes, None);
+    assert_eq!(parse_limits("Limit  Soft Limit  Hard Limit  Units\n"), None);
+}
+
+#[test]
+fn net_tcp_lists_listening_sockets_only() {
+    let table = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41230 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41231 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0BB8 0100007F:D2F4 01 00000000:00000000 00:00000000 00000000  1000        0 41999 1 0000000000000000 20 4 30 10 -1
+";
+    assert_eq!(parse_net_tcp(table), vec![(41230, 3000), (41231, 8080)]);
+    
+    // tcp6 addresses are longer but end in the port the same way
+    let table6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:01BB 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 5120 1 0000000000000000 100 0 0 10 0
+";
+    assert_eq!(parse_net_tcp(table6), vec![(5120, 443)]);
+    assert_eq!(parse_net_tcp(""), Vec::new());
+}
+
+#[test]
+fn socket_links_carry_the_inode() {
+    assert_eq!(parse_socket_link("socket:[41230]"), Some(41230));
+    assert_eq!(parse_socket_link("pipe:[41230]"), None);
+    assert_eq!(parse_socket_link("/dev/null"), None);
+    assert_eq!(parse_socket_link("socket:[]"), None);
+}
+
+#[test]
+fn thread_cpu_is_utime_plus_stime() {
+    let stat = "4250 (C2 Compiler) S 1 4242 4242 0 -1 4194624 9000 0 3 0 1234 56 0 0 20 0 57 0 900 9000000000 200000";
+    assert_eq!(parse_stat_cpu_ticks(stat), Some(1290));
+    assert_eq!(parse_stat_cpu_ticks("4250 (java) S 1 4242"), None);
+}
+
+#[test]
+fn syscall_stack_pointer_is_second_to_last() {
+    let blocked = "202 0x7f3a1c0008a8 0x80 0x0 0x0 0x0 0x0 0x7f3a0bffe9c0 0x7f3a2c891d25\n";
+    assert_eq!(parse_syscall_sp(blocked), Some(0x7f3a0bffe9c0));
+    assert_eq!(parse_syscall_sp("-1 0x7ffd5e4a1f00 0x55d4c0a12345\n"), Some(0x7ffd5e4a1f00));
+    assert_eq!(parse_syscall_sp("running\n"), None);
+}
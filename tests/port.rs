@@ -0,0 +1,51 @@
+// --port: selecting the processes listening on a TCP port
+
+use clap::Parser;
+use memon::cli::Args;
+
+#[test]
+fn port_stands_in_for_the_name() {
+    let args = Args::try_parse_from(["memon", "--port", "8080", "--port", "8443"]).unwrap();
+    assert_eq!(args.port, [8080, 8443]);
+    assert_eq!(args.process_name, None);
+    
+    assert!(Args::try_parse_from(["memon"]).is_err());
+    assert!(Args::try_parse_from(["memon", "--port", "http"]).is_err());
+    assert!(Args::try_parse_from(["memon", "--port", "8080", "--from-file", "table.txt"]).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn listeners_are_matched_by_port_and_name() {
+    use std::net::TcpListener;
+    use std::process::Command;
+    
+    // This test process listens; a second port nobody uses ORs in
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let unused = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port().to_string();
+    let own_pid = std::process::id().to_string();
+    let memon = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_memon")).args(args).output().unwrap();
+    
+    let output = memon(&["--port", &unused, "--port", &port]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.lines().next().is_some_and(|line| line.starts_with(&own_pid)), "{}", stdout);
+    // Its threads share the socket but are not matched themselves
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Found 1 procs"), "{}", stdout);
+    
+    // With a name, the process must match it too
+    let output = memon(&["no-such-process-name", "--port", &port]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("listening on port"));
+    
+    drop(listener);
+}
+
+#[cfg(not(target_os = "linux"))]
+#[test]
+fn port_is_refused_off_linux() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_memon")).args(["--port", "8080"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only supported on Linux"));
+}
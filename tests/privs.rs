@@ -0,0 +1,71 @@
+// --privs: users and capabilities compared with the tree's root
+
+mod common;
+
+use memon::options::Columns;
+use memon::privs::{self, Privileges};
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, privs: Option<Privileges>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { privs, ..common::node(pid, name, MB, children) }
+}
+
+fn user(uid: u32, euid: u32, user: &str, capabilities: u64) -> Option<Privileges> {
+    Some(Privileges { uid, euid, user: Some(user.to_string()), capabilities: Some(capabilities) })
+}
+
+// A developer's shell running sudo, ping with a file capability, and a
+// process of another user that could not be read
+fn shell() -> ProcessTree {
+    ProcessTree::new(node(100, "bash", user(1000, 1000, "dev", 0), vec![
+        node(101, "sudo", user(1000, 0, "root", 0x1ff_ffff_ffff), vec![node(102, "apt", user(0, 0, "root", 0x1ff_ffff_ffff), Vec::new())]),
+        node(103, "ping", user(1000, 1000, "dev", 0x2000), Vec::new()),
+        node(104, "vim", user(1000, 1000, "dev", 0), Vec::new()),
+        node(105, "agent", None, Vec::new()),
+    ]))
+}
+
+#[test]
+fn parses_uids_and_effective_capabilities() {
+    let status = "Name:\tping\nUid:\t1000\t0\t0\t0\nGid:\t1000\t1000\t1000\t1000\nCapInh:\t0000000000000000\nCapEff:\t0000000000002000\n";
+    assert_eq!(privs::parse_status(status), Some(Privileges { uid: 1000, euid: 0, user: None, capabilities: Some(0x2000) }));
+    // No capability line outside Linux-style status files, no privileges without a Uid line
+    assert_eq!(privs::parse_status("Uid:\t0\t0\t0\t0\n").unwrap().capabilities, None);
+    assert_eq!(privs::parse_status("Name:\tping\n"), None);
+    
+    let users = privs::parse_passwd("# local users\nroot:x:0:0:root:/root:/bin/bash\nwww-data:x:33:33::/var/www:/usr/sbin/nologin\nbroken\n");
+    assert_eq!(users.get(&33).map(String::as_str), Some("www-data"));
+    assert_eq!(users.len(), 2);
+}
+
+#[test]
+fn labels_mark_other_users_setuid_and_capabilities() {
+    let sudo = Privileges { uid: 1000, euid: 0, user: Some("root".to_string()), capabilities: Some(0x1ff_ffff_ffff) };
+    assert_eq!(sudo.label(Some(1000), false), "\u{2191}root setuid");
+    assert_eq!(sudo.label(Some(1000), true), "^root setuid");
+    // Root holds every capability anyway, so only its setuid shows
+    assert_eq!(sudo.label(Some(0), false), "setuid");
+    let worker = Privileges { uid: 33, euid: 33, user: None, capabilities: Some(0) };
+    assert_eq!(worker.label(Some(0), false), "\u{2193}33");
+    assert_eq!(worker.label(None, false), "");
+}
+
+#[test]
+fn privileged_processes_are_listed_under_the_tree() {
+    let tree = shell();
+    let (found, unreadable) = privs::privileged(&tree);
+    let pids: Vec<u32> = found.iter().map(|node| node.pid).collect();
+    assert_eq!((pids, unreadable), (vec![101, 102, 103], 1));
+    
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "bash".to_string(), matched: 1, trees: vec![tree], ..Default::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { privs: true, ..Columns::default() }, None).with_widths(3, 5);
+    let output = render_to_string(&renderer, &snapshot);
+    assert!(output.contains("  └─ 102 apt   1.0MB \u{2191}root\n"), "{}", output);
+    assert!(output.contains("├─ 103 ping  1.0MB caps 0x2000\n"), "{}", output);
+    assert!(output.contains("├─ 104 vim   1.0MB\n"), "{}", output);
+    assert!(output.contains("└─ 105 agent 1.0MB privs ?\n"), "{}", output);
+    assert!(output.ends_with("privileged: 3 procs: 101 sudo \u{2191}root setuid, 102 apt \u{2191}root, 103 ping caps 0x2000 (1 unreadable)\n"), "{}", output);
+}
@@ -0,0 +1,103 @@
+// Recording files (--record) and the offline report (`memon report`)
+
+#![cfg(feature = "record")]
+
+mod common;
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use memon::record::{self, HostInfo, RecordHeader, RecordSample, RecordWriter};
+use memon::snapshot::{ProcessTree, Snapshot};
+use common::node;
+
+const MB: u64 = 1024 * 1024;
+
+fn sample(timestamp: i64, server: u64, worker: u64) -> RecordSample {
+    let root = node(1, "server", server, vec![node(2, "worker", worker, Vec::new())]);
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "server".to_string(), matched: 2, trees: vec![ProcessTree::new(root)], ..Default::default() };
+    RecordSample { timestamp, snapshot }
+}
+
+fn header() -> RecordHeader {
+    let host = HostInfo { hostname: Some("web-1".to_string()), os: Some("Linux".to_string()), kernel: Some("6.5.0".to_string()), cpus: 8, total_memory: 16 * 1024 * MB };
+    RecordHeader::new("server", 5, 1_700_000_000, host)
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("memon-{}-{}.memon", name, std::process::id()))
+}
+
+fn write_recording(path: &Path, samples: &[RecordSample]) {
+    let mut writer = RecordWriter::create(path, &header()).unwrap();
+    for sample in samples {
+        writer.append(sample).unwrap();
+    }
+}
+
+#[test]
+fn round_trips_header_and_samples() {
+    let path = temp_path("roundtrip");
+    write_recording(&path, &[sample(1_700_000_000, 10 * MB, 20 * MB), sample(1_700_000_005, 10 * MB, 30 * MB)]);
+    let recording = record::read_recording(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(recording.header, header());
+    assert_eq!(recording.header.memon_version, env!("CARGO_PKG_VERSION"));
+    assert!(!recording.truncated);
+    let totals: Vec<u64> = recording.samples.iter().map(RecordSample::total_bytes).collect();
+    assert_eq!(totals, vec![30 * MB, 40 * MB]);
+}
+
+#[test]
+fn keeps_complete_samples_of_a_cut_off_file() {
+    let path = temp_path("truncated");
+    write_recording(&path, &[sample(1_700_000_000, 10 * MB, 20 * MB)]);
+    // Half a frame, as a crash mid-write leaves behind
+    let frame = zstd::encode_all(&b"{\"timestamp\":1700000005}\n"[..], 3).unwrap();
+    std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(&frame[..frame.len() / 2]).unwrap();
+    let recording = record::read_recording(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(recording.samples.len(), 1);
+    assert!(recording.truncated);
+}
+
+#[test]
+fn rejects_files_that_are_not_recordings() {
+    let path = temp_path("foreign");
+    std::fs::write(&path, zstd::encode_all(&b"{\"hello\":1}\n"[..], 3).unwrap()).unwrap();
+    let result = record::read_recording(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.unwrap_err().to_string().contains("not a memon recording"));
+}
+
+#[test]
+fn parses_durations() {
+    assert_eq!(record::parse_duration("90"), Ok(90));
+    assert_eq!(record::parse_duration("30s"), Ok(30));
+    assert_eq!(record::parse_duration("10m"), Ok(600));
+    assert_eq!(record::parse_duration("2h"), Ok(7200));
+    assert!(record::parse_duration("0s").is_err());
+    assert!(record::parse_duration("5d").is_err());
+    assert!(record::parse_duration("m").is_err());
+}
+
+#[test]
+fn report_summarizes_totals_peaks_and_growth() {
+    let path = temp_path("report");
+    write_recording(&path, &[sample(1_700_000_000, 10 * MB, 20 * MB), sample(1_700_000_005, 10 * MB, 50 * MB), sample(1_700_000_010, 10 * MB, 40 * MB)]);
+    let report = record::render_report(&record::read_recording(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(report.contains("Recording of 'server' made by memon"));
+    assert!(report.contains("Host: web-1, Linux (kernel 6.5.0), 8 CPUs, 16.0GB memory"));
+    assert!(report.contains("Samples: 3 every 5s"));
+    assert!(report.contains("min 30.0MB | avg 46.7MB | max 60.0MB"));
+    assert!(report.contains("first 30.0MB -> last 50.0MB (+20.0MB)"));
+
+    // The worker peaked at 50MB and leads the table
+    let table: Vec<&str> = report.lines().skip_while(|line| *line != "Largest process peaks").skip(2).take(2).collect();
+    assert!(table[0].contains("worker") && table[0].contains("50.0MB") && table[0].contains("40.0MB"));
+    assert!(table[1].contains("server"));
+}
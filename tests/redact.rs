@@ -0,0 +1,69 @@
+// --redact-args: command lines are redacted in the process table, before any renderer
+
+use std::collections::HashMap;
+
+use memon::format::OutputFormat;
+use memon::options::Columns;
+use memon::process::ProcessInfo;
+use memon::redact::{self, Redactions, REDACTED};
+use memon::render::{self, render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+#[test]
+fn secret_flag_values_are_replaced() {
+    assert_eq!(redact::redact_secret_flags("sync --token abc123 --verbose"), "sync --token [redacted] --verbose");
+    assert_eq!(redact::redact_secret_flags("db --PASSWORD=hunter2 --port=5432"), "db --PASSWORD=[redacted] --port=5432");
+    assert_eq!(redact::redact_secret_flags("vault --secret  s3cr3t"), "vault --secret  [redacted]");
+    assert_eq!(redact::redact_secret_flags("tool --secret"), "tool --secret");
+    assert_eq!(redact::redact_secret_flags("tool --tokens 5 --password-file /etc/pw"), "tool --tokens 5 --password-file /etc/pw");
+}
+
+#[test]
+fn patterns_match_name_or_command_line_ignoring_case() {
+    let redactions = Redactions::new(&["LegacySync".to_string(), "/opt/billing".to_string()]);
+    assert!(redactions.matches("legacysync", "legacysync -u admin"));
+    assert!(redactions.matches("python3", "python3 /opt/billing/export.py"));
+    assert!(!redactions.matches("python3", "python3 /opt/reports/export.py"));
+    assert!(!Redactions::default().matches("legacysync", ""));
+}
+
+// bash 1 -> legacysync 2 (credentials as bare arguments)
+//        -> uploader 3 (credentials behind --token)
+//        -> sleep 4 (nothing to hide)
+fn redacted_table(redactions: &Redactions) -> HashMap<u32, ProcessInfo> {
+    let mut processes = source::parse_table("1 - 4M bash\n2 1 30M legacysync\n3 1 12M uploader\n4 1 1M sleep\n").unwrap();
+    for (pid, args) in [(2, "legacysync -u admin -p hunter2"), (3, "uploader --token=tok_live_42 --bucket logs"), (4, "sleep 60")] {
+        let proc_info = processes.get_mut(&pid).unwrap();
+        proc_info.args = Some(args.to_string());
+        redactions.apply(proc_info);
+    }
+    source::link_children(&mut processes);
+    processes
+}
+
+#[test]
+fn raw_command_lines_never_reach_any_output() {
+    let redactions = Redactions::new(&["legacysync".to_string()]);
+    let mut processes = redacted_table(&redactions);
+    assert_eq!(processes[&2].args.as_deref(), Some(REDACTED));
+    assert_eq!(processes[&3].args.as_deref(), Some("uploader --token=[redacted] --bucket logs"));
+    assert_eq!(processes[&4].args.as_deref(), Some("sleep 60"));
+
+    let tree = ProcessTree::from_processes(&mut processes, 1).unwrap();
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "bash".to_string(), matched: 1, trees: vec![tree], ..Default::default() };
+    let text = TextRenderer::new(Theme::PLAIN, Columns { args: true, ..Columns::default() }, None);
+    let mut outputs = vec![render_to_string(&text, &snapshot)];
+    for format in [OutputFormat::Json, OutputFormat::Csv, OutputFormat::Html, OutputFormat::Markdown, OutputFormat::Dot] {
+        outputs.push(render::render_snapshot(format, &snapshot));
+    }
+    for output in &outputs {
+        for raw in ["hunter2", "admin", "tok_live_42"] {
+            assert!(!output.contains(raw), "{} leaked:\n{}", raw, output);
+        }
+    }
+    assert!(outputs[0].contains(" [redacted]"), "{}", outputs[0]);
+    assert!(outputs[0].contains("sleep 60"), "{}", outputs[0]);
+    assert!(outputs[1].contains("\"args\": \"[redacted]\""), "{}", outputs[1]);
+    assert!(outputs[2].contains("uploader --token=[redacted] --bucket logs"), "{}", outputs[2]);
+}
@@ -0,0 +1,46 @@
+// --ssh: parsing the output of the remote collection one-liner
+
+use memon::remote::{parse_remote_table, RemoteError};
+use memon::source::SourceError;
+
+const OUTPUT: &str = "Linux
+4096
+1 (systemd) S 0 1 1 0 -1 4194560 50000 0 100 0 200 300 0 0 20 0 1 0 10 170000000 3072 18446744073709551615
+812 (sshd) S 1 812 812 0 -1 4194560 900 0 0 0 3 1 0 0 20 0 1 0 900 15000000 2048 18446744073709551615
+9001 (tmux: server) S 812 9001 9001 0 -1 4194560 900 0 0 0 3 1 0 0 20 0 1 0 900 15000000 512 18446744073709551615
+9002 (odd) name)) R 9001 9002 9001 34816 9002 4194304 10 0 0 0 0 0 0 0 20 0 1 0 901 5000000 256 18446744073709551615
+2 (kthreadd) S 0 0 0 0 -1 2129984 0 0 0 0 0 0 0 0 20 0 1 0 1 0 0 18446744073709551615
+";
+
+#[test]
+fn stat_lines_become_a_process_table() {
+    let processes = parse_remote_table(OUTPUT).unwrap();
+    assert_eq!(processes.len(), 5);
+    let sshd = &processes[&812];
+    assert_eq!((sshd.name.as_str(), sshd.parent_pid, sshd.rss), ("sshd", Some(1), 2048 * 4096));
+    assert_eq!(processes[&9001].name, "tmux: server");
+    assert_eq!(processes[&9002].name, "odd) name)");
+    assert_eq!(processes[&9002].parent_pid, Some(9001));
+    assert_eq!(processes[&1].parent_pid, None);
+    assert_eq!(processes[&2].rss, 0);
+}
+
+#[test]
+fn unparsable_lines_are_skipped() {
+    let output = format!("{}9003 (truncated\n", OUTPUT);
+    assert_eq!(parse_remote_table(&output).unwrap().len(), 5);
+}
+
+#[test]
+fn other_systems_are_unsupported() {
+    assert_eq!(parse_remote_table("Darwin\n").unwrap_err(), RemoteError::Unsupported("Darwin".to_string()));
+    assert_eq!(parse_remote_table("").unwrap_err(), RemoteError::Unsupported("a system without uname".to_string()));
+    let err = SourceError::UnsupportedRemote { host: "mac.local".to_string(), os: "Darwin".to_string() };
+    assert_eq!(err.to_string(), "mac.local runs Darwin; --ssh needs a Linux host with /proc");
+}
+
+#[test]
+fn output_without_processes_is_malformed() {
+    assert!(matches!(parse_remote_table("Linux\n"), Err(RemoteError::Malformed(_))));
+    assert!(matches!(parse_remote_table("Linux\n4096\n"), Err(RemoteError::Malformed(_))));
+}
@@ -0,0 +1,398 @@
+// Renderer trait: every format writes to any io::Write, tree by tree
+
+mod common;
+
+use memon::format::OutputFormat;
+use memon::jvm::JvmHeap;
+use memon::match_stats::MatchStats;
+use memon::options::Columns;
+use memon::cli::TreeStyle;
+use memon::peak::PeakSource;
+use memon::process::MemoryLimits;
+use memon::render::{elide_middle, render_document, render_json, render_snapshot, render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+use memon::stability::Stability;
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, rss: u64, rank: Option<u8>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { rank, ..common::node(pid, name, rss, children) }
+}
+
+fn snapshot(tree_count: usize) -> Snapshot {
+    let trees = (0..tree_count)
+        .map(|i| {
+            let base = 100 * (i as u32 + 1);
+            ProcessTree::new(node(base, "server", 10 * MB, Some(2), vec![
+                node(base + 1, "worker", 30 * MB, Some(1), Vec::new()),
+                node(base + 2, "logger", 2 * MB, Some(3), Vec::new()),
+            ]))
+        })
+        .collect();
+    Snapshot { version: "0.1.0".to_string(), pattern: "server".to_string(), matched: 3 * tree_count, trees, ..Default::default() }
+}
+
+#[test]
+fn json_renderer_matches_whole_document_serialization() {
+    for tree_count in 0..3 {
+        let snapshot = snapshot(tree_count);
+        let expected = serde_json::to_string_pretty(&snapshot).unwrap() + "\n";
+        assert_eq!(render_snapshot(OutputFormat::Json, &snapshot), expected);
+    }
+    // --verbose adds the matcher's tally at the end
+    let mut snapshot = snapshot(1);
+    snapshot.match_stats = Some(MatchStats::default());
+    let expected = serde_json::to_string_pretty(&snapshot).unwrap() + "\n";
+    assert_eq!(render_snapshot(OutputFormat::Json, &snapshot), expected);
+}
+
+#[test]
+fn text_renderer_writes_tree_and_summary() {
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { counts: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot(2)).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB [2/2]🥈",
+        "├─ 101 worker   30.0MB [0/0]🥇",
+        "└─ 102 logger   2.0MB [0/0]🥉",
+        "3 procs | 14.0MB avg | 42.0MB total",
+        "",
+        "============================================================",
+        "200 server   10.0MB [2/2]🥈",
+        "├─ 201 worker   30.0MB [0/0]🥇",
+        "└─ 202 logger   2.0MB [0/0]🥉",
+        "3 procs | 14.0MB avg | 42.0MB total",
+    ]);
+}
+
+#[test]
+fn text_renderer_shows_memory_details_and_locked_total() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.locked_bytes = Some(4 * MB);
+    snapshot.trees[0].root.dirty_bytes = Some(MB);
+    snapshot.trees[0].root.children[0].locked_bytes = Some(0);
+    snapshot.trees[0].root.children[0].dirty_bytes = Some(20 * MB);
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { mem_details: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB lck 4.0MB dirty 1.0MB clean 9.0MB🥈",
+        "├─ 101 worker   30.0MB lck 0B dirty 20.0MB clean 10.0MB🥇",
+        "└─ 102 logger   2.0MB lck - dirty - clean -🥉",
+        "3 procs | 14.0MB avg | 42.0MB total | 4.0MB locked",
+    ]);
+    
+    // The JSON fields are left out wherever nothing was collected
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert_eq!(json.matches("\"locked_bytes\"").count(), 2);
+    assert_eq!(json.matches("\"dirty_bytes\": 20971520").count(), 1);
+}
+
+#[test]
+fn text_renderer_keeps_unexposed_huge_pages_apart_from_zero() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.anon_huge_bytes = Some(4 * MB);
+    snapshot.trees[0].root.children[0].anon_huge_bytes = Some(0);
+    snapshot.trees[0].root.children[0].hugetlb_bytes = Some(0);
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { hugepages: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB thp 4.0MB hugetlb -🥈",
+        "├─ 101 worker   30.0MB thp 0B hugetlb 0B🥇",
+        "└─ 102 logger   2.0MB thp - hugetlb -🥉",
+        "3 procs | 14.0MB avg | 42.0MB total | thp 4.0MB hugetlb 0B",
+    ]);
+}
+
+#[test]
+fn text_renderer_shows_gpu_column_and_tree_total() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.gpu_bytes = Some(0);
+    snapshot.trees[0].root.children[0].gpu_bytes = Some(3 * 1024 * MB);
+    snapshot.trees[0].root.children[1].gpu_bytes = Some(512 * MB);
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { gpu: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB gpu 0B🥈",
+        "├─ 101 worker   30.0MB gpu 3.0GB🥇",
+        "└─ 102 logger   2.0MB gpu 512.0MB🥉",
+        "3 procs | 14.0MB avg | 42.0MB total | gpu 3.5GB",
+    ]);
+    assert!(render_snapshot(OutputFormat::Json, &snapshot).contains("\"gpu_bytes\": 3221225472"));
+}
+
+#[test]
+fn text_renderer_shows_tty_and_marks_current_session() {
+    assert!(!render_snapshot(OutputFormat::Json, &snapshot(1)).contains("current_session"));
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].current_session = true;
+    snapshot.trees[0].root.tty = Some("pts/3".to_string());
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { tty: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB tty pts/3🥈",
+        "├─ 101 worker   30.0MB tty -🥇",
+        "└─ 102 logger   2.0MB tty -🥉",
+        "3 procs | 14.0MB avg | 42.0MB total (current session)",
+    ]);
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"tty\": \"pts/3\"") && json.contains("\"current_session\": true"));
+}
+
+#[test]
+fn text_renderer_shows_peak_and_its_source() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].peak_bytes = Some(60 * MB);
+    snapshot.trees[0].root.peak_bytes = Some(12 * MB);
+    snapshot.trees[0].root.peak_source = Some(PeakSource::Kernel);
+    snapshot.trees[0].root.children[0].peak_bytes = Some(30 * MB);
+    snapshot.trees[0].root.children[0].peak_source = Some(PeakSource::Observed);
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns { peak: true, ..Columns::default() }, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, vec![
+        "100 server   10.0MB peak 12.0MB (kernel)🥈",
+        "├─ 101 worker   30.0MB peak 30.0MB (observed)🥇",
+        "└─ 102 logger   2.0MB peak -🥉",
+        "3 procs | 14.0MB avg | 42.0MB total | peak 60.0MB",
+    ]);
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"peak_bytes\": 12582912") && json.contains("\"peak_source\": \"kernel\""));
+}
+
+#[test]
+fn text_renderer_shows_address_space_limits() {
+    let mut snapshot = snapshot(1);
+    let limited = MemoryLimits { address_space: Some(100 * MB), address_space_hard: None, resident: None, resident_hard: None, virtual_bytes: Some(85 * MB) };
+    snapshot.trees[0].root.limits = Some(MemoryLimits { virtual_bytes: Some(200 * MB), ..MemoryLimits::default() });
+    snapshot.trees[0].root.children[0].limits = Some(limited);
+    snapshot.trees[0].root.children[1].limits = Some(MemoryLimits { resident: Some(64 * MB), ..limited });
+    let columns = Columns { limits: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..3], &[
+        "100 server   10.0MB as-limit \u{221e}🥈".to_string(),
+        "├─ 101 worker   30.0MB as-limit 100.0MB (85%)🥇".to_string(),
+        "└─ 102 logger   2.0MB as-limit 100.0MB (85%) rss-limit 64.0MB🥉".to_string(),
+    ]);
+    
+    // Unlimited reads "unl" without Unicode, and above 80% is highlighted
+    let renderer = TextRenderer::new(Theme::ANSI, columns, None).with_widths(3, 8).with_tree_style(TreeStyle::Ascii, 2);
+    let text = render_to_string(&renderer, &snapshot);
+    assert!(text.contains("as-limit unl"));
+    assert!(text.contains(&format!("({}85%{})", Theme::ANSI.warning, Theme::ANSI.reset)));
+    
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"address_space\": 104857600") && json.contains("\"address_space_hard\": null"));
+}
+
+#[test]
+fn text_renderer_shows_jvm_heaps() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.children[0].jvm = Some(JvmHeap { max_bytes: Some(256 * MB), initial_bytes: Some(32 * MB), used_bytes: Some(8 * MB), committed_bytes: Some(32 * MB) });
+    snapshot.trees[0].root.children[1].jvm = Some(JvmHeap::default());
+    let columns = Columns { jvm: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..3], &[
+        "100 server   10.0MB🥈".to_string(),
+        "├─ 101 worker   30.0MB heap max 256.0MB used 8.0MB of 32.0MB🥇".to_string(),
+        "└─ 102 logger   2.0MB heap max default🥉".to_string(),
+    ]);
+    
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"max_bytes\": 268435456") && json.contains("\"used_bytes\": 8388608"));
+}
+
+#[test]
+fn elision_keeps_the_distinguishing_end() {
+    let seven = elide_middle("com.example.payments.settlement.worker-7", 24, "\u{2026}");
+    let eight = elide_middle("com.example.payments.settlement.worker-8", 24, "\u{2026}");
+    assert_eq!(seven, "com.exa\u{2026}tlement.worker-7");
+    assert_ne!(seven, eight);
+    assert_eq!(seven.chars().count(), 24);
+    assert_eq!(elide_middle("org.mozilla.firefox.contentproc", 12, "..."), "org...ntproc");
+    assert_eq!(elide_middle("com.example.worker", 18, "\u{2026}"), "com.example.worker");
+}
+
+#[test]
+fn elision_counts_characters_not_bytes() {
+    let name = "jp.co.例え.サービス.ワーカー-7";
+    let elided = elide_middle(name, 12, "\u{2026}");
+    assert_eq!(elided, "jp.\u{2026}ス.ワーカー-7");
+    assert_eq!(elided.chars().count(), 12);
+    assert_eq!(elide_middle("ünïcödé", 3, "\u{2026}"), "\u{2026}dé");
+    assert_eq!(elide_middle("ünïcödé", 1, "..."), "...");
+}
+
+#[test]
+fn full_names_run_past_the_column() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.children[0].name = "com.example.payments.worker-7".to_string();
+    let renderer = TextRenderer::plain().with_widths(3, 16);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines[1], "├─ 101 com.e\u{2026}s.worker-7 30.0MB🥇");
+    
+    let renderer = TextRenderer::plain().with_widths(3, 16).with_full_names(true);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines[1], "├─ 101 com.example.payments.worker-7 30.0MB🥇");
+    assert_eq!(lines[2], "└─ 102 logger           2.0MB🥉");
+}
+
+#[test]
+fn text_renderer_shows_labels_in_the_name_column() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.children[0].label = Some("Firefox tab renderer".to_string());
+    let (pid_width, name_width) = TextRenderer::column_widths(&snapshot.trees[0]);
+    assert_eq!(name_width, 40);
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(pid_width, 30);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..3], &[
+        "100 server                         10.0MB🥈".to_string(),
+        "├─ 101 worker (Firefox tab renderer)  30.0MB🥇".to_string(),
+        "└─ 102 logger                         2.0MB🥉".to_string(),
+    ]);
+    
+    // The label is dimmed, and the name gives way when the column is narrow
+    let renderer = TextRenderer::new(Theme::ANSI, Columns::default(), None).with_widths(pid_width, 28);
+    let text = render_to_string(&renderer, &snapshot);
+    assert!(text.contains(&format!("w{}\u{2026}{}ker{} (Firefox tab renderer){}", Theme::ANSI.dim, Theme::ANSI.reset, Theme::ANSI.dim, Theme::ANSI.reset)));
+    
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"label\": \"Firefox tab renderer\""));
+}
+
+#[test]
+fn text_renderer_shows_stability() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.stability = Some(Stability { last_change_epoch: 0, stable_secs: 1920, observed_change: false });
+    snapshot.trees[0].root.children[0].stability = Some(Stability { last_change_epoch: 0, stable_secs: 4, observed_change: true });
+    let columns = Columns { stability: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..3], &[
+        "100 server   10.0MB stable 32m🥈".to_string(),
+        "├─ 101 worker   30.0MB changed 4s ago🥇".to_string(),
+        "└─ 102 logger   2.0MB🥉".to_string(),
+    ]);
+}
+
+#[test]
+fn text_renderer_shows_restarts() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.restarts = Some(0);
+    snapshot.trees[0].root.children[0].restarts = Some(4);
+    let columns = Columns { churn: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..2], &[
+        "100 server   10.0MB🥈".to_string(),
+        "├─ 101 worker   30.0MB restarts: 4🥇".to_string(),
+    ]);
+}
+
+#[test]
+fn text_renderer_shows_listening_ports() {
+    let mut snapshot = snapshot(1);
+    snapshot.trees[0].root.children[0].ports = Some(vec![3000, 9229]);
+    let columns = Columns { ports: true, ..Columns::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(&lines[..3], &[
+        "100 server   10.0MB🥈".to_string(),
+        "├─ 101 worker   30.0MB \u{21e2} :3000 :9229🥇".to_string(),
+        "└─ 102 logger   2.0MB🥉".to_string(),
+    ]);
+    
+    let renderer = TextRenderer::new(Theme::PLAIN, columns, None).with_widths(3, 8).with_tree_style(TreeStyle::Ascii, 2);
+    assert!(render_to_string(&renderer, &snapshot).contains("30.0MB -> :3000 :9229"));
+    
+    let json = render_snapshot(OutputFormat::Json, &snapshot);
+    assert!(json.contains("\"ports\": [\n"));
+}
+
+#[test]
+fn renderers_write_to_any_writer() {
+    let mut buffer = Vec::new();
+    let renderer = TextRenderer::new(Theme::ANSI, Columns::default(), Some(10.0));
+    render_document(&renderer, &snapshot(1), &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains("\x1b[30m\x1b[48;5;214m30.0MB\x1b[0m"));
+    assert!(text.contains("└─ (1 procs below 10%, 2.0MB)"));
+}
+
+#[test]
+fn split_largest_selects_by_total_not_discovery_order() {
+    let mut snapshot = snapshot(3);
+    for (tree, total) in snapshot.trees.iter_mut().zip([5 * MB, 40 * MB, 20 * MB]) {
+        tree.total_bytes = total;
+    }
+    let (shown, omitted) = snapshot.split_largest(2);
+    assert_eq!(shown.trees.iter().map(|tree| tree.root_pid).collect::<Vec<_>>(), vec![200, 300]);
+    assert_eq!(omitted.iter().map(|tree| tree.root_pid).collect::<Vec<_>>(), vec![100]);
+    assert_eq!(shown.matched, snapshot.matched);
+    
+    let (shown, omitted) = snapshot.split_largest(5);
+    assert_eq!(shown.trees.len(), 3);
+    assert!(omitted.is_empty());
+}
+
+#[test]
+fn hyperlinks_wrap_pids_without_changing_alignment() {
+    let snapshot = snapshot(1);
+    let plain = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(5, 8);
+    let linked = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(5, 8).with_hyperlinks(true);
+    let text = render_to_string(&linked, &snapshot);
+    let uri = if cfg!(target_os = "linux") { "file:///proc/101" } else { "memon://pid/101" };
+    assert!(text.contains(&format!("├─ \x1b]8;;{}\x1b\\  101\x1b]8;;\x1b\\ worker", uri)));
+    
+    // Removing the OSC 8 sequences gives exactly the unlinked output
+    let mut stripped = String::new();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("\x1b]8;;") {
+        stripped.push_str(&rest[..start]);
+        let end = rest[start..].find("\x1b\\").unwrap();
+        rest = &rest[start + end + 2..];
+    }
+    stripped.push_str(rest);
+    assert_eq!(stripped, render_to_string(&plain, &snapshot));
+    
+    for format in [OutputFormat::Json, OutputFormat::Csv, OutputFormat::Html, OutputFormat::Markdown, OutputFormat::Dot] {
+        assert!(!render_snapshot(format, &snapshot).contains('\x1b'));
+    }
+}
+
+#[test]
+fn hyperlinks_stay_off_on_terminals_known_not_to_support_them() {
+    use memon::colors::hyperlinks_supported;
+    assert!(hyperlinks_supported(Some("xterm-256color"), None));
+    assert!(hyperlinks_supported(Some("xterm-kitty"), Some("WezTerm")));
+    assert!(!hyperlinks_supported(None, None));
+    assert!(!hyperlinks_supported(Some("dumb"), None));
+    assert!(!hyperlinks_supported(Some("linux"), None));
+    assert!(!hyperlinks_supported(Some("screen-256color"), None));
+    assert!(!hyperlinks_supported(Some("xterm-256color"), Some("Apple_Terminal")));
+}
+
+#[test]
+fn text_renderer_rolls_up_unreadable_processes() {
+    let mut hidden = node(103, "sshd", 0, None, vec![node(104, "bash", 0, None, Vec::new())]);
+    hidden.readable = false;
+    hidden.children[0].readable = false;
+    // Readable child under an unreadable parent keeps the parent on its own line
+    let mut parent = node(105, "sudo", 0, None, vec![node(106, "make", 4 * MB, None, Vec::new())]);
+    parent.readable = false;
+    let tree = ProcessTree::new(node(100, "sshd", 10 * MB, None, vec![hidden, parent]));
+    assert!(tree.lower_bound);
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "sshd".to_string(), matched: 1, trees: vec![tree], ..Default::default() };
+    let renderer = TextRenderer::plain().with_widths(3, 8);
+    let lines: Vec<String> = render_to_string(&renderer, &snapshot).lines().map(str::to_string).collect();
+    assert_eq!(lines, [
+        "100 sshd     10.0MB",
+        "├─ 105 sudo     ?",
+        "  └─ 106 make     4.0MB",
+        "└─ unreadable (other users): 2 procs, memory unknown",
+        "5 procs | 2.8MB avg | \u{2265} 14.0MB total",
+    ]);
+
+    let json = render_json(&snapshot);
+    assert!(json.contains("\"lower_bound\": true"));
+    assert_eq!(json.matches("\"readable\": false").count(), 3);
+}
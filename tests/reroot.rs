@@ -0,0 +1,141 @@
+// Following a watched root restarted under a new PID, and --no-reroot
+
+use std::collections::{HashMap, VecDeque};
+
+use memon::history_cols::HistoryTracker;
+use memon::process::ProcessInfo;
+use memon::reroot::{RootChange, RootTracker};
+use memon::source::{self, ProcessSource, SourceError};
+
+const MB: u64 = 1024 * 1024;
+
+// (pid, ppid, start_time, name)
+type Row = (u32, Option<u32>, u64, &'static str);
+
+// One table per watch sample
+struct ScriptedSource {
+    samples: VecDeque<Vec<Row>>,
+}
+
+impl ProcessSource for ScriptedSource {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+        let table = self.samples.pop_front().expect("a table for every sample");
+        Ok(table
+            .into_iter()
+            .map(|(pid, ppid, start_time, name)| {
+                let mut proc_info = ProcessInfo::new(pid, name.to_string(), 10 * MB, ppid);
+                proc_info.start_time = start_time;
+                (pid, proc_info)
+            })
+            .collect())
+    }
+}
+
+// pm2 restarting "api": PID 100 runs for two samples, is replaced by 200,
+// and "worker" keeps running beside it
+fn restarting_service() -> ScriptedSource {
+    let before = vec![(1, None, 0, "pm2"), (100, Some(1), 10, "api"), (101, Some(100), 11, "api"), (50, Some(1), 5, "worker")];
+    let after = vec![(1, None, 0, "pm2"), (200, Some(1), 30, "api"), (201, Some(200), 31, "api"), (50, Some(1), 5, "worker")];
+    ScriptedSource { samples: VecDeque::from([before.clone(), before, after.clone(), after]) }
+}
+
+// The roots of the processes named `name`, the way the CLI finds them
+fn sample(source: &mut ScriptedSource, name: &str) -> (HashMap<u32, ProcessInfo>, Vec<u32>) {
+    let mut processes = source.load().unwrap();
+    source::link_children(&mut processes);
+    let mut roots: Vec<u32> = processes
+        .values()
+        .filter(|proc_info| proc_info.name == name)
+        .filter(|proc_info| proc_info.parent_pid.and_then(|parent| processes.get(&parent)).is_none_or(|parent| parent.name != name))
+        .map(|proc_info| proc_info.pid)
+        .collect();
+    roots.sort_unstable();
+    (processes, roots)
+}
+
+#[test]
+fn restarted_root_is_followed_and_reported_once() {
+    let mut source = restarting_service();
+    let mut tracker = RootTracker::new(false);
+    let mut shown = Vec::new();
+    let mut changes = Vec::new();
+    for _ in 0..4 {
+        let (processes, roots) = sample(&mut source, "api");
+        shown.push(tracker.update(&processes, roots));
+        changes.push(tracker.take_changes());
+    }
+    assert_eq!(shown, [vec![100], vec![100], vec![200], vec![200]]);
+    assert!(changes[0].is_empty() && changes[1].is_empty() && changes[3].is_empty());
+    assert_eq!(changes[2], [RootChange { name: "api".to_string(), from: (100, 10), to: vec![(200, 30)] }]);
+    assert_eq!(changes[2][0].label(), "root changed: 100 \u{2192} 200 (restart detected)");
+    assert_eq!(tracker.total(), 1);
+}
+
+#[test]
+fn no_reroot_stays_on_the_first_roots() {
+    let mut source = restarting_service();
+    let mut tracker = RootTracker::new(true);
+    let mut shown = Vec::new();
+    for _ in 0..4 {
+        let (processes, roots) = sample(&mut source, "api");
+        shown.push(tracker.update(&processes, roots));
+        assert!(tracker.take_changes().is_empty());
+        if shown.len() == 4 {
+            assert_eq!(tracker.exited(&processes), [100]);
+        }
+    }
+    assert_eq!(shown, [vec![100], vec![100], vec![], vec![]]);
+    assert_eq!(tracker.total(), 0);
+}
+
+#[test]
+fn a_new_root_of_another_name_is_no_restart() {
+    let mut source = ScriptedSource { samples: VecDeque::from([
+        vec![(100, None, 10, "api"), (300, None, 20, "api-admin")],
+        vec![(200, None, 30, "api-admin"), (300, None, 20, "api-admin")],
+    ]) };
+    let mut tracker = RootTracker::new(false);
+    for _ in 0..2 {
+        // Every process is a root, as with the pattern "api"
+        let (processes, _) = sample(&mut source, "api");
+        let mut roots: Vec<u32> = processes.keys().copied().collect();
+        roots.sort_unstable();
+        tracker.update(&processes, roots);
+    }
+    assert!(tracker.take_changes().is_empty());
+}
+
+#[test]
+fn several_restarted_roots_of_one_name_pair_up_in_pid_order() {
+    let mut source = ScriptedSource { samples: VecDeque::from([
+        vec![(100, None, 10, "api"), (110, None, 10, "api")],
+        vec![(200, None, 30, "api"), (210, None, 30, "api"), (220, None, 30, "api")],
+    ]) };
+    let mut tracker = RootTracker::new(false);
+    for _ in 0..2 {
+        let (processes, roots) = sample(&mut source, "api");
+        tracker.update(&processes, roots);
+    }
+    let labels: Vec<String> = tracker.take_changes().iter().map(RootChange::label).collect();
+    assert_eq!(labels, ["root changed: 100 \u{2192} 200 (restart detected)", "root changed: 110 \u{2192} 210, 220 (restart detected)"]);
+}
+
+#[test]
+fn history_carries_over_to_the_new_root() {
+    let mut source = restarting_service();
+    let mut tracker = RootTracker::new(false);
+    let mut history = HistoryTracker::new();
+    let mut samples = Vec::new();
+    for _ in 0..4 {
+        let (mut processes, roots) = sample(&mut source, "api");
+        let roots = tracker.update(&processes, roots);
+        for change in tracker.take_changes() {
+            history.carry_over(change.from, change.to[0]);
+        }
+        history.observe(&mut processes, &roots);
+        samples.push(processes[&roots[0]].history.map(|history| history.samples));
+    }
+    // The replacement goes on from the two samples of the old root
+    assert_eq!(samples, [None, Some(2), Some(3), Some(4)]);
+    assert_eq!(history.len(), 1);
+}
@@ -0,0 +1,144 @@
+// --roots: strict, matched-top and all-matched root detection, and the
+// split of matches across user logins
+
+use std::collections::HashMap;
+
+use clap::Parser;
+use memon::cli::Args;
+use memon::process::ProcessInfo;
+use memon::roots::{self, RootPolicy};
+use memon::snapshot::ProcessTree;
+use memon::source;
+
+// (pid, ppid, name)
+type Row = (u32, Option<u32>, &'static str);
+
+fn table(rows: &[Row]) -> HashMap<u32, ProcessInfo> {
+    let mut processes: HashMap<u32, ProcessInfo> = rows.iter()
+        .map(|&(pid, ppid, name)| (pid, ProcessInfo::new(pid, name.to_string(), 1024, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    processes
+}
+
+// A launcher that re-execs through a shell, a worker chain with a match
+// under an unmatched process, and a match right under init
+const CHAINS: &[Row] = &[
+    (1, None, "init"),
+    (10, Some(1), "app"), (11, Some(10), "sh"), (12, Some(11), "app"), (13, Some(12), "app"),
+    (20, Some(1), "app"), (21, Some(20), "worker"), (22, Some(21), "app"),
+    (30, Some(1), "app"),
+];
+
+fn matching(processes: &HashMap<u32, ProcessInfo>, name: &str) -> Vec<u32> {
+    let mut pids: Vec<u32> = processes.values().filter(|proc_info| proc_info.name == name).map(|proc_info| proc_info.pid).collect();
+    pids.sort_unstable();
+    pids
+}
+
+#[test]
+fn strict_roots_stop_at_unmatched_parents() {
+    let processes = table(CHAINS);
+    let matched = matching(&processes, "app");
+    assert_eq!(roots::find_roots(&processes, &matched, RootPolicy::Strict, None), [10, 12, 20, 22, 30]);
+}
+
+#[test]
+fn matched_top_climbs_through_unmatched_parents() {
+    let processes = table(CHAINS);
+    let matched = matching(&processes, "app");
+    assert_eq!(roots::find_roots(&processes, &matched, RootPolicy::MatchedTop, None), [10, 20, 30]);
+    
+    // init itself is never climbed into, even when it matches
+    let processes = table(&[(1, None, "app"), (5, Some(1), "sh"), (6, Some(5), "app")]);
+    assert_eq!(roots::find_roots(&processes, &[1, 6], RootPolicy::MatchedTop, None), [1, 6]);
+}
+
+#[test]
+fn matched_top_survives_a_parent_loop() {
+    // Left by a reused PID: 40 and 41 name each other as parent
+    let mut processes = table(&[(40, None, "app"), (41, Some(40), "sh")]);
+    processes.get_mut(&40).unwrap().parent_pid = Some(41);
+    assert_eq!(roots::find_roots(&processes, &[40], RootPolicy::MatchedTop, None), [40]);
+}
+
+#[test]
+fn all_matched_gives_every_match_a_tree_without_nesting() {
+    let mut processes = table(CHAINS);
+    let matched = matching(&processes, "app");
+    let root_pids = roots::find_roots(&processes, &matched, RootPolicy::AllMatched, None);
+    assert_eq!(root_pids, [10, 12, 13, 20, 22, 30]);
+    
+    roots::detach_matched(&mut processes, &matched);
+    let counts: Vec<usize> = root_pids.iter()
+        .map(|&pid| ProcessTree::from_processes(&mut processes, pid).unwrap().process_count)
+        .collect();
+    // The unmatched shell and worker stay with the match above them
+    assert_eq!(counts, [2, 1, 1, 2, 1, 1]);
+    assert_eq!(counts.iter().sum::<usize>(), processes.len() - 1);
+}
+
+#[test]
+fn unknown_parents_make_roots_under_every_policy() {
+    let processes = table(&[(50, Some(999), "app"), (51, Some(50), "app")]);
+    for policy in [RootPolicy::Strict, RootPolicy::MatchedTop] {
+        assert_eq!(roots::find_roots(&processes, &[50, 51], policy, None), [50]);
+    }
+}
+
+// A system daemon and a user's instance of it started through the daemon,
+// e.g. a D-Bus activated helper: (pid, uid, session)
+const LOGINS: &[(u32, u32, u32)] = &[(60, 0, 60), (61, 1000, 900), (62, 1000, 900)];
+
+fn logins() -> (HashMap<u32, ProcessInfo>, HashMap<u32, u32>) {
+    let mut processes = table(&[(1, None, "init"), (60, Some(1), "app"), (61, Some(60), "app"), (62, Some(61), "app")]);
+    let mut sessions = HashMap::new();
+    for &(pid, uid, session) in LOGINS {
+        processes.get_mut(&pid).unwrap().uid = Some(uid);
+        sessions.insert(pid, session);
+    }
+    (processes, sessions)
+}
+
+#[test]
+fn matches_of_another_login_get_trees_of_their_own() {
+    let (processes, sessions) = logins();
+    for policy in [RootPolicy::Strict, RootPolicy::MatchedTop] {
+        assert_eq!(roots::find_roots(&processes, &[60, 61, 62], policy, Some(&sessions)), [60, 61]);
+        // --cross-user-trees
+        assert_eq!(roots::find_roots(&processes, &[60, 61, 62], policy, None), [60]);
+    }
+}
+
+#[test]
+fn privilege_dropping_workers_stay_with_their_master() {
+    // nginx: a root master with www-data workers in its own session
+    let (mut processes, mut sessions) = logins();
+    for pid in [61, 62] {
+        processes.get_mut(&pid).unwrap().uid = Some(33);
+        sessions.insert(pid, 60);
+    }
+    assert_eq!(roots::find_roots(&processes, &[60, 61, 62], RootPolicy::Strict, Some(&sessions)), [60]);
+    
+    // An unknown user or session never splits
+    let (mut processes, mut sessions) = logins();
+    sessions.remove(&61);
+    assert_eq!(roots::find_roots(&processes, &[60, 61, 62], RootPolicy::Strict, Some(&sessions)), [60]);
+    processes.get_mut(&60).unwrap().uid = None;
+    assert!(!roots::other_login(&processes[&60], &processes[&61], &sessions));
+}
+
+#[test]
+fn cross_user_trees_is_parsed() {
+    assert!(!Args::try_parse_from(["memon", "app"]).unwrap().cross_user_trees);
+    assert!(Args::try_parse_from(["memon", "app", "--cross-user-trees"]).unwrap().cross_user_trees);
+}
+
+#[test]
+fn roots_policy_is_parsed() {
+    let roots = |value: &str| Args::try_parse_from(["memon", "app", "--roots", value]).map(|args| args.roots);
+    assert_eq!(Args::try_parse_from(["memon", "app"]).unwrap().roots, RootPolicy::Strict);
+    assert_eq!(roots("matched-top").unwrap(), RootPolicy::MatchedTop);
+    assert_eq!(roots("all-matched").unwrap(), RootPolicy::AllMatched);
+    assert!(roots("top").is_err());
+}
@@ -0,0 +1,30 @@
+// `memon run`: spawning a command, sampling its tree and passing on its exit status
+
+#![cfg(unix)]
+
+use std::process::Command;
+
+fn memon_run(command: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_memon"))
+        .args(["run", "--interval", "1", "--"])
+        .args(command)
+        .output()
+        .unwrap();
+    (output.status.code(), String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn samples_the_tree_and_propagates_the_exit_status() {
+    let (code, stdout) = memon_run(&["sh", "-c", "sleep 1.5; exit 3"]);
+    assert_eq!(code, Some(3), "{}", stdout);
+    assert!(stdout.lines().any(|line| line.starts_with("[    0s] ") && line.contains(" procs | ")), "{}", stdout);
+    assert!(stdout.contains("sh exited with status 3 after "), "{}", stdout);
+    assert!(stdout.lines().last().is_some_and(|line| line.contains(" avg | ") && line.contains(" peak total")), "{}", stdout);
+}
+
+#[test]
+fn fails_when_the_command_cannot_start() {
+    let (code, stdout) = memon_run(&["/nonexistent/memon-test-command"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.is_empty());
+}
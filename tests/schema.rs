@@ -0,0 +1,58 @@
+// --schema, and --json output checked against it
+
+#![cfg(feature = "schema")]
+
+use std::process::Command;
+
+use serde_json::Value;
+
+use memon::schema;
+
+fn memon(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args(args).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn errors(schema: &Value, instance: &Value) -> Vec<String> {
+    let validator = jsonschema::validator_for(schema).unwrap();
+    validator.iter_errors(instance).map(|err| format!("{} at {}", err, err.instance_path)).collect()
+}
+
+#[test]
+fn schema_is_versioned_like_the_snapshots() {
+    let printed: Value = serde_json::from_str(&memon(&["--schema"])).unwrap();
+    assert_eq!(printed, serde_json::to_value(schema::schema()).unwrap());
+    assert_eq!(printed["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(printed["$schema"], "https://json-schema.org/draft/2020-12/schema");
+    
+    let snapshot: Value = serde_json::from_str(&memon(&["nginx", "--from-file", "tests/fixtures/small_tree.table", "--json"])).unwrap();
+    assert_eq!(snapshot["version"], printed["version"]);
+}
+
+#[test]
+fn json_output_validates_against_the_schema() {
+    let schema = serde_json::to_value(schema::schema()).unwrap();
+    let json = memon(&[
+        "nginx", "--from-file", "tests/fixtures/small_tree.table", "--json", "--verbose", "--budget", "1G", "--group-by", "exe",
+        "--by-depth", "--breakdown", "--matched-only-totals", "--siblings", "--dedupe-subtrees", "--labels", "--show-args",
+    ]);
+    let snapshot: Value = serde_json::from_str(&json).unwrap();
+    for key in ["budget", "match_stats", "groups", "metadata"] {
+        assert!(snapshot.get(key).is_some(), "{} missing from {}", key, json);
+    }
+    assert_eq!(errors(&schema, &snapshot), Vec::<String>::new());
+    
+    let merged: Value = serde_json::from_str(&memon(&["worker", "--from-file", "tests/fixtures/forest.table", "--json", "--merge-roots"])).unwrap();
+    assert_eq!(errors(&schema, &merged), Vec::<String>::new());
+}
+
+#[test]
+fn schema_rejects_a_malformed_snapshot() {
+    let schema = serde_json::to_value(schema::schema()).unwrap();
+    let mut snapshot: Value = serde_json::from_str(&memon(&["nginx", "--from-file", "tests/fixtures/small_tree.table", "--json"])).unwrap();
+    snapshot["trees"][0]["root"]["rss_bytes"] = Value::from("12M");
+    assert!(!errors(&schema, &snapshot).is_empty());
+    snapshot.as_object_mut().unwrap().remove("trees");
+    assert!(!errors(&schema, &snapshot).is_empty());
+}
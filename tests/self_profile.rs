@@ -0,0 +1,57 @@
+// --self-profile: phase times and work counts, on stderr and next to --output
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use memon::self_profile::{self, SelfProfile, REPORT_VERSION};
+
+#[test]
+fn phases_add_up_over_samples() {
+    let mut profile = SelfProfile::new(&["--show-args".to_string(), "--from-file host.table".to_string()], 4);
+    profile.record("scan", Duration::from_millis(30));
+    profile.record("render", Duration::from_millis(2));
+    profile.record("scan", Duration::from_millis(10));
+    profile.wall_ms = 50.0;
+
+    let phases: Vec<(&str, usize)> = profile.phases.iter().map(|time| (time.phase.as_str(), time.runs)).collect();
+    assert_eq!(phases, [("scan", 2), ("render", 1)]);
+    assert_eq!((profile.phases[0].total_ms, profile.phases[0].max_ms), (40.0, 30.0));
+    assert_eq!(profile.other_ms(), 8.0);
+    // Values such as paths stay out of the report
+    assert_eq!(profile.flags, ["--show-args", "--from-file"]);
+
+    let table = profile.table();
+    assert!(table.contains("  phase   runs   total     max\n  scan       2  40.0ms  30.0ms\n"), "{}", table);
+    assert!(table.contains("  other          8.0ms\n  wall          50.0ms\n"), "{}", table);
+}
+
+#[test]
+fn json_goes_next_to_the_output_file() {
+    assert_eq!(self_profile::json_path(Path::new("out/report.html")), Path::new("out/report.html.profile.json"));
+
+    let output = std::env::temp_dir().join(format!("memon-profile-{}.txt", std::process::id()));
+    let run = Command::new(env!("CARGO_BIN_EXE_memon"))
+        .args(["nginx", "--from-file", "tests/fixtures/small_tree.table", "--show-args", "--self-profile", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(stderr.contains("Self-profile (report 1, memon "), "{}", stderr);
+    assert!(stderr.contains("samples 1 | processes scanned 5 | matched 5 | trees 1 | largest tree 5 procs"), "{}", stderr);
+
+    let json_path = self_profile::json_path(&output);
+    let text = fs::read_to_string(&json_path).unwrap();
+    let _ = fs::remove_file(&output);
+    let _ = fs::remove_file(&json_path);
+    let report: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(report["report_version"], REPORT_VERSION);
+    let phases: Vec<&str> = report["phases"].as_array().unwrap().iter().map(|time| time["phase"].as_str().unwrap()).collect();
+    assert_eq!(phases, ["scan", "match", "roots", "collect args", "build", "render"]);
+    assert_eq!(report["counts"]["largest_tree"], 5);
+    assert!(!text.contains("nginx") && !text.contains("small_tree"), "{}", text);
+}
@@ -0,0 +1,55 @@
+// HTTP endpoint for --http: routing, status codes and bind errors
+
+#![cfg(feature = "http")]
+
+use memon::server::{bind, serve, sse_event, ServerError};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn start() -> String {
+    let listener = bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let collect = Arc::new(|| Ok("{\"matched\": 1}\n".to_string()));
+    thread::spawn(move || serve(listener, collect, Duration::from_secs(1)));
+    addr
+}
+
+fn get(addr: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn serves_snapshot_and_health() {
+    let addr = start();
+    
+    let snapshot = get(&addr, "/snapshot?pretty=1");
+    assert!(snapshot.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(snapshot.contains("Content-Type: application/json\r\n"));
+    assert!(snapshot.ends_with("\r\n\r\n{\"matched\": 1}\n"));
+    
+    assert!(get(&addr, "/healthz").ends_with("\r\n\r\nok\n"));
+    assert!(get(&addr, "/metrics").starts_with("HTTP/1.1 404 Not Found"));
+}
+
+#[test]
+fn reports_bad_addresses_and_ports_in_use() {
+    assert!(matches!(bind("not an address"), Err(ServerError::InvalidAddress(_))));
+    
+    let taken = bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap().to_string();
+    let err = bind(&addr).unwrap_err();
+    assert!(matches!(err, ServerError::AddressInUse(_)));
+    assert!(err.to_string().contains("address already in use"));
+}
+
+#[test]
+fn splits_multiline_payloads_into_data_lines() {
+    assert_eq!(sse_event("snapshot", "{\n  \"a\": 1\n}\n"), "event: snapshot\ndata: {\ndata:   \"a\": 1\ndata: }\n\n");
+}
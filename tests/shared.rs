@@ -0,0 +1,56 @@
+// --dedupe-shared: smaps split into private and shared memory, with shared
+// mappings counted once per tree. The fixtures are a Postgres postmaster and
+// a backend attached to the same shared_buffers and binary.
+
+use std::collections::HashMap;
+
+use memon::maps::{deduped_total, parse_sharing, Sharing};
+use memon::render::{render_to_string, TextRenderer};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+const KB: u64 = 1024;
+const POSTMASTER: &str = include_str!("fixtures/smaps/postmaster.smaps");
+const BACKEND: &str = include_str!("fixtures/smaps/backend.smaps");
+
+#[test]
+fn private_pages_are_own_and_shared_pages_are_keyed_by_mapping() {
+    let sharing = parse_sharing(POSTMASTER);
+    assert_eq!(sharing.own_bytes, (100 + 200 + 24) * KB);
+    let shared: HashMap<(&str, u64, u64), u64> = sharing.shared
+        .iter()
+        .map(|((device, inode, offset), &bytes)| ((device.as_str(), *inode, *offset), bytes))
+        .collect();
+    assert_eq!(shared, HashMap::from([
+        (("08:01", 1234, 0), 5800 * KB),
+        (("00:01", 98304, 0), 409600 * KB),
+    ]));
+}
+
+#[test]
+fn shared_anonymous_pages_stay_with_the_process() {
+    // Copy-on-write heap pages shared after fork have no identity to match on
+    assert_eq!(parse_sharing(BACKEND).own_bytes, (100 + 800) * KB);
+}
+
+#[test]
+fn each_mapping_counts_once_at_its_largest() {
+    let sharings = [parse_sharing(POSTMASTER), parse_sharing(BACKEND), parse_sharing(BACKEND)];
+    let own = (324 + 900 + 900) * KB;
+    let shared = (5800 + 409600 + 8192) * KB;
+    assert_eq!(deduped_total(&sharings), own + shared);
+    // Without smaps a process counts its whole RSS
+    assert_eq!(deduped_total(&[Sharing::unsplit(4 * KB)]), 4 * KB);
+    assert_eq!(deduped_total(&[]), 0);
+}
+
+#[test]
+fn summary_shows_the_deduped_total_next_to_the_naive_one() {
+    let mut processes = source::parse_table("1 - 416M postgres\n2 1 220M postgres\n").unwrap();
+    source::link_children(&mut processes);
+    let mut tree = ProcessTree::from_processes(&mut processes, 1).unwrap();
+    tree.shared_deduped_bytes = Some(deduped_total(&[parse_sharing(POSTMASTER), parse_sharing(BACKEND)]));
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "postgres".to_string(), matched: 2, trees: vec![tree], ..Default::default() };
+    let output = render_to_string(&TextRenderer::plain().with_widths(1, 8), &snapshot);
+    assert_eq!(output.lines().last(), Some("2 procs | 318.0MB avg | 636.0MB total | total (shared-deduped): 414.9MB"));
+}
@@ -0,0 +1,59 @@
+// Walking up to the terminal session for `memon shell`
+
+use std::collections::HashMap;
+
+use memon::process::ProcessInfo;
+use memon::shell::{self, RootKind, ShellRoot};
+use memon::source;
+
+fn table(rows: &[(u32, Option<u32>, &str)]) -> HashMap<u32, ProcessInfo> {
+    let mut processes: HashMap<u32, ProcessInfo> = rows
+        .iter()
+        .map(|&(pid, ppid, name)| (pid, ProcessInfo::new(pid, name.to_string(), 1024, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    processes
+}
+
+fn root(pid: u32, kind: RootKind) -> Option<ShellRoot> {
+    Some(ShellRoot { pid, kind, tmux_clients: Vec::new() })
+}
+
+#[test]
+fn stops_at_the_terminal_emulator() {
+    let processes = table(&[(1, None, "systemd"), (900, Some(1), "systemd"), (1000, Some(900), "gnome-terminal-"),
+                            (1100, Some(1000), "bash"), (1200, Some(1100), "memon")]);
+    assert_eq!(shell::find_root(&processes, 1200), root(1000, RootKind::Terminal));
+}
+
+#[test]
+fn stops_at_the_first_sshd() {
+    let processes = table(&[(1, None, "systemd"), (500, Some(1), "sshd"), (600, Some(500), "sshd"), (610, Some(600), "sshd"),
+                            (620, Some(610), "bash"), (630, Some(620), "memon")]);
+    assert_eq!(shell::find_root(&processes, 630), root(610, RootKind::Ssh));
+}
+
+#[test]
+fn stops_at_the_tmux_server_and_lists_clients() {
+    let processes = table(&[(1, None, "systemd"), (1000, Some(1), "kitty"), (1100, Some(1000), "zsh"), (1150, Some(1100), "tmux: client"),
+                            (2000, Some(1), "tmux: server"), (2100, Some(2000), "zsh"), (2200, Some(2100), "memon")]);
+    let found = shell::find_root(&processes, 2200).unwrap();
+    assert_eq!((found.pid, found.kind), (2000, RootKind::Tmux));
+    assert_eq!(found.tmux_clients, vec![1150]);
+}
+
+#[test]
+fn falls_back_to_the_child_of_a_session_starter() {
+    // A console login: nothing on the way is a terminal emulator
+    let processes = table(&[(1, None, "init"), (300, Some(1), "login"), (310, Some(300), "bash"), (320, Some(310), "memon")]);
+    assert_eq!(shell::find_root(&processes, 320), root(300, RootKind::TopLevel));
+    
+    let processes = table(&[(1, None, "systemd"), (40, Some(1), "lightdm"), (50, Some(40), "xinit"), (60, Some(50), "bash"), (70, Some(60), "memon")]);
+    assert_eq!(shell::find_root(&processes, 70), root(50, RootKind::TopLevel));
+}
+
+#[test]
+fn parentless_memon_has_no_session() {
+    let processes = table(&[(70, None, "memon")]);
+    assert_eq!(shell::find_root(&processes, 70), None);
+}
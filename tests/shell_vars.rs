@@ -0,0 +1,90 @@
+// --shell-vars: KEY=value lines for eval in a POSIX shell
+
+use std::collections::HashMap;
+
+use memon::process::ProcessInfo;
+use memon::shell_vars::{self, quote};
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::source;
+
+fn snapshot(pattern: &str, roots: &[u32]) -> Snapshot {
+    let rows = [(10, None, 4096), (11, Some(10), 8192), (12, Some(10), 8192), (20, None, 1024)];
+    let mut processes: HashMap<u32, ProcessInfo> = rows.iter()
+        .map(|&(pid, ppid, rss)| (pid, ProcessInfo::new(pid, "nginx".to_string(), rss, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    let trees: Vec<ProcessTree> = roots.iter().filter_map(|&pid| ProcessTree::from_processes(&mut processes, pid)).collect();
+    Snapshot { version: "0.1.0".to_string(), pattern: pattern.to_string(), matched: trees.len(), trees, ..Default::default() }
+}
+
+#[test]
+fn one_tree_has_only_the_totals() {
+    // The largest process ties between 11 and 12; the lower PID wins
+    assert_eq!(shell_vars::render(&snapshot("nginx", &[10])), "\
+MEMON_PATTERN=nginx
+MEMON_TREE_COUNT=1
+MEMON_TOTAL_BYTES=20480
+MEMON_PROC_COUNT=3
+MEMON_MAX_PID=11
+MEMON_MAX_BYTES=8192
+");
+}
+
+#[test]
+fn several_trees_are_numbered_after_the_combined_totals() {
+    let vars = shell_vars::render(&snapshot("nginx", &[10, 20]));
+    assert!(vars.contains("MEMON_TREE_COUNT=2\nMEMON_TOTAL_BYTES=21504\nMEMON_PROC_COUNT=4\n"), "{}", vars);
+    assert!(vars.ends_with("\
+MEMON_TREE_2_ROOT_PID=20
+MEMON_TREE_2_TOTAL_BYTES=1024
+MEMON_TREE_2_PROC_COUNT=1
+MEMON_TREE_2_MAX_PID=20
+MEMON_TREE_2_MAX_BYTES=1024
+"), "{}", vars);
+    assert!(vars.contains("MEMON_TREE_1_ROOT_PID=10\n"));
+    
+    // No match still sets every combined variable
+    let vars = shell_vars::render(&snapshot("nginx", &[]));
+    assert!(vars.contains("MEMON_TREE_COUNT=0\nMEMON_TOTAL_BYTES=0\nMEMON_PROC_COUNT=0\nMEMON_MAX_PID=''\nMEMON_MAX_BYTES=0\n"), "{}", vars);
+}
+
+#[test]
+fn values_are_quoted_for_sh() {
+    assert_eq!(quote("12345"), "12345");
+    assert_eq!(quote("/usr/bin/node"), "/usr/bin/node");
+    assert_eq!(quote(""), "''");
+    assert_eq!(quote("Web Content"), "'Web Content'");
+    assert_eq!(quote("it's"), "'it'\\''s'");
+    assert_eq!(quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+    assert_eq!(shell_vars::render(&snapshot("my app", &[])).lines().next(), Some("MEMON_PATTERN='my app'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn quoted_values_survive_eval() {
+    for value in ["Web Content", "it's \"here\"", "$(echo no) `echo no` $HOME", "tab\there\nnewline", "*?[a]"] {
+        let output = std::process::Command::new("sh")
+            .args(["-c", "eval \"VALUE=$1\"; printf %s \"$VALUE\"", "sh", &quote(value)])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), value);
+    }
+}
+
+#[test]
+fn shell_vars_is_the_only_output() {
+    use clap::Parser;
+    use memon::cli::Args;
+    
+    assert!(Args::try_parse_from(["memon", "nginx", "--shell-vars", "--json"]).is_err());
+    assert!(Args::try_parse_from(["memon", "nginx", "--shell-vars", "--watch", "2"]).is_err());
+    
+    let table = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/small_tree.table");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_memon"))
+        .args(["--from-file", table, "--shell-vars", "nginx"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().all(|line| line.starts_with("MEMON_") && line.contains('=')), "{}", stdout);
+    assert!(stdout.contains("MEMON_TREE_COUNT=1\n"), "{}", stdout);
+}
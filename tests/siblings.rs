@@ -0,0 +1,67 @@
+// --siblings: the matched root's parent with all of its children as context
+
+use std::collections::HashMap;
+
+use memon::options::Columns;
+use memon::process::ProcessInfo;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::snapshot::{ProcessTree, SiblingContext, Snapshot};
+use memon::source;
+
+// init 1 -> supervisor 10 -> worker 11 -> helper 12
+//                         -> cache 13 -> cache-helper 14
+//                         -> cron 15
+fn table() -> HashMap<u32, ProcessInfo> {
+    let text = "1 - 8M init\n10 1 4M supervisor\n11 10 16M worker\n12 11 1M helper\n13 10 64M cache\n14 13 8M cache-helper\n15 10 2M cron\n";
+    let mut processes = source::parse_table(text).unwrap();
+    source::link_children(&mut processes);
+    processes
+}
+
+fn snapshot(tree: ProcessTree) -> Snapshot {
+    Snapshot { version: "0.1.0".to_string(), pattern: "worker".to_string(), matched: 1, trees: vec![tree], ..Default::default() }
+}
+
+#[test]
+fn siblings_are_collapsed_to_their_subtree_totals() {
+    let processes = table();
+    let context = SiblingContext::collect(&processes, 11).unwrap();
+    assert_eq!((context.parent_pid, context.parent_name.as_str()), (10, "supervisor"));
+    let rows: Vec<(u32, usize, u64, bool)> = context.siblings.iter()
+        .map(|sibling| (sibling.pid, sibling.process_count, sibling.total_bytes >> 20, sibling.matched_root))
+        .collect();
+    assert_eq!(rows, [(13, 2, 72, false), (11, 2, 17, true), (15, 1, 2, false)]);
+    
+    // A root without a parent in the table has no context
+    assert!(SiblingContext::collect(&processes, 1).is_none());
+}
+
+#[test]
+fn context_is_left_out_of_the_tree_totals() {
+    let mut processes = table();
+    let mut tree = ProcessTree::from_processes(&mut processes, 11).unwrap();
+    tree.siblings = SiblingContext::collect(&processes, 11);
+    assert_eq!(tree.process_count, 2);
+    assert_eq!(tree.total_bytes, 17 * 1024 * 1024);
+    
+    let snapshot = snapshot(tree);
+    let output = render_to_string(&TextRenderer::new(Theme::PLAIN, Columns::default(), None), &snapshot);
+    assert!(output.ends_with("\
+2 procs | 8.5MB avg | 17.0MB total
+siblings under 10 supervisor (4.0MB):
+├─ 13 cache [2 procs] 72.0MB
+├─ 11 worker [2 procs] 17.0MB (this tree)
+└─ 15 cron [1 procs] 2.0MB
+"), "{}", output);
+    
+    // --max-children collapses the rest, but keeps the matched root
+    let capped = render_to_string(&TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_max_children(Some(1)), &snapshot);
+    assert!(capped.ends_with("\
+├─ 13 cache [2 procs] 72.0MB
+├─ 11 worker [2 procs] 17.0MB (this tree)
+└─ (+1 more, 2.0MB)
+"), "{}", capped);
+    
+    let json = memon::render::render_json(&snapshot);
+    assert_eq!(json.matches("\"matched_root\": true").count(), 1, "{}", json);
+}
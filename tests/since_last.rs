@@ -0,0 +1,98 @@
+// --since-last: matching runs, the cache file and the rendered deltas
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use memon::options::Columns;
+use memon::render::{render_to_string, TextRenderer, Theme};
+use memon::since_last::{self, RunCache, SavedProcess, SavedRun};
+use memon::snapshot::{ProcessTree, Snapshot};
+use common::node;
+
+const MB: u64 = 1024 * 1024;
+
+fn saved(pid: u32, start_time: u64, name: &str, bytes: u64) -> SavedProcess {
+    SavedProcess { pid, start_time, name: name.to_string(), bytes }
+}
+
+#[test]
+fn matches_by_identity_then_by_name() {
+    let before = vec![saved(10, 100, "server", 300), saved(11, 100, "worker", 40), saved(12, 100, "worker", 50), saved(13, 100, "cron", 7)];
+    let now = vec![
+        // Still running
+        saved(10, 100, "server", 320),
+        // Restarted workers pair with the old ones in PID order
+        saved(21, 500, "worker", 45),
+        saved(20, 500, "worker", 60),
+        saved(22, 500, "worker", 10),
+        // A reused PID is another process
+        saved(13, 900, "shell", 3),
+    ];
+    let matched = since_last::match_saved(&before, &now);
+    assert_eq!(matched, HashMap::from([(10, 300), (20, 40), (21, 50)]));
+}
+
+#[test]
+fn sets_growth_on_processes_and_trees() {
+    let previous = SavedRun {
+        timestamp: 0,
+        trees: vec![saved(1, 0, "server", 6 * MB)],
+        processes: vec![saved(1, 0, "server", 4 * MB), saved(2, 0, "worker", 2 * MB)],
+    };
+    let mut trees = vec![ProcessTree::new(node(1, "server", 5 * MB, vec![node(3, "worker", 3 * MB, Vec::new()), node(4, "helper", MB, Vec::new())]))];
+    since_last::apply(&mut trees, &previous, |_| 0);
+    let tree = &trees[0];
+    assert_eq!(tree.since_last_bytes, Some(3 * MB as i64));
+    assert_eq!(tree.root.since_last_bytes, Some(MB as i64));
+    assert_eq!(tree.root.children[0].since_last_bytes, Some(MB as i64));
+    assert_eq!(tree.root.children[1].since_last_bytes, None);
+    
+    let snapshot = Snapshot { version: "0.1.0".to_string(), pattern: "server".to_string(), matched: 1, trees, since_last_age_secs: Some(7200), ..Default::default() };
+    let renderer = TextRenderer::new(Theme::PLAIN, Columns::default(), None).with_widths(1, 6);
+    let output = render_to_string(&renderer, &snapshot);
+    assert!(output.contains("1 server 5.0MB +1.0MB"), "{}", output);
+    assert!(output.contains("3 worker 3.0MB +1.0MB"), "{}", output);
+    assert!(output.contains("4 helper 1.0MB new"), "{}", output);
+    assert!(output.contains("9.0MB total | +3.0MB since 2h ago"), "{}", output);
+}
+
+#[test]
+fn cache_files_are_keyed_by_host_and_pattern() {
+    let dir = Path::new("/cache");
+    let path = since_last::entry_path(dir, "web1", "my app/*");
+    assert!(path.starts_with(dir));
+    let file = path.file_name().unwrap().to_str().unwrap();
+    assert!(file.starts_with("my_app__-") && file.ends_with(".json"), "{}", file);
+    assert_eq!(path, since_last::entry_path(dir, "web1", "my app/*"));
+    assert_ne!(path, since_last::entry_path(dir, "web2", "my app/*"));
+    assert_ne!(path, since_last::entry_path(dir, "web1", "my app/?"));
+    
+    let env = |vars: &'static [(&'static str, &'static str)]| move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| PathBuf::from(value));
+    assert_eq!(since_last::cache_dir(env(&[("XDG_CACHE_HOME", "/xdg"), ("HOME", "/home/me")])), Some(PathBuf::from("/xdg/memon/runs")));
+    assert_eq!(since_last::cache_dir(env(&[("XDG_CACHE_HOME", "relative"), ("HOME", "/home/me")])), Some(PathBuf::from("/home/me/.cache/memon/runs")));
+    assert_eq!(since_last::cache_dir(env(&[])), None);
+}
+
+#[test]
+fn cached_runs_expire_and_can_be_forgotten() {
+    let dir = std::env::temp_dir().join(format!("memon-since-last-{}", std::process::id()));
+    let path = since_last::entry_path(&dir, "host", "server");
+    let run = SavedRun { timestamp: 1000, trees: vec![saved(1, 0, "server", MB)], processes: vec![saved(1, 0, "server", MB)] };
+    
+    assert_eq!(RunCache::new(path.clone(), 60).previous(1000), None);
+    RunCache::new(path.clone(), 60).save(&run).unwrap();
+    assert_eq!(RunCache::new(path.clone(), 60).previous(1060), Some(&run));
+    assert_eq!(RunCache::new(path.clone(), 60).previous(1061), None);
+    
+    // Watch samples keep comparing with the run from before the session
+    let mut cache = RunCache::new(path.clone(), 60);
+    assert_eq!(cache.previous(1010), Some(&run));
+    cache.save(&SavedRun { timestamp: 1020, ..run.clone() }).unwrap();
+    assert_eq!(cache.previous(1030).map(|run| run.timestamp), Some(1000));
+    
+    assert!(since_last::forget(&path).unwrap());
+    assert!(!since_last::forget(&path).unwrap());
+    let _ = std::fs::remove_dir_all(&dir);
+}
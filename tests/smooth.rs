@@ -0,0 +1,64 @@
+// Exponential smoothing of RSS readings (--smooth)
+
+use std::collections::HashMap;
+
+use memon::process::ProcessInfo;
+use memon::smooth::{parse_alpha, Smoother};
+
+const MB: u64 = 1024 * 1024;
+
+#[test]
+fn follows_a_noisy_series_with_a_damped_trajectory() {
+    let mut smoother = Smoother::new(0.5);
+    let readings = [100, 110, 90, 110, 90, 100].map(|mb| mb * MB);
+    let smoothed: Vec<u64> = readings.iter().map(|&raw| smoother.update((7, 1), raw)).collect();
+    let expected = [100.0, 105.0, 97.5, 103.75, 96.875, 98.4375].map(|mb| (mb * MB as f64).round() as u64);
+    assert_eq!(smoothed, expected);
+    
+    // The jitter of +-10MB shrinks to at most 5MB around the mean
+    assert!(smoothed.iter().all(|&value| value.abs_diff(100 * MB) <= 5 * MB));
+}
+
+#[test]
+fn alpha_one_passes_readings_through() {
+    let mut smoother = Smoother::new(1.0);
+    assert_eq!(smoother.update((7, 1), 10 * MB), 10 * MB);
+    assert_eq!(smoother.update((7, 1), 30 * MB), 30 * MB);
+}
+
+fn table(readings: &[(u32, u64, u64)]) -> HashMap<u32, ProcessInfo> {
+    readings.iter()
+        .map(|&(pid, start_time, rss)| {
+            let mut proc_info = ProcessInfo::new(pid, "worker".to_string(), rss, None);
+            proc_info.start_time = start_time;
+            (pid, proc_info)
+        })
+        .collect()
+}
+
+#[test]
+fn apply_keeps_the_raw_reading_and_evicts_exited_processes() {
+    let mut smoother = Smoother::new(0.5);
+    smoother.apply(&mut table(&[(1, 100, 10 * MB), (2, 100, 20 * MB)]));
+    
+    let mut processes = table(&[(1, 100, 20 * MB)]);
+    smoother.apply(&mut processes);
+    assert_eq!(processes[&1].rss, 15 * MB);
+    assert_eq!(processes[&1].rss_raw, Some(20 * MB));
+    assert_eq!(smoother.len(), 1);
+    
+    // PID 1 reused by a new process: no history carries over
+    let mut processes = table(&[(1, 200, 40 * MB)]);
+    smoother.apply(&mut processes);
+    assert_eq!(processes[&1].rss, 40 * MB);
+    assert_eq!(smoother.len(), 1);
+}
+
+#[test]
+fn alpha_must_be_a_weight() {
+    assert_eq!(parse_alpha("0.3"), Ok(0.3));
+    assert_eq!(parse_alpha("1"), Ok(1.0));
+    assert!(parse_alpha("0").is_err());
+    assert!(parse_alpha("1.5").is_err());
+    assert!(parse_alpha("abc").is_err());
+}
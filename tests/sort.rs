@@ -0,0 +1,73 @@
+// --sort delta: fastest growing trees and children first in watch mode
+
+use std::collections::HashMap;
+
+use clap::Parser;
+use memon::changes::{Change, ChangeKind};
+use memon::cli::Args;
+use memon::process::ProcessInfo;
+use memon::snapshot::ProcessTree;
+use memon::sort::{self, DeltaSort, SortKey};
+use memon::source;
+
+// Two servers: 10 with workers 11 and 12, and 20 with worker 21
+fn sample(sizes_mb: [u64; 5]) -> (Vec<ProcessTree>, HashMap<u32, ProcessInfo>) {
+    let rows = [(10, None), (11, Some(10)), (12, Some(10)), (20, None), (21, Some(20))];
+    let mut processes: HashMap<u32, ProcessInfo> = rows.iter().zip(sizes_mb)
+        .map(|(&(pid, ppid), mb)| (pid, ProcessInfo::new(pid, "server".to_string(), mb << 20, ppid)))
+        .collect();
+    source::link_children(&mut processes);
+    let trees = [10, 20].iter().filter_map(|&pid| ProcessTree::from_processes(&mut processes, pid)).collect();
+    (trees, processes)
+}
+
+fn order(trees: &[ProcessTree]) -> Vec<Vec<u32>> {
+    trees.iter().map(|tree| tree.iter_preorder().map(|(node, _)| node.pid).collect()).collect()
+}
+
+#[test]
+fn growth_since_the_previous_sample_comes_first() {
+    let mut deltas = DeltaSort::default();
+    // Nothing has grown on the first sample, so PID order stays
+    let (mut trees, processes) = sample([10, 10, 10, 10, 10]);
+    deltas.sort(&mut trees, &processes);
+    assert_eq!(order(&trees), [vec![10, 11, 12], vec![20, 21]]);
+    
+    // 21 grew by 30MB, 12 by 20MB and 11 shrank
+    let (mut trees, processes) = sample([10, 5, 30, 10, 40]);
+    deltas.sort(&mut trees, &processes);
+    assert_eq!(order(&trees), [vec![20, 21], vec![10, 12, 11]]);
+    
+    // Unchanged since then: ties fall back to PID order
+    let (mut trees, processes) = sample([10, 5, 30, 10, 40]);
+    deltas.sort(&mut trees, &processes);
+    assert_eq!(order(&trees), [vec![10, 11, 12], vec![20, 21]]);
+}
+
+#[test]
+fn a_reused_pid_counts_as_new() {
+    let mut deltas = DeltaSort::default();
+    let (mut trees, processes) = sample([10, 10, 10, 10, 10]);
+    deltas.sort(&mut trees, &processes);
+    // 11 was replaced by a smaller process, which grew from nothing
+    let (mut trees, mut processes) = sample([10, 8, 15, 10, 10]);
+    processes.get_mut(&11).unwrap().start_time = 60;
+    deltas.sort(&mut trees, &processes);
+    assert_eq!(order(&trees)[0], [10, 11, 12]);
+}
+
+#[test]
+fn changes_are_sorted_by_growth() {
+    let change = |kind, pid, delta_bytes| Change { kind, pid, name: "server".to_string(), rss_bytes: 0, delta_bytes, last_change_epoch: None };
+    let mut changes = vec![change(ChangeKind::Exited, 11, -5), change(ChangeKind::Changed, 12, 20), change(ChangeKind::Started, 13, 20), change(ChangeKind::Changed, 14, 3)];
+    sort::sort_changes(&mut changes);
+    let pids: Vec<u32> = changes.iter().map(|change| change.pid).collect();
+    assert_eq!(pids, [12, 13, 14, 11]);
+}
+
+#[test]
+fn sort_defaults_to_pid() {
+    assert_eq!(Args::try_parse_from(["memon", "java"]).unwrap().sort, SortKey::Pid);
+    assert_eq!(Args::try_parse_from(["memon", "java", "--watch", "5", "--sort", "delta"]).unwrap().sort, SortKey::Delta);
+    assert!(Args::try_parse_from(["memon", "java", "--sort", "rss"]).is_err());
+}
@@ -0,0 +1,77 @@
+// Leaving memon's own processes out of the match candidates, and --under subtrees
+
+use std::collections::{HashMap, HashSet};
+
+use memon::process::ProcessInfo;
+use memon::source::{self, ProcessSource, SelfExclusion, SourceError};
+
+// A fixed table standing in for the live scan:
+// init 1 -> sshd 10 -> bash 20 -> memon 30 (threads 31, 32), bash 20 -> vim 40
+struct FakeSource;
+
+impl ProcessSource for FakeSource {
+    fn load(&mut self) -> Result<HashMap<u32, ProcessInfo>, SourceError> {
+        let table = [(1, None, "init"), (10, Some(1), "sshd"), (20, Some(10), "bash"), (30, Some(20), "memon"),
+                     (31, Some(30), "memon"), (32, Some(30), "memon"), (40, Some(20), "vim")];
+        Ok(table
+            .into_iter()
+            .map(|(pid, ppid, name)| (pid, ProcessInfo::new(pid, name.to_string(), 1024, ppid)))
+            .collect())
+    }
+}
+
+fn load() -> HashMap<u32, ProcessInfo> {
+    let mut processes = FakeSource.load().unwrap();
+    source::link_children(&mut processes);
+    processes
+}
+
+fn set(pids: &[u32]) -> HashSet<u32> {
+    pids.iter().copied().collect()
+}
+
+#[test]
+fn own_process_and_its_threads_are_excluded_by_default() {
+    let processes = load();
+    assert_eq!(source::self_pids(&processes, 30, SelfExclusion::default()), set(&[30, 31, 32]));
+}
+
+#[test]
+fn exclude_self_tree_adds_the_ancestor_chain() {
+    let processes = load();
+    assert_eq!(source::self_pids(&processes, 30, SelfExclusion::Ancestors), set(&[1, 10, 20, 30, 31, 32]));
+    assert_eq!(source::ancestor_chain(&processes, 30), vec![30, 20, 10, 1]);
+}
+
+#[test]
+fn include_self_excludes_nothing() {
+    let processes = load();
+    assert!(source::self_pids(&processes, 30, SelfExclusion::Include).is_empty());
+}
+
+#[test]
+fn unknown_self_pid_only_excludes_itself() {
+    let processes = load();
+    assert_eq!(source::self_pids(&processes, 99, SelfExclusion::Ancestors), set(&[99]));
+}
+
+#[test]
+fn descendant_set_is_the_subtree() {
+    let processes = load();
+    assert_eq!(source::descendant_set(&processes, 20), set(&[20, 30, 31, 32, 40]));
+    assert_eq!(source::descendant_set(&processes, 40), set(&[40]));
+    assert_eq!(source::descendant_set(&processes, 1), set(&[1, 10, 20, 30, 31, 32, 40]));
+}
+
+#[test]
+fn descendant_set_of_an_unknown_pid_is_itself() {
+    assert_eq!(source::descendant_set(&load(), 99), set(&[99]));
+}
+
+#[test]
+fn descendant_set_survives_parent_loops() {
+    let mut processes = load();
+    // A corrupt table where vim lists sshd, its own ancestor, as a child
+    processes.get_mut(&40).unwrap().children.push(10);
+    assert_eq!(source::descendant_set(&processes, 10), set(&[10, 20, 30, 31, 32, 40]));
+}
@@ -0,0 +1,113 @@
+// Time since the last memory change across watch samples (--stability)
+
+use std::collections::HashMap;
+
+use memon::changes;
+use memon::process::ProcessInfo;
+use memon::snapshot::{ProcessTree, Snapshot};
+use memon::stability::{self, Stability, StabilityTracker, EPSILON_BYTES};
+
+const MB: u64 = 1024 * 1024;
+
+#[test]
+fn small_moves_are_not_changes() {
+    let mut tracker = StabilityTracker::default();
+    let first = tracker.update((1, 100), 10 * MB, 1000);
+    assert_eq!(first, Stability { last_change_epoch: 1000, stable_secs: 0, observed_change: false });
+    
+    let jitter = tracker.update((1, 100), 10 * MB + EPSILON_BYTES, 1030);
+    assert_eq!(jitter, Stability { last_change_epoch: 1000, stable_secs: 30, observed_change: false });
+    
+    let grown = tracker.update((1, 100), 12 * MB, 1060);
+    assert_eq!(grown, Stability { last_change_epoch: 1060, stable_secs: 0, observed_change: true });
+    assert_eq!(tracker.update((1, 100), 12 * MB, 1064).stable_secs, 4);
+}
+
+#[test]
+fn slow_creep_adds_up_to_a_change() {
+    let mut tracker = StabilityTracker::new(MB);
+    let step = MB / 4;
+    for sample in 0..4 {
+        let stability = tracker.update((1, 100), 10 * MB + sample * step, 1000 + sample as i64);
+        assert!(!stability.observed_change);
+    }
+    // Each step is below the epsilon, but together they pass it
+    let stability = tracker.update((1, 100), 10 * MB + 5 * step, 1005);
+    assert_eq!(stability.last_change_epoch, 1005);
+    assert!(stability.observed_change);
+}
+
+#[test]
+fn labels_read_as_recent_change_or_stable() {
+    let recent = Stability { last_change_epoch: 0, stable_secs: 4, observed_change: true };
+    assert_eq!(recent.label(), "changed 4s ago");
+    let settled = Stability { last_change_epoch: 0, stable_secs: 32 * 60 + 10, observed_change: true };
+    assert_eq!(settled.label(), "stable 32m");
+    // Without a change seen, a young process is still only known to be stable
+    let unchanged = Stability { last_change_epoch: 0, stable_secs: 4, observed_change: false };
+    assert_eq!(unchanged.label(), "stable 4s");
+}
+
+#[test]
+fn ages_use_the_largest_fitting_unit() {
+    assert_eq!(stability::format_age(0), "0s");
+    assert_eq!(stability::format_age(59), "59s");
+    assert_eq!(stability::format_age(60), "1m");
+    assert_eq!(stability::format_age(3599), "59m");
+    assert_eq!(stability::format_age(7200), "2h");
+    assert_eq!(stability::format_age(3 * 86400 + 5), "3d");
+}
+
+#[test]
+fn apply_forgets_exited_and_reused_processes() {
+    let mut tracker = StabilityTracker::default();
+    let mut processes = HashMap::new();
+    for pid in [1, 2] {
+        let mut proc_info = ProcessInfo::new(pid, "worker".to_string(), 10 * MB, None);
+        proc_info.start_time = 100;
+        processes.insert(pid, proc_info);
+    }
+    tracker.apply(&mut processes, 1000);
+    assert_eq!(tracker.len(), 2);
+    
+    // PID 1 exits; PID 2 is reused by a process that started later
+    processes.remove(&1);
+    let reused = processes.get_mut(&2).unwrap();
+    reused.start_time = 200;
+    reused.rss = 30 * MB;
+    tracker.apply(&mut processes, 1010);
+    assert_eq!(tracker.len(), 1);
+    assert_eq!(processes[&2].stability, Some(Stability { last_change_epoch: 1010, stable_secs: 0, observed_change: false }));
+}
+
+#[test]
+fn apply_compares_unsmoothed_readings() {
+    let mut tracker = StabilityTracker::default();
+    let mut processes = HashMap::new();
+    let mut proc_info = ProcessInfo::new(1, "worker".to_string(), 10 * MB, None);
+    processes.insert(1, proc_info.clone());
+    tracker.apply(&mut processes, 1000);
+    
+    // The smoothed value lags behind; the raw reading has moved
+    proc_info.rss_raw = Some(20 * MB);
+    processes.insert(1, proc_info);
+    tracker.apply(&mut processes, 1005);
+    assert!(processes[&1].stability.is_some_and(|stability| stability.observed_change));
+}
+
+#[test]
+fn change_events_carry_the_last_change() {
+    let snapshot = |rss: u64, now: i64, tracker: &mut StabilityTracker| {
+        let mut processes = HashMap::new();
+        processes.insert(1, ProcessInfo::new(1, "worker".to_string(), rss, None));
+        tracker.apply(&mut processes, now);
+        let tree = ProcessTree::from_processes(&mut processes, 1).unwrap();
+        Snapshot { version: String::new(), pattern: "worker".to_string(), matched: 1, trees: vec![tree], ..Default::default() }
+    };
+    let mut tracker = StabilityTracker::default();
+    let before = snapshot(10 * MB, 1000, &mut tracker);
+    let after = snapshot(20 * MB, 1005, &mut tracker);
+    let events = changes::diff(&before, &after, MB);
+    assert_eq!(events[0].last_change_epoch, Some(1005));
+    assert!(changes::render_ndjson(1005, &events).contains("\"last_change_epoch\":1005"));
+}
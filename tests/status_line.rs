@@ -0,0 +1,81 @@
+// --status-line on every exit path, probing a real child process
+
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use memon::status_line::StatusLine;
+use common::Probe;
+
+// Run memon and parse the last stderr line into its fields
+fn status_line(args: &[&str]) -> (Option<i32>, HashMap<String, String>) {
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args(args).output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr.lines().last().unwrap_or_default();
+    let fields = line.strip_prefix("memon: ").unwrap_or_else(|| panic!("no status line in {:?}", stderr));
+    let fields = fields
+        .split(' ')
+        .map(|field| {
+            let (key, value) = field.split_once('=').unwrap_or_else(|| panic!("not key=value: {:?}", field));
+            (key.to_string(), value.to_string())
+        })
+        .collect();
+    (output.status.code(), fields)
+}
+
+#[test]
+fn matched_process_is_summarized() {
+    let probe = Probe::start("a");
+    let (code, fields) = status_line(&["--status-line", &probe.name]);
+    assert_eq!(code, Some(0));
+    assert_eq!(fields["matched"], "1");
+    assert_eq!(fields["trees"], "1");
+    assert_eq!(fields["max_pid"], probe.child.id().to_string());
+    assert!(fields["total_bytes"].parse::<u64>().unwrap() > 0);
+    assert!(fields["duration_ms"].parse::<u64>().is_ok());
+    assert_eq!(fields["exit"], "0");
+}
+
+#[test]
+fn no_match_is_summarized() {
+    let (code, fields) = status_line(&["--status-line", "memon-no-such-process"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(fields["matched"], "0");
+    assert_eq!(fields["trees"], "0");
+    assert_eq!(fields["total_bytes"], "0");
+    assert_eq!(fields["max_pid"], "-");
+    assert_eq!(fields["exit"], "1");
+}
+
+#[test]
+fn exceeded_threshold_is_summarized() {
+    let probe = Probe::start("b");
+    let (code, fields) = status_line(&["--status-line", &probe.name, "--fail-if-total", "1K"]);
+    assert_eq!(code, Some(2));
+    assert_eq!(fields["matched"], "1");
+    assert_eq!(fields["max_pid"], probe.child.id().to_string());
+    assert_eq!(fields["exit"], "2");
+    
+    let (code, fields) = status_line(&["--status-line", "--check", &probe.name, "--fail-if-process", "1K"]);
+    assert_eq!(code, Some(2));
+    assert_eq!(fields["trees"], "1");
+    assert_eq!(fields["exit"], "2");
+}
+
+#[test]
+fn usage_error_is_summarized() {
+    let (code, fields) = status_line(&["--status-line", "--check", "sleep", "--watch", "5"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(fields["matched"], "0");
+    assert_eq!(fields["exit"], "1");
+}
+
+#[test]
+fn fields_keep_their_order() {
+    let status = StatusLine { matched: 14, trees: 2, total_bytes: 4_831_838_208, max_pid: Some(4312), duration_ms: 123, exit: 0 };
+    assert_eq!(status.to_string(), "memon: matched=14 trees=2 total_bytes=4831838208 max_pid=4312 duration_ms=123 exit=0");
+    assert!(StatusLine::default().to_string().contains(" max_pid=- "));
+}
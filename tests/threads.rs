@@ -0,0 +1,85 @@
+// `memon threads`: pools, the busiest threads and their stacks
+
+use memon::render::Theme;
+use memon::threads::{self, ThreadStat, ThreadsReport};
+
+const MAPS: &str = "\
+55d4c0a00000-55d4c0a21000 rw-p 00000000 00:00 0                          [heap]
+7f3a0b800000-7f3a0c000000 rw-p 00000000 00:00 0 
+7f3a0c000000-7f3a0c100000 rw-p 00000000 00:00 0 
+7ffd5e400000-7ffd5e500000 rw-p 00000000 00:00 0                          [stack]
+";
+
+fn thread(tid: u32, name: &str, cpu_ticks: u64, stack_pointer: Option<u64>) -> ThreadStat {
+    ThreadStat { tid, name: name.to_string(), cpu_ticks, stack_pointer }
+}
+
+// A JVM with a pool of four request threads next to the main and GC threads
+fn jvm() -> Vec<ThreadStat> {
+    vec![
+        thread(4242, "java", 150, Some(0x7ffd5e4a1f00)),
+        thread(4243, "GC Thread#0", 2_000, None),
+        thread(4250, "pool-1-thread-1", 19_000, Some(0x7f3a0bffe9c0)),
+        thread(4251, "pool-1-thread-2", 500, Some(0x7f3a0c0ff000)),
+        thread(4252, "pool-1-thread-3", 400, Some(0x1000)),
+        thread(4253, "pool-1-thread-4", 400, None),
+    ]
+}
+
+#[test]
+fn pools_strip_a_trailing_number() {
+    assert_eq!(threads::pool_name("pool-1-thread-12"), "pool-1-thread-*");
+    assert_eq!(threads::pool_name("GC Thread#3"), "GC Thread#*");
+    assert_eq!(threads::pool_name("tokio-runtime-w"), "tokio-runtime-w");
+    assert_eq!(threads::pool_name("42"), "42");
+}
+
+#[test]
+fn cpu_time_is_short() {
+    assert_eq!(threads::format_cpu(0.42), "0.42s");
+    assert_eq!(threads::format_cpu(187.5), "3m07s");
+    assert_eq!(threads::format_cpu(8100.0), "2h15m");
+}
+
+#[test]
+fn threads_are_sorted_by_cpu_and_sized_by_their_stack_mapping() {
+    let report = ThreadsReport::new(4242, Some("java".to_string()), 812 * 1024 * 1024, jvm(), MAPS, 4);
+    assert_eq!(report.thread_count, 6);
+    assert_eq!(report.hidden_threads, 2);
+    let rows: Vec<(u32, Option<u64>)> = report.threads.iter().map(|row| (row.tid, row.stack_bytes)).collect();
+    assert_eq!(rows, [(4250, Some(8 * 1024 * 1024)), (4243, None), (4251, Some(1024 * 1024)), (4252, None)]);
+    let pools: Vec<(&str, usize)> = report.pools.iter().map(|pool| (pool.name.as_str(), pool.threads)).collect();
+    assert_eq!(pools, [("pool-1-thread-*", 4), ("GC Thread#*", 1), ("java", 1)]);
+    assert_eq!(report.pools[0].cpu_secs, 203.0);
+    
+    assert_eq!(threads::render_text(&report, Theme::PLAIN), "\
+Threads of 4242 (java): 6 threads, 812.0MB RSS
+Memory is process-wide: RSS cannot be split per thread
+
+THREADS     CPU  POOL
+      4   3m23s  pool-1-thread-*
+      1  20.00s  GC Thread#*
+      1   1.50s  java
+
+ TID     CPU  STACK  NAME
+4250   3m10s  8.0MB  pool-1-thread-1
+4243  20.00s      -  GC Thread#0
+4251   5.00s  1.0MB  pool-1-thread-2
+4252   4.00s      -  pool-1-thread-3
+(2 more threads)
+");
+    assert!(threads::render_json(&report).contains("\"process_rss_bytes\": 851443712"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn own_threads_are_listed() {
+    use std::process::Command;
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_memon")).args(["threads", &std::process::id().to_string(), "--json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["pid"], std::process::id());
+    assert!(report["thread_count"].as_u64().unwrap() >= 1);
+    assert!(report["process_rss_bytes"].as_u64().unwrap() > 0);
+}
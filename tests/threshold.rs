@@ -0,0 +1,90 @@
+// Threshold checks and the edge-triggered alert trigger
+
+mod common;
+
+use memon::snapshot::{Snapshot, ProcessTree};
+use memon::hook::notification_text;
+use memon::threshold::{Exceeded, Thresholds, Trigger};
+use memon::units::parse_size;
+use std::time::{Duration, Instant};
+use common::node;
+
+fn snapshot() -> Snapshot {
+    let root = node(10, "server", 300, vec![node(11, "worker", 500, Vec::new()), node(12, "worker", 200, Vec::new())]);
+    Snapshot {
+        version: "test".to_string(),
+        pattern: "server".to_string(),
+        matched: 3,
+        trees: vec![ProcessTree { root_pid: 10, process_count: 3, total_bytes: 1000, average_bytes: 333, root, ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reports_largest_process_when_a_limit_is_exceeded() {
+    let by_process = Thresholds { total: None, process: Some(400) };
+    let breach = by_process.check(&snapshot()).unwrap();
+    assert_eq!((breach.pid, breach.name.as_str(), breach.rss, breach.total), (11, "worker", 500, 1000));
+    assert_eq!(breach.exceeded, Exceeded::Process(400));
+    
+    let by_total = Thresholds { total: Some(999), process: Some(400) };
+    let breach = by_total.check(&snapshot()).unwrap();
+    assert_eq!((breach.pid, breach.exceeded), (11, Exceeded::Total(999)));
+}
+
+#[test]
+fn no_breach_at_or_below_limits() {
+    let thresholds = Thresholds { total: Some(1000), process: Some(500) };
+    assert_eq!(thresholds.check(&snapshot()), None);
+    assert!(Thresholds::default().check(&snapshot()).is_none());
+}
+
+#[test]
+fn trigger_fires_on_rising_edge_only() {
+    let start = Instant::now();
+    let mut trigger = Trigger::new(Duration::ZERO);
+    assert!(!trigger.update(false, start));
+    assert!(trigger.update(true, start));
+    assert!(!trigger.update(true, start));
+    assert!(!trigger.update(false, start));
+    assert!(trigger.update(true, start));
+}
+
+#[test]
+fn trigger_respects_cooldown() {
+    let start = Instant::now();
+    let mut trigger = Trigger::new(Duration::from_secs(60));
+    assert!(trigger.update(true, start));
+    assert!(!trigger.update(false, start + Duration::from_secs(10)));
+    assert!(!trigger.update(true, start + Duration::from_secs(20)));
+    assert!(!trigger.update(false, start + Duration::from_secs(30)));
+    assert!(trigger.update(true, start + Duration::from_secs(61)));
+}
+
+#[test]
+fn parses_sizes_with_binary_units() {
+    assert_eq!(parse_size("4096"), Ok(4096));
+    assert_eq!(parse_size("64k"), Ok(64 * 1024));
+    assert_eq!(parse_size("512MB"), Ok(512 * 1024 * 1024));
+    assert_eq!(parse_size("1.5G"), Ok(3 * 512 * 1024 * 1024));
+    assert_eq!(parse_size("300p"), Ok(300 * memon::platform::page_size()));
+    assert!(parse_size("12TB").is_err());
+    assert!(parse_size("lots").is_err());
+}
+
+#[test]
+fn notification_names_the_crossed_limit() {
+    let gib = 1024 * 1024 * 1024;
+    let mut breach = Thresholds { total: Some(2 * gib), process: None }.check(&Snapshot {
+        trees: vec![ProcessTree { total_bytes: 3 * gib, ..snapshot().trees.remove(0) }],
+        budget: None,
+        match_stats: None,
+        groups: None,
+        ..snapshot()
+    }).unwrap();
+    assert_eq!(notification_text("myapp", &breach), "myapp tree exceeded 2.0GB: pid 11 at 0.0MB");
+    
+    breach.exceeded = Exceeded::Process(gib);
+    breach.rss = 3 * gib / 2;
+    assert_eq!(notification_text("myapp", &breach), "myapp process exceeded 1.0GB: pid 11 at 1.5GB");
+}
@@ -0,0 +1,55 @@
+// `memon top`: application trees of the whole system, merged by root name
+
+use memon::source;
+use memon::top::{self, AppTotal, TopReport};
+
+const MB: u64 = 1024 * 1024;
+
+fn report(limit: usize) -> TopReport {
+    let mut processes = source::load_table("tests/fixtures/system.table".as_ref()).unwrap();
+    source::link_children(&mut processes);
+    TopReport::new(&processes, limit)
+}
+
+fn app(name: &str, trees: usize, processes: usize, bytes: u64) -> AppTotal {
+    AppTotal { name: name.to_string(), trees, processes, bytes }
+}
+
+#[test]
+fn children_of_service_managers_are_applications() {
+    let mut processes = source::load_table("tests/fixtures/system.table".as_ref()).unwrap();
+    source::link_children(&mut processes);
+    // systemd --user (500) is an application of PID 1 and starts its own
+    assert_eq!(top::app_roots(&processes), vec![1, 2, 100, 200, 300, 400, 500, 501, 503]);
+}
+
+#[test]
+fn ranks_applications_and_merges_roots_by_name() {
+    let report = report(top::DEFAULT_LIMIT);
+    assert_eq!(report.applications, vec![
+        app("firefox", 1, 2, 350 * MB),
+        app("nginx", 2, 4, 70 * MB),
+        app("postgres", 1, 3, 60 * MB),
+        app("systemd", 2, 2, 21 * MB),
+        app("sshd", 1, 3, 13 * MB),
+        app("pipewire", 1, 1, 5 * MB),
+        app("kthreadd", 1, 3, 0),
+    ]);
+    assert_eq!((report.processes, report.total_bytes), (18, 519 * MB));
+    assert_eq!((report.omitted, report.omitted_bytes), (0, 0));
+}
+
+#[test]
+fn limit_keeps_the_largest_and_counts_the_rest() {
+    let report = report(2);
+    let names: Vec<&str> = report.applications.iter().map(|app| app.name.as_str()).collect();
+    assert_eq!(names, vec!["firefox", "nginx"]);
+    assert_eq!((report.omitted, report.omitted_bytes), (5, 99 * MB));
+    
+    let text = top::render_text(&report);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "Top 2 applications by tree memory (18 processes, 519.0MB total):");
+    assert_eq!(lines[1], "1  firefox  1 tree   2 procs  350.0MB  67.4%");
+    assert_eq!(lines[2], "2  nginx    2 trees  4 procs   70.0MB  13.5%");
+    assert_eq!(lines[3], "\u{2026} 5 more applications, 99.0MB total");
+}
@@ -0,0 +1,75 @@
+// SVG treemap output (--svg)
+
+mod common;
+
+use memon::snapshot::{ProcessNode, ProcessTree, Snapshot};
+use memon::treemap::{self, Rect};
+
+const MB: u64 = 1024 * 1024;
+
+fn node(pid: u32, name: &str, rss: u64, rank: Option<u8>, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode { rank, ..common::node(pid, name, rss, children) }
+}
+
+fn snapshot() -> Snapshot {
+    let root = node(1, "server", 100 * MB, Some(2), vec![
+        node(2, "worker", 300 * MB, Some(1), Vec::new()),
+        node(3, "<cache> & co", 50 * MB, Some(3), Vec::new()),
+        node(4, "tiny", MB, None, Vec::new()),
+    ]);
+    Snapshot { version: String::new(), pattern: "server".to_string(), matched: 1, trees: vec![ProcessTree::new(root)], ..Default::default() }
+}
+
+#[test]
+fn squarified_areas_are_proportional_and_inside() {
+    let canvas = Rect { x: 0.0, y: 0.0, w: 600.0, h: 400.0 };
+    let values = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0];
+    let rects = treemap::squarify(&values, canvas);
+    assert_eq!(rects.len(), values.len());
+    let scale = canvas.w * canvas.h / values.iter().sum::<f64>();
+    for (rect, value) in rects.iter().zip(values) {
+        assert!((rect.w * rect.h - value * scale).abs() < 1e-6);
+        assert!(rect.x >= -1e-9 && rect.y >= -1e-9);
+        assert!(rect.x + rect.w <= canvas.w + 1e-6 && rect.y + rect.h <= canvas.h + 1e-6);
+        // Squarified, not sliced: no sliver worse than 1:4
+        assert!(rect.w.max(rect.h) / rect.w.min(rect.h) < 4.0);
+    }
+}
+
+#[test]
+fn empty_values_get_empty_rectangles() {
+    let canvas = Rect { x: 10.0, y: 20.0, w: 100.0, h: 100.0 };
+    let rects = treemap::squarify(&[3.0, 0.0, 1.0], canvas);
+    assert_eq!(rects[1].w * rects[1].h, 0.0);
+    assert!((rects[0].w * rects[0].h - 7500.0).abs() < 1e-6);
+    assert!(treemap::squarify(&[0.0], canvas).iter().all(|rect| rect.w == 0.0));
+    assert!(treemap::squarify(&[], canvas).is_empty());
+}
+
+#[test]
+fn xml_special_characters_are_escaped() {
+    assert_eq!(treemap::escape(r#"a<b>&"c'"#), "a&lt;b&gt;&amp;&quot;c&apos;");
+    assert_eq!(treemap::escape("plain"), "plain");
+}
+
+#[test]
+fn svg_nests_processes_and_highlights_ranks() {
+    let svg = treemap::render(&snapshot());
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<rect").count(), 4);
+    assert!(svg.contains(r##"fill="#ffaf00""##) && svg.contains(r##"fill="#87d7ff""##) && svg.contains(r##"fill="#d0d0d0""##));
+    assert!(svg.contains("<text x=\"3.0\" y=\"12.0\">server 451.0MB</text>"));
+    assert!(svg.contains(">worker 300.0MB</text>"));
+    assert!(svg.contains("&lt;cache&gt; &amp; co"));
+    // Too small for its label, but still drawn with a tooltip
+    assert!(svg.contains("<title>tiny (4)"));
+    assert!(!svg.contains(">tiny 1.0MB</text>"));
+}
+
+#[test]
+fn same_snapshot_same_svg() {
+    assert_eq!(treemap::render(&snapshot()), treemap::render(&snapshot()));
+    let empty = Snapshot { version: String::new(), pattern: "x".to_string(), matched: 0, trees: Vec::new(), ..Default::default() };
+    assert!(!treemap::render(&empty).contains("<rect"));
+}
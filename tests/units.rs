@@ -0,0 +1,62 @@
+// Size formatting: units and digit grouping
+
+use memon::units::{group_digits, locale_separator, SizeFormat, Unit, DEFAULT_PAGE_SIZE};
+
+#[test]
+fn groups_digits_in_threes() {
+    assert_eq!(group_digits(0, ','), "0");
+    assert_eq!(group_digits(999, ','), "999");
+    assert_eq!(group_digits(1000, ','), "1,000");
+    assert_eq!(group_digits(48211234816, ','), "48,211,234,816");
+    assert_eq!(group_digits(u64::MAX, '.'), "18.446.744.073.709.551.615");
+}
+
+#[test]
+fn picks_the_separator_from_the_locale() {
+    assert_eq!(locale_separator("en_US.UTF-8"), ',');
+    assert_eq!(locale_separator("C"), ',');
+    assert_eq!(locale_separator("de_DE.UTF-8"), '.');
+    assert_eq!(locale_separator("de_CH.UTF-8"), '\'');
+    assert_eq!(locale_separator("fr_FR@euro"), ' ');
+}
+
+#[test]
+fn exact_bytes_are_grouped_unless_disabled() {
+    let grouped = SizeFormat::new(Unit::B, true, Some("en_US.UTF-8"));
+    assert_eq!(grouped.memory(48211234816), "48,211,234,816B");
+    assert_eq!(grouped.count(1234), "1,234");
+
+    let plain = SizeFormat::new(Unit::B, false, Some("en_US.UTF-8"));
+    assert_eq!(plain.memory(48211234816), "48211234816B");
+    assert_eq!(plain.count(1234), "1234");
+}
+
+#[test]
+fn auto_unit_keeps_the_short_form() {
+    let sizes = SizeFormat::new(Unit::Auto, true, None);
+    assert_eq!(sizes.memory(3 * 1024 * 1024 * 1024), "3.0GB");
+    assert_eq!(sizes.memory(0), "0B");
+}
+
+#[test]
+fn pages_are_exact_counts_with_the_page_size_noted() {
+    let sizes = SizeFormat::new(Unit::Pages, true, None).with_page_size(4096);
+    assert_eq!(sizes.memory(1_234_567 * 4096), "1,234,567p");
+    assert_eq!(sizes.memory(4096 + 4096), "2p");
+    // A smoothed reading between two pages rounds to the nearest
+    assert_eq!(sizes.memory(4096 + 2047), "1p");
+    assert_eq!(sizes.memory(4096 + 2048), "2p");
+    assert_eq!(sizes.page_note().as_deref(), Some("Sizes in pages of 4,096B"));
+    
+    let large = SizeFormat::new(Unit::Pages, false, None).with_page_size(16384);
+    assert_eq!(large.memory(3 * 16384), "3p");
+    assert_eq!(large.page_note().as_deref(), Some("Sizes in pages of 16384B"));
+    assert_eq!(SizeFormat::new(Unit::B, true, None).page_note(), None);
+}
+
+#[test]
+fn system_page_size_is_a_power_of_two() {
+    let page_size = memon::platform::page_size();
+    assert!(page_size.is_power_of_two() && page_size >= 1024, "{}", page_size);
+    assert_eq!(SizeFormat::default().page_size, DEFAULT_PAGE_SIZE);
+}
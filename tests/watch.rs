@@ -0,0 +1,39 @@
+// Watch mode stopped by a signal: the session recap never lands in a
+// machine-readable stdout
+
+#![cfg(unix)]
+
+mod common;
+
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use serde_json::Value;
+
+use common::Probe;
+
+#[test]
+fn json_samples_stay_ndjson_after_a_signal() {
+    let probe = Probe::start("w");
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        let memon = Command::new(env!("CARGO_BIN_EXE_memon"))
+            .args([probe.name.as_str(), "--watch", "1", "--json"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(1500));
+        unsafe { libc::kill(memon.id() as libc::pid_t, signal) };
+        let output = memon.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr);
+
+        let samples: Vec<Value> = stdout.lines()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|err| panic!("not a JSON sample: {:?}: {}", line, err)))
+            .collect();
+        assert!(!samples.is_empty(), "{}", stderr);
+        assert!(samples.iter().all(|sample| sample["pattern"] == probe.name.as_str()), "{}", stdout);
+        assert!(stderr.contains(&format!("Watched '{}' for ", probe.name)), "{}", stderr);
+    }
+}
@@ -0,0 +1,36 @@
+// Watch mode column widths: widen at once, shrink slowly, fit the terminal
+
+use memon::render::{StickyWidths, DECAY_SAMPLES};
+
+#[test]
+fn widths_widen_at_once_and_hold() {
+    let mut widths = StickyWidths::default();
+    assert_eq!(widths.observe((4, 40), None), (4, 40));
+    assert_eq!(widths.observe((7, 52), None), (7, 52));
+    // The long-named process exited; the layout stays put
+    assert_eq!(widths.observe((4, 40), None), (7, 52));
+}
+
+#[test]
+fn widths_shrink_one_step_per_sample_after_the_decay() {
+    let mut widths = StickyWidths::default();
+    widths.observe((7, 52), None);
+    for _ in 0..DECAY_SAMPLES {
+        assert_eq!(widths.observe((4, 40), None), (7, 52));
+    }
+    assert_eq!(widths.observe((4, 40), None), (6, 51));
+    assert_eq!(widths.observe((4, 40), None), (5, 50));
+    // Needing the width again stops the decay
+    assert_eq!(widths.observe((4, 60), None), (4, 60));
+    assert_eq!(widths.observe((4, 40), None), (4, 60));
+}
+
+#[test]
+fn name_column_is_clamped_to_the_terminal() {
+    let mut widths = StickyWidths::default();
+    assert_eq!(widths.observe((5, 90), Some(80)), (5, 59));
+    // Wider terminal: the remembered width comes back
+    assert_eq!(widths.observe((5, 40), Some(200)), (5, 90));
+    // A very narrow terminal still leaves a usable name column
+    assert_eq!(widths.observe((5, 40), Some(20)), (5, 12));
+}